@@ -0,0 +1,339 @@
+//! Модуль разбора параметров командной строки конвертера
+//!
+//! Функцию [`run`] вызывают бинарный файл `bank-converter-cli` и пример `cli`: разбор параметров,
+//! определение формата, проверка и конвертация выписки выполняются одним кодом.
+//!
+//! ```no_run
+//! use std::env;
+//! use std::process::ExitCode;
+//!
+//! fn main() -> ExitCode {
+//!     bank_converter::cli::run(env::args().collect())
+//! }
+//! ```
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter, Read};
+use std::path::Path;
+use std::process::ExitCode;
+use crate::detect::sniff;
+use crate::encoding::TextEncoding;
+use crate::errors::ConvertError;
+use crate::models::camt053::CamtVersion;
+use crate::models::csv_flat::FlatCsv;
+use crate::models::csv_profile::CsvProfile;
+use crate::models::narrative::NarrativeDialect;
+use crate::pipeline::{Format, PipelineConverter};
+use crate::validate::validate;
+
+/// Выполняет команду `args` (первый элемент - имя программы) и возвращает код завершения:
+/// ошибка параметров, чтения, конвертации или найденные несоответствия выписки - `ExitCode::FAILURE`.
+pub fn run(mut args: Vec<String>) -> ExitCode {
+    // Если аргументов недостаточно, показываем справку
+    if args.len() < 2 {
+        eprintln!("Использование:");
+        eprintln!("  [validate] проверить согласованность выписки вместо конвертации");
+        eprintln!("  -i <file name>");
+        eprintln!("  -o <file name>");
+        eprintln!("  --in_format CSV|XML|MT940|MT942|CAMT053|XLSX|1C (по умолчанию определяется автоматически)");
+        eprintln!("  --out_format CSV|XML|MT940|MT942|CAMT053|XLSX|1C");
+        eprintln!("  --camt-version 02|04|08 версия схемы выходного файла CAMT053 (по умолчанию 02)");
+        eprintln!("  --mt940-dialect SWIFT|GVC|DUTCH|TEXT диалект поля :86: входного файла MT940 или MT942 (по умолчанию определяется по файлу)");
+        eprintln!("  --csv-profile sberbank|<file name> профиль разметки входного файла CSV, TOML или JSON (по умолчанию sberbank)");
+        eprintln!("  --csv-flat <разделитель колонок>[<разделитель дробной части>] входной или выходной CSV - плоская таблица, например \";,\"");
+        eprintln!("  --out-csv-flat <разделитель колонок>[<разделитель дробной части>] выходной CSV - плоская таблица (например, для CSV СберБизнес)");
+        eprintln!("  --sheet <name> лист входного файла XLSX (по умолчанию первый)");
+        eprintln!("  --encoding UTF-8|CP1251|CP866|KOI8-R кодировка входного файла (по умолчанию определяется автоматически)");
+        eprintln!("  --out-encoding UTF-8|CP1251|CP866|KOI8-R кодировка выходного файла (по умолчанию UTF-8, для 1C - CP1251)");
+        eprintln!("  --validate проверить согласованность выписки перед записью");
+        return ExitCode::SUCCESS;
+    }
+    let only_validate = args[1] == "validate";
+    if only_validate {
+        args.remove(1);
+    }
+    let mut validate_before_write = false;
+    let mut camt_version: Option<CamtVersion> = None;
+    let mut narrative_dialect: Option<NarrativeDialect> = None;
+    let mut csv_profile: Option<CsvProfile> = None;
+    let mut csv_flat: Option<FlatCsv> = None;
+    let mut out_csv_flat: Option<FlatCsv> = None;
+    let mut xlsx_sheet: Option<String> = None;
+    let mut encoding: Option<TextEncoding> = None;
+    let mut out_encoding: Option<TextEncoding> = None;
+    let mut data_in: Option<Format> = None;
+    let mut data_out: Option<Format> = None;
+    let mut in_file = String::new();
+    let mut out_file = String::new();
+    while args.len() > 1
+    {
+        let arg = args.remove(1);
+        if matches!(arg.as_str(), "-i" | "-o" | "--in_format" | "--out_format" | "--camt-version" | "--mt940-dialect"
+                       | "--csv-profile" | "--csv-flat" | "--out-csv-flat" | "--sheet" | "--encoding" | "--out-encoding")
+            && args.len() < 2 {
+            eprintln!("Не указано значение параметра {}", arg);
+            return ExitCode::FAILURE;
+        }
+        match arg.as_str(){
+            "--validate" => {
+                validate_before_write = true;
+            }
+            "-i" => {
+                in_file = args.remove(1);
+            }
+            "-o" => {
+                out_file = args.remove(1);
+            }
+            "--in_format" => {
+                match args.remove(1).parse() {
+                    Ok(format) => data_in = Some(format),
+                    Err(e) => {
+                        eprintln!("{}", e);
+                        return ExitCode::FAILURE;
+                    }
+                }
+            }
+            "--camt-version" => {
+                match args.remove(1).parse() {
+                    Ok(version) => camt_version = Some(version),
+                    Err(e) => {
+                        eprintln!("{}", e);
+                        return ExitCode::FAILURE;
+                    }
+                }
+            }
+            "--mt940-dialect" => {
+                match args.remove(1).parse() {
+                    Ok(dialect) => narrative_dialect = Some(dialect),
+                    Err(e) => {
+                        eprintln!("{}", e);
+                        return ExitCode::FAILURE;
+                    }
+                }
+            }
+            "--csv-profile" => {
+                match CsvProfile::load(&args.remove(1)) {
+                    Ok(profile) => csv_profile = Some(profile),
+                    Err(e) => {
+                        eprintln!("{}", e);
+                        return ExitCode::FAILURE;
+                    }
+                }
+            }
+            "--csv-flat" => {
+                match args.remove(1).parse() {
+                    Ok(flat) => csv_flat = Some(flat),
+                    Err(e) => {
+                        eprintln!("{}", e);
+                        return ExitCode::FAILURE;
+                    }
+                }
+            }
+            "--out-csv-flat" => {
+                match args.remove(1).parse() {
+                    Ok(flat) => out_csv_flat = Some(flat),
+                    Err(e) => {
+                        eprintln!("{}", e);
+                        return ExitCode::FAILURE;
+                    }
+                }
+            }
+            "--sheet" => {
+                xlsx_sheet = Some(args.remove(1));
+            }
+            "--encoding" => {
+                match args.remove(1).parse() {
+                    Ok(value) => encoding = Some(value),
+                    Err(e) => {
+                        eprintln!("{}", e);
+                        return ExitCode::FAILURE;
+                    }
+                }
+            }
+            "--out-encoding" => {
+                match args.remove(1).parse() {
+                    Ok(value) => out_encoding = Some(value),
+                    Err(e) => {
+                        eprintln!("{}", e);
+                        return ExitCode::FAILURE;
+                    }
+                }
+            }
+            "--out_format" => {
+                match args.remove(1).parse() {
+                    Ok(format) => data_out = Some(format),
+                    Err(e) => {
+                        eprintln!("{}", e);
+                        return ExitCode::FAILURE;
+                    }
+                }
+            }
+            arg => {
+                eprintln!("Неизвестная команда: {}", arg);
+                return ExitCode::FAILURE;
+            }
+        }
+    }
+    if in_file.is_empty() || (out_file.is_empty() && !only_validate)  {
+        eprintln!("Не указаны входной или выходной файл");
+        return ExitCode::FAILURE;
+    }
+    if !Path::new(&in_file).exists() {
+        eprintln!("Файл {} не существует", in_file);
+        return ExitCode::FAILURE;
+    }
+    let (detection, reader) = match sniff(File::open(in_file).unwrap()) {
+        Ok(result) => result,
+        Err(e) => {
+            eprintln!("{}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+    let data_in = match (data_in, detection) {
+        (Some(data_in), _) => data_in,
+        (None, Some(detection)) => {
+            println!("Определен формат входного файла {} (уверенность {:.2})",
+                     detection.format, detection.confidence);
+            detection.format
+        }
+        (None, None) => {
+            eprintln!("Не удалось определить формат входного файла, укажите --in_format");
+            return ExitCode::FAILURE;
+        }
+    };
+    if only_validate {
+        let mut converter = PipelineConverter::new(data_in, data_in);
+        if let Some(dialect) = narrative_dialect {
+            converter = converter.with_narrative_dialect(dialect);
+        }
+        if let Some(profile) = csv_profile {
+            converter = converter.with_csv_profile(profile);
+        }
+        if let Some(flat) = csv_flat {
+            converter = converter.with_flat_csv(flat);
+        }
+        if let Some(sheet) = &xlsx_sheet {
+            converter = converter.with_xlsx_sheet(sheet);
+        }
+        if let Some(encoding) = encoding {
+            converter = converter.with_encoding(encoding);
+        }
+        let camt = match converter.read_camt(&mut BufReader::new(reader)) {
+            Ok(camt) => camt,
+            Err(e) => {
+                eprintln!("{}", e);
+                return ExitCode::FAILURE;
+            }
+        };
+        let issues = validate(&camt);
+        if issues.is_empty() {
+            println!("Выписка согласована");
+            return ExitCode::SUCCESS;
+        }
+        for issue in &issues {
+            eprintln!("{}", issue);
+        }
+        return ExitCode::FAILURE;
+    }
+    let Some(data_out) = data_out else {
+        eprintln!("Не указан формат выходного файла");
+        return ExitCode::FAILURE;
+    };
+    let mut converter = PipelineConverter::new(data_in, data_out).with_validation(validate_before_write);
+    if let Some(camt_version) = camt_version {
+        converter = converter.with_camt_version(camt_version);
+    }
+    if let Some(dialect) = narrative_dialect {
+        converter = converter.with_narrative_dialect(dialect);
+    }
+    if let Some(profile) = csv_profile {
+        converter = converter.with_csv_profile(profile);
+    }
+    if let Some(flat) = csv_flat {
+        converter = converter.with_flat_csv(flat);
+    }
+    if let Some(flat) = out_csv_flat {
+        converter = converter.with_out_flat_csv(flat);
+    }
+    if let Some(sheet) = &xlsx_sheet {
+        converter = converter.with_xlsx_sheet(sheet);
+    }
+    if let Some(encoding) = encoding {
+        converter = converter.with_encoding(encoding);
+    }
+    if let Some(out_encoding) = out_encoding {
+        converter = converter.with_out_encoding(out_encoding);
+    }
+    let mut reader = BufReader::new(reader);
+    if let Err(e) = convert_to_file(&converter, &mut reader, &out_file) {
+        eprintln!("{}", e);
+        return ExitCode::FAILURE;
+    }
+    print!("Конвертация успешна!");
+    ExitCode::SUCCESS
+}
+
+/// Конвертирует `reader` в файл `out_file`. Результат пишется во временный файл в том же каталоге
+/// и переименовывается в `out_file` только после успешной конвертации: при ошибке выходной файл
+/// не создается и прежний файл с тем же именем не затирается.
+fn convert_to_file<R: Read>(converter: &PipelineConverter, reader: &mut R, out_file: &str) -> Result<(), ConvertError> {
+    let out_path = Path::new(out_file);
+    let Some(file_name) = out_path.file_name() else {
+        return Err(ConvertError::BadArgument(format!("Bad output file name {}", out_file)));
+    };
+    let temp_path = out_path.with_file_name(format!(".{}.{}.tmp", file_name.to_string_lossy(), std::process::id()));
+    let file = File::create(&temp_path)
+        .map_err(|e| ConvertError::WriteError(format!("Can't create file {}: {}", out_file, e)))?;
+    let result = (|| {
+        let mut writer = BufWriter::new(file);
+        converter.convert(reader, &mut writer)?;
+        writer.into_inner().map_err(|e| e.into_error())?.sync_all()?;
+        fs::rename(&temp_path, out_path)?;
+        Ok(())
+    })();
+    if result.is_err() {
+        let _ = fs::remove_file(&temp_path);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(values: &[&str]) -> Vec<String> {
+        std::iter::once("bank-converter").chain(values.iter().copied()).map(String::from).collect()
+    }
+
+    #[test]
+    fn test_run_rejects_bad_format() {
+        let input = "../test_files/MT940 github 1.mt940.txt";
+        assert_eq!(ExitCode::FAILURE, run(args(&["-i", input, "-o", "out.xml", "--out_format", "MT941"])));
+        assert_eq!(ExitCode::FAILURE, run(args(&["-i", input, "-o", "out.xml", "--in_format", "MT941"])));
+        assert_eq!(ExitCode::SUCCESS, run(args(&["validate", "-i", "../test_files/1CClientBankExchange.txt"])));
+    }
+
+    #[test]
+    fn test_run_output_file() {
+        let input = "../test_files/MT940 github 1.mt940.txt";
+        let dir = std::env::temp_dir().join(format!("bank-converter-cli-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let missing = dir.join("no such dir").join("out.xml");
+        assert_eq!(ExitCode::FAILURE, run(args(&["-i", input, "-o", missing.to_str().unwrap(), "--out_format", "CAMT053"])));
+
+        let out = dir.join("out.xml");
+        fs::write(&out, "previous").unwrap();
+        // у выписки MT940 нет даты отчета :13D: - конвертация в MT942 завершается ошибкой
+        assert_eq!(ExitCode::FAILURE, run(args(&["-i", input, "-o", out.to_str().unwrap(), "--out_format", "MT942"])));
+        assert_eq!("previous", fs::read_to_string(&out).unwrap());
+        assert_eq!(ExitCode::SUCCESS, run(args(&["-i", input, "-o", out.to_str().unwrap(), "--out_format", "CAMT053"])));
+        assert!(fs::read_to_string(&out).unwrap().contains("<BkToCstmrStmt>"));
+        assert_eq!(1, fs::read_dir(&dir).unwrap().count());
+
+        // выписка СберБизнес в плоскую таблицу: входной и выходной формат - CSV
+        let flat = dir.join("flat.csv");
+        assert_eq!(ExitCode::SUCCESS, run(args(&["-i", "../test_files/Пример выписки по счёту 1.csv", "-o", flat.to_str().unwrap(),
+                                                 "--out_format", "CSV", "--out-csv-flat", ";,"])));
+        assert!(fs::read_to_string(&flat).unwrap().starts_with("account;booking_date;"));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,3 +1,6 @@
 pub mod parser;
 mod reader;
 mod writer;
+pub(crate) mod camt_writer;
+pub(crate) mod camt_reader;
+mod xlsx_reader;
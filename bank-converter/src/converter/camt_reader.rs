@@ -0,0 +1,210 @@
+//! Чтение документа CAMT.053 с разрешением пространств имен
+//!
+//! События XML выдаются с локальными именами элементов, а различия версий схемы
+//! .001.02 - .001.11 приводятся к структуре модели `DocumentCamt053`.
+use std::borrow::Cow;
+use std::io::BufRead;
+use quick_xml::events::attributes::Attribute;
+use quick_xml::events::{BytesStart, BytesText, Event};
+use quick_xml::name::{QName, ResolveResult};
+use quick_xml::reader::NsReader;
+use quick_xml::Writer;
+use crate::errors::ParserError;
+use crate::models::camt053::CamtVersion;
+
+/// Открытый элемент исходного документа
+struct Frame {
+    /// Локальное имя элемента
+    name: String,
+    /// Имя элемента в выходных событиях или `None`, если элемент пропускается,
+    /// а его содержимое переносится в родительский элемент
+    emitted: Option<Cow<'static, str>>,
+    /// Текст элемента - дата и время, от которых оставляется только дата
+    date_only: bool,
+}
+
+/// Источник событий XML документа CAMT.053 с локальными именами элементов
+pub(crate) struct CamtEvents<R: BufRead> {
+    reader: NsReader<R>,
+    buf: Vec<u8>,
+    frames: Vec<Frame>,
+    namespace: Option<Vec<u8>>,
+    version: Option<CamtVersion>,
+    skip_depth: usize,
+    after_ref: bool,
+}
+
+impl<R: BufRead> CamtEvents<R> {
+    /// Создает источник событий документа из `r`.
+    pub(crate) fn new(r: R) -> Self {
+        let reader = NsReader::from_reader(r);
+        Self { reader, buf: Vec::new(), frames: Vec::new(), namespace: None, version: None, skip_depth: 0, after_ref: false }
+    }
+
+    /// Версия схемы документа; известна после чтения корневого элемента.
+    pub(crate) fn version(&self) -> Option<CamtVersion> {
+        self.version
+    }
+
+    /// Читает следующее событие или `None` в конце документа.
+    pub(crate) fn next_event(&mut self) -> Result<Option<Event<'static>>, ParserError> {
+        loop {
+            self.buf.clear();
+            let (resolved, event) = self.reader.read_resolved_event_into(&mut self.buf)?;
+            let namespace = match resolved {
+                ResolveResult::Bound(namespace) => Some(namespace.as_ref().to_vec()),
+                ResolveResult::Unbound => None,
+                ResolveResult::Unknown(prefix) => return Err(ParserError::BadInputFormatFile(
+                    format!("Unknown namespace prefix {}", String::from_utf8_lossy(&prefix)))),
+            };
+            let event = event.into_owned();
+            let after_ref = std::mem::replace(&mut self.after_ref, matches!(event, Event::GeneralRef(_)));
+            let in_namespace = self.check_namespace(namespace, &event)?;
+            if self.skip_depth > 0 {
+                match event {
+                    Event::Start(_) => self.skip_depth += 1,
+                    Event::End(_) => self.skip_depth -= 1,
+                    Event::Eof => return Ok(None),
+                    _ => {}
+                }
+                continue;
+            }
+            match event {
+                Event::Start(start) => {
+                    if !in_namespace {
+                        self.skip_depth = 1;
+                        continue;
+                    }
+                    let frame = self.frame(&start);
+                    let emitted = frame.emitted.clone().map(|name| element(name, &start));
+                    self.frames.push(frame);
+                    if let Some(start) = emitted {
+                        return Ok(Some(Event::Start(start)));
+                    }
+                }
+                Event::Empty(start) => {
+                    if let (true, Some(name)) = (in_namespace, self.frame(&start).emitted) {
+                        return Ok(Some(Event::Empty(element(name, &start))));
+                    }
+                }
+                Event::End(_) => {
+                    if let Some(Frame { emitted: Some(name), .. }) = self.frames.pop() {
+                        return Ok(Some(Event::End(BytesStart::new(name).to_end().into_owned())));
+                    }
+                }
+                Event::Text(text) if self.frames.last().is_some_and(|frame| frame.date_only) => {
+                    let value = String::from_utf8_lossy(&text).trim().chars().take(10).collect::<String>();
+                    return Ok(Some(Event::Text(BytesText::from_escaped(value))));
+                }
+                // отступы между элементами пропускаются, пробелы между ссылками на сущности сохраняются
+                Event::Text(text) if !after_ref && text.iter().all(u8::is_ascii_whitespace) => {}
+                Event::Text(_) | Event::CData(_) | Event::GeneralRef(_) => return Ok(Some(event)),
+                Event::Eof => return Ok(None),
+                _ => {}
+            }
+        }
+    }
+
+    /// Определяет версию по корневому элементу и проверяет, что элемент принадлежит
+    /// пространству имен документа. Элементы других пространств имен (расширения) пропускаются.
+    fn check_namespace(&mut self, namespace: Option<Vec<u8>>, event: &Event) -> Result<bool, ParserError> {
+        if !matches!(event, Event::Start(_) | Event::Empty(_)) {
+            return Ok(true);
+        }
+        if self.frames.is_empty() && self.skip_depth == 0 {
+            if let Some(namespace) = &namespace {
+                let namespace_str = String::from_utf8_lossy(namespace);
+                self.version = Some(CamtVersion::from_namespace(&namespace_str).ok_or_else(||
+                    ParserError::BadInputFormatFile(format!("Unsupported namespace {}", namespace_str)))?);
+            }
+            self.namespace = namespace;
+            return Ok(true);
+        }
+        Ok(namespace == self.namespace)
+    }
+
+    /// Сопоставляет элемент исходного документа элементу модели.
+    fn frame(&self, start: &BytesStart) -> Frame {
+        let name = String::from_utf8_lossy(start.local_name().as_ref()).into_owned();
+        let parent = self.frames.last().map_or("", |frame| frame.name.as_str());
+        let (emitted, date_only) = match (parent, name.as_str()) {
+            // .001.07+: Sts/Cd вместо текстового Sts
+            ("Sts", "Cd" | "Prtry") => (None, false),
+            // .001.03+: TtlNetNtry/Amt вместо TtlNetNtryAmt
+            ("TtlNtries", "TtlNetNtry") => (None, false),
+            ("TtlNetNtry", "Amt") => (Some("TtlNetNtryAmt".into()), false),
+            // .001.08+: участник Dbtr/Pty
+            ("Dbtr" | "Cdtr" | "UltmtDbtr" | "UltmtCdtr" | "InitgPty", "Pty") => (None, false),
+            ("BookgDt" | "ValDt" | "Dt", "DtTm") => (Some("Dt".into()), true),
+            (_, "BICFI") => (Some("BIC".into()), false),
+            (_, other) => (Some(other.to_string().into()), false),
+        };
+        Frame { name, emitted, date_only }
+    }
+}
+
+/// Элемент с локальным именем `name` и атрибутами без объявлений пространств имен.
+fn element(name: Cow<'static, str>, start: &BytesStart) -> BytesStart<'static> {
+    let mut element = BytesStart::new(name);
+    for attr in start.attributes().with_checks(false).flatten() {
+        if attr.key.as_namespace_binding().is_some() || attr.key.prefix().is_some() {
+            continue;
+        }
+        element.push_attribute(Attribute { key: QName(attr.key.local_name().into_inner()), value: attr.value });
+    }
+    element
+}
+
+/// Приводит документ CAMT.053 к структуре модели: локальные имена элементов без пространств имен.
+///
+/// # Возвращает
+///
+/// Документ XML и версию схемы, если документ объявляет пространство имен camt.053.
+pub(crate) fn normalize_document<R: BufRead>(r: R) -> Result<(String, Option<CamtVersion>), ParserError> {
+    let mut events = CamtEvents::new(r);
+    let mut writer = Writer::new(Vec::new());
+    while let Some(event) = events.next_event()? {
+        writer.write_event(event)?;
+    }
+    let xml = String::from_utf8(writer.into_inner())
+        .map_err(|e| ParserError::BadInputFormatFile(e.to_string()))?;
+    Ok((xml, events.version()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_prefixed_document() {
+        let xml = r#"<?xml version="1.0"?>
+            <ns2:Document xmlns:ns2="urn:iso:std:iso:20022:tech:xsd:camt.053.001.08" xmlns:ext="urn:bank:ext">
+              <ns2:BkToCstmrStmt><ns2:Stmt><ns2:Ntry>
+                <ns2:Amt Ccy="EUR">1.00</ns2:Amt><ns2:Sts><ns2:Cd>BOOK</ns2:Cd></ns2:Sts>
+                <ns2:BookgDt><ns2:DtTm>2024-01-02T10:00:00</ns2:DtTm></ns2:BookgDt>
+                <ns2:AddtlNtryInf>xmlns=&quot;text&quot; &amp; more</ns2:AddtlNtryInf>
+                <ext:Extra><ns2:Amt>2</ns2:Amt></ext:Extra>
+              </ns2:Ntry></ns2:Stmt></ns2:BkToCstmrStmt>
+            </ns2:Document>"#;
+        let (xml, version) = normalize_document(xml.as_bytes()).unwrap();
+        assert_eq!(Some(CamtVersion::V08), version);
+        assert_eq!("<Document><BkToCstmrStmt><Stmt><Ntry><Amt Ccy=\"EUR\">1.00</Amt><Sts>BOOK</Sts>\
+            <BookgDt><Dt>2024-01-02</Dt></BookgDt>\
+            <AddtlNtryInf>xmlns=&quot;text&quot; &amp; more</AddtlNtryInf></Ntry></Stmt></BkToCstmrStmt></Document>", xml);
+    }
+
+    #[test]
+    fn test_normalize_versions() {
+        let xml = r#"<Document xmlns="urn:iso:std:iso:20022:tech:xsd:camt.053.001.04"><TtlNtries>
+            <TtlNetNtry><Amt>5.00</Amt><CdtDbtInd>DBIT</CdtDbtInd></TtlNetNtry></TtlNtries>
+            <FinInstnId><BICFI>SABRRUMM</BICFI></FinInstnId></Document>"#;
+        let (xml, version) = normalize_document(xml.as_bytes()).unwrap();
+        assert_eq!(Some(CamtVersion::V04), version);
+        assert_eq!("<Document><TtlNtries><TtlNetNtryAmt>5.00</TtlNetNtryAmt><CdtDbtInd>DBIT</CdtDbtInd></TtlNtries>\
+            <FinInstnId><BIC>SABRRUMM</BIC></FinInstnId></Document>", xml);
+        let xml = r#"<Document xmlns="urn:iso:std:iso:20022:tech:xsd:camt.052.001.02"/>"#;
+        assert!(normalize_document(xml.as_bytes()).is_err());
+        let (_, version) = normalize_document("<Document><A/></Document>".as_bytes()).unwrap();
+        assert_eq!(None, version);
+    }
+}
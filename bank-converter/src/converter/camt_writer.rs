@@ -0,0 +1,646 @@
+//! Запись документа CAMT.053 по схеме ISO 20022
+//!
+//! Элементы записываются в порядке схемы, пустые необязательные элементы не записываются.
+use std::fmt::Display;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use chrono::Local;
+use quick_xml::events::{BytesDecl, BytesEnd, BytesStart, BytesText, Event};
+use quick_xml::Writer;
+use crate::errors::ConvertError;
+use crate::models::camt053::{AcctAttribute, AmtAttribute, BalanceAttribute, BxTxCdAttribute, CamtVersion,
+                             CcyXchgAttribute, HeaderAttribute, IdIbanAttribute, IdTxDtlsAttribute, NtryAttribute,
+                             OtherAttribute, PostalAddressAttribute, PrtryAmtAttribute, PrvtIdAttribute,
+                             StatementAttribute, SvcrAttribute, TxDtlsAttribute, TxsSummryAttribute};
+use crate::models::date::IsoDateTime;
+
+/// Элемент XML, собираемый перед записью
+pub(crate) struct XmlElement {
+    name: &'static str,
+    attrs: Vec<(&'static str, String)>,
+    text: Option<String>,
+    children: Vec<XmlElement>,
+    required: bool,
+}
+
+impl XmlElement {
+    /// Создает составной элемент `name`.
+    pub(crate) fn new(name: &'static str) -> Self {
+        Self { name, attrs: Vec::new(), text: None, children: Vec::new(), required: false }
+    }
+
+    /// Создает элемент `name` с текстом `value`.
+    pub(crate) fn leaf(name: &'static str, value: impl Display) -> Self {
+        let mut element = Self::new(name);
+        element.text = Some(value.to_string().trim().to_string());
+        element
+    }
+
+    /// Добавляет атрибут, если его значение не пустое.
+    pub(crate) fn attr(mut self, name: &'static str, value: &str) -> Self {
+        if !value.is_empty() {
+            self.attrs.push((name, value.to_string()));
+        }
+        self
+    }
+
+    /// Помечает элемент обязательным: он записывается, даже если пуст.
+    pub(crate) fn required(mut self) -> Self {
+        self.required = true;
+        self
+    }
+
+    /// Добавляет дочерний элемент, если он не пуст.
+    pub(crate) fn child(mut self, child: XmlElement) -> Self {
+        if !child.is_empty() {
+            self.children.push(child);
+        }
+        self
+    }
+
+    /// Добавляет дочерний элемент `name` с текстом `value`, если текст не пуст.
+    pub(crate) fn text(self, name: &'static str, value: impl Display) -> Self {
+        self.child(Self::leaf(name, value))
+    }
+
+    /// Добавляет дочерние элементы, пропуская пустые.
+    pub(crate) fn children(self, children: impl IntoIterator<Item = XmlElement>) -> Self {
+        children.into_iter().fold(self, Self::child)
+    }
+
+    /// Элемент не содержит данных и не обязателен.
+    pub(crate) fn is_empty(&self) -> bool {
+        !self.required && self.children.is_empty() && self.text.as_deref().is_none_or(str::is_empty)
+    }
+
+    /// Записывает элемент и его дочерние элементы.
+    pub(crate) fn write<W: Write>(&self, writer: &mut Writer<W>) -> Result<(), ConvertError> {
+        let mut start = BytesStart::new(self.name);
+        for (name, value) in &self.attrs {
+            start.push_attribute((*name, value.as_str()));
+        }
+        match (&self.text, self.children.is_empty()) {
+            (Some(text), _) if !text.is_empty() => {
+                writer.write_event(Event::Start(start))?;
+                writer.write_event(Event::Text(BytesText::new(text)))?;
+                writer.write_event(Event::End(BytesEnd::new(self.name)))?;
+            }
+            (_, true) => writer.write_event(Event::Empty(start))?,
+            (_, false) => {
+                writer.write_event(Event::Start(start))?;
+                for child in &self.children {
+                    child.write(writer)?;
+                }
+                writer.write_event(Event::End(BytesEnd::new(self.name)))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Потоковая запись документа CAMT.053: заголовок, выписки, завершение документа
+pub(crate) struct CamtWriter<W: Write> {
+    writer: Writer<W>,
+    version: CamtVersion,
+}
+
+impl<W: Write> CamtWriter<W> {
+    /// Создает запись в `w` по схеме версии `version`.
+    pub(crate) fn new(w: W, version: CamtVersion) -> Self {
+        Self { writer: Writer::new_with_indent(w, b' ', 2), version }
+    }
+
+    /// Записывает начало документа и заголовок сообщения GrpHdr.
+    pub(crate) fn start(&mut self, grp_hdr: &HeaderAttribute, first: Option<&StatementAttribute>) -> Result<(), ConvertError> {
+        let namespace = self.version.namespace();
+        let schema_location = format!("{} {}.xsd", namespace, self.version.code());
+        self.writer.write_event(Event::Decl(BytesDecl::new("1.0", Some("UTF-8"), None)))?;
+        let document = BytesStart::new("Document")
+            .with_attributes([("xmlns", namespace.as_str()),
+                ("xmlns:xsi", "http://www.w3.org/2001/XMLSchema-instance"),
+                ("xsi:schemaLocation", schema_location.as_str())]);
+        self.writer.write_event(Event::Start(document))?;
+        self.writer.write_event(Event::Start(BytesStart::new("BkToCstmrStmt")))?;
+        let msg_id = match (grp_hdr.msg_id.is_empty(), first) {
+            (true, Some(stmt)) => statement_id(stmt),
+            _ => grp_hdr.msg_id.clone(),
+        };
+        let cre_dt_tm = grp_hdr.cre_dt_tm
+            .or_else(|| first.and_then(|stmt| stmt.cre_dt_tm))
+            .unwrap_or_else(|| IsoDateTime::from(Local::now().fixed_offset()));
+        XmlElement::new("GrpHdr")
+            .text("MsgId", msg_id)
+            .text("CreDtTm", cre_dt_tm)
+            .write(&mut self.writer)
+    }
+
+    /// Записывает начало выписки Stmt: реквизиты и остатки. Итоги записываются отдельно
+    /// (см. [`CamtWriter::write_summary`]), когда они рассчитаны по записям.
+    pub(crate) fn start_statement(&mut self, stmt: &StatementAttribute, cre_dt_tm: Option<IsoDateTime>) -> Result<(), ConvertError> {
+        let head = statement_head(stmt, cre_dt_tm, self.version)?;
+        self.writer.write_event(Event::Start(BytesStart::new("Stmt")))?;
+        for element in head {
+            if !element.is_empty() {
+                element.write(&mut self.writer)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Записывает итоги выписки TxsSummry, если они не пусты.
+    pub(crate) fn write_summary(&mut self, txs_summry: &TxsSummryAttribute) -> Result<(), ConvertError> {
+        let element = summary(txs_summry, self.version);
+        if !element.is_empty() {
+            element.write(&mut self.writer)?;
+        }
+        Ok(())
+    }
+
+    /// Переносит в документ записи Ntry, накопленные в `spool`, и очищает его.
+    pub(crate) fn write_spooled(&mut self, spool: &mut EntrySpool) -> Result<(), ConvertError> {
+        spool.drain_into(self.writer.get_mut())
+    }
+
+    /// Записывает конец выписки Stmt.
+    pub(crate) fn end_statement(&mut self) -> Result<(), ConvertError> {
+        self.writer.write_event(Event::End(BytesEnd::new("Stmt")))?;
+        Ok(())
+    }
+
+    /// Записывает конец документа и возвращает исходный writer.
+    pub(crate) fn finish(mut self) -> Result<W, ConvertError> {
+        self.writer.write_event(Event::End(BytesEnd::new("BkToCstmrStmt")))?;
+        self.writer.write_event(Event::End(BytesEnd::new("Document")))?;
+        let mut w = self.writer.into_inner();
+        w.write_all(b"\n")?;
+        w.flush()?;
+        Ok(w)
+    }
+}
+
+/// Номер следующего временного файла записей процесса
+static SPOOL_NUMBER: AtomicUsize = AtomicUsize::new(0);
+
+/// Записи Ntry одной выписки во временном файле
+///
+/// Итоги TxsSummry в схеме стоят до записей, а рассчитываются по ним, поэтому при потоковой записи
+/// записи выписки сначала записываются в XML во временный файл и переносятся в документ после итогов:
+/// объем памяти не зависит от числа записей. Отступы записей совпадают с отступами в документе.
+/// Файл удаляется вместе с хранилищем.
+pub(crate) struct EntrySpool {
+    writer: Writer<BufWriter<File>>,
+    path: PathBuf,
+    start: u64,
+    version: CamtVersion,
+}
+
+impl EntrySpool {
+    /// Создает временный файл записей по схеме версии `version`.
+    pub(crate) fn new(version: CamtVersion) -> Result<Self, ConvertError> {
+        let path = std::env::temp_dir().join(format!("bank-converter-{}-{}.ntry.xml", std::process::id(),
+                                                     SPOOL_NUMBER.fetch_add(1, Ordering::Relaxed)));
+        let file = OpenOptions::new().read(true).write(true).create_new(true).open(&path)?;
+        let mut writer = Writer::new_with_indent(BufWriter::new(file), b' ', 2);
+        // записи вложены в Document/BkToCstmrStmt/Stmt; начальные элементы в документ не переносятся
+        for name in ["Document", "BkToCstmrStmt", "Stmt"] {
+            writer.write_event(Event::Start(BytesStart::new(name)))?;
+        }
+        let start = writer.get_mut().stream_position()?;
+        Ok(Self { writer, path, start, version })
+    }
+
+    /// Записывает запись Ntry во временный файл.
+    pub(crate) fn write_entry(&mut self, ntry: &NtryAttribute) -> Result<(), ConvertError> {
+        entry(ntry, self.version).write(&mut self.writer)
+    }
+
+    /// Объем записей во временном файле в байтах.
+    pub(crate) fn len(&mut self) -> Result<u64, ConvertError> {
+        Ok(self.writer.get_mut().stream_position()? - self.start)
+    }
+
+    /// Копирует записи в `w` и очищает временный файл для следующей выписки.
+    fn drain_into<W: Write>(&mut self, w: &mut W) -> Result<(), ConvertError> {
+        let len = self.len()?;
+        let buffered = self.writer.get_mut();
+        buffered.seek(SeekFrom::Start(self.start))?;
+        let file = buffered.get_mut();
+        io::copy(&mut Read::by_ref(file).take(len), w)?;
+        file.set_len(self.start)?;
+        buffered.seek(SeekFrom::Start(self.start))?;
+        Ok(())
+    }
+}
+
+impl Drop for EntrySpool {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Идентификатор выписки; если он не задан (выписка CSV), составляется из номера счета и даты окончания периода.
+fn statement_id(stmt: &StatementAttribute) -> String {
+    if !stmt.id.is_empty() {
+        return stmt.id.clone();
+    }
+    let acct = [&stmt.acct.id.iban, &stmt.acct.id.othr.id].into_iter()
+        .find(|id| !id.is_empty())
+        .map_or("STMT", |id| id.as_str());
+    match stmt.fr_to_dt.to_dt_tm {
+        Some(to_dt_tm) => format!("{}-{}", acct, to_dt_tm.date().format("%Y%m%d")),
+        None => acct.to_string(),
+    }
+}
+
+/// Порядковый номер выписки: число, при записи MT940 вида `5/1` - номер до `/`.
+fn sequence_number(value: &str) -> &str {
+    let number = value.split('/').next().unwrap_or_default().trim();
+    if !number.is_empty() && number.chars().all(|c| c.is_ascii_digit()) { number } else { "" }
+}
+
+fn amount(name: &'static str, amt: &AmtAttribute) -> XmlElement {
+    if amt.ccy.is_empty() && amt.amt.is_zero() {
+        return XmlElement::new(name);
+    }
+    XmlElement::leaf(name, amt.amt).attr("Ccy", &amt.ccy)
+}
+
+fn postal_address(adr: &PostalAddressAttribute) -> XmlElement {
+    XmlElement::new("PstlAdr")
+        .text("StrtNm", &adr.strt_nm)
+        .text("BldgNb", &adr.bldg_nb)
+        .text("PstCd", &adr.pst_cd)
+        .text("TwnNm", &adr.twn_nm)
+        .text("Ctry", &adr.ctry)
+        .children(adr.adr_line.iter().map(|line| XmlElement::leaf("AdrLine", line)))
+}
+
+fn other_id(othr: &OtherAttribute) -> XmlElement {
+    if othr.id.is_empty() {
+        return XmlElement::new("Othr");
+    }
+    XmlElement::new("Othr")
+        .text("Id", &othr.id)
+        .child(XmlElement::new("SchmeNm").text("Cd", &othr.schme_nm.cd))
+}
+
+fn account_id(id: &IdIbanAttribute) -> XmlElement {
+    if !id.iban.is_empty() {
+        return XmlElement::new("Id").text("IBAN", &id.iban);
+    }
+    XmlElement::new("Id").child(other_id(&id.othr))
+}
+
+/// Идентификатор счета выписки Acct/Id. Читатель MT940 хранит номер счета :25: в `Ownr/Id`,
+/// поэтому без IBAN и `Othr/Id` номер берется оттуда; пустой элемент, если номера нет нигде.
+fn statement_account_id(acct: &AcctAttribute) -> XmlElement {
+    let id = account_id(&acct.id);
+    if !id.is_empty() {
+        return id;
+    }
+    XmlElement::new("Id").child(other_id(&acct.ownr.id.org_id.othr))
+}
+
+fn party_account(name: &'static str, acct: &IdTxDtlsAttribute) -> XmlElement {
+    let id = if !acct.id.iban.is_empty() {
+        account_id(&acct.id)
+    } else {
+        XmlElement::new("Id").child(XmlElement::new("Othr").text("Id", acct.account()))
+    };
+    XmlElement::new(name).child(id)
+}
+
+fn party(name: &'static str, nm: &str, pstl_adr: &PostalAddressAttribute, id: &PrvtIdAttribute,
+         version: CamtVersion) -> XmlElement {
+    let party = XmlElement::new(if version >= CamtVersion::V08 { "Pty" } else { name })
+        .text("Nm", nm)
+        .child(postal_address(pstl_adr))
+        .child(XmlElement::new("Id")
+            .child(XmlElement::new("OrgId").child(XmlElement::new("Othr").text("Id", &id.othr.id))));
+    if version >= CamtVersion::V08 {
+        return XmlElement::new(name).child(party);
+    }
+    party
+}
+
+fn agent(name: &'static str, svcr: &SvcrAttribute, version: CamtVersion) -> XmlElement {
+    let bic = if version >= CamtVersion::V04 { "BICFI" } else { "BIC" };
+    XmlElement::new(name).child(XmlElement::new("FinInstnId")
+        .text(bic, &svcr.fin_instn_id.bic)
+        .text("Nm", &svcr.fin_instn_id.nm)
+        .child(postal_address(&svcr.fin_instn_id.pstl_adr)))
+}
+
+fn account(acct: &AcctAttribute, version: CamtVersion) -> XmlElement {
+    let ownr = &acct.ownr;
+    let mut pstl_adr = ownr.pstl_adr.clone();
+    if pstl_adr.bldg_nb.is_empty() && ownr.bldg_nb != 0 {
+        pstl_adr.bldg_nb = ownr.bldg_nb.to_string();
+    }
+    if pstl_adr.pst_cd.is_empty() && ownr.pst_cd != 0 {
+        pstl_adr.pst_cd = ownr.pst_cd.to_string();
+    }
+    if pstl_adr.twn_nm.is_empty() {
+        pstl_adr.twn_nm = ownr.twn_nm.clone();
+    }
+    if pstl_adr.ctry.is_empty() {
+        pstl_adr.ctry = ownr.ctry.clone();
+    }
+    XmlElement::new("Acct")
+        .child(statement_account_id(acct).required())
+        .text("Ccy", &acct.ccy)
+        .text("Nm", &acct.nm)
+        .child(XmlElement::new("Ownr")
+            .text("Nm", &ownr.nm)
+            .child(postal_address(&pstl_adr))
+            .child(XmlElement::new("Id").child(XmlElement::new("OrgId").child(other_id(&ownr.id.org_id.othr)))))
+        .child(agent("Svcr", &acct.svcr, version))
+}
+
+fn balance(bal: &BalanceAttribute) -> XmlElement {
+    XmlElement::new("Bal")
+        .child(XmlElement::new("Tp").child(XmlElement::new("CdOrPrtry").text("Cd", bal.tp.cd_or_prtry.cd)))
+        .child(amount("Amt", &bal.amt).required())
+        .text("CdtDbtInd", bal.cdt_dbt_ind)
+        .child(XmlElement::new("Dt").text("Dt", bal.dt.dt.map(|dt| dt.to_string()).unwrap_or_default()))
+}
+
+fn summary(txs_summry: &TxsSummryAttribute, version: CamtVersion) -> XmlElement {
+    if txs_summry.is_empty() {
+        return XmlElement::new("TxsSummry");
+    }
+    let ttl_ntries = &txs_summry.ttl_ntries;
+    let cdt_dbt_ind = ttl_ntries.cdt_dbt_ind.map(|cd| cd.to_string()).unwrap_or_default();
+    let nb_of_ntries = ttl_ntries.nb_of_ntries.map(|count| count.to_string()).unwrap_or_default();
+    let mut total = XmlElement::new("TtlNtries").text("NbOfNtries", &nb_of_ntries);
+    if !cdt_dbt_ind.is_empty() {
+        total = if version >= CamtVersion::V03 {
+            total.child(XmlElement::new("TtlNetNtry")
+                .text("Amt", ttl_ntries.ttl_net_ntry_amt)
+                .text("CdtDbtInd", &cdt_dbt_ind))
+        } else {
+            total.text("TtlNetNtryAmt", ttl_ntries.ttl_net_ntry_amt).text("CdtDbtInd", &cdt_dbt_ind)
+        };
+    }
+    let side = |name, ttl: &crate::models::camt053::TtlCdtDbtNtriesAttribute| XmlElement::new(name)
+        .text("NbOfNtries", ttl.nb_of_ntries)
+        .text("Sum", ttl.sum);
+    let mut element = XmlElement::new("TxsSummry").child(total);
+    // итоги по кредиту и дебету записываются, только если они есть в выписке или рассчитаны по записям
+    for (name, ttl) in [("TtlCdtNtries", &txs_summry.ttl_cdt_ntries), ("TtlDbtNtries", &txs_summry.ttl_dbt_ntries)] {
+        if let Some(ttl) = ttl {
+            element = element.child(side(name, ttl));
+        }
+    }
+    element
+}
+
+/// Элементы выписки Stmt до итогов TxsSummry в порядке схемы.
+///
+/// Счет выписки Acct/Id обязателен: для счета без IBAN и `Othr/Id` возвращается `ConvertError::WriteError`.
+pub(crate) fn statement_head(stmt: &StatementAttribute, cre_dt_tm: Option<IsoDateTime>,
+                             version: CamtVersion) -> Result<Vec<XmlElement>, ConvertError> {
+    if statement_account_id(&stmt.acct).is_empty() {
+        return Err(ConvertError::WriteError(format!("Statement {}: no account id (IBAN or Othr/Id)", stmt.id)));
+    }
+    let cre_dt_tm = stmt.cre_dt_tm.or(cre_dt_tm).map(|dt| dt.to_string()).unwrap_or_default();
+    let fr_to_dt = match (stmt.fr_to_dt.fr_dt_tm, stmt.fr_to_dt.to_dt_tm) {
+        (Some(fr_dt_tm), Some(to_dt_tm)) => XmlElement::new("FrToDt")
+            .text("FrDtTm", fr_dt_tm)
+            .text("ToDtTm", to_dt_tm),
+        _ => XmlElement::new("FrToDt"),
+    };
+    let mut head = vec![
+        XmlElement::leaf("Id", statement_id(stmt)),
+        XmlElement::leaf("ElctrncSeqNb", sequence_number(&stmt.elctrnc_seq_nb)),
+        XmlElement::leaf("LglSeqNb", sequence_number(&stmt.lgl_seq_nb)),
+        XmlElement::leaf("CreDtTm", cre_dt_tm),
+        fr_to_dt,
+        account(&stmt.acct, version).required(),
+    ];
+    head.extend(stmt.bal.iter().map(balance));
+    Ok(head)
+}
+
+fn bank_tx_code(bk_tx_cd: &BxTxCdAttribute) -> XmlElement {
+    let domn = &bk_tx_cd.domn;
+    XmlElement::new("BkTxCd")
+        .child(XmlElement::new("Domn")
+            .text("Cd", &domn.cd)
+            .child(XmlElement::new("Fmly").text("Cd", &domn.fmly.cd).text("SubFmlyCd", &domn.fmly.sub_fmly_cd)))
+        .child(XmlElement::new("Prtry").text("Cd", &bk_tx_cd.prtry.cd).text("Issr", &bk_tx_cd.prtry.issr))
+}
+
+fn currency_exchange(ccy_xchg: &CcyXchgAttribute) -> XmlElement {
+    if ccy_xchg.src_ccy.is_empty() || ccy_xchg.xchg_rate.is_empty() {
+        return XmlElement::new("CcyXchg");
+    }
+    XmlElement::new("CcyXchg")
+        .text("SrcCcy", &ccy_xchg.src_ccy)
+        .text("TrgtCcy", &ccy_xchg.trgt_ccy)
+        .text("UnitCcy", &ccy_xchg.unit_ccy)
+        .text("XchgRate", &ccy_xchg.xchg_rate)
+}
+
+fn amount_and_exchange(name: &'static str, amt: &PrtryAmtAttribute) -> XmlElement {
+    let element = XmlElement::new(name).child(amount("Amt", &amt.amt));
+    if element.is_empty() {
+        return element;
+    }
+    element.child(currency_exchange(&amt.ccy_xchg))
+}
+
+fn transaction(tx: &TxDtlsAttribute, version: CamtVersion) -> XmlElement {
+    let refs = &tx.refs;
+    let pties = &tx.rltd_pties;
+    let mut prtry_amt = XmlElement::new("PrtryAmt");
+    if !tx.amt_dtls.prtry_amt.tp.is_empty() {
+        prtry_amt = prtry_amt.text("Tp", &tx.amt_dtls.prtry_amt.tp).child(amount("Amt", &tx.amt_dtls.prtry_amt.amt));
+    }
+    let cdtr_ref_inf = &tx.rmt_inf.strd.cdtr_ref_inf;
+    XmlElement::new("TxDtls")
+        .child(XmlElement::new("Refs")
+            .text("PmtInfId", &refs.pmt_inf_id)
+            .text("InstrId", &refs.instr_id)
+            .text("EndToEndId", &refs.end_to_end_id)
+            .text("TxId", &refs.tx_id)
+            .child(XmlElement::new("Prtry").text("Tp", &refs.prtry.tp).text("Ref", &refs.prtry.refdt)))
+        .child(XmlElement::new("AmtDtls")
+            .child(amount_and_exchange("InstdAmt", &tx.amt_dtls.instd_amt))
+            .child(amount_and_exchange("TxAmt", &tx.amt_dtls.tx_amt))
+            .child(prtry_amt))
+        .child(bank_tx_code(&tx.bk_tx_cd))
+        .child(XmlElement::new("RltdPties")
+            .child(party("Dbtr", &pties.dbtr.nm, &pties.dbtr.pstl_adr, &pties.dbtr.id, version))
+            .child(party_account("DbtrAcct", &pties.dbtr_acct))
+            .child(party("Cdtr", &pties.cdtr.nm, &pties.cdtr.pstl_adr, &pties.cdtr.id, version))
+            .child(party_account("CdtrAcct", &pties.cdtr_acct)))
+        .child(XmlElement::new("RltdAgts")
+            .child(agent("DbtrAgt", &tx.rltd_agts.dbtr_agt, version))
+            .child(agent("CdtrAgt", &tx.rltd_agts.cdtr_agt, version)))
+        .child(XmlElement::new("RmtInf")
+            .children(tx.rmt_inf.ustrd.iter().map(|ustrd| XmlElement::leaf("Ustrd", ustrd)))
+            .child(XmlElement::new("Strd").child(XmlElement::new("CdtrRefInf")
+                .child(XmlElement::new("Tp").child(XmlElement::new("CdOrPrtry").text("Cd", &cdtr_ref_inf.tp.cd_or_prtry.cd)))
+                .text("Ref", &cdtr_ref_inf.ref_cdtr))))
+        .child(XmlElement::new("RltdDts")
+            .text("AccptncDtTm", tx.rltd_dts.accptnc_dt_tm.map(|dt| dt.to_string()).unwrap_or_default()))
+        .child(XmlElement::new("Tax")
+            .child(XmlElement::new("Cdtr").text("RegnId", &tx.tax.cdtr.regn_id))
+            .child(XmlElement::new("Dbtr").text("RegnId", &tx.tax.dbtr.regn_id)))
+        .text("AddtlTxInf", &tx.addtl_tx_inf)
+}
+
+/// Запись выписки Ntry в порядке схемы.
+pub(crate) fn entry(ntry: &NtryAttribute, version: CamtVersion) -> XmlElement {
+    let sts = if ntry.sts.trim().is_empty() { "BOOK" } else { ntry.sts.trim() };
+    let sts = if version >= CamtVersion::V07 {
+        XmlElement::new("Sts").text("Cd", sts)
+    } else {
+        XmlElement::leaf("Sts", sts)
+    };
+    let date = |name, dt: Option<chrono::NaiveDate>| XmlElement::new(name)
+        .text("Dt", dt.map(|dt| dt.to_string()).unwrap_or_default());
+    let ntry_ref = if ntry.ntry_ref == 0 { String::new() } else { ntry.ntry_ref.to_string() };
+    let btch = if ntry.ntry_dtls.btch.nb_of_txs == 0 { String::new() } else { ntry.ntry_dtls.btch.nb_of_txs.to_string() };
+    XmlElement::new("Ntry")
+        .text("NtryRef", ntry_ref)
+        .child(amount("Amt", &ntry.amt).required())
+        .text("CdtDbtInd", ntry.cdt_dbt_ind)
+        .text("RvslInd", if ntry.rvsl_ind { "true" } else { "" })
+        .child(sts)
+        .child(date("BookgDt", ntry.bookg_dt.dt))
+        .child(date("ValDt", ntry.val_dt.dt))
+        .text("AcctSvcrRef", &ntry.acct_svcr_ref)
+        .child(bank_tx_code(&ntry.bk_tx_cd).required())
+        .child(XmlElement::new("AddtlInfInd").text("MsgNmId", &ntry.addtl_inf_ind.msg_nm_id))
+        .child(XmlElement::new("NtryDtls")
+            .child(XmlElement::new("Btch").text("NbOfTxs", btch))
+            .children(ntry.ntry_dtls.tx_dtls.iter().map(|tx| transaction(tx, version))))
+        .text("AddtlNtryInf", &ntry.addtl_ntry_inf)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+    use crate::models::builder::{BalanceBuilder, EntryBuilder, PartyBuilder, StatementBuilder};
+    use crate::models::camt053::{DocumentCamt053, TtlCdtDbtNtriesAttribute};
+    use crate::models::codes::BalanceType;
+    use crate::models::money::Money;
+
+    fn document() -> DocumentCamt053 {
+        let date = NaiveDate::from_ymd_opt(2024, 1, 2).unwrap();
+        let statement = StatementBuilder::new("STMT-1")
+            .msg_id("MSG-1")
+            .created("2024-01-03T10:00:00+03:00".parse().unwrap())
+            .sequence("5/1", "")
+            .account("40702810440000030888", "RUB")
+            .balance(BalanceBuilder::new(BalanceType::Opbd).amount(Money::new(10000, 2), "RUB").credit().date(date).build())
+            .entry(EntryBuilder::new().amount(Money::new(1000, 2), "RUB").debit().booking_date(date)
+                .creditor(PartyBuilder::new("ООО Ромашка").id("7735602068").kpp("773501001").account("40702810400000000001").build())
+                .build())
+            .balance(BalanceBuilder::new(BalanceType::Clbd).amount(Money::new(9000, 2), "RUB").credit().date(date).build())
+            .build();
+        DocumentCamt053 { version: None, bk_to_cstmr_stmt: vec![statement] }
+    }
+
+    fn write(version: CamtVersion) -> String {
+        let mut output = Vec::new();
+        document().write_to_version(&mut output, version).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn test_write_omits_empty_elements() {
+        let xml = write(CamtVersion::V02);
+        assert!(xml.contains("xmlns=\"urn:iso:std:iso:20022:tech:xsd:camt.053.001.02\""));
+        for empty in ["<IBAN>", "<IBAN/>", "<BldgNb>", "<PstlAdr>", "<NtryRef>", "<Btch>", "<AddtlTxInf>", "<Domn>"] {
+            assert!(!xml.contains(empty), "{} in {}", empty, xml);
+        }
+        assert!(xml.contains("<ElctrncSeqNb>5</ElctrncSeqNb>"));
+        assert!(xml.contains("<Sts>BOOK</Sts>"));
+        assert!(xml.contains("<BkTxCd/>"));
+        assert!(xml.contains("<OrgId>\n                    <Othr>\n                      <Id>7735602068</Id>"));
+        assert!(xml.contains("<TtlNetNtryAmt>10.00</TtlNetNtryAmt>"));
+    }
+
+    #[test]
+    fn test_write_account_id() {
+        for version in [CamtVersion::V02, CamtVersion::V04, CamtVersion::V08] {
+            let mut document = document();
+            document.bk_to_cstmr_stmt[0].stmt.acct.id.othr.id.clear();
+            let mut output = Vec::new();
+            let error = document.write_to_version(&mut output, version).unwrap_err();
+            assert!(error.to_string().contains("STMT-1: no account id"), "{}", error);
+            assert!(!String::from_utf8(output).unwrap().contains("<Stmt>"));
+
+            // номер счета :25: выписки MT940 хранится в Ownr/Id
+            document.bk_to_cstmr_stmt[0].stmt.acct.ownr.id.org_id.othr.id = "NL81ASNB9999999999".to_string();
+            let mut output = Vec::new();
+            document.write_to_version(&mut output, version).unwrap();
+            let xml: String = String::from_utf8(output).unwrap().split_whitespace().collect();
+            assert!(xml.contains("<Acct><Id><Othr><Id>NL81ASNB9999999999</Id></Othr></Id>"), "{}", xml);
+        }
+    }
+
+    #[test]
+    fn test_write_summary_sides() {
+        let mut txs_summry = TxsSummryAttribute::default();
+        txs_summry.ttl_ntries.nb_of_ntries = Some(1);
+        txs_summry.ttl_dbt_ntries = Some(TtlCdtDbtNtriesAttribute { nb_of_ntries: 1, sum: Money::new(1000, 2) });
+        let mut writer = Writer::new(Vec::new());
+        summary(&txs_summry, CamtVersion::V02).write(&mut writer).unwrap();
+        assert_eq!("<TxsSummry><TtlNtries><NbOfNtries>1</NbOfNtries></TtlNtries>\
+            <TtlDbtNtries><NbOfNtries>1</NbOfNtries><Sum>10.00</Sum></TtlDbtNtries></TxsSummry>",
+                   String::from_utf8(writer.into_inner()).unwrap());
+    }
+
+    #[test]
+    fn test_write_element_order() {
+        let xml = write(CamtVersion::V02);
+        let order = ["<GrpHdr>", "<MsgId>MSG-1", "<Stmt>", "<Id>STMT-1", "<CreDtTm>", "<Acct>",
+            "<Cd>OPBD", "<Cd>CLBD", "<TxsSummry>", "<Ntry>", "<Amt Ccy=\"RUB\">10.00", "<CdtDbtInd>DBIT",
+            "<Sts>", "<BookgDt>", "<BkTxCd/>", "<NtryDtls>"];
+        let mut position = 0;
+        for tag in order {
+            position += xml[position..].find(tag).unwrap_or_else(|| panic!("{} out of order in {}", tag, xml));
+        }
+    }
+
+    #[test]
+    fn test_write_versions() {
+        let xml = write(CamtVersion::V04);
+        assert!(xml.contains("camt.053.001.04"));
+        assert!(xml.contains("<TtlNetNtry>\n"));
+        let xml = write(CamtVersion::V08);
+        assert!(xml.contains("camt.053.001.08"));
+        assert!(xml.contains("<Sts>\n          <Cd>BOOK</Cd>"));
+        assert_eq!(CamtVersion::V08, "camt.053.001.08".parse().unwrap());
+        assert_eq!(CamtVersion::V04, "04".parse().unwrap());
+        assert!("12".parse::<CamtVersion>().is_err());
+        let mut output = Vec::new();
+        assert!(document().write_to_version(&mut output, CamtVersion::V05).is_err());
+    }
+
+    #[test]
+    fn test_write_read_round_trip() {
+        for version in [CamtVersion::V02, CamtVersion::V04, CamtVersion::V08] {
+            let xml = write(version);
+            let mut read = DocumentCamt053::from_read(&mut xml.as_bytes()).unwrap();
+            assert_eq!(Some(version), read.version);
+            let stmt = &read.bk_to_cstmr_stmt[0].stmt;
+            assert_eq!("MSG-1", read.bk_to_cstmr_stmt[0].grp_hdr.msg_id);
+            assert_eq!(2, stmt.bal.len());
+            assert_eq!("BOOK", stmt.ntry[0].sts);
+            assert_eq!("7735602068", stmt.ntry[0].ntry_dtls.tx_dtls[0].rltd_pties.cdtr.id.othr.id);
+            assert_eq!("40702810400000000001", stmt.ntry[0].ntry_dtls.tx_dtls[0].rltd_pties.cdtr_acct.account());
+            assert_eq!("773501001", stmt.ntry[0].ntry_dtls.tx_dtls[0].tax.cdtr.regn_id);
+            let mut output = Vec::new();
+            read.write_to(&mut output).unwrap();
+            assert_eq!(xml, String::from_utf8(output).unwrap());
+        }
+    }
+}
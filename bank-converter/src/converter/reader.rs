@@ -1,10 +1,16 @@
 use std::io::{Read};
+use crate::encoding::DecodeReader;
 use crate::errors::ParserError;
 use crate::models::camt053::{DocumentCamt053};
 use crate::models::mt940::{DocumentMt940};
+use crate::models::mt942::DocumentMt942;
+use crate::models::client_bank::DocumentClientBank;
+use crate::models::narrative::NarrativeDialect;
 use crate::models::csv::{DocumentCsv, RowCsv};
+use crate::models::csv_flat::FlatCsv;
+use crate::models::csv_profile::CsvProfile;
 use csv::{ReaderBuilder};
-use regex::{Regex};
+use crate::converter::camt_reader::normalize_document;
 
 
 impl DocumentCamt053 {
@@ -13,9 +19,10 @@ impl DocumentCamt053 {
     /// # Примеры
     ///
     /// ```
-    /// use your_crate::DocumentCamt053;
+    /// use bank_converter::models::camt053::DocumentCamt053;
     ///
     /// let doc = DocumentCamt053::new();
+    /// ```
     pub fn new() -> Self {
         DocumentCamt053::default()
     }
@@ -37,41 +44,31 @@ impl DocumentCamt053 {
     /// * Неверный формат файла
     /// * Неверный входной тип
     /// * Ошибка десерелизации xml
+    ///
+    /// Кодировка файла определяется автоматически (см. [`crate::encoding`]).
     /// # Примеры
     ///
     /// ## Чтение из файла
     ///
     /// ```no_run
     /// use std::fs::File;
-    /// use your_crate::DocumentCamt053;
+    /// use bank_converter::models::camt053::DocumentCamt053;
     ///
     /// let mut file = File::open("camt053.xml").unwrap();
     /// match DocumentCamt053::from_read(&mut file) {
     ///     Ok(document) => println!("Успешно загружено: {:?}", document),
     ///     Err(e) => eprintln!("Ошибка загрузки: {}", e),
     /// }
-    ///
+    /// ```
     ///
     pub fn from_read<R: Read>(r: &mut R) -> Result<Self, ParserError> {
-        let mut xml_str = String::new();
-        r.read_to_string(&mut xml_str)?;
-        if let Some(xml_str) = DocumentCamt053::remove_name_space(&xml_str){
-            Ok(serde_xml_rs::from_str(&xml_str)?)
-        }
-        else {
-            Err(ParserError::BadInputFormatFile("Error parse CAMT053 document".to_string()))
-        }
-    }
-
-    fn remove_name_space(xml: &str) -> Option<String>{
-        if let Ok(regexp) = Regex::new(r#"xmlns[= \w/\d:".-]+"#){
-            let result = regexp.replace_all(xml, "");
-            return Some(result.to_string());
-        }
-        None
-
+        let mut bytes = Vec::new();
+        DecodeReader::new(r, None)?.read_to_end(&mut bytes)?;
+        let (xml, version) = normalize_document(bytes.as_slice())?;
+        let mut document: DocumentCamt053 = serde_xml_rs::from_str(&xml)?;
+        document.version = version;
+        Ok(document)
     }
-
 }
 
 impl DocumentMt940 {
@@ -92,16 +89,117 @@ impl DocumentMt940 {
     /// * Ошибка чтения файла
     /// * Неверный формат файла
     /// * Неверный входной тип
+    /// * Ошибка разбора записи (в тексте ошибки указан номер записи и её смещение в байтах)
+    /// * Номера страниц выписки :28C: идут не подряд
+    ///
+    /// Страницы одной выписки (см. `:28C:` и промежуточные остатки 60M/62M) объединяются в одну выписку.
+    /// Файлы без заголовков SWIFT (`:20:` ... `-`) разбираются по полям :20:.
+    /// Кодировка файла определяется автоматически (см. [`crate::encoding`]).
     ///
     pub fn from_read<R: Read>(r: &mut R) -> Result<Self, ParserError> {
-        let mut regex_pattern = String::new();
-        r.read_to_string(&mut regex_pattern)?;
-        if let Some(records) = DocumentMt940::find_record(&regex_pattern) {
-            for record in records {
-                DocumentMt940::parse_one_record(&regex_pattern[record.0..record.1]);
+        DocumentMt940::from_read_with_dialect(r, None)
+    }
+
+    /// Читает файл формата MT940, разбирая поля :86: в диалекте `dialect`.
+    ///
+    /// Если диалект не задан, он определяется по всем полям :86: файла
+    /// (см. [`NarrativeDialect::detect_document`]).
+    ///
+    /// # Ошибки
+    ///
+    /// Те же, что и у [`DocumentMt940::from_read`].
+    pub fn from_read_with_dialect<R: Read>(r: &mut R, dialect: Option<NarrativeDialect>) -> Result<Self, ParserError> {
+        let mut document = String::new();
+        DecodeReader::new(r, None)?.read_to_string(&mut document)?;
+        let dialect = dialect.unwrap_or_else(|| DocumentMt940::detect_dialect(&document));
+        let mut mt940 = DocumentMt940 { document: Vec::new() };
+        if let Some(records) = DocumentMt940::find_record(&document) {
+            for (index, record) in records.iter().enumerate() {
+                match DocumentMt940::parse_one_record(&document[record.0..record.1], dialect) {
+                    Ok(statement) => mt940.document.push(statement),
+                    Err(e) => {
+                        return Err(ParserError::BadInputFormatFile(
+                            format!("Error parse MT940 record {} at offset {}: {}", index + 1, record.0, e)));
+                    }
+                }
             }
         }
-        Err(ParserError::BadInputFormatFile("No implement parse document".to_string()))
+        mt940.document = DocumentMt940::stitch_pages(mt940.document)?;
+        if mt940.document.is_empty() {
+            return Err(ParserError::BadInputFormatFile("No MT940 records found".to_string()));
+        }
+        Ok(mt940)
+    }
+}
+
+impl DocumentMt942 {
+    /// Читает файл промежуточных отчетов MT942 и возвращает его содержимое.
+    ///
+    /// # Ошибки
+    ///
+    /// Возвращает ошибку, если:
+    /// * Ошибка чтения файла
+    /// * Неверный формат файла
+    /// * Ошибка разбора записи (в тексте ошибки указан номер записи и её смещение в байтах)
+    ///
+    /// Каждое сообщение - отдельный отчет: отчеты за день не объединяются, даже если у них общий номер :28C:.
+    /// Файлы без заголовков SWIFT (`:20:` ... `-`) разбираются по полям :20:.
+    /// Кодировка файла определяется автоматически (см. [`crate::encoding`]).
+    ///
+    pub fn from_read<R: Read>(r: &mut R) -> Result<Self, ParserError> {
+        DocumentMt942::from_read_with_dialect(r, None)
+    }
+
+    /// Читает файл формата MT942, разбирая поля :86: в диалекте `dialect`
+    /// (см. [`DocumentMt940::from_read_with_dialect`]).
+    ///
+    /// # Ошибки
+    ///
+    /// Те же, что и у [`DocumentMt942::from_read`].
+    pub fn from_read_with_dialect<R: Read>(r: &mut R, dialect: Option<NarrativeDialect>) -> Result<Self, ParserError> {
+        let mut document = String::new();
+        DecodeReader::new(r, None)?.read_to_string(&mut document)?;
+        let dialect = dialect.unwrap_or_else(|| DocumentMt940::detect_dialect(&document));
+        let mut mt942 = DocumentMt942::new();
+        for (index, record) in DocumentMt940::find_record(&document).unwrap_or_default().iter().enumerate() {
+            let report = DocumentMt940::parse_record(&document[record.0..record.1], "942", dialect)
+                .map_err(|e| ParserError::BadInputFormatFile(
+                    format!("Error parse MT942 record {} at offset {}: {}", index + 1, record.0, e)))?;
+            mt942.document.push(report);
+        }
+        if mt942.document.is_empty() {
+            return Err(ParserError::BadInputFormatFile("No MT942 records found".to_string()));
+        }
+        Ok(mt942)
+    }
+}
+
+impl DocumentClientBank {
+    /// Читает файл обмена 1С с клиент-банком (1CClientBankExchange) и возвращает его содержимое.
+    ///
+    /// # Аргументы
+    ///
+    /// * `r` - reader (любой тип реал изующий терейт Read)
+    ///
+    /// # Возвращает
+    ///
+    /// `Ok(DocumentClientBank)` с содержимым файла в случае успеха,
+    /// `Err(ParseError)` в случае ошибки.
+    ///
+    /// # Ошибки
+    ///
+    /// Возвращает ошибку, если:
+    /// * Ошибка чтения файла
+    /// * Файл не начинается строкой `1CClientBankExchange` или секция не закрыта
+    /// * Неверная дата или сумма
+    /// * Документ не относится ни к одному счету файла
+    ///
+    /// Кодировка файла (обычно Windows-1251) определяется автоматически (см. [`crate::encoding`]).
+    ///
+    pub fn from_read<R: Read>(r: &mut R) -> Result<Self, ParserError> {
+        let mut document = String::new();
+        DecodeReader::new(r, None)?.read_to_string(&mut document)?;
+        DocumentClientBank::parse_document(&document)
     }
 }
 
@@ -125,13 +223,46 @@ impl DocumentCsv {
     /// * Неверный входной тип
     /// * Ошибка десерилизации Csv
     ///
+    /// Строки документа разбираются по встроенному профилю СберБизнес (см. [`CsvProfile::sberbank`]).
+    /// Кодировка файла (UTF-8, UTF-8 с BOM, Windows-1251...) определяется автоматически
+    /// (см. [`crate::encoding`]).
+    ///
     pub fn from_read<R: Read>(r: &mut R) -> Result<Self, ParserError> {
+        DocumentCsv::from_read_with_profile(r, CsvProfile::default())
+    }
+
+    /// Читает файл формата CSV, строки которого разбираются по профилю разметки `profile`
+    /// (см. [`crate::models::csv_profile`]).
+    ///
+    /// # Ошибки
+    ///
+    /// Те же, что и у [`DocumentCsv::from_read`].
+    pub fn from_read_with_profile<R: Read>(r: &mut R, profile: CsvProfile) -> Result<Self, ParserError> {
+        let mut csv_document: DocumentCsv = DocumentCsv::new();
+        csv_document.profile = Box::new(profile);
+        let mut csv_rdr = ReaderBuilder::new().has_headers(false).flexible(true)
+                                                            .from_reader(DecodeReader::new(r, None)?);
+        for record in csv_rdr.records() {
+            csv_document.rows.push(RowCsv::from_record(record?.iter()));
+        }
+        Ok(csv_document)
+    }
+
+    /// Читает плоский документ CSV с параметрами `flat` (см. [`crate::models::csv_flat`]).
+    ///
+    /// Записи могут содержать разное число колонок.
+    ///
+    /// # Ошибки
+    ///
+    /// Те же, что и у [`DocumentCsv::from_read`].
+    pub fn from_read_flat<R: Read>(r: &mut R, flat: FlatCsv) -> Result<Self, ParserError> {
         let mut csv_document: DocumentCsv = DocumentCsv::new();
-        let mut csv_rdr = ReaderBuilder::new().has_headers(false)
-                                                            .from_reader(r);
-        for row in csv_rdr.deserialize() {
-            let row_data: RowCsv = row?;
-            csv_document.rows.push(row_data);
+        csv_document.flat = Some(flat);
+        let mut csv_rdr = ReaderBuilder::new().has_headers(false).flexible(true)
+                                                            .delimiter(flat.delimiter)
+                                                            .from_reader(DecodeReader::new(r, None)?);
+        for record in csv_rdr.records() {
+            csv_document.rows.push(RowCsv::from_record(record?.iter()));
         }
         Ok(csv_document)
     }
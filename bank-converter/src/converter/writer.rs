@@ -1,14 +1,22 @@
 use std::io::{Write};
 use csv::{WriterBuilder};
-use serde_xml_rs::to_string;
 use crate::errors::{ConvertError};
-use crate::models::camt053::{DocumentCamt053};
+use crate::models::camt053::{CamtVersion, DocumentCamt053};
 use crate ::models::mt940::{DocumentMt940};
+use crate::models::mt942::DocumentMt942;
 use crate::models::csv::{DocumentCsv};
+use crate::models::csv_flat::fields;
+use crate::models::client_bank::DocumentClientBank;
+use crate::encoding::TextEncoding;
+use crate::stream::{CamtStreamWriter, ClientBankStreamWriter, Mt940StreamWriter, Mt942StreamWriter, StatementWriter};
 
 
 impl DocumentCamt053 {
-    /// Сохраняет файл формата CAMT053.
+    /// Сохраняет файл формата CAMT053 по схеме версии прочитанного документа, если она
+    /// поддерживается для записи, иначе по схеме camt.053.001.02.
+    ///
+    /// Перед записью итоги TxsSummry рассчитываются по записям выписок; итоги, противоречащие
+    /// записям, не записываются.
     ///
     /// # Аргументы
     ///
@@ -25,15 +33,29 @@ impl DocumentCamt053 {
     /// * Ошибка записи файла
     ///
     pub fn write_to<W: Write>(&mut self, writer: &mut W) -> Result<(), ConvertError> {
-        //let mut record_write = String::new();
-        let mut record_write = to_string(&self)?;
-        record_write = record_write.replace("<?xml version=\"1.0\" encoding=\"UTF-8\"?><Document>",
-                                            "<Document xmlns=\"urn:iso:std:iso:20022:tech:xsd:camt.053.001.02\" \
-                                            xmlns:xsi=\"http://www.w3.org/2001/XMLSchema-instance\" \
-                                            xsi:schemaLocation=\"urn:iso:std:iso:20022:tech:xsd:camt.053.001.02 camt.053.001.02.xsd\">");
-        writer.write_all(record_write.as_bytes())?;
-        writer.flush()?;
-        Ok(())
+        let version = self.version.filter(CamtVersion::is_writable).unwrap_or_default();
+        self.write_to_version(writer, version)
+    }
+
+    /// Сохраняет файл формата CAMT053 по схеме версии `version`.
+    ///
+    /// Элементы записываются в порядке схемы ISO 20022, пустые необязательные элементы
+    /// не записываются. Все выписки записываются в одно сообщение BkToCstmrStmt с заголовком
+    /// первой выписки.
+    ///
+    /// # Ошибки
+    ///
+    /// Возвращает ошибку, если:
+    /// * Версия схемы не поддерживается для записи
+    /// * Ошибка записи файла
+    ///
+    pub fn write_to_version<W: Write>(&mut self, writer: &mut W, version: CamtVersion) -> Result<(), ConvertError> {
+        if !version.is_writable() {
+            return Err(ConvertError::BadArgument(
+                format!("{} is not supported for writing, use 02, 04 or 08", version)));
+        }
+        self.normalize_summary()?;
+        CamtStreamWriter::new(writer, version)?.write_document(self)
     }
 }
 
@@ -55,33 +77,73 @@ impl DocumentMt940 {
     /// * Ошибка записи файла
     ///
     pub fn write_to<W: Write>(&mut self, writer: &mut W) -> Result<(), ConvertError> {
-        let mut record_write = String::new();
+        let mut stream_writer = Mt940StreamWriter::new(writer);
         for record in &self.document {
-            record_write.push_str("{1:F01");
-            record_write.push_str(&record.stmt.acct.svcr.fin_instn_id.bic);
-            record_write.push_str("}\n");
-            record_write.push_str("{2:");
-            record_write.push_str(&record.grp_hdr.msg_id);
-            record_write.push_str("}\n{3:}\n{4:\n");
-            record_write.push_str(":20:");
-            record_write.push_str(&record.grp_hdr.msg_id);
-            record_write.push_str("\n");
-            record_write.push_str(":25:");
-            record_write.push_str(&record.stmt.acct.ownr.id.org_id.othr.id);
-            record_write.push_str("\n");
-            record_write.push_str(":28C:");
-            record_write.push_str(&record.stmt.elctrnc_seq_nb);
-            record_write.push_str("/");
-            record_write.push_str(&record.stmt.lgl_seq_nb);
-            record_write.push_str("\n");
-            DocumentMt940::extract_field_6x_mt940(&record, &mut record_write);
-            DocumentMt940::extract_field_61_86_mt940(&record.stmt.ntry, &mut record_write);
-            record_write.push_str("}\n{5:-}\n");
-            writer.write_all(record_write.as_bytes())?;
-            writer.flush()?;
-            record_write.clear();
+            stream_writer.start_statement(record)?;
+            for ntry in &record.stmt.ntry {
+                stream_writer.write_entry(ntry)?;
+            }
+            stream_writer.end_statement()?;
         }
-        Ok(())
+        stream_writer.finish()
+    }
+}
+
+impl DocumentMt942 {
+    /// Сохраняет файл промежуточных отчетов MT942: каждая выписка записывается отдельным сообщением.
+    ///
+    /// Итоги :90D:/:90C: рассчитываются по записанным записям.
+    ///
+    /// # Ошибки
+    ///
+    /// Возвращает ошибку, если:
+    /// * У выписки нет валюты (:34F:) или даты отчета (:13D:)
+    /// * Валюта записи отличается от валюты отчета
+    /// * Записей одного признака больше 99999
+    /// * Ошибка записи файла
+    ///
+    pub fn write_to<W: Write>(&mut self, writer: &mut W) -> Result<(), ConvertError> {
+        let mut stream_writer = Mt942StreamWriter::new(writer);
+        for record in &self.document {
+            stream_writer.start_statement(record)?;
+            for ntry in &record.stmt.ntry {
+                stream_writer.write_entry(ntry)?;
+            }
+            stream_writer.end_statement()?;
+        }
+        stream_writer.finish()
+    }
+}
+
+impl DocumentClientBank {
+    /// Сохраняет файл обмена 1С с клиент-банком в кодировке Windows-1251.
+    ///
+    /// # Аргументы
+    ///
+    /// * `w` - writer (любой тип реал изующий терейт Write)
+    ///
+    /// # Возвращает
+    ///
+    /// `Ok(())` с содержимым файла в случае успеха,
+    /// `Err(ConvertError)` в случае ошибки.
+    ///
+    /// # Ошибки
+    ///
+    /// Возвращает ошибку, если:
+    /// * В документе нет выписок
+    /// * Символа нет в кодировке Windows-1251
+    /// * Ошибка записи файла
+    ///
+    pub fn write_to<W: Write>(&mut self, writer: &mut W) -> Result<(), ConvertError> {
+        let mut stream_writer = ClientBankStreamWriter::new(writer, TextEncoding::Windows1251)?;
+        for record in &self.document {
+            stream_writer.start_statement(record)?;
+            for ntry in &record.stmt.ntry {
+                stream_writer.write_entry(ntry)?;
+            }
+            stream_writer.end_statement()?;
+        }
+        stream_writer.finish()
     }
 }
 
@@ -102,10 +164,24 @@ impl DocumentCsv {
     /// Возвращает ошибку, если:
     /// * Ошибка записи файла
     ///
+    /// Плоский документ записывается с разделителем колонок из его параметров и без пустых
+    /// колонок после последней колонки заголовка.
+    ///
     pub fn write_to<W: Write>(&mut self, writer: &mut W) -> Result<(), ConvertError> {
-        let mut csv_wrt = WriterBuilder::new().has_headers(false).from_writer(writer);
+        if let Some(flat) = self.flat {
+            let mut csv_wrt = WriterBuilder::new().has_headers(false).delimiter(flat.delimiter).from_writer(writer);
+            let width = self.rows.first().map_or(0, |header| {
+                header.values().enumerate().filter(|(_, name)| !name.is_empty()).last().map_or(0, |(last, _)| last + 1)
+            });
+            for row in &self.rows {
+                csv_wrt.write_record(fields(row, width))?;
+            }
+            csv_wrt.flush()?;
+            return Ok(());
+        }
+        let mut csv_wrt = WriterBuilder::new().has_headers(false).flexible(true).from_writer(writer);
         for row in &self.rows {
-            csv_wrt.serialize(row)?;
+            csv_wrt.write_record(row.values())?;
             csv_wrt.flush()?;
         }
         Ok(())
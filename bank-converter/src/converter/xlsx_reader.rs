@@ -0,0 +1,110 @@
+//! Чтение книги Excel XLSX в таблицу документа CSV
+//!
+//! Лист книги переносится в ту же сетку строк и колонок, что и выгрузка CSV: ячейка `A1` - колонка
+//! `a` строки 0. Поэтому выписку XLSX разбирает тот же профиль разметки (см. [`crate::models::csv_profile`]).
+use std::io::{Read, Seek};
+use calamine::{Data, Dimensions, Reader, Xlsx};
+use crate::errors::ParserError;
+use crate::models::csv::{DocumentCsv, RowCsv};
+use crate::models::csv_profile::CsvProfile;
+
+/// Формат даты ячеек-дат, как в выгрузке CSV
+const DATE_FORMAT: &str = "%d.%m.%Y";
+
+/// Текст ячейки в том виде, в котором его записывает выгрузка CSV.
+///
+/// Даты записываются как `ДД.ММ.ГГГГ`, числа - без лишних нулей дробной части, переводы строк
+/// многострочных ячеек приводятся к `\n`.
+fn cell_text(data: &Data) -> String {
+    match data {
+        Data::Empty => String::new(),
+        Data::String(text) | Data::DateTimeIso(text) | Data::DurationIso(text) => text.replace("\r\n", "\n"),
+        Data::Float(value) => value.to_string(),
+        Data::Int(value) => value.to_string(),
+        Data::Bool(value) => value.to_string().to_uppercase(),
+        Data::DateTime(value) => match value.as_datetime() {
+            Some(datetime) if value.is_datetime() => datetime.format(DATE_FORMAT).to_string(),
+            _ => value.as_f64().to_string(),
+        },
+        Data::Error(error) => error.to_string(),
+    }
+}
+
+impl DocumentCsv {
+    /// Читает лист `sheet` книги XLSX (первый лист, если имя не задано) в таблицу документа CSV,
+    /// строки которой разбираются по профилю разметки `profile`.
+    ///
+    /// Значение объединенных ячеек берется из левой верхней ячейки области, остальные ячейки
+    /// области пусты, как при сохранении книги в CSV.
+    ///
+    /// # Ошибки
+    ///
+    /// Возвращает ошибку, если:
+    /// * Данные не являются книгой XLSX
+    /// * В книге нет листов или нет листа `sheet`
+    pub fn from_xlsx<R: Read + Seek>(r: R, sheet: Option<&str>, profile: CsvProfile) -> Result<Self, ParserError> {
+        let mut workbook: Xlsx<R> = Xlsx::new(r)?;
+        let sheet = match sheet {
+            Some(sheet) => sheet.to_string(),
+            None => workbook.sheet_names().into_iter().next()
+                .ok_or_else(|| ParserError::BadInputFormatFile("XLSX workbook has no worksheets".to_string()))?,
+        };
+        let range = workbook.worksheet_range(&sheet)?;
+        let merged = workbook.worksheet_merge_cells(&sheet).transpose()?.unwrap_or_default();
+        let covered = |row: u32, column: u32| merged.iter()
+            .any(|area: &Dimensions| area.contains(row, column) && area.start != (row, column));
+        let mut csv_document = DocumentCsv::new();
+        csv_document.profile = Box::new(profile);
+        let (Some(start), Some(end)) = (range.start(), range.end()) else {
+            return Ok(csv_document);
+        };
+        csv_document.rows = vec![RowCsv::new(); end.0 as usize + 1];
+        for (row, column, data) in range.used_cells() {
+            let (row, column) = (start.0 + row as u32, start.1 + column as u32);
+            if !covered(row, column) {
+                csv_document.rows[row as usize].set_cell(column as usize, &cell_text(data));
+            }
+        }
+        Ok(csv_document)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::File;
+    use std::io::BufReader;
+
+    const XLSX: &str = "../test_files/Пример выписки по счёту 1.xlsx";
+
+    #[test]
+    fn test_from_xlsx_matches_csv() {
+        let xlsx = DocumentCsv::from_xlsx(BufReader::new(File::open(XLSX).unwrap()), None, CsvProfile::default()).unwrap();
+        let mut file = File::open("../test_files/Пример выписки по счёту 1.csv").unwrap();
+        let csv = DocumentCsv::from_read(&mut file).unwrap();
+        assert_eq!("20.02.2024", xlsx.rows[11].b);
+        assert_eq!("40702810440000030888\n7735602068\nООО РОМАШКА", xlsx.rows[11].e);
+        assert_eq!(csv.parse_to_camt().unwrap(), xlsx.parse_to_camt().unwrap());
+    }
+
+    #[test]
+    fn test_from_xlsx_sheet() {
+        let xlsx = DocumentCsv::from_xlsx(BufReader::new(File::open(XLSX).unwrap()),
+                                          Some("40702810440000030888"), CsvProfile::default());
+        assert!(xlsx.is_ok());
+        let missing = DocumentCsv::from_xlsx(BufReader::new(File::open(XLSX).unwrap()),
+                                             Some("Лист2"), CsvProfile::default());
+        assert!(missing.unwrap_err().to_string().contains("Лист2"));
+        let text = DocumentCsv::from_xlsx(std::io::Cursor::new(b"a,b\n1,2\n".to_vec()), None, CsvProfile::default());
+        assert!(text.is_err());
+    }
+
+    #[test]
+    fn test_cell_text() {
+        assert_eq!("1540", cell_text(&Data::Float(1540.0)));
+        assert_eq!("256.67", cell_text(&Data::Float(256.67)));
+        assert_eq!("a\nb", cell_text(&Data::String("a\r\nb".to_string())));
+        let date = calamine::ExcelDateTime::new(45342.652465, calamine::ExcelDateTimeType::DateTime, false);
+        assert_eq!("20.02.2024", cell_text(&Data::DateTime(date)));
+    }
+}
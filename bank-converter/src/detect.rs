@@ -0,0 +1,241 @@
+//! Модуль автоматического определения формата входного документа
+//!
+//! Предоставляет функциональность по определению формата по первым килобайтам данных
+//!
+//! ```no_run
+//! use std::fs::File;
+//! use bank_converter::detect::sniff;
+//!
+//! let (detection, mut reader) = sniff(File::open("statement.txt").unwrap()).unwrap();
+//! if let Some(detection) = detection {
+//!     println!("{} ({:.2})", detection.format, detection.confidence);
+//! }
+//! ```
+use std::io::{Chain, Cursor, Read};
+use crate::encoding::decode_head;
+use crate::pipeline::Format;
+
+/// Количество байт от начала документа, по которым определяется формат
+pub const SNIFF_LEN: usize = 4096;
+
+/// Reader, который отдает прочитанное при определении формата начало документа и его остаток
+pub type SniffedReader<R> = Chain<Cursor<Vec<u8>>, R>;
+
+/// Результат определения формата
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Detection {
+    /// Определенный формат
+    pub format: Format,
+    /// Уверенность в диапазоне от 0 до 1
+    pub confidence: f32,
+}
+
+/// Сигнатура архива ZIP, в котором хранится книга XLSX
+const ZIP_SIGNATURE: &[u8] = b"PK\x03\x04";
+
+fn score_xlsx(head: &[u8]) -> f32 {
+    let mut score: f32 = 0.0;
+    if head.starts_with(ZIP_SIGNATURE) {
+        score += 0.6;
+        if head.windows(3).any(|name| name == b"xl/") {
+            score += 0.3;
+        }
+        if head.windows(19).any(|name| name == b"[Content_Types].xml") {
+            score += 0.1;
+        }
+    }
+    score
+}
+
+fn score_camt053(text: &str) -> f32 {
+    let mut score: f32 = 0.0;
+    if text.contains("BkToCstmrStmt") {
+        score += 0.6;
+    }
+    if text.contains("<Document") || text.contains(":Document") {
+        score += 0.2;
+    }
+    if text.contains("camt.053") {
+        score += 0.2;
+    }
+    score
+}
+
+fn score_mt940(text: &str) -> f32 {
+    let mut score: f32 = 0.0;
+    if text.contains("{1:F01") {
+        score += 0.4;
+    }
+    if text.contains("{2:I940") || text.contains("{2:O940") {
+        score += 0.2;
+    }
+    for tag in [":20:", ":25:", ":28C:", ":60F:"] {
+        if text.contains(tag) {
+            score += 0.1;
+        }
+    }
+    score
+}
+
+fn score_mt942(text: &str) -> f32 {
+    let mut score: f32 = 0.0;
+    if text.contains("{1:F01") {
+        score += 0.4;
+    }
+    if text.contains("{2:I942") || text.contains("{2:O942") {
+        score += 0.2;
+    }
+    for tag in [":20:", ":25:", ":28C:", ":34F:", ":13D:"] {
+        if text.contains(tag) {
+            score += 0.1;
+        }
+    }
+    score
+}
+
+fn score_csv(text: &str) -> f32 {
+    let mut score: f32 = 0.0;
+    if text.contains("СберБизнес") {
+        score += 0.5;
+    }
+    if text.contains("ВЫПИСКА ОПЕРАЦИЙ ПО ЛИЦЕВОМУ СЧЕТУ") {
+        score += 0.4;
+    }
+    let header = text.lines().next().unwrap_or_default().to_lowercase();
+    if header.contains("booking_date") && header.contains("credit_debit") {
+        score += 0.9;
+    }
+    if score > 0.0 && text.lines().take(5).all(|line| line.contains(',')) {
+        score += 0.1;
+    }
+    score
+}
+
+fn score_client_bank(text: &str) -> f32 {
+    let mut score: f32 = 0.0;
+    if text.trim_start().starts_with("1CClientBankExchange") {
+        score += 0.8;
+    }
+    if text.contains("СекцияРасчСчет") || text.contains("СекцияДокумент") {
+        score += 0.2;
+    }
+    score
+}
+
+/// Определяет формат документа по его началу `head`.
+///
+/// Текстовые форматы распознаются в любой поддерживаемой кодировке (см. [`crate::encoding`]).
+///
+/// # Возвращает
+///
+/// `Some(Detection)` с наиболее вероятным форматом, `None` если ни один формат не распознан.
+pub fn detect_format(head: &[u8]) -> Option<Detection> {
+    let head = &head[..head.len().min(SNIFF_LEN)];
+    if head.starts_with(ZIP_SIGNATURE) {
+        return Some(Detection { format: Format::Xlsx, confidence: score_xlsx(head) });
+    }
+    let text = decode_head(head);
+    let text = text.trim_start_matches('\u{feff}');
+    // при равной уверенности выбирается формат, стоящий в списке позже: MT942 уступает MT940
+    [
+        (Format::Camt053, score_camt053(text)),
+        (Format::Mt942, score_mt942(text)),
+        (Format::Mt940, score_mt940(text)),
+        (Format::Csv, score_csv(text)),
+        (Format::ClientBank, score_client_bank(text)),
+    ].into_iter()
+        .filter(|(_, score)| *score > 0.0)
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(format, score)| Detection { format, confidence: score.min(1.0) })
+}
+
+/// Читает начало документа из `r` и определяет его формат.
+///
+/// # Возвращает
+///
+/// Результат определения и reader, который заново отдает прочитанное начало и остаток документа.
+///
+/// # Ошибки
+///
+/// Возвращает ошибку, если не удалось прочитать данные.
+pub fn sniff<R: Read>(mut r: R) -> std::io::Result<(Option<Detection>, SniffedReader<R>)> {
+    let mut head = Vec::with_capacity(SNIFF_LEN);
+    (&mut r).take(SNIFF_LEN as u64).read_to_end(&mut head)?;
+    let detection = detect_format(&head);
+    Ok((detection, Cursor::new(head).chain(r)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_camt053() {
+        let head = r#"<?xml version="1.0"?><Document xmlns="urn:iso:std:iso:20022:tech:xsd:camt.053.001.02"><BkToCstmrStmt>"#;
+        let detection = detect_format(head.as_bytes()).unwrap();
+        assert_eq!(Format::Camt053, detection.format);
+        assert_eq!(1.0, detection.confidence);
+    }
+
+    #[test]
+    fn test_detect_mt940() {
+        let head = "{1:F01GSCRUS30XXXX3614000002}{2:I940GSCRUS30XXXXN}{4:\n:20:15486025400\n:25:107048825\n";
+        assert_eq!(Format::Mt940, detect_format(head.as_bytes()).unwrap().format);
+        let bare = ":20:15486025400\n:25:107048825\n:28C:49/2\n:60F:C250218USD1,00\n";
+        assert_eq!(Format::Mt940, detect_format(bare.as_bytes()).unwrap().format);
+    }
+
+    #[test]
+    fn test_detect_mt942() {
+        let head = std::fs::read("../test_files/MT942 intraday.mt942.txt").unwrap();
+        let detection = detect_format(&head).unwrap();
+        assert_eq!(Format::Mt942, detection.format);
+        assert_eq!(1.0, detection.confidence);
+        let bare = ":20:INTRADAY0001\n:25:DE89370400440532013000\n:28C:5/1\n:34F:EUR0,\n:13D:2402201530+0100\n";
+        assert_eq!(Format::Mt942, detect_format(bare.as_bytes()).unwrap().format);
+    }
+
+    #[test]
+    fn test_detect_csv() {
+        let head = "\u{feff},,,\n,14.10.2025,,,,СберБизнес. 03.002.01-4923,,\n,ВЫПИСКА ОПЕРАЦИЙ ПО ЛИЦЕВОМУ СЧЕТУ,,\n";
+        let detection = detect_format(head.as_bytes()).unwrap();
+        assert_eq!(Format::Csv, detection.format);
+        assert!(detection.confidence > 0.9);
+        let flat = "account;booking_date;value_date;amount;currency;credit_debit\n";
+        assert_eq!(Format::Csv, detect_format(flat.as_bytes()).unwrap().format);
+    }
+
+    #[test]
+    fn test_detect_xlsx_and_encodings() {
+        let xlsx = std::fs::read("../test_files/Пример выписки по счёту 1.xlsx").unwrap();
+        let detection = detect_format(&xlsx).unwrap();
+        assert_eq!(Format::Xlsx, detection.format);
+        assert!(detection.confidence > 0.8);
+        let text = std::fs::read_to_string("../test_files/Пример выписки по счёту 1.csv").unwrap();
+        let cp1251 = encoding_rs::WINDOWS_1251.encode(text.trim_start_matches('\u{feff}')).0;
+        assert_eq!(Format::Csv, detect_format(&cp1251).unwrap().format);
+    }
+
+    #[test]
+    fn test_detect_client_bank() {
+        let cp1251 = std::fs::read("../test_files/1CClientBankExchange.txt").unwrap();
+        let detection = detect_format(&cp1251).unwrap();
+        assert_eq!(Format::ClientBank, detection.format);
+        assert!(detection.confidence > 0.9);
+    }
+
+    #[test]
+    fn test_detect_unknown() {
+        assert_eq!(None, detect_format(b"plain text"));
+    }
+
+    #[test]
+    fn test_sniff_keeps_data() {
+        let data = ":20:1\n:25:2\n".repeat(1000);
+        let (detection, mut reader) = sniff(data.as_bytes()).unwrap();
+        assert_eq!(Format::Mt940, detection.unwrap().format);
+        let mut result = String::new();
+        reader.read_to_string(&mut result).unwrap();
+        assert_eq!(data, result);
+    }
+}
@@ -0,0 +1,424 @@
+//! Модуль кодировок входных и выходных документов
+//!
+//! Банковские выгрузки приходят не только в UTF-8: российские банки отдают файлы в Windows-1251,
+//! реже в CP866 и KOI8-R, или в UTF-8 с меткой порядка байт (BOM). `DecodeReader` определяет
+//! кодировку по началу документа (BOM, объявление XML `encoding=`, частоты кириллических букв)
+//! и отдает текст в UTF-8, поэтому разборщики форматов работают только с UTF-8.
+//! `EncodeWriter` перекодирует выходной документ, например в Windows-1251 для старых систем.
+//!
+//! ```
+//! use std::io::{Read, Write};
+//! use bank_converter::encoding::{DecodeReader, EncodeWriter, TextEncoding};
+//!
+//! let mut output = Vec::new();
+//! let mut writer = EncodeWriter::new(&mut output, TextEncoding::Windows1251).unwrap();
+//! writer.write_all("Выписка по счету".as_bytes()).unwrap();
+//! writer.flush().unwrap();
+//! assert_eq!(16, output.len());
+//!
+//! let mut text = String::new();
+//! DecodeReader::new(output.as_slice(), None).unwrap().read_to_string(&mut text).unwrap();
+//! assert_eq!("Выписка по счету", text);
+//! ```
+use std::borrow::Cow;
+use std::fmt::{Display, Formatter};
+use std::io::{Error, ErrorKind, Read, Write};
+use std::str::FromStr;
+use std::sync::LazyLock;
+use encoding_rs::{Decoder, DecoderResult, Encoder, EncoderResult, Encoding};
+use regex::bytes::Regex;
+use crate::detect::SNIFF_LEN;
+use crate::errors::{ConvertError, ParserError};
+
+/// Метка порядка байт UTF-8
+const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+
+/// Размер блока, который `DecodeReader` читает и перекодирует за один раз
+const CHUNK_LEN: usize = 8192;
+
+/// Атрибут `encoding` объявления XML в начале документа
+static XML_ENCODING: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"^\s*<\?xml[^>]*?\sencoding\s*=\s*["']([A-Za-z0-9._:-]+)["']"#).unwrap()
+});
+
+/// Поддерживаемые кодировки документов
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TextEncoding {
+    /// UTF-8, с BOM или без него
+    #[default]
+    Utf8,
+    /// UTF-16 little-endian (только чтение)
+    Utf16Le,
+    /// UTF-16 big-endian (только чтение)
+    Utf16Be,
+    /// Windows-1251 (CP1251)
+    Windows1251,
+    /// CP866 (альтернативная кодировка DOS)
+    Cp866,
+    /// KOI8-R
+    Koi8R,
+}
+
+impl TextEncoding {
+    /// Однобайтовые кириллические кодировки в порядке предпочтения при равной оценке
+    const CYRILLIC: [TextEncoding; 3] = [TextEncoding::Windows1251, TextEncoding::Cp866, TextEncoding::Koi8R];
+
+    fn encoding(self) -> &'static Encoding {
+        match self {
+            TextEncoding::Utf8 => encoding_rs::UTF_8,
+            TextEncoding::Utf16Le => encoding_rs::UTF_16LE,
+            TextEncoding::Utf16Be => encoding_rs::UTF_16BE,
+            TextEncoding::Windows1251 => encoding_rs::WINDOWS_1251,
+            TextEncoding::Cp866 => encoding_rs::IBM866,
+            TextEncoding::Koi8R => encoding_rs::KOI8_R,
+        }
+    }
+
+    /// Кодировка по ее названию (`utf-8`, `cp1251`, `windows-1251`, `866`, `koi8-r`...)
+    /// или `None`, если кодировка не поддерживается.
+    pub fn for_label(label: &str) -> Option<Self> {
+        let encoding = Encoding::for_label(label.trim().as_bytes())?;
+        [TextEncoding::Utf8, TextEncoding::Utf16Le, TextEncoding::Utf16Be, TextEncoding::Windows1251,
+         TextEncoding::Cp866, TextEncoding::Koi8R].into_iter()
+            .find(|candidate| candidate.encoding() == encoding)
+    }
+
+    /// В кодировке можно записать выходной документ.
+    pub fn is_writable(&self) -> bool {
+        !matches!(self, TextEncoding::Utf16Le | TextEncoding::Utf16Be)
+    }
+}
+
+impl FromStr for TextEncoding {
+    type Err = ParserError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        TextEncoding::for_label(value)
+            .ok_or_else(|| ParserError::BadInputFormatFile(format!("Unsupported encoding {}", value)))
+    }
+}
+
+impl Display for TextEncoding {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.encoding().name())
+    }
+}
+
+/// Оценка того, что `text` - русский текст: частые строчные буквы дают больше всего,
+/// псевдографика и служебные символы, которые появляются при чтении в чужой кодировке, штрафуются.
+fn cyrillic_score(text: &str) -> i64 {
+    text.chars().map(|c| match c {
+        'о' | 'е' | 'а' | 'и' | 'н' | 'т' | 'с' | 'р' | 'в' | 'л' => 2,
+        'а'..='я' | 'ё' => 1,
+        'А'..='Я' | 'Ё' => 0,
+        '\u{80}'..='\u{bf}' | '\u{2500}'..='\u{25ff}' => -2,
+        _ => 0,
+    }).sum()
+}
+
+/// Определяет кодировку документа по его началу `head`.
+///
+/// Порядок проверки: BOM, объявление XML `encoding=`, корректный UTF-8 (в том числе ASCII)
+/// и, для остальных данных, наиболее вероятная из Windows-1251, CP866 и KOI8-R.
+///
+/// # Ошибки
+///
+/// Возвращает ошибку, если объявление XML указывает неподдерживаемую кодировку.
+pub fn detect_encoding(head: &[u8]) -> Result<TextEncoding, ParserError> {
+    if let Some((encoding, _)) = Encoding::for_bom(head) {
+        return Ok(TextEncoding::for_label(encoding.name()).unwrap_or_default());
+    }
+    if let Some(captures) = XML_ENCODING.captures(head) {
+        let label = String::from_utf8_lossy(&captures[1]);
+        return label.parse();
+    }
+    match std::str::from_utf8(head) {
+        Ok(_) => return Ok(TextEncoding::Utf8),
+        Err(e) if e.error_len().is_none() => return Ok(TextEncoding::Utf8),
+        Err(_) => {}
+    }
+    Ok(TextEncoding::CYRILLIC.into_iter()
+        .map(|encoding| (encoding, cyrillic_score(&encoding.encoding().decode_without_bom_handling(head).0)))
+        .rev()
+        .max_by_key(|(_, score)| *score)
+        .map(|(encoding, _)| encoding)
+        .unwrap_or_default())
+}
+
+/// Текст начала документа `head` в определенной по нему кодировке; байты, которые не удалось
+/// перекодировать, заменяются символом `U+FFFD`.
+pub(crate) fn decode_head(head: &[u8]) -> Cow<'_, str> {
+    detect_encoding(head).unwrap_or_default().encoding().decode(head).0
+}
+
+/// Заменяет кодировку в объявлении XML в начале `head` на `encoding`.
+fn declare_encoding(head: &[u8], encoding: TextEncoding) -> Cow<'_, [u8]> {
+    match XML_ENCODING.captures(head).and_then(|captures| captures.get(1)) {
+        Some(label) => {
+            let mut result = head[..label.start()].to_vec();
+            result.extend_from_slice(encoding.to_string().as_bytes());
+            result.extend_from_slice(&head[label.end()..]);
+            Cow::Owned(result)
+        }
+        None => Cow::Borrowed(head),
+    }
+}
+
+/// Reader, который перекодирует документ из его кодировки в UTF-8
+///
+/// Документ в UTF-8 без BOM передается без изменений. Для других кодировок BOM удаляется,
+/// а объявление XML `encoding=` заменяется на `UTF-8`, чтобы разборщик XML не перекодировал текст повторно.
+pub struct DecodeReader<R> {
+    inner: R,
+    decoder: Option<Decoder>,
+    encoding: TextEncoding,
+    input: Vec<u8>,
+    output: Vec<u8>,
+    position: usize,
+    offset: usize,
+    finished: bool,
+}
+
+impl<R: Read> DecodeReader<R> {
+    /// Создает reader документа `inner` в кодировке `encoding`; если кодировка не задана,
+    /// она определяется по началу документа (см. [`detect_encoding`]).
+    ///
+    /// # Ошибки
+    ///
+    /// Возвращает ошибку, если начало документа не удалось прочитать или перекодировать.
+    pub fn new(mut inner: R, encoding: Option<TextEncoding>) -> Result<Self, ParserError> {
+        let mut head = Vec::with_capacity(SNIFF_LEN);
+        (&mut inner).take(SNIFF_LEN as u64).read_to_end(&mut head)?;
+        let encoding = match encoding {
+            Some(encoding) => encoding,
+            None => detect_encoding(&head)?,
+        };
+        let mut reader = DecodeReader { inner, decoder: None, encoding, input: Vec::new(), output: Vec::new(),
+                                        position: 0, offset: 0, finished: false };
+        if encoding == TextEncoding::Utf8 && !head.starts_with(UTF8_BOM) {
+            reader.output = head;
+            return Ok(reader);
+        }
+        reader.decoder = Some(encoding.encoding().new_decoder());
+        reader.decode(&head, false)?;
+        reader.output = declare_encoding(&reader.output, TextEncoding::Utf8).into_owned();
+        Ok(reader)
+    }
+
+    /// Кодировка документа
+    pub fn encoding(&self) -> TextEncoding {
+        self.encoding
+    }
+
+    fn decode(&mut self, input: &[u8], last: bool) -> std::io::Result<()> {
+        let Some(decoder) = self.decoder.as_mut() else {
+            return Ok(());
+        };
+        let length = decoder.max_utf8_buffer_length_without_replacement(input.len()).unwrap_or(input.len() * 3 + 16);
+        self.output.clear();
+        self.output.resize(length, 0);
+        self.position = 0;
+        let (result, read, written) = decoder.decode_to_utf8_without_replacement(input, &mut self.output, last);
+        self.output.truncate(written);
+        self.offset += read;
+        if let DecoderResult::Malformed(_, _) = result {
+            return Err(Error::new(ErrorKind::InvalidData,
+                                  format!("Malformed {} input at byte {}", self.encoding, self.offset)));
+        }
+        Ok(())
+    }
+}
+
+impl<R: Read> Read for DecodeReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        loop {
+            if self.position < self.output.len() {
+                let length = buf.len().min(self.output.len() - self.position);
+                buf[..length].copy_from_slice(&self.output[self.position..self.position + length]);
+                self.position += length;
+                return Ok(length);
+            }
+            if self.decoder.is_none() {
+                return self.inner.read(buf);
+            }
+            if self.finished {
+                return Ok(0);
+            }
+            let mut input = std::mem::take(&mut self.input);
+            input.resize(CHUNK_LEN, 0);
+            let read = self.inner.read(&mut input)?;
+            self.finished = read == 0;
+            let result = self.decode(&input[..read], self.finished);
+            self.input = input;
+            result?;
+        }
+    }
+}
+
+/// Writer, который перекодирует документ из UTF-8 в кодировку `encoding`
+///
+/// Объявление XML `encoding=` в начале документа заменяется на название выходной кодировки.
+/// Символ, которого нет в выходной кодировке, приводит к ошибке записи. После записи документа
+/// нужно вызвать `flush`.
+pub struct EncodeWriter<W: Write> {
+    inner: W,
+    encoding: TextEncoding,
+    encoder: Encoder,
+    pending: Vec<u8>,
+    head: Option<Vec<u8>>,
+}
+
+impl<W: Write> EncodeWriter<W> {
+    /// Создает writer, записывающий в `inner` документ в кодировке `encoding`.
+    ///
+    /// # Ошибки
+    ///
+    /// Возвращает ошибку, если в кодировке нельзя записать документ (UTF-16).
+    pub fn new(inner: W, encoding: TextEncoding) -> Result<Self, ConvertError> {
+        if !encoding.is_writable() {
+            return Err(ConvertError::BadArgument(format!("Encoding {} is not supported for output", encoding)));
+        }
+        Ok(EncodeWriter { inner, encoding, encoder: encoding.encoding().new_encoder(), pending: Vec::new(),
+                          head: Some(Vec::new()) })
+    }
+
+    /// Начало документа достаточно для замены объявления XML.
+    fn head_ready(head: &[u8]) -> bool {
+        let prefix = head.len().min(5);
+        head[..prefix] != b"<?xml"[..prefix] || head.windows(2).any(|pair| pair == b"?>") || head.len() >= 1024
+    }
+
+    fn write_head(&mut self) -> std::io::Result<()> {
+        if let Some(head) = self.head.take() {
+            let head = declare_encoding(&head, self.encoding);
+            self.encode(&head)?;
+        }
+        Ok(())
+    }
+
+    fn encode(&mut self, buf: &[u8]) -> std::io::Result<()> {
+        self.pending.extend_from_slice(buf);
+        let valid = match std::str::from_utf8(&self.pending) {
+            Ok(text) => text.len(),
+            Err(e) if e.error_len().is_none() => e.valid_up_to(),
+            Err(e) => return Err(Error::new(ErrorKind::InvalidData, e)),
+        };
+        if self.encoding == TextEncoding::Utf8 {
+            self.inner.write_all(&self.pending[..valid])?;
+        } else {
+            let text = std::str::from_utf8(&self.pending[..valid]).map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+            let mut output = Vec::with_capacity(
+                self.encoder.max_buffer_length_from_utf8_without_replacement(text.len()).unwrap_or(text.len()));
+            let (result, _) = self.encoder.encode_from_utf8_to_vec_without_replacement(text, &mut output, false);
+            if let EncoderResult::Unmappable(c) = result {
+                return Err(Error::new(ErrorKind::InvalidData,
+                                      format!("Character '{}' cannot be encoded in {}", c, self.encoding)));
+            }
+            self.inner.write_all(&output)?;
+        }
+        self.pending.drain(..valid);
+        Ok(())
+    }
+}
+
+impl<W: Write> Write for EncodeWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        match self.head.as_mut() {
+            Some(head) => {
+                head.extend_from_slice(buf);
+                if EncodeWriter::<W>::head_ready(head) {
+                    self.write_head()?;
+                }
+            }
+            None => self.encode(buf)?,
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.write_head()?;
+        self.inner.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEXT: &str = "Выписка операций по лицевому счету 40702810000000000001, остаток на начало периода";
+
+    fn encode(text: &str, encoding: TextEncoding) -> Vec<u8> {
+        encoding.encoding().encode(text).0.into_owned()
+    }
+
+    fn decode(bytes: &[u8], encoding: Option<TextEncoding>) -> Result<String, std::io::Error> {
+        let mut text = String::new();
+        DecodeReader::new(bytes, encoding).map_err(|e| Error::other(e.to_string()))?.read_to_string(&mut text)?;
+        Ok(text)
+    }
+
+    #[test]
+    fn test_encoding_from_str() {
+        assert_eq!(TextEncoding::Windows1251, "cp1251".parse().unwrap());
+        assert_eq!(TextEncoding::Windows1251, "Windows-1251".parse().unwrap());
+        assert_eq!(TextEncoding::Cp866, "866".parse().unwrap());
+        assert_eq!(TextEncoding::Koi8R, "KOI8-R".parse().unwrap());
+        assert_eq!(TextEncoding::Utf8, "utf8".parse().unwrap());
+        assert!("latin1".parse::<TextEncoding>().is_err());
+        assert_eq!("windows-1251", TextEncoding::Windows1251.to_string());
+    }
+
+    #[test]
+    fn test_detect_encoding() {
+        assert_eq!(TextEncoding::Utf8, detect_encoding(TEXT.as_bytes()).unwrap());
+        assert_eq!(TextEncoding::Utf8, detect_encoding(&TEXT.as_bytes()[..3]).unwrap());
+        assert_eq!(TextEncoding::Utf16Le, detect_encoding(b"\xFF\xFE:\x00").unwrap());
+        for encoding in TextEncoding::CYRILLIC {
+            assert_eq!(encoding, detect_encoding(&encode(TEXT, encoding)).unwrap(), "{}", encoding);
+        }
+        let xml = br#"<?xml version="1.0" encoding="windows-1251"?><Document/>"#;
+        assert_eq!(TextEncoding::Windows1251, detect_encoding(xml).unwrap());
+        assert!(detect_encoding(br#"<?xml version="1.0" encoding="EBCDIC"?>"#).is_err());
+    }
+
+    #[test]
+    fn test_decode_reader() {
+        for encoding in TextEncoding::CYRILLIC {
+            assert_eq!(TEXT, decode(&encode(TEXT, encoding), None).unwrap());
+        }
+        let long = TEXT.repeat(200);
+        assert_eq!(long, decode(&encode(&long, TextEncoding::Windows1251), None).unwrap());
+        let bom = [UTF8_BOM, TEXT.as_bytes()].concat();
+        assert_eq!(TEXT, decode(&bom, None).unwrap());
+        let utf16: Vec<u8> = [0xFF, 0xFE].into_iter()
+            .chain(TEXT.encode_utf16().flat_map(u16::to_le_bytes)).collect();
+        assert_eq!(TEXT, decode(&utf16, None).unwrap());
+        assert_eq!("Ђ", decode(b"\x80", Some(TextEncoding::Windows1251)).unwrap());
+        assert!(decode(&utf16[..5], Some(TextEncoding::Utf16Le)).is_err());
+    }
+
+    #[test]
+    fn test_decode_reader_xml_declaration() {
+        let xml = r#"<?xml version="1.0" encoding="windows-1251"?><Nm>ООО Ромашка</Nm>"#;
+        let text = decode(&encode(xml, TextEncoding::Windows1251), None).unwrap();
+        assert_eq!(r#"<?xml version="1.0" encoding="UTF-8"?><Nm>ООО Ромашка</Nm>"#, text);
+    }
+
+    #[test]
+    fn test_encode_writer() {
+        let mut output = Vec::new();
+        let mut writer = EncodeWriter::new(&mut output, TextEncoding::Windows1251).unwrap();
+        writer.write_all(br#"<?xml version="1.0" "#).unwrap();
+        writer.write_all(br#"encoding="UTF-8"?>"#).unwrap();
+        let text = "<Nm>ООО Ромашка</Nm>".as_bytes();
+        writer.write_all(&text[..6]).unwrap();
+        writer.write_all(&text[6..]).unwrap();
+        writer.flush().unwrap();
+        assert_eq!(encode(r#"<?xml version="1.0" encoding="windows-1251"?><Nm>ООО Ромашка</Nm>"#,
+                          TextEncoding::Windows1251), output);
+        let mut output = Vec::new();
+        let mut writer = EncodeWriter::new(&mut output, TextEncoding::Windows1251).unwrap();
+        assert!(writer.write_all("Сумма 10 好".as_bytes()).is_err());
+        assert!(EncodeWriter::new(Vec::new(), TextEncoding::Utf16Le).is_err());
+    }
+}
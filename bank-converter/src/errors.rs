@@ -19,7 +19,9 @@ pub enum ConvertError
     /// Ошибки конвертации
     ParseError(String),
     /// Ошибки записи сконвертировнных данных
-    WriteError(String)
+    WriteError(String),
+    /// Ошибки проверки согласованности выписки
+    ValidationError(String),
 }
 
 /// Перчисление ошибо чтеняи и парсинга входныхх данных
@@ -27,6 +29,9 @@ pub enum ConvertError
 /// Ошибка формата входных файлов
 /// Ошибка xml десерилизации
 /// Ошибка CSV десерелизации
+/// Ошибка формата денежной суммы
+/// Ошибка формата даты
+/// Неизвестный код
 ///
 #[derive(Error, Debug, Deserialize)]
 pub enum ParserError
@@ -39,6 +44,12 @@ pub enum ParserError
     BadXmlDeserializeError(String),
     /// Ошибка CSV десерелизации
     BadCsvDeserializeError(String),
+    /// Ошибка формата денежной суммы
+    BadAmount(String),
+    /// Ошибка формата даты
+    BadDate(String),
+    /// Неизвестный код (признак кредит/дебет, тип остатка)
+    UnknownCode(String),
 }
 
 impl Display for ParserError {
@@ -48,6 +59,9 @@ impl Display for ParserError {
             ParserError::BadInputFormatFile(s) => write!(f, "Bad input format file: {}", s),
             ParserError::BadCsvDeserializeError(s) => write!(f, "Csv format deserialize error: {}", s),
             ParserError::BadXmlDeserializeError(s) => write!(f, "Xml format deserialize error: {}", s),
+            ParserError::BadAmount(s) => write!(f, "Bad amount: {}", s),
+            ParserError::BadDate(s) => write!(f, "Bad date: {}", s),
+            ParserError::UnknownCode(s) => write!(f, "Unknown code: {}", s),
         }
     }
 }
@@ -58,12 +72,24 @@ impl  From<serde_xml_rs::Error> for ParserError{
     }
 }
 
+impl From<quick_xml::Error> for ParserError {
+    fn from(err: quick_xml::Error) -> Self {
+        ParserError::BadXmlDeserializeError(err.to_string())
+    }
+}
+
 impl  From<csv::Error> for ParserError {
     fn from(err: csv::Error) -> Self {
         ParserError::BadCsvDeserializeError(err.to_string())
     }
 }
 
+impl From<calamine::XlsxError> for ParserError {
+    fn from(err: calamine::XlsxError) -> Self {
+        ParserError::BadInputFormatFile(format!("XLSX: {}", err))
+    }
+}
+
 impl From<std::io::Error> for ParserError {
     fn from(value: Error) -> Self {
         ParserError::FileReadError(value.to_string())
@@ -81,6 +107,7 @@ impl Display for ConvertError {
             ConvertError::BadArgument(s) => write!(f, "Bad argument: {}", s),
             ConvertError::ParseError(s) => write!(f, "Parse error: {}", s),
             ConvertError::WriteError(s) => write!(f, "Write error: {}", s),
+            ConvertError::ValidationError(s) => write!(f, "Validation error: {}", s),
         }
     }
 }
@@ -91,6 +118,12 @@ impl  From<serde_xml_rs::Error> for ConvertError{
     }
 }
 
+impl From<quick_xml::Error> for ConvertError {
+    fn from(err: quick_xml::Error) -> Self {
+        ConvertError::WriteError(err.to_string())
+    }
+}
+
 impl From<std::io::Error>  for ConvertError{
     fn from(value: Error) -> Self {
         ConvertError::WriteError(value.to_string())
@@ -98,6 +131,12 @@ impl From<std::io::Error>  for ConvertError{
 
 }
 
+impl From<rust_xlsxwriter::XlsxError> for ConvertError {
+    fn from(err: rust_xlsxwriter::XlsxError) -> Self {
+        ConvertError::WriteError(err.to_string())
+    }
+}
+
 impl From<csv::Error> for ConvertError {
     fn from(err: csv::Error) -> Self {
         ConvertError::WriteError(err.to_string())
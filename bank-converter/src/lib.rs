@@ -5,5 +5,12 @@
 pub mod errors;
 pub(crate) mod converter;
 pub mod models;
+pub mod pipeline;
+pub mod detect;
+pub mod encoding;
+pub mod validate;
+pub mod stream;
+pub mod source;
+pub mod cli;
 mod macros;
 
@@ -1,34 +1,127 @@
 /// Макрос создает структуру для документа CSV
 ///
+/// Именованные поля соответствуют первым колонкам записи, значения остальных колонок хранятся
+/// в поле `extra`, поэтому записи могут иметь любое число колонок. Колонка задается буквами, как в
+/// электронных таблицах (`a`, `u`, `v`, `aa`), или номером с нуля (`"21"`).
+///
 ///  # Пример
-/// ```no_run
+/// ```ignore
 ///
 ///  csv_data!(RowCsv, String, a, b, c, d, e, f, g, h, i, j, k, l, m, n, o, p, q, r, s, t, u);
-///
+/// ```
 #[macro_export]
 macro_rules! csv_data {
     ($name: ident, $type:ty, $($field:ident),+) => {
-        #[derive(Debug, Deserialize, Serialize)]
-        pub(crate) struct $name {
-            $($field: $type,)+
+        /// Строка документа CSV, поля соответствуют колонкам таблицы
+        #[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq)]
+        pub struct $name {
+            $(
+            #[doc = concat!("Значение колонки ", stringify!($field))]
+            pub $field: $type,
+            )+
+            /// Значения колонок после последней именованной колонки
+            #[serde(default)]
+            pub extra: Vec<$type>,
         }
 
         impl $name {
+            /// Имена именованных колонок в порядке следования
+            pub const NAMES: &'static [&'static str] = &[$(stringify!($field)),+];
+
             /// Создает новый экземпляр структуры со значениями по умолчанию.
             ///
             /// # Примеры
             ///
+            /// ```ignore
+            /// let row = RowCsv::new();
             /// ```
-            /// use your_crate::your_struct;
-            ///
-            /// let doc = your_struct::new();
             pub fn new() -> Self {
                 Self {
-                    $($field: "".to_string()),+
+                    $($field: "".to_string(),)+
+                    extra: Vec::new(),
+                }
+            }
+
+            /// Создает строку из значений колонок записи `values` любой длины.
+            pub fn from_record<'a>(values: impl IntoIterator<Item = &'a str>) -> Self {
+                let mut row = Self::new();
+                for (index, value) in values.into_iter().enumerate() {
+                    row.set_cell(index, value);
+                }
+                row
+            }
+
+            /// Номер колонки с нуля по ссылке `column`: буквы `a` - `zz` или номер, `None` для
+            /// других ссылок (например, названия колонки из заголовка таблицы).
+            pub fn column_index(column: &str) -> Option<usize> {
+                if !column.is_empty() && column.bytes().all(|b| b.is_ascii_digit()) {
+                    return column.parse().ok();
+                }
+                if column.is_empty() || column.len() > 2 || !column.bytes().all(|b| b.is_ascii_lowercase()) {
+                    return None;
+                }
+                Some(column.bytes().fold(0, |index, b| index * 26 + (b - b'a') as usize + 1) - 1)
+            }
+
+            /// Ссылка-буквы на колонку с номером `index`: `a`, ..., `z`, `aa`, ...
+            pub fn column_name(index: usize) -> String {
+                let mut name = String::new();
+                let mut rest = index + 1;
+                while rest > 0 {
+                    name.insert(0, char::from(b'a' + ((rest - 1) % 26) as u8));
+                    rest = (rest - 1) / 26;
+                }
+                name
+            }
+
+            /// Число колонок строки: именованные и дополнительные.
+            pub fn len(&self) -> usize {
+                Self::NAMES.len() + self.extra.len()
+            }
+
+            /// Строка не содержит колонок.
+            pub fn is_empty(&self) -> bool {
+                self.len() == 0
+            }
+
+            /// Значения всех колонок строки по порядку.
+            pub fn values(&self) -> impl Iterator<Item = &str> {
+                [$(self.$field.as_str()),+].into_iter().chain(self.extra.iter().map(String::as_str))
+            }
+
+            /// Значение колонки с номером `index` или `None`, если колонки нет.
+            pub fn cell(&self, index: usize) -> Option<&str> {
+                self.values().nth(index)
+            }
+
+            /// Записывает `value` в колонку с номером `index`, добавляя недостающие колонки.
+            pub fn set_cell(&mut self, index: usize, value: &str) {
+                if let Some(field) = [$(&mut self.$field),+].into_iter().nth(index) {
+                    *field = value.to_string();
+                    return;
+                }
+                let extra = index - Self::NAMES.len();
+                if self.extra.len() <= extra {
+                    self.extra.resize(extra + 1, String::new());
+                }
+                self.extra[extra] = value.to_string();
+            }
+
+            /// Значение колонки `column` (буквы или номер, например `"m"`) или `None`, если колонки нет.
+            pub fn get(&self, column: &str) -> Option<&str> {
+                self.cell(Self::column_index(column)?)
+            }
+
+            /// Записывает `value` в колонку `column`; возвращает `false`, если ссылка на колонку неверна.
+            pub fn set(&mut self, column: &str, value: &str) -> bool {
+                match Self::column_index(column) {
+                    Some(index) => {
+                        self.set_cell(index, value);
+                        true
+                    }
+                    None => false,
                 }
             }
         }
     };
 }
-
-
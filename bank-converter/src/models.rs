@@ -3,4 +3,14 @@
 //! Предоставляет функциональность по описании структуры и трансформаци данных между форматами
 pub mod camt053;
 pub mod mt940;
-pub mod csv;
\ No newline at end of file
+pub mod mt942;
+pub mod csv;
+pub mod csv_profile;
+pub mod csv_flat;
+pub mod client_bank;
+pub mod builder;
+pub mod money;
+pub mod date;
+pub mod codes;
+pub mod narrative;
+pub mod swift;
@@ -0,0 +1,329 @@
+//! Модуль содержит построители для модели CAMT.053
+//!
+//! Предоставляет функциональность по созданию выписок, записей, остатков и участников
+//! операций без чтения файлов
+//!
+//! ```
+//! use bank_converter::models::builder::{BalanceBuilder, EntryBuilder, PartyBuilder, StatementBuilder};
+//! use bank_converter::models::codes::BalanceType;
+//! use bank_converter::models::money::Money;
+//! use chrono::NaiveDate;
+//!
+//! let creditor = PartyBuilder::new("ООО РОМАШКА")
+//!     .id("7735602068")
+//!     .account("40702810440000030888")
+//!     .bic("044525545")
+//!     .build();
+//! let statement = StatementBuilder::new("STMT-1")
+//!     .account("40702810440000030888", "RUB")
+//!     .balance(BalanceBuilder::new(BalanceType::Opbd).amount(Money::new(10000, 2), "RUB").credit().date(NaiveDate::from_ymd_opt(2024, 1, 1).unwrap()).build())
+//!     .entry(EntryBuilder::new().amount(Money::new(1000, 2), "RUB").credit().booking_date(NaiveDate::from_ymd_opt(2024, 1, 2).unwrap())
+//!         .creditor(creditor).build())
+//!     .build();
+//! assert_eq!(1, statement.stmt.ntry.len());
+//! ```
+use chrono::NaiveDate;
+use crate::models::camt053::{AmtAttribute, BalanceAttribute, BkToCstmrStmt, DtAttribute, NtryAttribute,
+                             TxDtlsAttribute};
+use crate::models::codes::{BalanceType, CreditDebit};
+use crate::models::date::IsoDateTime;
+use crate::models::money::Money;
+
+/// Построитель выписки `BkToCstmrStmt`
+#[derive(Debug, Default)]
+pub struct StatementBuilder {
+    statement: BkToCstmrStmt,
+}
+
+/// Построитель остатка `BalanceAttribute`
+#[derive(Debug, Default)]
+pub struct BalanceBuilder {
+    balance: BalanceAttribute,
+}
+
+/// Построитель записи выписки `NtryAttribute`
+#[derive(Debug, Default)]
+pub struct EntryBuilder {
+    entry: NtryAttribute,
+    details: TxDtlsAttribute,
+}
+
+/// Участник операции: плательщик или получатель вместе со счетом и банком
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Party {
+    /// Наименование участника
+    pub nm: String,
+    /// Идентификатор участника (например, ИНН)
+    pub id: String,
+    /// Регистрационный номер участника (например, КПП)
+    pub kpp: String,
+    /// Номер счета участника
+    pub acct: String,
+    /// BIC банка участника
+    pub bic: String,
+    /// Наименование банка участника
+    pub bank_nm: String,
+}
+
+/// Построитель участника операции `Party`
+#[derive(Debug, Default)]
+pub struct PartyBuilder {
+    party: Party,
+}
+
+impl StatementBuilder {
+    /// Создает построитель выписки с идентификатором выписки и сообщения `id`.
+    pub fn new(id: &str) -> Self {
+        let mut statement = BkToCstmrStmt::default();
+        statement.grp_hdr.msg_id = id.to_string();
+        statement.stmt.id = id.to_string();
+        Self { statement }
+    }
+
+    /// Задает идентификатор сообщения (GrpHdr/MsgId).
+    pub fn msg_id(mut self, msg_id: &str) -> Self {
+        self.statement.grp_hdr.msg_id = msg_id.to_string();
+        self
+    }
+
+    /// Задает дату и время создания сообщения и выписки.
+    pub fn created(mut self, cre_dt_tm: IsoDateTime) -> Self {
+        self.statement.grp_hdr.cre_dt_tm = Some(cre_dt_tm);
+        self.statement.stmt.cre_dt_tm = Some(cre_dt_tm);
+        self
+    }
+
+    /// Задает электронный и юридический порядковые номера выписки.
+    pub fn sequence(mut self, elctrnc_seq_nb: &str, lgl_seq_nb: &str) -> Self {
+        self.statement.stmt.elctrnc_seq_nb = elctrnc_seq_nb.to_string();
+        self.statement.stmt.lgl_seq_nb = lgl_seq_nb.to_string();
+        self
+    }
+
+    /// Задает период выписки.
+    pub fn period(mut self, fr_dt_tm: IsoDateTime, to_dt_tm: IsoDateTime) -> Self {
+        self.statement.stmt.fr_to_dt.fr_dt_tm = Some(fr_dt_tm);
+        self.statement.stmt.fr_to_dt.to_dt_tm = Some(to_dt_tm);
+        self
+    }
+
+    /// Задает номер счета и его валюту.
+    pub fn account(mut self, id: &str, ccy: &str) -> Self {
+        self.statement.stmt.acct.id.othr.id = id.to_string();
+        self.statement.stmt.acct.ccy = ccy.to_string();
+        self
+    }
+
+    /// Задает IBAN счета.
+    pub fn iban(mut self, iban: &str) -> Self {
+        self.statement.stmt.acct.id.iban = iban.to_string();
+        self
+    }
+
+    /// Задает наименование владельца счета.
+    pub fn owner(mut self, nm: &str) -> Self {
+        self.statement.stmt.acct.ownr.nm = nm.to_string();
+        self
+    }
+
+    /// Задает BIC обслуживающего банка.
+    pub fn servicer_bic(mut self, bic: &str) -> Self {
+        self.statement.stmt.acct.svcr.fin_instn_id.bic = bic.to_string();
+        self
+    }
+
+    /// Добавляет остаток.
+    pub fn balance(mut self, balance: BalanceAttribute) -> Self {
+        self.statement.stmt.bal.push(balance);
+        self
+    }
+
+    /// Добавляет запись.
+    pub fn entry(mut self, entry: NtryAttribute) -> Self {
+        self.statement.stmt.ntry.push(entry);
+        self
+    }
+
+    /// Возвращает собранную выписку.
+    pub fn build(self) -> BkToCstmrStmt {
+        self.statement
+    }
+}
+
+impl BalanceBuilder {
+    /// Создает построитель остатка типа `cd`.
+    pub fn new(cd: BalanceType) -> Self {
+        let mut balance = BalanceAttribute::default();
+        balance.tp.cd_or_prtry.cd = cd;
+        Self { balance }
+    }
+
+    /// Задает сумму и валюту остатка.
+    pub fn amount(mut self, amt: Money, ccy: &str) -> Self {
+        self.balance.amt = AmtAttribute::new(amt, ccy);
+        self
+    }
+
+    /// Отмечает остаток как кредитовый.
+    pub fn credit(mut self) -> Self {
+        self.balance.cdt_dbt_ind = CreditDebit::Credit;
+        self
+    }
+
+    /// Отмечает остаток как дебетовый.
+    pub fn debit(mut self) -> Self {
+        self.balance.cdt_dbt_ind = CreditDebit::Debit;
+        self
+    }
+
+    /// Задает дату остатка.
+    pub fn date(mut self, dt: NaiveDate) -> Self {
+        self.balance.dt = DtAttribute::new(dt);
+        self
+    }
+
+    /// Возвращает собранный остаток.
+    pub fn build(self) -> BalanceAttribute {
+        self.balance
+    }
+}
+
+impl EntryBuilder {
+    /// Создает построитель пустой записи.
+    pub fn new() -> Self {
+        EntryBuilder::default()
+    }
+
+    /// Задает сумму и валюту записи.
+    pub fn amount(mut self, amt: Money, ccy: &str) -> Self {
+        self.entry.amt = AmtAttribute::new(amt, ccy);
+        self
+    }
+
+    /// Отмечает запись как кредитовую.
+    pub fn credit(mut self) -> Self {
+        self.entry.cdt_dbt_ind = CreditDebit::Credit;
+        self
+    }
+
+    /// Отмечает запись как дебетовую.
+    pub fn debit(mut self) -> Self {
+        self.entry.cdt_dbt_ind = CreditDebit::Debit;
+        self
+    }
+
+    /// Задает статус записи (например, BOOK).
+    pub fn status(mut self, sts: &str) -> Self {
+        self.entry.sts = sts.to_string();
+        self
+    }
+
+    /// Задает дату проводки.
+    pub fn booking_date(mut self, dt: NaiveDate) -> Self {
+        self.entry.bookg_dt = DtAttribute::new(dt);
+        self
+    }
+
+    /// Задает дату валютирования.
+    pub fn value_date(mut self, dt: NaiveDate) -> Self {
+        self.entry.val_dt = DtAttribute::new(dt);
+        self
+    }
+
+    /// Задает ссылку обслуживающего банка.
+    pub fn servicer_ref(mut self, acct_svcr_ref: &str) -> Self {
+        self.entry.acct_svcr_ref = acct_svcr_ref.to_string();
+        self
+    }
+
+    /// Задает собственный код банковской операции и его эмитента.
+    pub fn bank_tx_code(mut self, cd: &str, issr: &str) -> Self {
+        self.entry.bk_tx_cd.prtry.cd = cd.to_string();
+        self.entry.bk_tx_cd.prtry.issr = issr.to_string();
+        self
+    }
+
+    /// Задает сквозной идентификатор операции.
+    pub fn end_to_end_id(mut self, end_to_end_id: &str) -> Self {
+        self.details.refs.end_to_end_id = end_to_end_id.to_string();
+        self
+    }
+
+    /// Добавляет строку неструктурированного назначения платежа.
+    pub fn remittance(mut self, ustrd: &str) -> Self {
+        self.details.rmt_inf.ustrd.push(ustrd.to_string());
+        self
+    }
+
+    /// Задает плательщика, его счет и банк.
+    pub fn debtor(mut self, party: Party) -> Self {
+        let pties = &mut self.details.rltd_pties;
+        pties.dbtr.nm = party.nm;
+        pties.dbtr.id.othr.id = party.id;
+        self.details.tax.dbtr.regn_id = party.kpp;
+        pties.dbtr_acct.other.id = party.acct;
+        self.details.rltd_agts.dbtr_agt.fin_instn_id.bic = party.bic;
+        self.details.rltd_agts.dbtr_agt.fin_instn_id.nm = party.bank_nm;
+        self
+    }
+
+    /// Задает получателя, его счет и банк.
+    pub fn creditor(mut self, party: Party) -> Self {
+        let pties = &mut self.details.rltd_pties;
+        pties.cdtr.nm = party.nm;
+        pties.cdtr.id.othr.id = party.id;
+        self.details.tax.cdtr.regn_id = party.kpp;
+        pties.cdtr_acct.other.id = party.acct;
+        self.details.rltd_agts.cdtr_agt.fin_instn_id.bic = party.bic;
+        self.details.rltd_agts.cdtr_agt.fin_instn_id.nm = party.bank_nm;
+        self
+    }
+
+    /// Возвращает собранную запись с единственной деталью операции.
+    pub fn build(mut self) -> NtryAttribute {
+        self.entry.ntry_dtls.tx_dtls.push(self.details);
+        self.entry
+    }
+}
+
+impl PartyBuilder {
+    /// Создает построитель участника с наименованием `nm`.
+    pub fn new(nm: &str) -> Self {
+        Self { party: Party { nm: nm.to_string(), ..Default::default() } }
+    }
+
+    /// Задает идентификатор участника (например, ИНН).
+    pub fn id(mut self, id: &str) -> Self {
+        self.party.id = id.to_string();
+        self
+    }
+
+    /// Задает регистрационный номер участника (например, КПП).
+    pub fn kpp(mut self, kpp: &str) -> Self {
+        self.party.kpp = kpp.to_string();
+        self
+    }
+
+    /// Задает номер счета участника.
+    pub fn account(mut self, acct: &str) -> Self {
+        self.party.acct = acct.to_string();
+        self
+    }
+
+    /// Задает BIC банка участника.
+    pub fn bic(mut self, bic: &str) -> Self {
+        self.party.bic = bic.to_string();
+        self
+    }
+
+    /// Задает наименование банка участника.
+    pub fn bank_name(mut self, bank_nm: &str) -> Self {
+        self.party.bank_nm = bank_nm.to_string();
+        self
+    }
+
+    /// Возвращает собранного участника.
+    pub fn build(self) -> Party {
+        self.party
+    }
+}
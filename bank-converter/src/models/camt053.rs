@@ -3,418 +3,1025 @@
 //! Предоставляет функциональность по описании структуры и трансформаци данных между форматами
 //! 
 use serde::{Deserialize, Serialize};
+use chrono::NaiveDate;
+use crate::models::codes::{deserialize_optional_credit_debit, BalanceType, CreditDebit};
+use crate::models::date::{deserialize_optional, IsoDateTime};
+use crate::models::money::Money;
+use crate::validate::summary_issues;
+use crate::errors::ParserError;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// Версия схемы ISO 20022 camt.053
+///
+/// Читаются документы версий .001.02 - .001.11, записываются версии .001.02, .001.04 и .001.08.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub enum CamtVersion {
+    /// camt.053.001.02
+    #[default]
+    V02,
+    /// camt.053.001.03
+    V03,
+    /// camt.053.001.04
+    V04,
+    /// camt.053.001.05
+    V05,
+    /// camt.053.001.06
+    V06,
+    /// camt.053.001.07
+    V07,
+    /// camt.053.001.08
+    V08,
+    /// camt.053.001.09
+    V09,
+    /// camt.053.001.10
+    V10,
+    /// camt.053.001.11
+    V11,
+}
+
+impl CamtVersion {
+    /// Идентификатор сообщения, например `camt.053.001.02`.
+    pub fn code(&self) -> &'static str {
+        match self {
+            CamtVersion::V02 => "camt.053.001.02",
+            CamtVersion::V03 => "camt.053.001.03",
+            CamtVersion::V04 => "camt.053.001.04",
+            CamtVersion::V05 => "camt.053.001.05",
+            CamtVersion::V06 => "camt.053.001.06",
+            CamtVersion::V07 => "camt.053.001.07",
+            CamtVersion::V08 => "camt.053.001.08",
+            CamtVersion::V09 => "camt.053.001.09",
+            CamtVersion::V10 => "camt.053.001.10",
+            CamtVersion::V11 => "camt.053.001.11",
+        }
+    }
+
+    /// Пространство имен XML схемы.
+    pub fn namespace(&self) -> String {
+        format!("{}{}", CAMT_NAMESPACE_PREFIX, self.code())
+    }
+
+    /// Версия по пространству имен XML схемы или `None`, если это не схема camt.053.
+    pub fn from_namespace(namespace: &str) -> Option<Self> {
+        namespace.strip_prefix(CAMT_NAMESPACE_PREFIX)?.parse().ok()
+    }
+
+    /// Версия поддерживается для записи.
+    pub fn is_writable(&self) -> bool {
+        matches!(self, CamtVersion::V02 | CamtVersion::V04 | CamtVersion::V08)
+    }
+}
+
+/// Начало пространства имен XML схем ISO 20022
+const CAMT_NAMESPACE_PREFIX: &str = "urn:iso:std:iso:20022:tech:xsd:";
+
+impl FromStr for CamtVersion {
+    type Err = ParserError;
+
+    /// Разбирает версию вида `02`, `001.02` или `camt.053.001.02`.
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let number = value.trim().trim_start_matches("camt.053.").trim_start_matches("001.");
+        match number.trim_start_matches('0') {
+            "2" => Ok(CamtVersion::V02),
+            "3" => Ok(CamtVersion::V03),
+            "4" => Ok(CamtVersion::V04),
+            "5" => Ok(CamtVersion::V05),
+            "6" => Ok(CamtVersion::V06),
+            "7" => Ok(CamtVersion::V07),
+            "8" => Ok(CamtVersion::V08),
+            "9" => Ok(CamtVersion::V09),
+            "10" => Ok(CamtVersion::V10),
+            "11" => Ok(CamtVersion::V11),
+            _ => Err(ParserError::UnknownCode(value.to_string())),
+        }
+    }
+}
+
+impl Display for CamtVersion {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.code())
+    }
+}
 /// Структура для документа CAMT.053
 /// Содержит методы для чтения, транофрмации и запис данных CAMT053
-/// ```no_run
+/// ```ignore
 ///
 /// pub fn from_read<R: Read>(r: &mut R) -> Result<Self, ParserError> 
 /// pub fn write_to<W: Write>(&mut self, writer: &mut W) -> Result<(), ConvertError> 
-/// 
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
-#[serde(rename="Document", default)]
+/// pub fn write_to_version<W: Write>(&mut self, writer: &mut W, version: CamtVersion) -> Result<(), ConvertError>
+/// ```
+///
+/// При чтении выписки одного сообщения (несколько Stmt под одним GrpHdr) разворачиваются
+/// в отдельные `BkToCstmrStmt` с общим заголовком.
+#[derive(Debug, Clone, Deserialize, Serialize, Default, PartialEq)]
+#[serde(rename="Document", default, from = "DocumentXml")]
 pub struct DocumentCamt053 {
+    /// Версия схемы прочитанного документа; `None`, если документ без пространства имен
+    #[serde(skip)]
+    pub version: Option<CamtVersion>,
+    /// Выписки документа (BkToCstmrStmt)
     #[serde(rename="BkToCstmrStmt")]
-    pub(crate) bk_to_cstmr_stmt: Vec<BkToCstmrStmt>
+    pub bk_to_cstmr_stmt: Vec<BkToCstmrStmt>,
 }
 
-#[derive(Debug, Deserialize, Serialize, Default, PartialEq)]
+/// Выписка банка клиенту (BkToCstmrStmt): заголовок сообщения и тело выписки
+#[derive(Debug, Clone, Deserialize, Serialize, Default, PartialEq)]
 #[serde(rename_all = "PascalCase", default)]
-pub(crate) struct BkToCstmrStmt {
-    pub(crate) grp_hdr: HeaderAttribute,
-    pub(crate) stmt: StatementAttribute,
+pub struct BkToCstmrStmt {
+    /// Заголовок сообщения (GrpHdr)
+    pub grp_hdr: HeaderAttribute,
+    /// Выписка (Stmt)
+    pub stmt: StatementAttribute,
 }
 
-#[derive(Debug, Deserialize, Serialize, Default, PartialEq)]
+/// Заголовок сообщения (GrpHdr)
+#[derive(Debug, Clone, Deserialize, Serialize, Default, PartialEq)]
 #[serde(rename_all = "PascalCase", default)]
-pub(crate) struct HeaderAttribute{
-    pub(crate) msg_id: String, //message id
-    pub(crate) cre_dt_tm: String, //datetime create file
+pub struct HeaderAttribute {
+    /// Идентификатор сообщения (MsgId)
+    pub msg_id: String,
+    /// Дата и время создания сообщения (CreDtTm)
+    #[serde(skip_serializing_if = "Option::is_none", deserialize_with = "deserialize_optional")]
+    pub cre_dt_tm: Option<IsoDateTime>,
 }
 
-#[derive(Debug, Deserialize, Serialize, Default, PartialEq)]
+/// Выписка по счету (Stmt)
+#[derive(Debug, Clone, Deserialize, Serialize, Default, PartialEq)]
 #[serde(rename_all = "PascalCase", default)]
-pub(crate)  struct StatementAttribute{
-    pub(crate) id: String, //id
-    pub(crate) elctrnc_seq_nb: String, //ElctrncSeqNb
-    pub(crate) lgl_seq_nb: String, //LglSeqNb
-    pub(crate) cre_dt_tm: String, //CreDtTm
-    pub(crate) fr_to_dt: FromToDtAttribute, //FrToDt
-    pub(crate) acct: AcctAttribute, //Acct
-    pub(crate) bal: Vec<BalanceAttribute>, //Bal
-    pub(crate) txs_summry: TxsSummryAttribute, //TxsSummry
-    pub(crate) ntry: Vec<NtryAttribute> //Ntry
+pub struct StatementAttribute {
+    /// Идентификатор выписки (Id)
+    pub id: String,
+    /// Электронный порядковый номер (ElctrncSeqNb)
+    pub elctrnc_seq_nb: String,
+    /// Юридический порядковый номер (LglSeqNb)
+    pub lgl_seq_nb: String,
+    /// Дата и время создания выписки (CreDtTm)
+    #[serde(skip_serializing_if = "Option::is_none", deserialize_with = "deserialize_optional")]
+    pub cre_dt_tm: Option<IsoDateTime>,
+    /// Период выписки (FrToDt)
+    pub fr_to_dt: FromToDtAttribute,
+    /// Счет (Acct)
+    pub acct: AcctAttribute,
+    /// Остатки (Bal)
+    pub bal: Vec<BalanceAttribute>,
+    /// Минимальные суммы записей отчета (поле :34F: MT942); в CAMT.053 не записываются
+    #[serde(skip)]
+    pub flr_lmt: Vec<FloorLimitAttribute>,
+    /// Итоги по операциям (TxsSummry); пустые итоги не записываются
+    #[serde(skip_serializing_if = "TxsSummryAttribute::is_empty")]
+    pub txs_summry: TxsSummryAttribute,
+    /// Записи выписки (Ntry)
+    pub ntry: Vec<NtryAttribute>,
 
 }
 
-#[derive(Debug, Deserialize, Serialize, Default, PartialEq)]
+/// Запись (проводка) выписки (Ntry)
+#[derive(Debug, Clone, Deserialize, Serialize, Default, PartialEq)]
 #[serde(rename_all = "PascalCase", default)]
-pub (crate) struct NtryAttribute{
-    pub(crate) ntry_ref: u32, //NtryRef
-    pub(crate) amt: AmtAttribute, //Amt
-    pub(crate) cdt_dbt_ind: String, //CdtDbtInd
-    pub(crate) sts: String, //Sts
-    pub(crate) bookg_dt: DtAttribute, //BookgDt
-    pub(crate) val_dt: DtAttribute, //ValDt
-    pub(crate) acct_svcr_ref: String, //AcctSvcrRef
-    pub(crate) bk_tx_cd: BxTxCdAttribute, //BkTxCd
-    pub(crate) addtl_inf_ind: AddtlTxInfAtttribute, //AddtlTxInf
-    pub(crate) ntry_dtls: NtryDtlsAttribute//NtryDtls
-
+pub struct NtryAttribute {
+    /// Ссылка на запись (NtryRef)
+    pub ntry_ref: u32,
+    /// Сумма (Amt)
+    pub amt: AmtAttribute,
+    /// Признак кредит/дебет (CdtDbtInd)
+    pub cdt_dbt_ind: CreditDebit,
+    /// Признак сторно (RvslInd)
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub rvsl_ind: bool,
+    /// Код средств MT940 - третья буква кода валюты в поле :61:; в CAMT.053 не записывается
+    #[serde(skip)]
+    pub funds_code: String,
+    /// Статус записи (Sts)
+    pub sts: String,
+    /// Дата проводки (BookgDt)
+    pub bookg_dt: DtAttribute,
+    /// Дата валютирования (ValDt)
+    pub val_dt: DtAttribute,
+    /// Ссылка обслуживающего банка (AcctSvcrRef)
+    pub acct_svcr_ref: String,
+    /// Код банковской операции (BkTxCd)
+    pub bk_tx_cd: BxTxCdAttribute,
+    /// Дополнительная информация (AddtlInfInd)
+    pub addtl_inf_ind: AddtlTxInfAtttribute,
+    /// Детали записи (NtryDtls)
+    pub ntry_dtls: NtryDtlsAttribute,
+    /// Дополнительная информация о записи (AddtlNtryInf)
+    pub addtl_ntry_inf: String,
 }
 
-#[derive(Debug, Deserialize, Serialize, Default, PartialEq)]
+/// Детали записи (NtryDtls)
+#[derive(Debug, Clone, Deserialize, Serialize, Default, PartialEq)]
 #[serde(rename_all = "PascalCase", default)]
-pub(crate) struct NtryDtlsAttribute{
-    pub(crate) btch: BtchAttribute, //Btch
-    pub(crate) tx_dtls: Vec<TxDtlsAttribute>//TxDtls
+pub struct NtryDtlsAttribute {
+    /// Сведения о пакете (Btch)
+    pub btch: BtchAttribute,
+    /// Детали операций (TxDtls)
+    pub tx_dtls: Vec<TxDtlsAttribute>,
 }
-#[derive(Debug, Deserialize, Serialize, Default, PartialEq)]
+/// Сведения о пакете операций (Btch)
+#[derive(Debug, Clone, Deserialize, Serialize, Default, PartialEq)]
 #[serde(rename_all = "PascalCase", default)]
-pub(crate) struct BtchAttribute{
-    pub(crate) nb_of_txs: u32, //NbOfTxs
+pub struct BtchAttribute {
+    /// Количество операций (NbOfTxs)
+    pub nb_of_txs: u32,
 }
-#[derive(Debug, Deserialize, Serialize, Default, PartialEq)]
+/// Детали операции (TxDtls)
+#[derive(Debug, Clone, Deserialize, Serialize, Default, PartialEq)]
 #[serde(rename_all = "PascalCase", default)]
-pub(crate) struct TxDtlsAttribute {
-    pub(crate) refs: EndToEndIdAttribute, //Refs
-    pub(crate) amt_dtls: TxAmtAttribute, //AmtDtls
-    pub(crate) bk_tx_cd: BxTxCdAttribute, //BxTxCd
-    pub(crate) rltd_pties: RltdPtiesAttribute, //RltdPties
-    pub(crate) rltd_agts: CdtrAgtAttribute, //RltdAgts
-    pub(crate) rmt_inf: RmtInfAttribute, //RmtInf
-    pub(crate) rltd_dts: RltdDtsAttribute, //RltdDts
-    pub(crate) addtl_tx_inf: String
+pub struct TxDtlsAttribute {
+    /// Ссылки (Refs)
+    pub refs: EndToEndIdAttribute,
+    /// Суммы (AmtDtls)
+    pub amt_dtls: TxAmtAttribute,
+    /// Код банковской операции (BkTxCd)
+    pub bk_tx_cd: BxTxCdAttribute,
+    /// Участники (RltdPties)
+    pub rltd_pties: RltdPtiesAttribute,
+    /// Банки участников (RltdAgts)
+    pub rltd_agts: CdtrAgtAttribute,
+    /// Назначение платежа (RmtInf)
+    pub rmt_inf: RmtInfAttribute,
+    /// Связанные даты (RltdDts)
+    pub rltd_dts: RltdDtsAttribute,
+    /// Налоговые реквизиты участников (Tax)
+    pub tax: TaxAttribute,
+    /// Дополнительная информация об операции (AddtlTxInf)
+    pub addtl_tx_inf: String,
 
 }
 
-#[derive(Debug, Deserialize, Default, Serialize, PartialEq)]
+/// Дополнительная информация о сообщении (AddtlInfInd)
+#[derive(Debug, Clone, Deserialize, Serialize, Default, PartialEq)]
 #[serde(rename_all = "PascalCase", default)]
-pub(crate) struct AddtlTxInfAtttribute{
-    pub(crate) msg_nm_id: String,
+pub struct AddtlTxInfAtttribute {
+    /// Идентификатор сообщения (MsgNmId)
+    pub msg_nm_id: String,
 }
 
-#[derive(Debug, Deserialize, Serialize, Default, PartialEq)]
+/// Банки участников операции (RltdAgts)
+#[derive(Debug, Clone, Deserialize, Serialize, Default, PartialEq)]
 #[serde(rename_all = "PascalCase", default)]
-pub(crate) struct CdtrAgtAttribute{
-    pub(crate) cdtr_agt: SvcrAttribute,
-    pub(crate) dbtr_agt: SvcrAttribute,
+pub struct CdtrAgtAttribute {
+    /// Банк получателя (CdtrAgt)
+    pub cdtr_agt: SvcrAttribute,
+    /// Банк плательщика (DbtrAgt)
+    pub dbtr_agt: SvcrAttribute,
 }
 
-#[derive(Debug, Deserialize, Serialize, Default, PartialEq)]
+/// Ссылки операции (Refs)
+#[derive(Debug, Clone, Deserialize, Serialize, Default, PartialEq)]
 #[serde(rename_all = "PascalCase", default)]
-pub(crate) struct EndToEndIdAttribute{
-    pub(crate) pmt_inf_id: String, //PmtInfId
-    pub(crate) instr_id: String,//InstrId
-    pub(crate) end_to_end_id: String,
-    pub(crate) tx_id: String,
-    pub(crate) prtry: PrtryDetAttribute
+pub struct EndToEndIdAttribute {
+    /// Идентификатор платежной информации (PmtInfId)
+    pub pmt_inf_id: String,
+    /// Идентификатор инструкции (InstrId)
+    pub instr_id: String,
+    /// Сквозной идентификатор (EndToEndId)
+    pub end_to_end_id: String,
+    /// Идентификатор операции (TxId)
+    pub tx_id: String,
+    /// Собственная ссылка (Prtry)
+    pub prtry: PrtryDetAttribute,
 }
 
-#[derive(Debug, Deserialize, Serialize, Default, PartialEq)]
+/// Собственная ссылка банка (Prtry)
+#[derive(Debug, Clone, Deserialize, Serialize, Default, PartialEq)]
 #[serde(rename_all = "PascalCase", default)]
-pub(crate) struct PrtryDetAttribute{
-    pub(crate) tp: String,
+pub struct PrtryDetAttribute {
+    /// Тип ссылки (Tp)
+    pub tp: String,
+    /// Значение ссылки (Ref)
     #[serde(rename="Ref")]
-    pub(crate) refdt: String
+    pub refdt: String,
 }
 
-#[derive(Debug, Deserialize, Serialize, Default, PartialEq)]
+/// Суммы операции (AmtDtls)
+#[derive(Debug, Clone, Deserialize, Serialize, Default, PartialEq)]
 #[serde(rename_all = "PascalCase", default)]
-pub(crate) struct TxAmtAttribute{
-    pub(crate) end_to_end_id: String,
-    pub(crate) instd_amt: PrtryAmtAttribute,
-    pub(crate) tx_amt: PrtryAmtAttribute, //TxAmt
-    pub(crate) prtry_amt: PrtryAmtAttribute,//PrtryAmt
-    pub(crate) amt: String,
+pub struct TxAmtAttribute {
+    /// Сквозной идентификатор (EndToEndId)
+    pub end_to_end_id: String,
+    /// Сумма инструкции (InstdAmt)
+    pub instd_amt: PrtryAmtAttribute,
+    /// Сумма операции (TxAmt)
+    pub tx_amt: PrtryAmtAttribute,
+    /// Собственная сумма банка (PrtryAmt)
+    pub prtry_amt: PrtryAmtAttribute,
+    /// Исходная сумма операции (код `/OAMT/` поля :86:)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub amt: Option<Money>,
 }
 
 
 
-#[derive(Debug, Deserialize, Serialize, Default, PartialEq)]
+/// Сумма с типом и курсом конвертации
+#[derive(Debug, Clone, Deserialize, Serialize, Default, PartialEq)]
 #[serde(rename_all = "PascalCase", default)]
-pub(crate) struct PrtryAmtAttribute{
-    pub(crate) tp: String,
-    pub(crate) amt: AmtAttribute,
-    pub(crate) ccy_xchg: CcyXchgAttribute
+pub struct PrtryAmtAttribute {
+    /// Тип суммы (Tp)
+    pub tp: String,
+    /// Сумма (Amt)
+    pub amt: AmtAttribute,
+    /// Курс конвертации (CcyXchg)
+    pub ccy_xchg: CcyXchgAttribute,
 }
 
-#[derive(Debug, Deserialize, Serialize, Default, PartialEq)]
+/// Курс конвертации валют (CcyXchg)
+#[derive(Debug, Clone, Deserialize, Serialize, Default, PartialEq)]
 #[serde(rename_all = "PascalCase", default)]
-pub(crate) struct CcyXchgAttribute{
-    pub(crate) src_ccy: String, //SrcCcy
-    pub(crate) trgt_ccy: String, //TrgtCcy
-    pub(crate) unit_ccy: String, //UnitCcy
-    pub(crate) xchg_rate: String //XchgRate
+pub struct CcyXchgAttribute {
+    /// Исходная валюта (SrcCcy)
+    pub src_ccy: String,
+    /// Целевая валюта (TrgtCcy)
+    pub trgt_ccy: String,
+    /// Единица валюты курса (UnitCcy)
+    pub unit_ccy: String,
+    /// Курс (XchgRate)
+    pub xchg_rate: String,
 }
 
-#[derive(Debug, Deserialize, Serialize, Default, PartialEq)]
+/// Участники операции (RltdPties)
+#[derive(Debug, Clone, Deserialize, Serialize, Default, PartialEq)]
 #[serde(rename_all = "PascalCase", default)]
-pub(crate) struct RltdPtiesAttribute{
-    pub(crate) dbtr: DbtrAttribute,
-    pub(crate) dbtr_acct: IdTxDtlsAttribute,
-    pub(crate) cdtr: CdtrAttribue,
-    pub(crate) cdtr_acct: IdTxDtlsAttribute,
+pub struct RltdPtiesAttribute {
+    /// Плательщик (Dbtr)
+    pub dbtr: DbtrAttribute,
+    /// Счет плательщика (DbtrAcct)
+    pub dbtr_acct: IdTxDtlsAttribute,
+    /// Получатель (Cdtr)
+    pub cdtr: CdtrAttribue,
+    /// Счет получателя (CdtrAcct)
+    pub cdtr_acct: IdTxDtlsAttribute,
 }
 
-#[derive(Debug, Deserialize, Serialize, Default, PartialEq)]
+/// Плательщик (Dbtr)
+#[derive(Debug, Clone, Deserialize, Serialize, Default, PartialEq)]
 #[serde(rename_all = "PascalCase", default)]
-pub(crate) struct DbtrAttribute{
-    pub(crate) id: PrvtIdAttribute,
-    pub(crate) nm: String,
-    pub(crate) pstl_adr: PostalAddressAttribute
+pub struct DbtrAttribute {
+    /// Идентификация (Id)
+    pub id: PrvtIdAttribute,
+    /// Наименование (Nm)
+    pub nm: String,
+    /// Почтовый адрес (PstlAdr)
+    pub pstl_adr: PostalAddressAttribute,
+}
+
+/// Идентификация участника (Id)
+///
+/// При чтении принимается как `Id/Othr`, так и `Id/OrgId/Othr` и `Id/PrvtId/Othr` схемы ISO 20022.
+#[derive(Debug, Clone, Deserialize, Serialize, Default, PartialEq)]
+#[serde(rename_all = "PascalCase", default, from = "PartyIdXml")]
+pub struct PrvtIdAttribute {
+    /// Прочий идентификатор, например ИНН (Othr)
+    pub othr: IdDtldAttribute,
 }
 
-#[derive(Debug, Deserialize, Serialize, Default, PartialEq)]
+/// Получатель (Cdtr)
+#[derive(Debug, Clone, Deserialize, Serialize, Default, PartialEq)]
 #[serde(rename_all = "PascalCase", default)]
-pub(crate) struct PrvtIdAttribute {
-    pub(crate) othr: IdDtldAttribute
+pub struct CdtrAttribue {
+    /// Идентификация (Id)
+    pub id: PrvtIdAttribute,
+    /// Наименование (Nm)
+    pub nm: String,
+    /// Почтовый адрес (PstlAdr)
+    pub pstl_adr: PostalAddressAttribute,
 }
 
-#[derive(Debug, Deserialize, Serialize, Default, PartialEq)]
+/// Назначение платежа (RmtInf)
+#[derive(Debug, Clone, Deserialize, Serialize, Default, PartialEq)]
 #[serde(rename_all = "PascalCase", default)]
-pub(crate) struct CdtrAttribue{
-    pub(crate) id: PrvtIdAttribute,
-    pub(crate) nm: String,
-    pub(crate) pstl_adr: PostalAddressAttribute //PstlAdr
+pub struct RmtInfAttribute {
+    /// Неструктурированное назначение платежа (Ustrd)
+    pub ustrd: Vec<String>,
+    /// Структурированное назначение платежа (Strd)
+    pub strd: StrdAttribute,
+
 }
 
-#[derive(Debug, Deserialize, Serialize, Default, PartialEq)]
+/// Структурированное назначение платежа (Strd)
+#[derive(Debug, Clone, Deserialize, Serialize, Default, PartialEq)]
 #[serde(rename_all = "PascalCase", default)]
-pub(crate) struct RmtInfAttribute{
-    pub(crate) ustrd: Vec<String>,
-    pub(crate) strd: StrdAttribute
+pub struct StrdAttribute {
+   /// Ссылка получателя (CdtrRefInf)
+   pub cdtr_ref_inf: CdtrRefInfAttribute,
+}
 
+/// Даты, связанные с операцией (RltdDts)
+#[derive(Debug, Clone, Deserialize, Serialize, Default, PartialEq)]
+#[serde(rename_all = "PascalCase", default)]
+pub struct RltdDtsAttribute {
+    /// Дата и время акцепта (AccptncDtTm)
+    #[serde(skip_serializing_if = "Option::is_none", deserialize_with = "deserialize_optional")]
+    pub accptnc_dt_tm: Option<IsoDateTime>,
 }
 
-#[derive(Debug, Deserialize, Serialize, Default, PartialEq)]
+/// Налоговые реквизиты участников (Tax)
+#[derive(Debug, Clone, Deserialize, Serialize, Default, PartialEq)]
 #[serde(rename_all = "PascalCase", default)]
-pub(crate) struct StrdAttribute {
-   pub(crate) cdtr_ref_inf: CdtrRefInfAttribute //CdrtRefInf
+pub struct TaxAttribute {
+    /// Реквизиты получателя (Cdtr)
+    pub cdtr: TaxPartyAttribute,
+    /// Реквизиты плательщика (Dbtr)
+    pub dbtr: TaxPartyAttribute,
 }
 
-#[derive(Debug, Deserialize, Serialize, Default, PartialEq)]
+/// Налоговые реквизиты участника (Cdtr, Dbtr)
+#[derive(Debug, Clone, Deserialize, Serialize, Default, PartialEq)]
 #[serde(rename_all = "PascalCase", default)]
-pub(crate) struct RltdDtsAttribute{
-    pub(crate) accptnc_dt_tm: String,
+pub struct TaxPartyAttribute {
+    /// Регистрационный номер, например КПП (RegnId)
+    pub regn_id: String,
 }
 
-#[derive(Debug, Deserialize, Serialize, Default, PartialEq)]
+/// Ссылка получателя (CdtrRefInf)
+#[derive(Debug, Clone, Deserialize, Serialize, Default, PartialEq)]
 #[serde(default)]
-pub(crate) struct CdtrRefInfAttribute{
+pub struct CdtrRefInfAttribute {
+    /// Тип ссылки (Tp)
     #[serde(rename="Tp")]
-    pub(crate) tp: CdOrPrtryAttribute,//Tp
+    pub tp: CdOrPrtryAttribute,
+    /// Значение ссылки (Ref)
     #[serde(rename="Ref")]
-    pub(crate) ref_cdtr: String //Ref
+    pub ref_cdtr: String,
 
 }
 
-#[derive(Debug, Deserialize, Serialize, Default, PartialEq)]
+/// Тип ссылки получателя (Tp)
+#[derive(Debug, Clone, Deserialize, Serialize, Default, PartialEq)]
 #[serde(rename_all = "PascalCase", default)]
-pub(crate) struct CdOrPrtryAttribute{
-    pub(crate) cd_or_prtry: CdAttribute//CdOrPrtry
+pub struct CdOrPrtryAttribute {
+    /// Код или собственный код (CdOrPrtry)
+    pub cd_or_prtry: CdAttribute,
 }
 
-#[derive(Debug, Deserialize, Serialize, Default, PartialEq)]
+/// Счет участника операции (DbtrAcct, CdtrAcct)
+#[derive(Debug, Clone, Deserialize, Serialize, Default, PartialEq)]
 #[serde(rename_all = "PascalCase", default)]
-pub(crate) struct IdTxDtlsAttribute{
-    pub(crate) id: IdIbanAttribute, //Id
-    pub(crate) other: IdDtldAttribute//Other
+pub struct IdTxDtlsAttribute {
+    /// Идентификатор счета (Id)
+    pub id: IdIbanAttribute,
+    /// Прочий идентификатор счета (Other)
+    pub other: IdDtldAttribute,
 }
 
-#[derive(Debug, Deserialize, Serialize, Default, PartialEq)]
+/// Прочий идентификатор (Othr)
+#[derive(Debug, Clone, Deserialize, Serialize, Default, PartialEq)]
 #[serde(rename_all = "PascalCase", default)]
-pub(crate) struct IdDtldAttribute{
-    pub(crate) id: String
+pub struct IdDtldAttribute {
+    /// Идентификатор (Id)
+    pub id: String,
 }
 
-#[derive(Debug, Deserialize, Serialize, Default, PartialEq)]
-#[serde(rename_all = "PascalCase", default)]
-pub(crate) struct AmtAttribute{
+/// Сумма с кодом валюты (Amt)
+#[derive(Debug, Clone, Deserialize, Serialize, Default, PartialEq)]
+#[serde(rename_all = "PascalCase", default, try_from = "AmtXml")]
+pub struct AmtAttribute {
+    /// Код валюты ISO 4217 (атрибут Ccy)
     #[serde(rename="@Ccy")]
-    pub(crate) ccy: String,//Ccy
+    pub ccy: String,
+    /// Значение суммы
     #[serde(rename="#text")]
-    pub(crate) amt: String
+    pub amt: Money,
 }
 
-#[derive(Debug, Deserialize, Serialize, Default, PartialEq)]
+/// Код банковской операции (BkTxCd)
+#[derive(Debug, Clone, Deserialize, Serialize, Default, PartialEq)]
 #[serde(rename_all = "PascalCase", default)]
-pub(crate) struct BxTxCdAttribute{
-    pub(crate) domn: DomnAttribute, //Domn
-    pub(crate) prtry: PrtryAttribute//Prtry
+pub struct BxTxCdAttribute {
+    /// Домен (Domn)
+    pub domn: DomnAttribute,
+    /// Собственный код (Prtry)
+    pub prtry: PrtryAttribute,
 }
 
-#[derive(Debug, Deserialize, Serialize, Default, PartialEq)]
+/// Домен кода банковской операции (Domn)
+#[derive(Debug, Clone, Deserialize, Serialize, Default, PartialEq)]
 #[serde(rename_all = "PascalCase", default)]
-pub(crate) struct DomnAttribute{
-    pub(crate) cd: String, //Cd
-    pub(crate) fmly: FmlyAttribute, //Fmly
+pub struct DomnAttribute {
+    /// Код домена (Cd)
+    pub cd: String,
+    /// Семейство (Fmly)
+    pub fmly: FmlyAttribute,
 }
 
-#[derive(Debug, Deserialize, Serialize, Default, PartialEq)]
+/// Семейство кода банковской операции (Fmly)
+#[derive(Debug, Clone, Deserialize, Serialize, Default, PartialEq)]
 #[serde(rename_all = "PascalCase", default)]
-pub(crate) struct FmlyAttribute{
-    pub(crate) cd: String, //Cd
-    pub(crate) sub_fmly_cd: String//SubFmlyCd
+pub struct FmlyAttribute {
+    /// Код семейства (Cd)
+    pub cd: String,
+    /// Код подсемейства (SubFmlyCd)
+    pub sub_fmly_cd: String,
 }
 
-#[derive(Debug, Deserialize, Serialize, Default, PartialEq)]
+/// Собственный код банковской операции (Prtry)
+#[derive(Debug, Clone, Deserialize, Serialize, Default, PartialEq)]
 #[serde(rename_all = "PascalCase", default)]
-pub(crate) struct PrtryAttribute{
-    pub(crate) cd: String, //cd
-    pub(crate) issr: String//Issr
+pub struct PrtryAttribute {
+    /// Код (Cd)
+    pub cd: String,
+    /// Эмитент кода (Issr)
+    pub issr: String,
 }
 
-#[derive(Debug, Deserialize, Serialize, Default, PartialEq)]
+/// Итоги по операциям выписки (TxsSummry)
+#[derive(Debug, Clone, Deserialize, Serialize, Default, PartialEq)]
 #[serde(rename_all = "PascalCase", default)]
-pub(crate) struct TxsSummryAttribute{
-    pub(crate) ttl_ntries: TtlNtriesAttribute, //TtlNtries
-    pub(crate) ttl_cdt_ntries: TtlCdtDbtNtriesAttribute,//TtlCdtNtries
-    pub(crate) ttl_dbt_ntries: TtlCdtDbtNtriesAttribute//TtlDbtNtries
-
+pub struct TxsSummryAttribute {
+    /// Общие итоги (TtlNtries)
+    pub ttl_ntries: TtlNtriesAttribute,
+    /// Итоги по кредиту (TtlCdtNtries), `None`, если элемента нет в выписке
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ttl_cdt_ntries: Option<TtlCdtDbtNtriesAttribute>,
+    /// Итоги по дебету (TtlDbtNtries), `None`, если элемента нет в выписке
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ttl_dbt_ntries: Option<TtlCdtDbtNtriesAttribute>,
+    /// Валюта записей, по которым рассчитаны итоги; в документ не записывается
+    #[serde(skip)]
+    pub ccy: String,
 }
 
-#[derive(Debug, Deserialize, Serialize, Default, PartialEq)]
+/// Общие итоги по записям (TtlNtries)
+#[derive(Debug, Clone, Deserialize, Serialize, Default, PartialEq)]
 #[serde(rename_all = "PascalCase", default)]
-pub(crate) struct TtlNtriesAttribute{
-    pub(crate) nb_of_ntries: String, //NbOfNtries
-    pub(crate) ttl_net_ntry_amt: f64,//TtlNetNtryAmt
-    pub(crate) cdt_dbt_ind: String//CdtDbtInd
+pub struct TtlNtriesAttribute {
+    /// Количество записей (NbOfNtries), `None`, если элемента нет в выписке
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nb_of_ntries: Option<u32>,
+    /// Чистая сумма записей (TtlNetNtryAmt)
+    pub ttl_net_ntry_amt: Money,
+    /// Признак кредит/дебет чистой суммы (CdtDbtInd)
+    #[serde(skip_serializing_if = "Option::is_none", deserialize_with = "deserialize_optional_credit_debit")]
+    pub cdt_dbt_ind: Option<CreditDebit>,
 
 }
 
-#[derive(Debug, Deserialize, Serialize, Default, PartialEq)]
+/// Итоги по кредитовым или дебетовым записям (TtlCdtNtries, TtlDbtNtries)
+#[derive(Debug, Clone, Deserialize, Serialize, Default, PartialEq)]
 #[serde(rename_all = "PascalCase", default)]
-pub(crate) struct TtlCdtDbtNtriesAttribute{
-    pub(crate) nb_of_ntries: u32, //NbOfNtries
-    pub(crate) sum: String, //Sum
+pub struct TtlCdtDbtNtriesAttribute {
+    /// Количество записей (NbOfNtries)
+    pub nb_of_ntries: u32,
+    /// Сумма записей (Sum)
+    pub sum: Money,
 }
 
-#[derive(Debug, Deserialize, Serialize, Default, PartialEq)]
+/// Остаток по счету (Bal)
+#[derive(Debug, Clone, Deserialize, Serialize, Default, PartialEq)]
 #[serde(rename_all = "PascalCase", default)]
-pub(crate) struct BalanceAttribute{
-    pub(crate) tp: TpBalanceAttribute, // tp
-    pub(crate) amt: AmtAttribute,
-    pub(crate) cdt_dbt_ind: String, //CdtDbtInd
-    pub(crate) dt:  DtAttribute,
-    #[serde(skip_serializing)]
-    pub(crate) cd: String
+pub struct BalanceAttribute {
+    /// Тип остатка (Tp)
+    pub tp: TpBalanceAttribute,
+    /// Сумма остатка (Amt)
+    pub amt: AmtAttribute,
+    /// Признак кредит/дебет (CdtDbtInd)
+    pub cdt_dbt_ind: CreditDebit,
+    /// Дата остатка (Dt)
+    pub dt: DtAttribute,
+}
+/// Минимальная сумма записей промежуточного отчета (FlrLmt): записи с меньшей суммой в отчет не включаются
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FloorLimitAttribute {
+    /// Сумма (Amt)
+    pub amt: AmtAttribute,
+    /// Признак кредит/дебет записей, к которым относится сумма; `None` - к кредитовым и дебетовым
+    pub cdt_dbt_ind: Option<CreditDebit>,
 }
-#[derive(Debug, Deserialize, Serialize, Default, PartialEq)]
+/// Дата (Dt)
+#[derive(Debug, Clone, Deserialize, Serialize, Default, PartialEq)]
 #[serde(rename_all = "PascalCase", default)]
-pub(crate) struct DtAttribute{
-    pub(crate) dt: String,
+pub struct DtAttribute {
+    /// Дата (Dt)
+    #[serde(skip_serializing_if = "Option::is_none", deserialize_with = "deserialize_optional")]
+    pub dt: Option<NaiveDate>,
 }
-#[derive(Debug, Deserialize, Serialize, Default, PartialEq)]
+/// Тип остатка (Tp)
+#[derive(Debug, Clone, Deserialize, Serialize, Default, PartialEq)]
 #[serde(rename_all = "PascalCase", default)]
-pub(crate) struct TpBalanceAttribute{
-    pub(crate) cd_or_prtry: CdAttribute
+pub struct TpBalanceAttribute {
+    /// Код типа остатка (CdOrPrtry)
+    pub cd_or_prtry: BalanceCdAttribute,
 }
-#[derive(Debug, Deserialize, Serialize, Default, PartialEq)]
+/// Код типа остатка (CdOrPrtry)
+#[derive(Debug, Clone, Deserialize, Serialize, Default, PartialEq)]
 #[serde(rename_all = "PascalCase", default)]
-pub(crate) struct CdAttribute{
-    pub(crate) cd: String,
+pub struct BalanceCdAttribute {
+    /// Код типа остатка (Cd)
+    pub cd: BalanceType,
 }
-#[derive(Debug, Deserialize, Serialize, Default, PartialEq)]
+/// Код (Cd)
+#[derive(Debug, Clone, Deserialize, Serialize, Default, PartialEq)]
 #[serde(rename_all = "PascalCase", default)]
-pub(crate) struct FromToDtAttribute{
-    pub(crate) fr_dt_tm: String, //FrDtTm
-    pub(crate) to_dt_tm: String, //ToDtTm
+pub struct CdAttribute {
+    /// Код (Cd)
+    pub cd: String,
 }
-#[derive(Debug, Deserialize, Serialize, Default, PartialEq)]
+/// Период выписки (FrToDt)
+#[derive(Debug, Clone, Deserialize, Serialize, Default, PartialEq)]
 #[serde(rename_all = "PascalCase", default)]
-pub(crate)  struct AcctAttribute {
-    pub(crate) id: IdIbanAttribute, //id
-    pub(crate) ccy: String, //Ccy
-    pub(crate) nm: String, //nm
-    pub(crate) ownr: OwnerAttribute, //ownr
-    pub(crate) svcr: SvcrAttribute, //svcr
+pub struct FromToDtAttribute {
+    /// Начало периода (FrDtTm)
+    #[serde(skip_serializing_if = "Option::is_none", deserialize_with = "deserialize_optional")]
+    pub fr_dt_tm: Option<IsoDateTime>,
+    /// Конец периода (ToDtTm)
+    #[serde(skip_serializing_if = "Option::is_none", deserialize_with = "deserialize_optional")]
+    pub to_dt_tm: Option<IsoDateTime>,
+}
+/// Счет выписки (Acct)
+#[derive(Debug, Clone, Deserialize, Serialize, Default, PartialEq)]
+#[serde(rename_all = "PascalCase", default)]
+pub struct AcctAttribute {
+    /// Идентификатор счета (Id)
+    pub id: IdIbanAttribute,
+    /// Валюта счета (Ccy)
+    pub ccy: String,
+    /// Наименование счета (Nm)
+    pub nm: String,
+    /// Владелец счета (Ownr)
+    pub ownr: OwnerAttribute,
+    /// Обслуживающий банк (Svcr)
+    pub svcr: SvcrAttribute,
 
 
 }
-#[derive(Debug, Deserialize, Serialize, Default, PartialEq)]
+/// Идентификатор счета (Id)
+#[derive(Debug, Clone, Deserialize, Serialize, Default, PartialEq)]
 #[serde(default)]
-pub(crate) struct IdIbanAttribute{
+pub struct IdIbanAttribute {
+    /// IBAN счета (IBAN)
     #[serde(rename="IBAN")]
-    pub(crate) iban: String, //IBAN
+    pub iban: String,
+    /// Прочий идентификатор счета (Othr)
     #[serde(rename="Othr")]
-    pub(crate) othr: OtherAttribute,
+    pub othr: OtherAttribute,
 }
-#[derive(Debug, Deserialize, Serialize, Default, PartialEq)]
+/// Обслуживающий банк (Svcr)
+#[derive(Debug, Clone, Deserialize, Serialize, Default, PartialEq)]
 #[serde(rename_all = "PascalCase", default)]
-pub(crate)  struct SvcrAttribute{
-    pub(crate) fin_instn_id: FinInstIdAttribute //FinInstnId
+pub struct SvcrAttribute {
+    /// Идентификация банка (FinInstnId)
+    pub fin_instn_id: FinInstIdAttribute,
 }
-#[derive(Debug, Deserialize, Serialize, Default, PartialEq)]
+/// Идентификация банка (FinInstnId)
+#[derive(Debug, Clone, Deserialize, Serialize, Default, PartialEq)]
 #[serde(default)]
-pub(crate)  struct FinInstIdAttribute{
+pub struct FinInstIdAttribute {
+    /// BIC банка (BIC)
     #[serde(rename = "BIC")]
-    pub(crate) bic: String, //BIC
+    pub bic: String,
+    /// Наименование банка (Nm)
     #[serde(rename = "Nm")]
-    pub(crate) nm: String,
+    pub nm: String,
+    /// Почтовый адрес банка (PstlAdr)
     #[serde(rename = "PstlAdr")]
-    pub(crate) pstl_adr: PostalAddressAttribute
+    pub pstl_adr: PostalAddressAttribute,
 }
-#[derive(Debug, Deserialize, Serialize, Default, PartialEq)]
+/// Владелец счета (Ownr)
+#[derive(Debug, Clone, Deserialize, Serialize, Default, PartialEq)]
 #[serde(rename_all = "PascalCase", default)]
-pub(crate) struct OwnerAttribute{
-    pub(crate) nm: String, //nm
-    pub(crate) pstl_adr: PostalAddressAttribute, //pstl_addr
-    pub(crate) bldg_nb: u32, //BldgNb
-    pub(crate) pst_cd: u32, //PstCd
-    pub(crate) twn_nm: String, //TwnNm
-    pub(crate) ctry: String, //Ctry
-    pub(crate) id: IdAttribute, //Id
+pub struct OwnerAttribute {
+    /// Наименование владельца (Nm)
+    pub nm: String,
+    /// Почтовый адрес (PstlAdr)
+    pub pstl_adr: PostalAddressAttribute,
+    /// Номер здания (BldgNb)
+    pub bldg_nb: u32,
+    /// Почтовый индекс (PstCd)
+    pub pst_cd: u32,
+    /// Город (TwnNm)
+    pub twn_nm: String,
+    /// Страна (Ctry)
+    pub ctry: String,
+    /// Идентификация владельца (Id)
+    pub id: IdAttribute,
 }
 
-#[derive(Debug, Deserialize, Serialize, Default, PartialEq)]
+/// Идентификация организации (Id)
+#[derive(Debug, Clone, Deserialize, Serialize, Default, PartialEq)]
 #[serde(rename_all = "PascalCase", default)]
-pub(crate) struct IdAttribute{
-    pub(crate) org_id: OrgIdAttribute//OrgId
+pub struct IdAttribute {
+    /// Идентификация организации (OrgId)
+    pub org_id: OrgIdAttribute,
 }
-#[derive(Debug, Deserialize, Serialize, Default, PartialEq)]
+/// Идентификатор организации (OrgId)
+#[derive(Debug, Clone, Deserialize, Serialize, Default, PartialEq)]
 #[serde(rename_all = "PascalCase", default)]
-pub(crate) struct OrgIdAttribute{
-    pub(crate) othr: OtherAttribute //Othr
+pub struct OrgIdAttribute {
+    /// Прочий идентификатор (Othr)
+    pub othr: OtherAttribute,
 }
-#[derive(Debug, Deserialize, Serialize, Default, PartialEq)]
+/// Прочий идентификатор со схемой (Othr)
+#[derive(Debug, Clone, Deserialize, Serialize, Default, PartialEq)]
 #[serde(rename_all = "PascalCase", default)]
-pub(crate) struct OtherAttribute{
-    pub(crate) id: String, //id
-    pub(crate) schme_nm: ShemeNumberAttribute //SchmeNm
+pub struct OtherAttribute {
+    /// Идентификатор (Id)
+    pub id: String,
+    /// Схема идентификатора (SchmeNm)
+    pub schme_nm: ShemeNumberAttribute,
 }
-#[derive(Debug, Deserialize, Serialize, Default, PartialEq)]
+/// Схема идентификатора (SchmeNm)
+#[derive(Debug, Clone, Deserialize, Serialize, Default, PartialEq)]
 #[serde(rename_all = "PascalCase", default)]
-pub(crate) struct ShemeNumberAttribute{
-    pub(crate) cd: String, //cd
+pub struct ShemeNumberAttribute {
+    /// Код схемы (Cd)
+    pub cd: String,
 }
-#[derive(Debug, Deserialize, Serialize, Default, PartialEq)]
+/// Почтовый адрес (PstlAdr)
+#[derive(Debug, Clone, Deserialize, Serialize, Default, PartialEq)]
 #[serde(rename_all = "PascalCase", default)]
-pub(crate) struct PostalAddressAttribute{
-    pub(crate) strt_nm: String, //strt_nm
-    pub(crate) bldg_nb: String, //BldgNb
-    pub(crate) pst_cd: String, //PstCd
-    pub(crate) twn_nm: String, //TwnNm
-    pub(crate) ctry: String,
-    pub(crate) adr_line: Vec<String>
+pub struct PostalAddressAttribute {
+    /// Улица (StrtNm)
+    pub strt_nm: String,
+    /// Номер здания (BldgNb)
+    pub bldg_nb: String,
+    /// Почтовый индекс (PstCd)
+    pub pst_cd: String,
+    /// Город (TwnNm)
+    pub twn_nm: String,
+    /// Страна (Ctry)
+    pub ctry: String,
+    /// Строки адреса (AdrLine)
+    pub adr_line: Vec<String>,
 }
 
-impl Default for DocumentCamt053{
-    fn default() -> Self {
-        Self {
-            bk_to_cstmr_stmt: Vec::new()
-        }
+/// Документ CAMT.053 в представлении XML: одно сообщение BkToCstmrStmt с несколькими Stmt
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct DocumentXml {
+    #[serde(rename = "BkToCstmrStmt")]
+    bk_to_cstmr_stmt: Vec<BkToCstmrStmtXml>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "PascalCase", default)]
+struct BkToCstmrStmtXml {
+    grp_hdr: HeaderAttribute,
+    stmt: Vec<StatementAttribute>,
+}
+
+impl From<DocumentXml> for DocumentCamt053 {
+    fn from(value: DocumentXml) -> Self {
+        let bk_to_cstmr_stmt = value.bk_to_cstmr_stmt.into_iter()
+            .flat_map(|message| {
+                let grp_hdr = message.grp_hdr;
+                message.stmt.into_iter()
+                    .map(move |stmt| BkToCstmrStmt { grp_hdr: grp_hdr.clone(), stmt })
+            })
+            .collect();
+        Self { version: None, bk_to_cstmr_stmt }
+    }
+}
+
+/// Идентификация участника в представлении XML
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "PascalCase", default)]
+struct PartyIdXml {
+    othr: IdDtldAttribute,
+    org_id: PartyOthrXml,
+    prvt_id: PartyOthrXml,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "PascalCase", default)]
+struct PartyOthrXml {
+    othr: IdDtldAttribute,
+}
+
+impl From<PartyIdXml> for PrvtIdAttribute {
+    fn from(value: PartyIdXml) -> Self {
+        let othr = [value.othr, value.org_id.othr, value.prvt_id.othr].into_iter()
+            .find(|othr| !othr.id.is_empty())
+            .unwrap_or_default();
+        Self { othr }
+    }
+}
+
+impl IdTxDtlsAttribute {
+    /// Номер счета участника: IBAN, `Id/Othr/Id` или `Other/Id`.
+    pub fn account(&self) -> &str {
+        [&self.id.iban, &self.id.othr.id, &self.other.id].into_iter()
+            .find(|id| !id.is_empty())
+            .map_or("", |id| id.as_str())
     }
 }
 
 impl DtAttribute {
-    pub(crate) fn format_dt(dt_str: &str) -> Self {
+    /// Создает дату `dt`.
+    pub fn new(dt: NaiveDate) -> Self {
+        Self { dt: Some(dt) }
+    }
+}
+
+/// Сумма с кодом валюты в представлении XML: значение суммы обязательно
+#[derive(Deserialize)]
+struct AmtXml {
+    #[serde(rename = "@Ccy", default)]
+    ccy: String,
+    #[serde(rename = "#text")]
+    amt: Option<Money>,
+}
+
+impl TryFrom<AmtXml> for AmtAttribute {
+    type Error = ParserError;
+
+    fn try_from(value: AmtXml) -> Result<Self, Self::Error> {
+        let amt = value.amt.ok_or_else(|| ParserError::BadAmount(format!("empty amount {}", value.ccy)))?;
+        Ok(Self { ccy: value.ccy, amt })
+    }
+}
+
+impl AmtAttribute {
+    /// Создает сумму в валюте `ccy`, дополняя дробную часть до количества знаков валюты.
+    pub fn new(amt: Money, ccy: &str) -> Self {
+        Self { ccy: ccy.to_string(), amt: amt.with_minor_units(ccy) }
+    }
+}
+
+impl TxsSummryAttribute {
+    /// Создает нулевые итоги TtlNtries, TtlCdtNtries и TtlDbtNtries выписки без записей.
+    pub fn zero() -> Self {
         Self {
-            dt: if dt_str.len() > 5 {
-                format!("20{}-{}-{}", dt_str[0..2].to_string(),
-                        dt_str[2..4].to_string(), dt_str[4..6].to_string())
-            } else {
-                "1979-01-01".to_string()
-            }
+            ttl_ntries: TtlNtriesAttribute {
+                nb_of_ntries: Some(0),
+                ttl_net_ntry_amt: Money::default(),
+                cdt_dbt_ind: Some(CreditDebit::Credit),
+            },
+            ttl_cdt_ntries: Some(TtlCdtDbtNtriesAttribute::default()),
+            ttl_dbt_ntries: Some(TtlCdtDbtNtriesAttribute::default()),
+            ccy: String::new(),
+        }
+    }
+
+    /// Рассчитывает итоги TtlNtries, TtlCdtNtries и TtlDbtNtries по записям `ntry`.
+    ///
+    /// # Ошибки
+    ///
+    /// Возвращает `ParserError::BadAmount`, если сумма записей не помещается в денежный тип
+    /// или записи выписки в разных валютах.
+    pub fn from_entries(ntry: &[NtryAttribute]) -> Result<Self, ParserError> {
+        let mut summary = Self::zero();
+        for entry in ntry {
+            summary.add_entry(entry)?;
+        }
+        Ok(summary)
+    }
+
+    /// Добавляет запись `ntry` к итогам; используется при потоковой обработке выписки,
+    /// когда записи не хранятся в памяти. Итоги считаются в валюте первой записи с валютой.
+    ///
+    /// # Ошибки
+    ///
+    /// Возвращает `ParserError::BadAmount`, если сумма записей не помещается в денежный тип
+    /// или валюта записи отличается от валюты итогов; итоги при этом не меняются.
+    pub fn add_entry(&mut self, ntry: &NtryAttribute) -> Result<(), ParserError> {
+        if !ntry.amt.ccy.is_empty() && !self.ccy.is_empty() && self.ccy != ntry.amt.ccy {
+            return Err(ParserError::BadAmount(format!("entry currency {} differs from summary currency {}",
+                                                      ntry.amt.ccy, self.ccy)));
+        }
+        let total = match ntry.cdt_dbt_ind {
+            CreditDebit::Credit => &mut self.ttl_cdt_ntries,
+            CreditDebit::Debit => &mut self.ttl_dbt_ntries,
+        }.get_or_insert_default();
+        total.sum = total.sum.checked_add(ntry.amt.amt)?;
+        total.nb_of_ntries += 1;
+        if self.ccy.is_empty() {
+            self.ccy = ntry.amt.ccy.clone();
+        }
+        for total in [&mut self.ttl_cdt_ntries, &mut self.ttl_dbt_ntries].into_iter().flatten() {
+            total.sum = total.sum.with_minor_units(&self.ccy);
+        }
+        self.update_net()
+    }
+
+    /// Итоги по кредиту или нулевые итоги, если элемента TtlCdtNtries нет.
+    pub fn credit(&self) -> TtlCdtDbtNtriesAttribute {
+        self.ttl_cdt_ntries.clone().unwrap_or_default()
+    }
+
+    /// Итоги по дебету или нулевые итоги, если элемента TtlDbtNtries нет.
+    pub fn debit(&self) -> TtlCdtDbtNtriesAttribute {
+        self.ttl_dbt_ntries.clone().unwrap_or_default()
+    }
+
+    /// Пересчитывает общее количество записей и чистую сумму TtlNtries.
+    fn update_net(&mut self) -> Result<(), ParserError> {
+        let (credit, debit) = (self.credit(), self.debit());
+        let net = credit.sum.checked_sub(debit.sum)?;
+        self.ttl_ntries.nb_of_ntries = Some(credit.nb_of_ntries + debit.nb_of_ntries);
+        self.ttl_ntries.ttl_net_ntry_amt = net.abs();
+        self.ttl_ntries.cdt_dbt_ind = Some(if net.is_negative() { CreditDebit::Debit } else { CreditDebit::Credit });
+        Ok(())
+    }
+
+    /// Итоги не заполнены.
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+impl StatementAttribute {
+    /// Заполняет итоги TxsSummry по записям выписки.
+    ///
+    /// Итоги исходного документа, противоречащие записям, не исправляются, а удаляются,
+    /// чтобы не записать в документ недостоверные данные.
+    ///
+    /// # Возвращает
+    ///
+    /// `false`, если итоги исходного документа противоречили записям и были удалены.
+    ///
+    /// # Ошибки
+    ///
+    /// Возвращает `ParserError::BadAmount`, если сумма записей не помещается в денежный тип.
+    pub fn normalize_summary(&mut self) -> Result<bool, ParserError> {
+        if !summary_issues(self).is_empty() {
+            self.txs_summry = TxsSummryAttribute::default();
+            return Ok(false);
+        }
+        self.txs_summry = if self.ntry.is_empty() {
+            TxsSummryAttribute::default()
+        } else {
+            TxsSummryAttribute::from_entries(&self.ntry)?
+        };
+        Ok(true)
+    }
+}
+
+impl DocumentCamt053 {
+    /// Заполняет итоги TxsSummry всех выписок документа (см. [`StatementAttribute::normalize_summary`]).
+    pub fn normalize_summary(&mut self) -> Result<(), ParserError> {
+        for statement in &mut self.bk_to_cstmr_stmt {
+            statement.stmt.normalize_summary()?;
         }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::builder::{EntryBuilder, StatementBuilder};
+
+    fn statement() -> StatementAttribute {
+        StatementBuilder::new("STMT-1")
+            .entry(EntryBuilder::new().amount(Money::new(3050, 2), "RUB").credit().build())
+            .entry(EntryBuilder::new().amount(Money::new(20000, 2), "RUB").debit().build())
+            .entry(EntryBuilder::new().amount(Money::new(1000, 2), "RUB").debit().build())
+            .build().stmt
+    }
+
+    #[test]
+    fn test_normalize_summary() {
+        let mut stmt = statement();
+        assert!(stmt.normalize_summary().unwrap());
+        let summary = &stmt.txs_summry;
+        assert_eq!(Some(3), summary.ttl_ntries.nb_of_ntries);
+        assert_eq!(Money::new(17950, 2), summary.ttl_ntries.ttl_net_ntry_amt);
+        assert_eq!(Some(CreditDebit::Debit), summary.ttl_ntries.cdt_dbt_ind);
+        assert_eq!(Some(TtlCdtDbtNtriesAttribute { nb_of_ntries: 1, sum: Money::new(3050, 2) }), summary.ttl_cdt_ntries);
+        assert_eq!(Some(TtlCdtDbtNtriesAttribute { nb_of_ntries: 2, sum: Money::new(21000, 2) }), summary.ttl_dbt_ntries);
+    }
+
+    #[test]
+    fn test_normalize_summary_inconsistent() {
+        let mut stmt = statement();
+        stmt.txs_summry.ttl_dbt_ntries = Some(TtlCdtDbtNtriesAttribute { nb_of_ntries: 5, sum: Money::new(21000, 2) });
+        assert!(!stmt.normalize_summary().unwrap());
+        assert!(stmt.txs_summry.is_empty());
+        let xml = serde_xml_rs::to_string(&stmt).unwrap();
+        assert!(!xml.contains("TxsSummry"));
+    }
+
+    #[test]
+    fn test_summary_without_sides() {
+        let xml = "<TxsSummry><TtlNtries><NbOfNtries>3</NbOfNtries></TtlNtries></TxsSummry>";
+        let summary: TxsSummryAttribute = serde_xml_rs::from_str(xml).unwrap();
+        assert_eq!(None, summary.ttl_cdt_ntries);
+        assert_eq!(None, summary.ttl_dbt_ntries);
+        assert_eq!(Some(3), summary.ttl_ntries.nb_of_ntries);
+        let bad = "<TxsSummry><TtlNtries><NbOfNtries>три</NbOfNtries></TtlNtries></TxsSummry>";
+        assert!(serde_xml_rs::from_str::<TxsSummryAttribute>(bad).is_err());
+        let none: TxsSummryAttribute = serde_xml_rs::from_str("<TxsSummry><TtlNtries/></TxsSummry>").unwrap();
+        assert_eq!(None, none.ttl_ntries.nb_of_ntries);
+        let mut stmt = statement();
+        stmt.txs_summry = summary;
+        assert!(stmt.normalize_summary().unwrap());
+        assert_eq!(Some(2), stmt.txs_summry.ttl_dbt_ntries.map(|total| total.nb_of_ntries));
+    }
+
+    #[test]
+    fn test_summary_mixed_currencies() {
+        let mut stmt = statement();
+        stmt.ntry[2].amt = AmtAttribute::new(Money::new(1000, 0), "JPY");
+        let error = TxsSummryAttribute::from_entries(&stmt.ntry).unwrap_err();
+        assert!(error.to_string().contains("JPY"), "{}", error);
+        assert!(stmt.normalize_summary().is_err());
+
+        let mut summary = TxsSummryAttribute::from_entries(&stmt.ntry[..2]).unwrap();
+        let before = summary.clone();
+        assert!(summary.add_entry(&stmt.ntry[2]).is_err());
+        assert_eq!(before, summary);
+        assert_eq!("RUB", summary.ccy);
     }
 }
@@ -0,0 +1,576 @@
+//! Модуль представляет модель для формата обмена 1С с клиент-банком (1CClientBankExchange)
+//!
+//! Файл обмена - текст из строк `Ключ=Значение`: заголовок файла, секции остатков по счетам
+//! `СекцияРасчСчет` ... `КонецРасчСчет`, секции документов `СекцияДокумент=<вид>` ... `КонецДокумента`
+//! и строка `КонецФайла`. Каждая секция остатков становится выпиской, документ относится к выписке
+//! по счету плательщика (списание) или получателя (поступление) и дате проводки.
+//!
+
+use chrono::NaiveDate;
+use crate::encoding::TextEncoding;
+use crate::errors::ParserError;
+use crate::models::builder::{BalanceBuilder, EntryBuilder, Party};
+use crate::models::camt053::{BkToCstmrStmt, NtryAttribute, TxDtlsAttribute, TxsSummryAttribute};
+use crate::models::codes::{BalanceType, CreditDebit};
+use crate::models::date::IsoDateTime;
+use crate::models::money::Money;
+
+/// Первая строка файла обмена
+pub(crate) const SIGNATURE: &str = "1CClientBankExchange";
+
+/// Конец строки файла обмена
+const EOL: &str = "\r\n";
+
+/// Формат дат файла обмена
+const DATE_FORMAT: &str = "%d.%m.%Y";
+
+/// Формат времени файла обмена
+const TIME_FORMAT: &str = "%H:%M:%S";
+
+/// Валюта счетов файла обмена: 1С обменивается с банком рублевыми документами
+const CURRENCY: &str = "RUB";
+
+/// Версия формата записываемых файлов
+const FORMAT_VERSION: &str = "1.03";
+
+/// Виды документов файла обмена; документ другого вида записывается как платежное поручение
+const DOCUMENT_KINDS: [&str; 6] = ["Платежное поручение", "Платежное требование", "Инкассовое поручение",
+    "Платежный ордер", "Банковский ордер", "Мемориальный ордер"];
+
+/// Блок файла обмена
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Block {
+    /// Заголовок файла
+    Header,
+    /// Секция остатков по счету
+    Account,
+    /// Секция документа
+    Document,
+}
+
+/// Секция файла обмена: вид документа и пары `Ключ=Значение` в порядке следования
+#[derive(Debug, Default)]
+struct Section {
+    kind: String,
+    values: Vec<(String, String)>,
+}
+
+impl Section {
+    /// Значение ключа `key`; пустая строка, если ключа нет.
+    fn get(&self, key: &str) -> &str {
+        self.values.iter().find(|(name, _)| name == key).map_or("", |(_, value)| value.as_str())
+    }
+
+    /// Первое непустое значение ключей `keys`.
+    fn first(&self, keys: &[&str]) -> &str {
+        keys.iter().map(|key| self.get(key)).find(|value| !value.is_empty()).unwrap_or_default()
+    }
+
+    /// Все значения ключа `key` (например, счета `РасчСчет` заголовка файла).
+    fn all<'a>(&'a self, key: &'a str) -> impl Iterator<Item = &'a str> {
+        self.values.iter().filter(move |(name, _)| name == key).map(|(_, value)| value.as_str())
+    }
+
+    /// Дата из первого непустого значения ключей `keys` или `None`, если значений нет.
+    fn date(&self, keys: &[&str]) -> Result<Option<NaiveDate>, ParserError> {
+        let value = self.first(keys);
+        if value.is_empty() {
+            return Ok(None);
+        }
+        NaiveDate::parse_from_str(value, DATE_FORMAT).map(Some).map_err(|_| ParserError::BadDate(value.to_string()))
+    }
+
+    /// Сумма ключа `key` или `None`, если значения нет.
+    fn amount(&self, key: &str) -> Result<Option<Money>, ParserError> {
+        let value = self.get(key);
+        if value.is_empty() {
+            return Ok(None);
+        }
+        value.parse().map(Some)
+    }
+
+    /// Реквизиты участника с префиксом ключей `prefix` (`Плательщик` или `Получатель`).
+    ///
+    /// Наименование и ИНН, если ключей `<prefix>1` и `<prefix>ИНН` нет, берутся из значения
+    /// `<prefix>` вида `ИНН <номер> <наименование>`.
+    fn party(&self, prefix: &str) -> Party {
+        let key = |suffix: &str| format!("{}{}", prefix, suffix);
+        let full = self.get(prefix);
+        let (full_id, full_nm) = match full.strip_prefix("ИНН") {
+            Some(rest) => {
+                let rest = rest.trim_start();
+                let end = rest.find(|c: char| !c.is_ascii_digit()).unwrap_or(rest.len());
+                (&rest[..end], rest[end..].trim_start())
+            }
+            None => ("", full),
+        };
+        let or = |value: &str, default: &str| if value.is_empty() { default.to_string() } else { value.to_string() };
+        Party { nm: or(self.get(&key("1")), full_nm), id: or(self.get(&key("ИНН")), full_id),
+            kpp: self.get(&key("КПП")).to_string(), acct: self.first(&[&key("Счет"), &key("РасчСчет")]).to_string(),
+            bic: self.get(&key("БИК")).to_string(), bank_nm: self.get(&key("Банк1")).to_string() }
+    }
+}
+
+/// Структура для документа 1CClientBankExchange
+/// Содержит методы для чтения, транофрмации и записи файла обмена 1С
+/// ```ignore
+///
+/// pub fn from_read<R: Read>(r: &mut R) -> Result<Self, ParserError>
+/// pub fn write_to<W: Write>(&mut self, writer: &mut W) -> Result<(), ConvertError>
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DocumentClientBank {
+    /// Выписки документа, по одной на каждую секцию РасчСчет
+    pub document: Vec<BkToCstmrStmt>,
+}
+
+impl DocumentClientBank {
+    /// Создает пустой документ 1CClientBankExchange.
+    pub fn new() -> Self {
+        DocumentClientBank::default()
+    }
+
+    /// Разбивает текст файла обмена на заголовок, секции остатков и секции документов.
+    fn parse_sections(document: &str) -> Result<(Section, Vec<Section>, Vec<Section>), ParserError> {
+        let mut lines = document.lines().map(str::trim).filter(|line| !line.is_empty());
+        if lines.next().map(|line| line.trim_start_matches('\u{feff}')) != Some(SIGNATURE) {
+            return Err(ParserError::BadInputFormatFile(format!("No {} header", SIGNATURE)));
+        }
+        let (mut header, mut accounts, mut documents) = (Section::default(), Vec::new(), Vec::new());
+        let mut current: Option<(Block, Section)> = None;
+        for line in lines {
+            let (key, value) = line.split_once('=').map_or((line, ""), |(key, value)| (key.trim(), value.trim()));
+            let block = current.as_ref().map_or(Block::Header, |(block, _)| *block);
+            match (key, block) {
+                ("СекцияРасчСчет", Block::Header) => current = Some((Block::Account, Section::default())),
+                ("СекцияДокумент", Block::Header) => {
+                    current = Some((Block::Document, Section { kind: value.to_string(), values: Vec::new() }));
+                }
+                ("КонецРасчСчет", Block::Account) | ("КонецДокумента", Block::Document) => {
+                    if let Some((block, section)) = current.take() {
+                        if block == Block::Account { accounts.push(section) } else { documents.push(section) }
+                    }
+                }
+                ("КонецФайла", Block::Header) => break,
+                ("СекцияРасчСчет" | "СекцияДокумент" | "КонецРасчСчет" | "КонецДокумента" | "КонецФайла", _) => {
+                    return Err(ParserError::BadInputFormatFile(format!("Unexpected {} in {:?} section", key, block)));
+                }
+                _ => match current.as_mut() {
+                    Some((_, section)) => section.values.push((key.to_string(), value.to_string())),
+                    None => header.values.push((key.to_string(), value.to_string())),
+                },
+            }
+        }
+        if let Some((block, _)) = current {
+            return Err(ParserError::BadInputFormatFile(format!("Unterminated {:?} section", block)));
+        }
+        Ok((header, accounts, documents))
+    }
+
+    /// Выписка по секции остатков `section`; период, не заданный в секции, берется из заголовка `header`.
+    fn parse_statement(header: &Section, section: &Section, account: &str) -> Result<BkToCstmrStmt, ParserError> {
+        let mut record = BkToCstmrStmt::default();
+        if let Some(date) = header.date(&["ДатаСоздания"])? {
+            let time = chrono::NaiveTime::parse_from_str(header.get("ВремяСоздания"), TIME_FORMAT).unwrap_or_default();
+            record.grp_hdr.cre_dt_tm = Some(IsoDateTime::new(date.and_time(time), None));
+        }
+        record.stmt.acct.id.othr.id = account.to_string();
+        record.stmt.acct.ccy = CURRENCY.to_string();
+        let from = section.date(&["ДатаНачала"])?.or(header.date(&["ДатаНачала"])?);
+        let to = section.date(&["ДатаКонца"])?.or(header.date(&["ДатаКонца"])?);
+        record.stmt.fr_to_dt.fr_dt_tm = from.map(IsoDateTime::start_of_day);
+        record.stmt.fr_to_dt.to_dt_tm = to.map(IsoDateTime::end_of_day);
+        for (key, balance_type, date) in [("НачальныйОстаток", BalanceType::Opbd, from), ("КонечныйОстаток", BalanceType::Clbd, to)] {
+            let Some(amount) = section.amount(key)? else {
+                continue;
+            };
+            let mut balance = BalanceBuilder::new(balance_type).amount(amount.abs(), CURRENCY);
+            balance = if amount.is_negative() { balance.debit() } else { balance.credit() };
+            if let Some(date) = date {
+                balance = balance.date(date);
+            }
+            record.stmt.bal.push(balance.build());
+        }
+        Ok(record)
+    }
+
+    /// Запись выписки по секции документа `section` с направлением `cdt_dbt_ind`.
+    ///
+    /// Дата проводки - дата списания или поступления, иначе дата документа.
+    fn parse_entry(section: &Section, cdt_dbt_ind: CreditDebit) -> Result<NtryAttribute, ParserError> {
+        let booked_key = match cdt_dbt_ind {
+            CreditDebit::Credit => "ДатаПоступило",
+            CreditDebit::Debit => "ДатаСписано",
+        };
+        let Some(date) = section.date(&[booked_key, "Дата"])? else {
+            return Err(ParserError::BadDate(String::new()));
+        };
+        let amount = section.amount("Сумма")?.ok_or_else(|| ParserError::BadAmount(String::new()))?;
+        let mut entry = EntryBuilder::new().amount(amount.abs(), CURRENCY).booking_date(date).value_date(date)
+            .servicer_ref(section.get("Номер"))
+            .bank_tx_code(section.get("ВидОплаты"), "")
+            .debtor(section.party("Плательщик"))
+            .creditor(section.party("Получатель"));
+        entry = match cdt_dbt_ind {
+            CreditDebit::Credit => entry.credit(),
+            CreditDebit::Debit => entry.debit(),
+        };
+        let purpose = section.get("НазначениеПлатежа");
+        if purpose.is_empty() {
+            for line in (1..=6).map(|index| section.get(&format!("НазначениеПлатежа{}", index))).filter(|line| !line.is_empty()) {
+                entry = entry.remittance(line);
+            }
+        } else {
+            entry = entry.remittance(purpose);
+        }
+        let mut ntry = entry.build();
+        ntry.addtl_ntry_inf = section.kind.clone();
+        Ok(ntry)
+    }
+
+    /// Добавляет документ `section` в выписку его счета.
+    ///
+    /// Документ списания относится к выписке по счету плательщика, поступления - по счету получателя;
+    /// из нескольких выписок счета выбирается та, в период которой входит дата проводки. Если в файле
+    /// одна выписка и счета не совпадают, направление определяется по дате поступления.
+    fn push_document(statements: &mut [BkToCstmrStmt], section: &Section) -> Result<(), ParserError> {
+        let payer = section.first(&["ПлательщикСчет", "ПлательщикРасчСчет"]);
+        let payee = section.first(&["ПолучательСчет", "ПолучательРасчСчет"]);
+        let owns = |record: &BkToCstmrStmt, account: &str| !account.is_empty() && record.stmt.acct.id.othr.id == account;
+        let cdt_dbt_ind = if statements.iter().any(|record| owns(record, payer)) {
+            CreditDebit::Debit
+        } else if statements.iter().any(|record| owns(record, payee)) || !section.get("ДатаПоступило").is_empty() {
+            CreditDebit::Credit
+        } else {
+            CreditDebit::Debit
+        };
+        let own = if cdt_dbt_ind == CreditDebit::Debit { payer } else { payee };
+        let ntry = DocumentClientBank::parse_entry(section, cdt_dbt_ind)?;
+        let candidates: Vec<usize> = (0..statements.len())
+            .filter(|index| statements.len() == 1 || owns(&statements[*index], own))
+            .collect();
+        let in_period = |record: &BkToCstmrStmt| {
+            let period = &record.stmt.fr_to_dt;
+            ntry.bookg_dt.dt.is_some_and(|date| period.fr_dt_tm.is_none_or(|from| from.date() <= date)
+                && period.to_dt_tm.is_none_or(|to| date <= to.date()))
+        };
+        let Some(&index) = candidates.iter().find(|index| in_period(&statements[**index])).or(candidates.first()) else {
+            return Err(ParserError::BadInputFormatFile(
+                format!("Document {} does not belong to any account of the file", section.get("Номер"))));
+        };
+        let record = &mut statements[index];
+        if record.stmt.acct.ownr.nm.is_empty() {
+            let details = ntry.ntry_dtls.tx_dtls.first();
+            record.stmt.acct.ownr.nm = details.map(|tx| match cdt_dbt_ind {
+                CreditDebit::Credit => tx.rltd_pties.cdtr.nm.clone(),
+                CreditDebit::Debit => tx.rltd_pties.dbtr.nm.clone(),
+            }).unwrap_or_default();
+        }
+        record.stmt.ntry.push(ntry);
+        Ok(())
+    }
+
+    /// Итоги оборотов выписки по ее записям; суммы `ВсегоПоступило` и `ВсегоСписано` секции остатков
+    /// сохраняются, чтобы расхождение с документами обнаружила проверка выписки.
+    fn summary(record: &BkToCstmrStmt, section: Option<&Section>) -> Result<TxsSummryAttribute, ParserError> {
+        if record.stmt.ntry.is_empty() {
+            return Ok(TxsSummryAttribute::default());
+        }
+        let mut summary = TxsSummryAttribute::from_entries(&record.stmt.ntry)?;
+        if let Some(section) = section {
+            for (key, total) in [("ВсегоПоступило", &mut summary.ttl_cdt_ntries), ("ВсегоСписано", &mut summary.ttl_dbt_ntries)] {
+                if let Some(sum) = section.amount(key)? {
+                    total.get_or_insert_default().sum = sum;
+                }
+            }
+        }
+        Ok(summary)
+    }
+
+    /// Разбирает текст файла обмена.
+    ///
+    /// Если в файле нет секций остатков, выписки строятся по счетам `РасчСчет` заголовка файла.
+    pub(crate) fn parse_document(document: &str) -> Result<Self, ParserError> {
+        let (header, accounts, documents) = DocumentClientBank::parse_sections(document)?;
+        let mut statements = accounts.iter()
+            .map(|section| DocumentClientBank::parse_statement(&header, section, section.get("РасчСчет")))
+            .collect::<Result<Vec<_>, _>>()?;
+        if statements.is_empty() {
+            let mut header_accounts: Vec<&str> = header.all("РасчСчет").filter(|account| !account.is_empty()).collect();
+            if header_accounts.is_empty() {
+                header_accounts.push("");
+            }
+            for account in header_accounts {
+                statements.push(DocumentClientBank::parse_statement(&header, &Section::default(), account)?);
+            }
+        }
+        for section in &documents {
+            DocumentClientBank::push_document(&mut statements, section)?;
+        }
+        for (index, record) in statements.iter_mut().enumerate() {
+            record.stmt.txs_summry = DocumentClientBank::summary(record, accounts.get(index))?;
+        }
+        Ok(DocumentClientBank { document: statements })
+    }
+
+    /// Значение ключа `Кодировка` для кодировки файла `encoding` или `None`, если формат ее не допускает.
+    pub(crate) fn encoding_label(encoding: TextEncoding) -> Option<&'static str> {
+        match encoding {
+            TextEncoding::Windows1251 => Some("Windows"),
+            TextEncoding::Cp866 => Some("DOS"),
+            _ => None,
+        }
+    }
+
+    /// Счет выписки `record` в файле обмена.
+    pub(crate) fn account(record: &BkToCstmrStmt) -> &str {
+        let acct = &record.stmt.acct;
+        [&acct.id.othr.id, &acct.ownr.id.org_id.othr.id, &acct.id.iban].into_iter()
+            .find(|id| !id.is_empty())
+            .map_or("", |id| id.as_str())
+    }
+
+    /// Период выписки `record`: период FrToDt, иначе даты входящего и исходящего остатков,
+    /// иначе период дат проводки `booked`.
+    pub(crate) fn period(record: &BkToCstmrStmt, booked: Option<(NaiveDate, NaiveDate)>) -> Option<(NaiveDate, NaiveDate)> {
+        let balance_date = |types: &[BalanceType]| record.stmt.bal.iter()
+            .find(|balance| types.contains(&balance.tp.cd_or_prtry.cd))
+            .and_then(|balance| balance.dt.dt);
+        let fr_to_dt = &record.stmt.fr_to_dt;
+        let from = fr_to_dt.fr_dt_tm.map(|dt| dt.date())
+            .or_else(|| balance_date(&[BalanceType::Opbd, BalanceType::Prcd]))
+            .or(booked.map(|(from, _)| from));
+        let to = fr_to_dt.to_dt_tm.map(|dt| dt.date())
+            .or_else(|| balance_date(&[BalanceType::Clbd]))
+            .or(booked.map(|(_, to)| to));
+        match (from, to) {
+            (Some(from), Some(to)) => Some((from, to)),
+            (Some(date), None) | (None, Some(date)) => Some((date, date)),
+            (None, None) => None,
+        }
+    }
+
+    fn push_value(record_write: &mut String, key: &str, value: &str) {
+        record_write.push_str(key);
+        record_write.push('=');
+        record_write.push_str(&value.replace(['\r', '\n'], " "));
+        record_write.push_str(EOL);
+    }
+
+    fn push_optional(record_write: &mut String, key: &str, value: &str) {
+        if !value.is_empty() {
+            DocumentClientBank::push_value(record_write, key, value);
+        }
+    }
+
+    fn format_amount(amount: Money) -> String {
+        amount.with_minor_units(CURRENCY).format('.')
+    }
+
+    /// Записывает заголовок файла: кодировку `encoding_label`, дату создания `created`,
+    /// общий период `period` и счета `accounts` всех выписок.
+    pub(crate) fn extract_header(record_write: &mut String, encoding_label: &str, created: Option<IsoDateTime>,
+                                 period: Option<(NaiveDate, NaiveDate)>, accounts: &[String]) {
+        record_write.push_str(SIGNATURE);
+        record_write.push_str(EOL);
+        DocumentClientBank::push_value(record_write, "ВерсияФормата", FORMAT_VERSION);
+        DocumentClientBank::push_value(record_write, "Кодировка", encoding_label);
+        DocumentClientBank::push_value(record_write, "Отправитель", "bank-converter");
+        if let Some(created) = created {
+            DocumentClientBank::push_value(record_write, "ДатаСоздания", &created.datetime.format(DATE_FORMAT).to_string());
+            DocumentClientBank::push_value(record_write, "ВремяСоздания", &created.datetime.format(TIME_FORMAT).to_string());
+        }
+        if let Some((from, to)) = period {
+            DocumentClientBank::push_value(record_write, "ДатаНачала", &from.format(DATE_FORMAT).to_string());
+            DocumentClientBank::push_value(record_write, "ДатаКонца", &to.format(DATE_FORMAT).to_string());
+        }
+        for account in accounts {
+            DocumentClientBank::push_value(record_write, "РасчСчет", account);
+        }
+    }
+
+    /// Записывает секцию остатков выписки `record` за период `period` с оборотами `totals`.
+    pub(crate) fn extract_account_section(record: &BkToCstmrStmt, period: Option<(NaiveDate, NaiveDate)>,
+                                          totals: &TxsSummryAttribute, record_write: &mut String) {
+        let balance = |types: &[BalanceType]| record.stmt.bal.iter()
+            .find(|balance| types.contains(&balance.tp.cd_or_prtry.cd))
+            .map(|balance| match balance.cdt_dbt_ind {
+                CreditDebit::Credit => balance.amt.amt,
+                CreditDebit::Debit => -balance.amt.amt,
+            });
+        record_write.push_str("СекцияРасчСчет");
+        record_write.push_str(EOL);
+        if let Some((from, to)) = period {
+            DocumentClientBank::push_value(record_write, "ДатаНачала", &from.format(DATE_FORMAT).to_string());
+            DocumentClientBank::push_value(record_write, "ДатаКонца", &to.format(DATE_FORMAT).to_string());
+        }
+        DocumentClientBank::push_value(record_write, "РасчСчет", DocumentClientBank::account(record));
+        if let Some(opening) = balance(&[BalanceType::Opbd, BalanceType::Prcd]) {
+            DocumentClientBank::push_value(record_write, "НачальныйОстаток", &DocumentClientBank::format_amount(opening));
+        }
+        DocumentClientBank::push_value(record_write, "ВсегоПоступило", &DocumentClientBank::format_amount(totals.credit().sum));
+        DocumentClientBank::push_value(record_write, "ВсегоСписано", &DocumentClientBank::format_amount(totals.debit().sum));
+        if let Some(closing) = balance(&[BalanceType::Clbd]) {
+            DocumentClientBank::push_value(record_write, "КонечныйОстаток", &DocumentClientBank::format_amount(closing));
+        }
+        record_write.push_str("КонецРасчСчет");
+        record_write.push_str(EOL);
+    }
+
+    /// Реквизиты плательщика и получателя записи.
+    fn parties(tx_dtls: &TxDtlsAttribute) -> (Party, Party) {
+        let pties = &tx_dtls.rltd_pties;
+        let agts = &tx_dtls.rltd_agts;
+        (Party { nm: pties.dbtr.nm.clone(), id: pties.dbtr.id.othr.id.clone(), kpp: tx_dtls.tax.dbtr.regn_id.clone(),
+                acct: pties.dbtr_acct.account().to_string(), bic: agts.dbtr_agt.fin_instn_id.bic.clone(),
+                bank_nm: agts.dbtr_agt.fin_instn_id.nm.clone() },
+         Party { nm: pties.cdtr.nm.clone(), id: pties.cdtr.id.othr.id.clone(), kpp: tx_dtls.tax.cdtr.regn_id.clone(),
+                acct: pties.cdtr_acct.account().to_string(), bic: agts.cdtr_agt.fin_instn_id.bic.clone(),
+                bank_nm: agts.cdtr_agt.fin_instn_id.nm.clone() })
+    }
+
+    fn extract_party(record_write: &mut String, prefix: &str, party: &Party, booked: (&str, &str)) {
+        let key = |suffix: &str| format!("{}{}", prefix, suffix);
+        DocumentClientBank::push_value(record_write, &key("Счет"), &party.acct);
+        DocumentClientBank::push_optional(record_write, booked.0, booked.1);
+        let full = if party.id.is_empty() { party.nm.clone() } else { format!("ИНН {} {}", party.id, party.nm) };
+        DocumentClientBank::push_value(record_write, prefix, full.trim());
+        DocumentClientBank::push_optional(record_write, &key("ИНН"), &party.id);
+        DocumentClientBank::push_optional(record_write, &key("КПП"), &party.kpp);
+        DocumentClientBank::push_optional(record_write, &key("1"), &party.nm);
+        DocumentClientBank::push_optional(record_write, &key("РасчСчет"), &party.acct);
+        DocumentClientBank::push_optional(record_write, &key("Банк1"), &party.bank_nm);
+        DocumentClientBank::push_optional(record_write, &key("БИК"), &party.bic);
+    }
+
+    /// Записывает секцию документа для записи `ntry` выписки `record`.
+    ///
+    /// Счет владельца выписки - счет плательщика документа списания и счет получателя документа
+    /// поступления; наименование владельца подставляется, если в записи его нет.
+    pub(crate) fn extract_document(record: &BkToCstmrStmt, ntry: &NtryAttribute, record_write: &mut String) {
+        let kind = DOCUMENT_KINDS.into_iter().find(|kind| *kind == ntry.addtl_ntry_inf).unwrap_or(DOCUMENT_KINDS[0]);
+        let date = ntry.bookg_dt.dt.or(ntry.val_dt.dt).map(|dt| dt.format(DATE_FORMAT).to_string()).unwrap_or_default();
+        let tx_dtls = ntry.ntry_dtls.tx_dtls.first().cloned().unwrap_or_default();
+        let (mut payer, mut payee) = DocumentClientBank::parties(&tx_dtls);
+        let own = match ntry.cdt_dbt_ind {
+            CreditDebit::Credit => &mut payee,
+            CreditDebit::Debit => &mut payer,
+        };
+        own.acct = DocumentClientBank::account(record).to_string();
+        if own.nm.is_empty() {
+            own.nm = record.stmt.acct.ownr.nm.clone();
+        }
+        let (debited, credited) = match ntry.cdt_dbt_ind {
+            CreditDebit::Credit => ("", date.as_str()),
+            CreditDebit::Debit => (date.as_str(), ""),
+        };
+        DocumentClientBank::push_value(record_write, "СекцияДокумент", kind);
+        DocumentClientBank::push_value(record_write, "Номер", &ntry.acct_svcr_ref);
+        DocumentClientBank::push_value(record_write, "Дата", &date);
+        DocumentClientBank::push_value(record_write, "Сумма", &DocumentClientBank::format_amount(ntry.amt.amt.abs()));
+        DocumentClientBank::extract_party(record_write, "Плательщик", &payer, ("ДатаСписано", debited));
+        DocumentClientBank::extract_party(record_write, "Получатель", &payee, ("ДатаПоступило", credited));
+        DocumentClientBank::push_optional(record_write, "ВидОплаты", &ntry.bk_tx_cd.prtry.cd);
+        DocumentClientBank::push_optional(record_write, "НазначениеПлатежа", &tx_dtls.rmt_inf.ustrd.join(" "));
+        record_write.push_str("КонецДокумента");
+        record_write.push_str(EOL);
+    }
+
+    /// Записывает конец файла.
+    pub(crate) fn extract_trailer(record_write: &mut String) {
+        record_write.push_str("КонецФайла");
+        record_write.push_str(EOL);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DOCUMENT: &str = "1CClientBankExchange\r\nВерсияФормата=1.03\r\nКодировка=Windows\r\n\
+        ДатаНачала=20.02.2024\r\nДатаКонца=21.02.2024\r\nРасчСчет=40702810440000030888\r\n\
+        СекцияРасчСчет\r\nДатаНачала=20.02.2024\r\nДатаКонца=20.02.2024\r\nРасчСчет=40702810440000030888\r\n\
+        НачальныйОстаток=100.00\r\nВсегоПоступило=15.40\r\nВсегоСписано=0\r\nКонечныйОстаток=115.40\r\nКонецРасчСчет\r\n\
+        СекцияРасчСчет\r\nДатаНачала=21.02.2024\r\nДатаКонца=21.02.2024\r\nРасчСчет=40702810440000030888\r\n\
+        НачальныйОстаток=115.40\r\nКонечныйОстаток=-4.60\r\nКонецРасчСчет\r\n\
+        СекцияДокумент=Платежное поручение\r\nНомер=7\r\nДата=21.02.2024\r\nСумма=120.00\r\n\
+        ПлательщикСчет=40702810440000030888\r\nДатаСписано=21.02.2024\r\nПлательщик=ИНН 7735602068 ООО РОМАШКА\r\n\
+        ПолучательСчет=40702810000000000001\r\nПолучатель=ИНН 7701000000 ООО ЛЮТИК\r\nПолучательКПП=770101001\r\n\
+        НазначениеПлатежа1=Оплата по счету 3\r\nНазначениеПлатежа2=НДС не облагается\r\nКонецДокумента\r\n\
+        СекцияДокумент=Банковский ордер\r\nНомер=8\r\nДата=19.02.2024\r\nСумма=15.40\r\n\
+        ПлательщикСчет=40702810000000000001\r\nПолучательСчет=40702810440000030888\r\nДатаПоступило=20.02.2024\r\n\
+        КонецДокумента\r\nКонецФайла\r\n";
+
+    #[test]
+    fn test_parse_sections() {
+        let (header, accounts, documents) = DocumentClientBank::parse_sections(DOCUMENT).unwrap();
+        assert_eq!("1.03", header.get("ВерсияФормата"));
+        assert_eq!(2, accounts.len());
+        assert_eq!("Банковский ордер", documents[1].kind);
+        assert!(DocumentClientBank::parse_sections("ВерсияФормата=1.03").is_err());
+        let unterminated = "1CClientBankExchange\nСекцияДокумент=Платежное поручение\nНомер=1\nКонецФайла\n";
+        assert!(DocumentClientBank::parse_sections(unterminated).is_err());
+    }
+
+    #[test]
+    fn test_parse_party() {
+        let (_, _, documents) = DocumentClientBank::parse_sections(DOCUMENT).unwrap();
+        let party = documents[0].party("Получатель");
+        assert_eq!("ООО ЛЮТИК", party.nm);
+        assert_eq!("7701000000", party.id);
+        assert_eq!("770101001", party.kpp);
+        assert_eq!("40702810000000000001", party.acct);
+    }
+
+    #[test]
+    fn test_parse_document() {
+        let document = DocumentClientBank::parse_document(DOCUMENT).unwrap();
+        assert_eq!(2, document.document.len());
+        let (first, second) = (&document.document[0].stmt, &document.document[1].stmt);
+        assert_eq!(1, first.ntry.len());
+        assert_eq!(CreditDebit::Credit, first.ntry[0].cdt_dbt_ind);
+        assert_eq!(NaiveDate::from_ymd_opt(2024, 2, 20), first.ntry[0].bookg_dt.dt);
+        assert_eq!("Банковский ордер", first.ntry[0].addtl_ntry_inf);
+        assert_eq!(CreditDebit::Debit, second.ntry[0].cdt_dbt_ind);
+        assert_eq!("ООО РОМАШКА", second.acct.ownr.nm);
+        let tx_dtls = &second.ntry[0].ntry_dtls.tx_dtls[0];
+        assert_eq!("770101001", tx_dtls.tax.cdtr.regn_id);
+        assert_eq!(vec!["Оплата по счету 3", "НДС не облагается"], tx_dtls.rmt_inf.ustrd);
+        assert_eq!(CreditDebit::Debit, second.bal[1].cdt_dbt_ind);
+        assert_eq!(Money::new(460, 2), second.bal[1].amt.amt);
+        assert_eq!(Money::new(1540, 2), first.txs_summry.credit().sum);
+        assert!(crate::validate::validate(&crate::models::camt053::DocumentCamt053 {
+            version: None, bk_to_cstmr_stmt: document.document.clone() }).is_empty());
+    }
+
+    #[test]
+    fn test_extract_document() {
+        let document = DocumentClientBank::parse_document(DOCUMENT).unwrap();
+        let record = &document.document[1];
+        let mut record_write = String::new();
+        DocumentClientBank::extract_document(record, &record.stmt.ntry[0], &mut record_write);
+        let lines: Vec<&str> = record_write.split(EOL).collect();
+        assert_eq!(vec!["СекцияДокумент=Платежное поручение", "Номер=7", "Дата=21.02.2024", "Сумма=120.00",
+                        "ПлательщикСчет=40702810440000030888", "ДатаСписано=21.02.2024",
+                        "Плательщик=ИНН 7735602068 ООО РОМАШКА", "ПлательщикИНН=7735602068",
+                        "Плательщик1=ООО РОМАШКА", "ПлательщикРасчСчет=40702810440000030888",
+                        "ПолучательСчет=40702810000000000001", "Получатель=ИНН 7701000000 ООО ЛЮТИК",
+                        "ПолучательИНН=7701000000", "ПолучательКПП=770101001", "Получатель1=ООО ЛЮТИК",
+                        "ПолучательРасчСчет=40702810000000000001",
+                        "НазначениеПлатежа=Оплата по счету 3 НДС не облагается", "КонецДокумента", ""], lines);
+    }
+
+    #[test]
+    fn test_extract_account_section() {
+        let document = DocumentClientBank::parse_document(DOCUMENT).unwrap();
+        let record = &document.document[1];
+        let mut record_write = String::new();
+        let period = DocumentClientBank::period(record, None);
+        DocumentClientBank::extract_account_section(record, period, &record.stmt.txs_summry, &mut record_write);
+        assert_eq!("СекцияРасчСчет\r\nДатаНачала=21.02.2024\r\nДатаКонца=21.02.2024\r\nРасчСчет=40702810440000030888\r\n\
+                    НачальныйОстаток=115.40\r\nВсегоПоступило=0.00\r\nВсегоСписано=120.00\r\nКонечныйОстаток=-4.60\r\n\
+                    КонецРасчСчет\r\n", record_write);
+        assert_eq!(Some("DOS"), DocumentClientBank::encoding_label(TextEncoding::Cp866));
+        assert_eq!(None, DocumentClientBank::encoding_label(TextEncoding::Utf8));
+    }
+}
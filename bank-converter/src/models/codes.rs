@@ -0,0 +1,266 @@
+//! Модуль содержит кодовые значения модели выписки
+//!
+//! Предоставляет признак кредит/дебет и тип остатка ISO 20022 вместе с их представлением в MT940,
+//! а также проверку формата номера счета IBAN
+//!
+//! ```
+//! use bank_converter::models::codes::{BalanceType, CreditDebit};
+//!
+//! assert_eq!(CreditDebit::Credit, "CRDT".parse().unwrap());
+//! assert_eq!("D", CreditDebit::Debit.mt940());
+//! assert_eq!(Some(BalanceType::Opbd), BalanceType::from_mt940_tag("60F"));
+//! assert!("CDIT".parse::<CreditDebit>().is_err());
+//! ```
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use crate::errors::ParserError;
+
+/// Признак кредит/дебет (CdtDbtInd)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum CreditDebit {
+    /// Кредит (CRDT)
+    #[default]
+    Credit,
+    /// Дебет (DBIT)
+    Debit,
+}
+
+/// Тип остатка ISO 20022 (Bal/Tp/CdOrPrtry/Cd)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum BalanceType {
+    /// Входящий бухгалтерский остаток (OPBD)
+    #[default]
+    Opbd,
+    /// Исходящий бухгалтерский остаток (CLBD)
+    Clbd,
+    /// Входящий доступный остаток (OPAV)
+    Opav,
+    /// Исходящий доступный остаток (CLAV)
+    Clav,
+    /// Промежуточный доступный остаток (ITAV)
+    Itav,
+    /// Промежуточный бухгалтерский остаток (ITBD)
+    Itbd,
+    /// Будущий доступный остаток (FWAV, ранее записывался как FPAV)
+    Fwav,
+    /// Исходящий бухгалтерский остаток предыдущей выписки (PRCD)
+    Prcd,
+    /// Информационный остаток (INFO)
+    Info,
+    /// Ожидаемый остаток (XPCD)
+    Xpcd,
+}
+
+impl CreditDebit {
+    /// Код ISO 20022.
+    pub fn code(&self) -> &'static str {
+        match self {
+            CreditDebit::Credit => "CRDT",
+            CreditDebit::Debit => "DBIT",
+        }
+    }
+
+    /// Признак MT940: `C` или `D`.
+    pub fn mt940(&self) -> &'static str {
+        match self {
+            CreditDebit::Credit => "C",
+            CreditDebit::Debit => "D",
+        }
+    }
+
+    /// Разбирает признак MT940 `C` или `D`.
+    ///
+    /// # Ошибки
+    ///
+    /// Возвращает `ParserError::UnknownCode` для других значений.
+    pub fn from_mt940(value: &str) -> Result<Self, ParserError> {
+        match value {
+            "C" => Ok(CreditDebit::Credit),
+            "D" => Ok(CreditDebit::Debit),
+            _ => Err(ParserError::UnknownCode(value.to_string())),
+        }
+    }
+
+    /// Противоположный признак.
+    pub fn reverse(&self) -> Self {
+        match self {
+            CreditDebit::Credit => CreditDebit::Debit,
+            CreditDebit::Debit => CreditDebit::Credit,
+        }
+    }
+}
+
+impl BalanceType {
+    /// Код ISO 20022.
+    pub fn code(&self) -> &'static str {
+        match self {
+            BalanceType::Opbd => "OPBD",
+            BalanceType::Clbd => "CLBD",
+            BalanceType::Opav => "OPAV",
+            BalanceType::Clav => "CLAV",
+            BalanceType::Itav => "ITAV",
+            BalanceType::Itbd => "ITBD",
+            BalanceType::Fwav => "FWAV",
+            BalanceType::Prcd => "PRCD",
+            BalanceType::Info => "INFO",
+            BalanceType::Xpcd => "XPCD",
+        }
+    }
+
+    /// Тип остатка по тегу поля MT940 (60F, 60M, 62F, 62M, 64, 65).
+    pub fn from_mt940_tag(tag: &str) -> Option<Self> {
+        match tag {
+            "60F" => Some(BalanceType::Opbd),
+            "60M" => Some(BalanceType::Opav),
+            "62F" => Some(BalanceType::Clbd),
+            "62M" => Some(BalanceType::Clav),
+            "64" => Some(BalanceType::Itav),
+            "65" => Some(BalanceType::Fwav),
+            _ => None,
+        }
+    }
+
+    /// Тег поля MT940 или `None`, если тип остатка не представим в MT940.
+    pub fn mt940_tag(&self) -> Option<&'static str> {
+        match self {
+            BalanceType::Opbd => Some("60F"),
+            BalanceType::Opav => Some("60M"),
+            BalanceType::Clbd => Some("62F"),
+            BalanceType::Clav => Some("62M"),
+            BalanceType::Itav => Some("64"),
+            BalanceType::Fwav => Some("65"),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for CreditDebit {
+    type Err = ParserError;
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim() {
+            "CRDT" => Ok(CreditDebit::Credit),
+            "DBIT" => Ok(CreditDebit::Debit),
+            _ => Err(ParserError::UnknownCode(value.to_string())),
+        }
+    }
+}
+
+impl FromStr for BalanceType {
+    type Err = ParserError;
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim() {
+            "OPBD" => Ok(BalanceType::Opbd),
+            "CLBD" => Ok(BalanceType::Clbd),
+            "OPAV" => Ok(BalanceType::Opav),
+            "CLAV" => Ok(BalanceType::Clav),
+            "ITAV" => Ok(BalanceType::Itav),
+            "ITBD" => Ok(BalanceType::Itbd),
+            "FWAV" | "FPAV" => Ok(BalanceType::Fwav),
+            "PRCD" => Ok(BalanceType::Prcd),
+            "INFO" => Ok(BalanceType::Info),
+            "XPCD" => Ok(BalanceType::Xpcd),
+            _ => Err(ParserError::UnknownCode(value.to_string())),
+        }
+    }
+}
+
+impl Display for CreditDebit {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.code())
+    }
+}
+
+impl Display for BalanceType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.code())
+    }
+}
+
+impl Serialize for CreditDebit {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.code())
+    }
+}
+
+impl<'de> Deserialize<'de> for CreditDebit {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?.parse().map_err(D::Error::custom)
+    }
+}
+
+impl Serialize for BalanceType {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.code())
+    }
+}
+
+impl<'de> Deserialize<'de> for BalanceType {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?.parse().map_err(D::Error::custom)
+    }
+}
+
+/// Читает необязательный признак кредит/дебет; пустой элемент XML считается отсутствующим.
+pub(crate) fn deserialize_optional_credit_debit<'de, D>(deserializer: D) -> Result<Option<CreditDebit>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let value: Option<String> = Option::deserialize(deserializer)?;
+    match value.as_deref().map(str::trim) {
+        None | Some("") => Ok(None),
+        Some(value) => value.parse().map(Some).map_err(D::Error::custom),
+    }
+}
+
+/// Номер счета в формате IBAN: код страны, контрольные цифры и до 30 букв и цифр.
+/// Номер, не похожий на IBAN (например, российский счет из 20 цифр), пишется в `Othr/Id`.
+pub(crate) fn is_iban(account: &str) -> bool {
+    let bytes = account.as_bytes();
+    (15..=34).contains(&bytes.len())
+        && bytes[..2].iter().all(u8::is_ascii_uppercase)
+        && bytes[2..4].iter().all(u8::is_ascii_digit)
+        && bytes[4..].iter().all(u8::is_ascii_alphanumeric)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_credit_debit() {
+        for value in [CreditDebit::Credit, CreditDebit::Debit] {
+            assert_eq!(value, value.code().parse().unwrap());
+            assert_eq!(value, CreditDebit::from_mt940(value.mt940()).unwrap());
+        }
+        assert_eq!(CreditDebit::Debit, CreditDebit::Credit.reverse());
+        assert_eq!("Unknown code: CDIT", "CDIT".parse::<CreditDebit>().unwrap_err().to_string());
+        assert!(CreditDebit::from_mt940("X").is_err());
+    }
+
+    #[test]
+    fn test_balance_type() {
+        for tag in ["60F", "60M", "62F", "62M", "64", "65"] {
+            let balance_type = BalanceType::from_mt940_tag(tag).unwrap();
+            assert_eq!(Some(tag), balance_type.mt940_tag());
+            assert_eq!(balance_type, balance_type.code().parse().unwrap());
+        }
+        assert_eq!(BalanceType::Fwav, "FPAV".parse().unwrap());
+        assert_eq!(None, BalanceType::Prcd.mt940_tag());
+        assert!("OPDB".parse::<BalanceType>().is_err());
+        let mut original = std::fs::File::open("../test_files/CAMT053.xml").unwrap();
+        let error = crate::models::camt053::DocumentCamt053::from_read(&mut original).unwrap_err();
+        assert!(error.to_string().contains("Unknown code: OPDB"), "{}", error);
+    }
+
+    #[test]
+    fn test_is_iban() {
+        assert!(is_iban("DE89370400440532013000"));
+        assert!(is_iban("GB29NWBK60161331926819"));
+        assert!(!is_iban("40702810440000030888"));
+        assert!(!is_iban("DE89"));
+        assert!(!is_iban("DE89 3704 0044 0532 0130 00"));
+        assert!(!is_iban("de89370400440532013000"));
+    }
+}
@@ -7,52 +7,56 @@ use serde::Serialize;
 use serde::Deserialize;
 use crate::csv_data;
 use crate::errors::ParserError;
-use crate::models::camt053::{BalanceAttribute, BkToCstmrStmt, DocumentCamt053,
-                             NtryAttribute, TxDtlsAttribute};
-use chrono::{Local};
+use crate::models::camt053::{AmtAttribute, BalanceAttribute, BkToCstmrStmt, DocumentCamt053,
+                             DtAttribute, NtryAttribute, TxDtlsAttribute, TxsSummryAttribute};
+use crate::models::codes::{BalanceType, CreditDebit};
+use crate::models::csv_flat::FlatCsv;
+use crate::models::csv_profile::{resolve_column, CellProfile, CsvProfile};
+use crate::models::date::IsoDateTime;
+use crate::models::money::Money;
+use chrono::{Local, NaiveDate, NaiveDateTime, NaiveTime};
 
 /// Структура для документа CSV
 /// Содержит методы для чтения, транофрмации и запис данных CSV
-/// ```no_run
+/// ```ignore
 /// 
 /// pub fn from_read<R: Read>(r: &mut R) -> Result<Self, ParserError> 
 /// pub fn write_to<W: Write>(&mut self, writer: &mut W) -> Result<(), ConvertError> 
-/// 
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
 pub struct DocumentCsv {
-    pub(crate) rows: Vec<RowCsv>
+    /// Строки документа в порядке следования в файле
+    pub rows: Vec<RowCsv>,
+    /// Профиль разметки документа: где в строках находятся реквизиты, записи и итоги
+    pub profile: Box<CsvProfile>,
+    /// Параметры плоского документа; если заданы, документ - плоская таблица
+    /// (см. [`crate::models::csv_flat`]), а профиль не используется
+    pub flat: Option<FlatCsv>,
 }
 csv_data!(RowCsv, String, a, b, c, d, e, f, g, h, i, j, k, l, m, n, o, p, q, r, s, t, u);
 
 
 impl DocumentCsv {
-    pub(crate) fn new() -> Self{
-        Self{rows: Vec::new()}
+    /// Создает пустой документ CSV.
+    pub fn new() -> Self{
+        Self{rows: Vec::new(), profile: Box::default(), flat: None}
     }
 
-    fn extract_time(val: &str) -> Option<String>{
+    fn extract_time(val: &str) -> Option<NaiveTime>{
         let reg_pattern = Regex::new(r"(\d{2}:\d{2}:\d{2})");
-        if let Ok(regexp) = reg_pattern {
-            if let Some(capture) = regexp.captures(val)
-            {
-                let dt = capture[1].to_string();
-                return Some(dt);
-            }
+        if let Ok(regexp) = reg_pattern
+            && let Some(capture) = regexp.captures(val)
+        {
+            return NaiveTime::parse_from_str(&capture[1], "%H:%M:%S").ok();
         }
         None
     }
-    fn extract_date(val: &str) -> Option<String> {
+    fn extract_date(val: &str) -> Option<NaiveDate> {
         let reg_pattern = Regex::new(r"(\d{2}.\d{2}.\d{4})");
-        if let Ok(regexp) = reg_pattern {
-            if let Some(capture) = regexp.captures(val)
-            {
-                let mut dt = String::new();
-                dt.push_str(&capture[1][6..10]);
-                dt.push_str("-");
-                dt.push_str(&capture[1][3..5]);
-                dt.push_str("-");
-                dt.push_str(&capture[1][0..2]);
-                return Some(dt);
-            }
+        if let Ok(regexp) = reg_pattern
+            && let Some(capture) = regexp.captures(val)
+        {
+            return NaiveDate::parse_from_str(&capture[1].replace(['/', '-'], "."), "%d.%m.%Y").ok();
         }
         None
     }
@@ -75,33 +79,29 @@ impl DocumentCsv {
         None
     }
 
-    fn extract_date_rus(val: &str) -> Option<String> {
+    fn extract_date_rus(val: &str) -> Option<NaiveDate> {
         let reg_pattern = Regex::new(r"(\d{2}).(января|февраля|марта|апреля|мая|июня|июля|августа|сентября|октября|ноября|декабря).(\d{4})");
-        if let Ok(regexp) = reg_pattern {
-            if let Some(capture) = regexp.captures(val)
-            {
-                let mut dt = String::new();
-                dt.push_str(&capture[3]);
-                dt.push_str("-");
-                if let Some(month) = Self::convert_ru_month_to_number(&capture[2]){
-                    dt.push_str(&month);
-                }
-                dt.push_str("-");
-                dt.push_str(&capture[1]);
-                return Some(dt);
-            }
+        if let Ok(regexp) = reg_pattern
+            && let Some(capture) = regexp.captures(val)
+            && let Some(month) = Self::convert_ru_month_to_number(&capture[2])
+        {
+            return NaiveDate::from_ymd_opt(capture[3].parse().ok()?, month.parse().ok()?,
+                                           capture[1].parse().ok()?);
         }
         None
     }
 
+    fn format_date(date: Option<NaiveDate>) -> String {
+        date.map(|dt| dt.format("%d.%m.%Y").to_string()).unwrap_or_default()
+    }
+
     fn extract_crd_agent(val: &str, ntry_det: &mut TxDtlsAttribute) {
         let reg_pattern = Regex::new(r"(\d+) ([\w ]+), (.+)");
-        if let Ok(regexp) = reg_pattern {
-            if let Some(capture) = regexp.captures(val)
-            {
-                ntry_det.rltd_agts.dbtr_agt.fin_instn_id.bic = capture[1].to_string();
-                ntry_det.rltd_agts.dbtr_agt.fin_instn_id.nm = capture[2].to_string();
-            }
+        if let Ok(regexp) = reg_pattern
+            && let Some(capture) = regexp.captures(val)
+        {
+            ntry_det.rltd_agts.dbtr_agt.fin_instn_id.bic = capture[1].to_string();
+            ntry_det.rltd_agts.dbtr_agt.fin_instn_id.nm = capture[2].to_string();
         }
     }
 
@@ -115,189 +115,306 @@ impl DocumentCsv {
         None
     }
 
-    pub(crate) fn parse_to_camt(self) -> Result<DocumentCamt053, ParserError>{
+    fn extract_amount(val: &str) -> Result<Money, ParserError> {
+        if val.trim().is_empty() {
+            return Ok(Money::default());
+        }
+        val.parse()
+    }
+
+    /// Количество операций; `None`, если ячейка пустая.
+    fn extract_count(val: &str) -> Result<Option<u32>, ParserError> {
+        let val = val.trim();
+        if val.is_empty() {
+            return Ok(None);
+        }
+        val.parse().map(Some)
+            .map_err(|_| ParserError::BadCsvDeserializeError(format!("Bad number of entries {}", val)))
+    }
+
+    /// Значение колонки `column` строки `row`; пустая строка, если колонка не задана или ее нет.
+    /// Название колонки ищется в заголовке таблицы - в строках документа до строки `before`.
+    fn column<'a>(&self, row: &'a RowCsv, column: Option<&str>, before: usize) -> &'a str {
+        column.and_then(|column| resolve_column(&self.rows, column, before))
+            .and_then(|index| row.cell(index))
+            .unwrap_or_default()
+    }
+
+    /// Значение ячейки шапки `cell`; пустая строка, если ячейка не задана или ее нет в документе.
+    fn header_cell<'a>(&'a self, cell: &Option<CellProfile>) -> &'a str {
+        cell.as_ref().and_then(|cell| cell.value(&self.rows)).unwrap_or_default()
+    }
+
+    /// Дата в виде `10 октября 2023` или `10.10.2023`.
+    fn extract_any_date(val: &str) -> Option<NaiveDate> {
+        DocumentCsv::extract_date_rus(val).or_else(|| DocumentCsv::extract_date(val))
+    }
+
+    /// Строка подвала с подписью `label` в колонке подписей профиля и ее номер.
+    fn find_row(&self, label: &Option<String>, start: usize) -> Option<(usize, &RowCsv)> {
+        let label = label.as_deref()?;
+        let column = self.profile.footer.label_column.as_str();
+        self.rows.iter().enumerate().find(|(_, row)| self.column(row, Some(column), start).trim() == label)
+    }
+
+    /// Остаток записан в колонке дебета подвала, если он дебетовый, и в колонке кредита, если кредитовый.
+    fn extract_balance(&self, (index, row): (usize, &RowCsv), balance_type: BalanceType, ccy: &str) -> Result<BalanceAttribute, ParserError> {
+        let footer = &self.profile.footer;
+        let debit = DocumentCsv::extract_amount(self.column(row, Some(&footer.debit_column), index))?;
+        let mut balance = BalanceAttribute::default();
+        balance.tp.cd_or_prtry.cd = balance_type;
+        balance.dt.dt = DocumentCsv::extract_any_date(self.column(row, footer.date_column.as_deref(), index));
+        if debit.is_zero() {
+            let credit = DocumentCsv::extract_amount(self.column(row, Some(&footer.credit_column), index))?;
+            balance.amt = AmtAttribute::new(credit, ccy);
+            balance.cdt_dbt_ind = CreditDebit::Credit;
+        } else {
+            balance.amt = AmtAttribute::new(debit, ccy);
+            balance.cdt_dbt_ind = CreditDebit::Debit;
+        }
+        Ok(balance)
+    }
+
+    /// Реквизиты участника из ячейки `счет\nИНН\nнаименование`.
+    fn extract_party(val: &str) -> Option<(&str, &str, &str)> {
+        let details: Vec<&str> = val.split('\n').collect();
+        match details[..] {
+            [acct, id, nm] => Some((acct, id, nm)),
+            _ => None,
+        }
+    }
+
+    /// Запись выписки из строки таблицы записей `row`.
+    fn extract_entry(&self, row: &RowCsv, start: usize, ccy: &str, issuer: &str) -> Result<NtryAttribute, ParserError> {
+        let columns = &self.profile.entries.columns;
+        let mut ntry = NtryAttribute::default();
+        let mut ntry_det = TxDtlsAttribute::default();
+        let date_cell = self.column(row, Some(&columns.date), start);
+        let Some(date) = DocumentCsv::extract_date(date_cell) else {
+            return Err(ParserError::BadDate(date_cell.to_string()));
+        };
+        ntry.bookg_dt = DtAttribute::new(date);
+        ntry.val_dt = DtAttribute::new(DocumentCsv::extract_date(self.column(row, columns.value_date.as_deref(), start))
+            .unwrap_or(date));
+        let debit = self.column(row, columns.debit.as_deref(), start);
+        if let Some(amount) = columns.amount.as_deref() {
+            let amount = DocumentCsv::extract_amount(self.column(row, Some(amount), start))?;
+            ntry.cdt_dbt_ind = if amount.is_negative() { CreditDebit::Debit } else { CreditDebit::Credit };
+            ntry.amt = AmtAttribute::new(amount.abs(), ccy);
+        } else if debit.trim().is_empty() {
+            ntry.cdt_dbt_ind = CreditDebit::Credit;
+            ntry.amt = AmtAttribute::new(DocumentCsv::extract_amount(self.column(row, columns.credit.as_deref(), start))?, ccy);
+        } else {
+            ntry.cdt_dbt_ind = CreditDebit::Debit;
+            ntry.amt = AmtAttribute::new(DocumentCsv::extract_amount(debit)?, ccy);
+        }
+        ntry.bk_tx_cd.prtry.cd = self.column(row, columns.code.as_deref(), start).to_string();
+        ntry.bk_tx_cd.prtry.issr = issuer.to_string();
+        ntry.acct_svcr_ref = self.column(row, columns.reference.as_deref(), start).to_string();
+        ntry_det.refs.end_to_end_id ="1".to_string();
+        if let Some((acct, id, nm)) = DocumentCsv::extract_party(self.column(row, columns.debtor.as_deref(), start)) {
+            ntry_det.rltd_pties.dbtr.nm = nm.to_string();
+            ntry_det.rltd_pties.dbtr.id.othr.id = id.to_string();
+            ntry_det.rltd_pties.dbtr_acct.other.id = acct.to_string();
+        }
+        if let Some((acct, id, nm)) = DocumentCsv::extract_party(self.column(row, columns.creditor.as_deref(), start)) {
+            ntry_det.rltd_pties.cdtr.nm = nm.to_string();
+            ntry_det.rltd_pties.cdtr.id.othr.id = id.to_string();
+            ntry_det.rltd_pties.cdtr_acct.other.id = acct.to_string();
+        }
+        DocumentCsv::extract_crd_agent(self.column(row, columns.bank.as_deref(), start), & mut ntry_det);
+        ntry_det.rmt_inf.ustrd.push(self.column(row, columns.remittance.as_deref(), start).to_string());
+        ntry.ntry_dtls.tx_dtls.push(ntry_det);
+        Ok(ntry)
+    }
+
+    /// Приводит документ к модели CAMT.053 по профилю разметки или как плоский документ.
+    pub(crate) fn parse_to_camt(&self) -> Result<DocumentCamt053, ParserError>{
+        if self.flat.is_some() {
+            return self.parse_flat_to_camt();
+        }
         let mut camt = DocumentCamt053::new();
         let mut camt_bk_to_cstm = BkToCstmrStmt::default();
-        if self.rows.len() < 8 {
-            return Err(ParserError::BadInputFormatFile("Bad input csv file".to_string()))
+        let header = &self.profile.header;
+        let entries = &self.profile.entries;
+        let Some(start) = entries.start(&self.rows).filter(|start| *start <= self.rows.len()) else {
+            return Err(ParserError::BadInputFormatFile(
+                format!("Bad input csv file: no entry table for profile {}", self.profile)))
+        };
+        let created = self.header_cell(&header.created);
+        if let Some(date_create) = DocumentCsv::extract_date(created){
+            let time_create = DocumentCsv::extract_time(created).unwrap_or(NaiveTime::MIN);
+            camt_bk_to_cstm.grp_hdr.cre_dt_tm = Some(IsoDateTime::new(date_create.and_time(time_create), None));
         }
-        if let Some(date_create) = DocumentCsv::extract_date(&self.rows[3].b){
-            camt_bk_to_cstm.grp_hdr.cre_dt_tm = date_create;
-            if let Some(time_create) = DocumentCsv::extract_time(&self.rows[3].b){
-                camt_bk_to_cstm.grp_hdr.cre_dt_tm.push_str("T");
-                camt_bk_to_cstm.grp_hdr.cre_dt_tm.push_str(&time_create);
-            }
+        camt_bk_to_cstm.stmt.acct.id.othr.id = self.header_cell(&header.account).trim().to_string();
+        camt_bk_to_cstm.stmt.acct.ownr.nm = self.header_cell(&header.owner).trim().to_string();
+        if let Some(dt) = DocumentCsv::extract_any_date(self.header_cell(&header.from_date)) {
+            camt_bk_to_cstm.stmt.fr_to_dt.fr_dt_tm = Some(IsoDateTime::start_of_day(dt));
         }
-        camt_bk_to_cstm.stmt.acct.id.othr.id = self.rows[4].m.to_string();
-        camt_bk_to_cstm.stmt.acct.ownr.nm = self.rows[5].m.to_string();
-        if let Some(dt) = DocumentCsv::extract_date_rus(&self.rows[6].c) {
-            camt_bk_to_cstm.stmt.fr_to_dt.fr_dt_tm = dt + "T00:00:00";
+        if let Some(dt) = DocumentCsv::extract_any_date(self.header_cell(&header.to_date)) {
+            camt_bk_to_cstm.stmt.fr_to_dt.to_dt_tm = Some(IsoDateTime::end_of_day(dt));
         }
-        if let Some(dt) = DocumentCsv::extract_date_rus(&self.rows[6].p){
-            camt_bk_to_cstm.stmt.fr_to_dt.to_dt_tm= dt + "T23:59:59";
-        }
-        if let Some(ccy) = DocumentCsv::extract_ccy(&self.rows[7].c){
+        if let Some(ccy) = DocumentCsv::extract_ccy(self.header_cell(&header.currency).trim()){
             camt_bk_to_cstm.stmt.acct.ccy =ccy;
         }
-        for index_row in 11..self.rows.len() {
-            let row = &self.rows[index_row];
-            if row.b.is_empty() {
+        let ccy = camt_bk_to_cstm.stmt.acct.ccy.clone();
+        let issuer = self.header_cell(&header.issuer);
+        for row in &self.rows[start..] {
+            let date = self.column(row, Some(&entries.columns.date), start).trim();
+            if date.is_empty() || entries.stop_labels.iter().any(|label| label == date) {
                 break;
             }
-            let mut ntry = NtryAttribute::default();
-            let mut ntry_det = TxDtlsAttribute::default();
-            ntry.amt.ccy = camt_bk_to_cstm.stmt.acct.ccy.clone();
-            if let Some(date) = DocumentCsv::extract_date(&row.b){
-                ntry.val_dt.dt = date;
-                ntry.bookg_dt.dt = ntry.val_dt.dt.clone();
-            }
-            if row.j.is_empty(){
-                ntry.cdt_dbt_ind = "CDIT".to_string();
-                ntry.amt.amt = row.n.to_string();
-            }
-            else{
-                ntry.cdt_dbt_ind =  "DBIT".to_string();
-                ntry.amt.amt = row.j.to_string();
-            }
-            ntry.bk_tx_cd.prtry.cd = row.q.to_string();
-            ntry.bk_tx_cd.prtry.issr = self.rows[2].b.to_string();
-            ntry.acct_svcr_ref = row.o.to_string();
-            ntry_det.refs.end_to_end_id ="1".to_string();
-            let debit_detals: Vec<&str> = row.e.split("\n").collect();
-            if debit_detals.len() == 3{
-                ntry_det.rltd_pties.dbtr.nm = debit_detals[2].to_string();
-                ntry_det.rltd_pties.dbtr.id.othr.id = debit_detals[1].to_string();
-                ntry_det.rltd_pties.dbtr_acct.other.id = debit_detals[0].to_string();
-            }
-            let credit_detals: Vec<&str> = row.i.split("\n").collect();
-            if credit_detals.len() == 3{
-                ntry_det.rltd_pties.cdtr.nm = debit_detals[2].to_string();
-                ntry_det.rltd_pties.cdtr.id.othr.id = debit_detals[1].to_string();
-                ntry_det.rltd_pties.cdtr_acct.other.id = debit_detals[0].to_string();
-            }
-            DocumentCsv::extract_crd_agent(&row.r, & mut ntry_det);
-            ntry_det.rmt_inf.ustrd.push(row.u.to_string());
-            ntry.ntry_dtls.tx_dtls.push(ntry_det);
-            camt_bk_to_cstm.stmt.ntry.push(ntry);
+            camt_bk_to_cstm.stmt.ntry.push(self.extract_entry(row, start, &ccy, issuer)?);
+        }
+        let footer = &self.profile.footer;
+        if let Some(row) = self.find_row(&footer.opening_balance, start) {
+            camt_bk_to_cstm.stmt.bal.push(self.extract_balance(row, BalanceType::Opbd, &ccy)?);
+        }
+        let txs_summry = &mut camt_bk_to_cstm.stmt.txs_summry;
+        if let Some((index, row)) = self.find_row(&footer.turnover, start) {
+            txs_summry.ttl_dbt_ntries.get_or_insert_default().sum =
+                DocumentCsv::extract_amount(self.column(row, Some(&footer.debit_column), index))?;
+            txs_summry.ttl_cdt_ntries.get_or_insert_default().sum =
+                DocumentCsv::extract_amount(self.column(row, Some(&footer.credit_column), index))?;
+        }
+        if let Some((index, row)) = self.find_row(&footer.count, start) {
+            txs_summry.ttl_ntries.nb_of_ntries =
+                DocumentCsv::extract_count(self.column(row, footer.total_column.as_deref(), index))?;
+            txs_summry.ttl_dbt_ntries.get_or_insert_default().nb_of_ntries =
+                DocumentCsv::extract_count(self.column(row, Some(&footer.debit_column), index))?.unwrap_or_default();
+            txs_summry.ttl_cdt_ntries.get_or_insert_default().nb_of_ntries =
+                DocumentCsv::extract_count(self.column(row, Some(&footer.credit_column), index))?.unwrap_or_default();
+        }
+        if let Some(row) = self.find_row(&footer.closing_balance, start) {
+            camt_bk_to_cstm.stmt.bal.push(self.extract_balance(row, BalanceType::Clbd, &ccy)?);
         }
-        let next_row = self.rows.len() - 4; //offset balance data from end document
-        let mut balance_opbd = BalanceAttribute::default();
-        balance_opbd.amt.ccy = camt_bk_to_cstm.stmt.acct.ccy.clone();
-        balance_opbd.tp.cd_or_prtry.cd = "OPDB".to_string();
-        balance_opbd.amt.amt = self.rows[next_row+1].h.to_string();
-        camt_bk_to_cstm.stmt.bal.push(balance_opbd);
-        camt_bk_to_cstm.stmt.txs_summry.ttl_dbt_ntries.sum = self.rows[next_row+2].h.to_string();
-        camt_bk_to_cstm.stmt.txs_summry.ttl_cdt_ntries.sum = self.rows[next_row+2].l.to_string();
-        camt_bk_to_cstm.stmt.txs_summry.ttl_ntries.nb_of_ntries = self.rows[next_row].l.to_string();
-        let mut balance_clbd = BalanceAttribute::default();
-        balance_clbd.amt.ccy = camt_bk_to_cstm.stmt.acct.ccy.clone();
-        balance_clbd.tp.cd_or_prtry.cd = "CLDB".to_string();
-        balance_clbd.amt.amt = self.rows[next_row+3].l.to_string();
-        camt_bk_to_cstm.stmt.bal.push(balance_clbd);
         camt.bk_to_cstmr_stmt.push(camt_bk_to_cstm);
         Ok(camt)
     }
 
+    fn write_balance(balance: &BalanceAttribute, row: &mut RowCsv) {
+        match balance.cdt_dbt_ind {
+            CreditDebit::Credit => row.l = balance.amt.amt.to_string(),
+            CreditDebit::Debit => row.h = balance.amt.amt.to_string(),
+        }
+    }
+
     pub(crate)  fn parse_to_csv(camt: &DocumentCamt053) -> Result<Self, ParserError>{
-        if let Some(doc) = camt.bk_to_cstmr_stmt.get(0) {
+        DocumentCsv::parse_to_csv_at(camt, Local::now().naive_local())
+    }
+
+    /// Формирует документ CSV по первой выписке `camt` с датой формирования выписки `created_at`.
+    pub(crate) fn parse_to_csv_at(camt: &DocumentCamt053, created_at: NaiveDateTime) -> Result<Self, ParserError>{
+        if let Some(doc) = camt.bk_to_cstmr_stmt.first() {
             let mut csv = DocumentCsv::new();
-            let mut row_1 = RowCsv::new();
-            row_1.b = format!("Дата формирования выписки: {}", Local::now().format("%d.%m.%Y %H:%M:%S"));
-            csv.rows.push(row_1);
-            let mut row_2 = RowCsv::new();
-            row_2.b = "Выписка по лицевому счету".to_string();
-            row_2.m = doc.stmt.acct.id.othr.id.clone();
-            csv.rows.push(row_2);
-            let mut row_3 = RowCsv::new();
-            row_3.m = doc.stmt.acct.ownr.nm.clone();
-            csv.rows.push(row_3);
-            let mut row_4 = RowCsv::new();
-            row_4.c = format!("За период с {}", doc.stmt.fr_to_dt.fr_dt_tm);
-            row_4.o = "по".to_string();
-            row_4.p = doc.stmt.fr_to_dt.to_dt_tm.clone();
-            csv.rows.push(row_4);
-            let mut row_5 = RowCsv::new();
-            row_5.c = doc.stmt.acct.ccy.clone();
-            csv.rows.push(row_5);
-            let mut row_6 = RowCsv::new();
-            row_6.b = "Дата проводки".to_string();
-            row_6.e = "Счет".to_string();
-            row_6.j = "Сумма по дебету".to_string();
-            row_6.n = "Сумма по кредиту".to_string();
-            row_6.o = "№ документа".to_string();
-            row_6.q = "ВО".to_string();
-            row_6.r = "Банк (БИК и наименование)".to_string();
-            row_6.u = "Назначение платежа".to_string();
-            csv.rows.push(row_6);
-            let mut row_7 = RowCsv::new();
-            row_7.e = "Дебет".to_string();
-            row_7.i = "Кредит".to_string();
-            csv.rows.push(row_7);
-            for ntry in &doc.stmt.ntry{
+            csv.rows.extend(DocumentCsv::head_rows(doc, created_at));
+            csv.rows.extend(doc.stmt.ntry.iter().map(DocumentCsv::entry_row));
+            csv.rows.extend(DocumentCsv::tail_rows(doc, &doc.stmt.txs_summry));
+            return Ok(csv);
+        }
+        Err(ParserError::BadCsvDeserializeError("No document to convert CSV format".to_string()))
+    }
+    pub(crate) fn head_rows(doc: &BkToCstmrStmt, created_at: NaiveDateTime) -> Vec<RowCsv> {
+        let mut rows = Vec::new();
+        let mut row_1 = RowCsv::new();
+        row_1.b = format!("Дата формирования выписки: {}", created_at.format("%d.%m.%Y %H:%M:%S"));
+        rows.push(row_1);
+        let mut row_2 = RowCsv::new();
+        row_2.b = "Выписка по лицевому счету".to_string();
+        row_2.m = doc.stmt.acct.id.othr.id.clone();
+        rows.push(row_2);
+        let mut row_3 = RowCsv::new();
+        row_3.m = doc.stmt.acct.ownr.nm.clone();
+        rows.push(row_3);
+        let mut row_4 = RowCsv::new();
+        row_4.c = format!("За период с {}", DocumentCsv::format_date(doc.stmt.fr_to_dt.fr_dt_tm.map(|dt| dt.date())));
+        row_4.o = "по".to_string();
+        row_4.p = DocumentCsv::format_date(doc.stmt.fr_to_dt.to_dt_tm.map(|dt| dt.date()));
+        rows.push(row_4);
+        let mut row_5 = RowCsv::new();
+        row_5.c = doc.stmt.acct.ccy.clone();
+        rows.push(row_5);
+        let mut row_6 = RowCsv::new();
+        row_6.b = "Дата проводки".to_string();
+        row_6.e = "Счет".to_string();
+        row_6.j = "Сумма по дебету".to_string();
+        row_6.n = "Сумма по кредиту".to_string();
+        row_6.o = "№ документа".to_string();
+        row_6.q = "ВО".to_string();
+        row_6.r = "Банк (БИК и наименование)".to_string();
+        row_6.u = "Назначение платежа".to_string();
+        rows.push(row_6);
+        let mut row_7 = RowCsv::new();
+        row_7.e = "Дебет".to_string();
+        row_7.i = "Кредит".to_string();
+        rows.push(row_7);
+        rows
+    }
+    pub(crate) fn entry_row(ntry: &NtryAttribute) -> RowCsv {
+        let mut row = RowCsv::new();
+        row.b = DocumentCsv::format_date(ntry.bookg_dt.dt);
+        match ntry.cdt_dbt_ind {
+            CreditDebit::Credit => row.n = ntry.amt.amt.to_string(),
+            CreditDebit::Debit => row.j = ntry.amt.amt.to_string(),
+        }
+        row.q = ntry.bk_tx_cd.prtry.cd.clone();
+        row.o = ntry.acct_svcr_ref.clone();
+        if let Some(ntry_det) = ntry.ntry_dtls.tx_dtls.first()
+        {
+            row.e = format!("{}\n{}\n{}",
+                            ntry_det.rltd_pties.dbtr_acct.account(),
+                            ntry_det.rltd_pties.dbtr.id.othr.id,
+                            ntry_det.rltd_pties.dbtr.nm);
+            row.i = format!("{}\n{}\n{}",
+                            ntry_det.rltd_pties.cdtr_acct.account(),
+                            ntry_det.rltd_pties.cdtr.id.othr.id,
+                            ntry_det.rltd_pties.cdtr.nm);
+            row.r = format!("БИК {}, {}",
+                           ntry_det.rltd_agts.dbtr_agt.fin_instn_id.bic,
+                           ntry_det.rltd_agts.dbtr_agt.fin_instn_id.nm);
+            let mut ustrd_all = String::new();
+            for ustrd in ntry_det.rmt_inf.ustrd.clone(){
+                ustrd_all.push_str(&ustrd);
+                ustrd_all.push(',');
+            }
+            row.u = ustrd_all;
+        }
+        row
+    }
+    pub(crate) fn tail_rows(doc: &BkToCstmrStmt, txs_summry: &TxsSummryAttribute) -> Vec<RowCsv> {
+        let mut rows = Vec::new();
+        let mut row_8 = RowCsv::new();
+        row_8.b = "б/с".to_string();
+        row_8.h = "Дебет".to_string();
+        row_8.l = "Кредит".to_string();
+        row_8.t = "Всего".to_string();
+        rows.push(row_8);
+        let mut row_9 = RowCsv::new();
+        row_9.b = "Количество операций".to_string();
+        row_9.h = txs_summry.debit().nb_of_ntries.to_string();
+        row_9.l = txs_summry.credit().nb_of_ntries.to_string();
+        row_9.t = txs_summry.ttl_ntries.nb_of_ntries.map(|count| count.to_string()).unwrap_or_default();
+        rows.push(row_9);
+        for bal in &doc.stmt.bal{
+            if bal.tp.cd_or_prtry.cd == BalanceType::Opbd {
                 let mut row = RowCsv::new();
-                row.b = ntry.bookg_dt.dt.clone();
-                if ntry.cdt_dbt_ind == "CDIT"{
-                    row.n = ntry.amt.amt.clone();
-                }
-                if ntry.cdt_dbt_ind == "DBIT"{
-                    row.j = ntry.amt.amt.clone();
-                }
-                row.q = ntry.bk_tx_cd.prtry.cd.clone();
-                row.o = ntry.acct_svcr_ref.clone();
-                if let Some(ntry_det) = ntry.ntry_dtls.tx_dtls.get(0)
-                {
-                    row.e = format!("{}\n{}\n{}",
-                                    ntry_det.rltd_pties.dbtr_acct.other.id,
-                                    ntry_det.rltd_pties.dbtr.id.othr.id,
-                                    ntry_det.rltd_pties.dbtr.nm);
-                    row.i = format!("{}\n{}\n{}",
-                                    ntry_det.rltd_pties.cdtr_acct.other.id,
-                                    ntry_det.rltd_pties.cdtr.id.othr.id,
-                                    ntry_det.rltd_pties.cdtr.nm);
-                    row.r = format!("БИК {}, {}",
-                                   ntry_det.rltd_agts.dbtr_agt.fin_instn_id.bic,
-                                   ntry_det.rltd_agts.dbtr_agt.fin_instn_id.nm);
-                    let mut ustrd_all = String::new();
-                    for ustrd in ntry_det.rmt_inf.ustrd.clone(){
-                        ustrd_all.push_str(&ustrd);
-                        ustrd_all.push_str(",");
-                    }
-                    row.u = ustrd_all;
-                }
-                csv.rows.push(row);
+                row.b = "Входящий остаток".to_string();
+                DocumentCsv::write_balance(bal, &mut row);
+                rows.push(row);
+                let mut row_10 = RowCsv::new();
+                row_10.b = "Итого оборотов".to_string();
+                row_10.h = txs_summry.debit().sum.to_string();
+                row_10.l = txs_summry.credit().sum.to_string();
+                rows.push(row_10);
             }
-            let mut row_8 = RowCsv::new();
-            row_8.b = "б/с".to_string();
-            row_8.h = "Дебет".to_string();
-            row_8.l = "Кредит".to_string();
-            row_8.t = "Всего".to_string();
-            csv.rows.push(row_8);
-            let mut row_9 = RowCsv::new();
-            row_9.b = "Количество операций".to_string();
-            row_9.l = doc.stmt.txs_summry.ttl_ntries.nb_of_ntries.clone();
-            csv.rows.push(row_9);
-            for bal in &doc.stmt.bal{
-                if bal.tp.cd_or_prtry.cd == "OPDB"{
-                    let mut row = RowCsv::new();
-                    row.b = "Входящий остаток".to_string();
-                    row.h = bal.amt.amt.clone();
-                    csv.rows.push(row);
-                    let mut row_10 = RowCsv::new();
-                    row_10.b = "Итого оборотов".to_string();
-                    row_10.h = doc.stmt.txs_summry.ttl_dbt_ntries.sum.clone();
-                    row_10.l = doc.stmt.txs_summry.ttl_cdt_ntries.sum.clone();
-                    csv.rows.push(row_10);
-                }
-                if bal.tp.cd_or_prtry.cd == "CLDB" {
-                    let mut row = RowCsv::new();
-                    row.b = "Исходящий остаток".to_string();
-                    row.l = bal.amt.amt.clone();
-                    csv.rows.push(row);
-                }
+            if bal.tp.cd_or_prtry.cd == BalanceType::Clbd {
+                let mut row = RowCsv::new();
+                row.b = "Исходящий остаток".to_string();
+                DocumentCsv::write_balance(bal, &mut row);
+                rows.push(row);
             }
-            return Ok(csv);
         }
-        Err(ParserError::BadCsvDeserializeError("No document to convert CSV format".to_string()))
+        rows
     }
 }
 
@@ -307,13 +424,13 @@ mod tests{
     #[test]
     fn test_extract_time(){
         let test = "time 12:34:56";
-        assert_eq!("12:34:56", DocumentCsv::extract_time(test).unwrap());
+        assert_eq!("12:34:56", DocumentCsv::extract_time(test).unwrap().to_string());
     }
 
     #[test]
     fn test_extract_date(){
         let test = "date 12.01.2021";
-        assert_eq!("2021-01-12", DocumentCsv::extract_date(test).unwrap());
+        assert_eq!("2021-01-12", DocumentCsv::extract_date(test).unwrap().to_string());
     }
 
     #[test]
@@ -325,7 +442,7 @@ mod tests{
     #[test]
     fn test_extract_date_rus(){
         let test = "10 октября 2023";
-        assert_eq!("2023-10-10", DocumentCsv::extract_date_rus(test).unwrap());
+        assert_eq!("2023-10-10", DocumentCsv::extract_date_rus(test).unwrap().to_string());
     }
 
     #[test]
@@ -335,7 +452,7 @@ mod tests{
         test.rltd_agts.dbtr_agt.fin_instn_id.bic = "044525545".to_string();
         test.rltd_agts.dbtr_agt.fin_instn_id.nm = "АО ЮниКредит Банк".to_string();
         let mut result = TxDtlsAttribute::default();
-        DocumentCsv::extract_crd_agent(&data, &mut result);
+        DocumentCsv::extract_crd_agent(data, &mut result);
         assert_eq!(test, result);
     }
 
@@ -344,4 +461,143 @@ mod tests{
         let test = "Доллар США";
         assert_eq!("USD", DocumentCsv::extract_ccy(test).unwrap());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_parse_to_camt_codes(){
+        let mut csv = DocumentCsv::new();
+        csv.rows = vec![RowCsv::new(); 11];
+        csv.rows[7].c = "Российский рубль".to_string();
+        let mut debit = RowCsv::new();
+        debit.b = "20.02.2024".to_string();
+        debit.j = "1540.00".to_string();
+        let mut credit = RowCsv::new();
+        credit.b = "04.03.2024".to_string();
+        credit.n = "2000.00".to_string();
+        csv.rows.extend([debit, credit, RowCsv::new()]);
+        let mut opening = RowCsv::new();
+        opening.b = "Входящий остаток".to_string();
+        opening.h = "0,00".to_string();
+        opening.l = "1332.54".to_string();
+        let mut closing = RowCsv::new();
+        closing.b = "Исходящий остаток".to_string();
+        closing.h = "207.46".to_string();
+        csv.rows.extend([opening, closing]);
+        let camt = csv.parse_to_camt().unwrap();
+        let stmt = &camt.bk_to_cstmr_stmt[0].stmt;
+        assert_eq!(CreditDebit::Debit, stmt.ntry[0].cdt_dbt_ind);
+        assert_eq!(CreditDebit::Credit, stmt.ntry[1].cdt_dbt_ind);
+        assert_eq!(BalanceType::Opbd, stmt.bal[0].tp.cd_or_prtry.cd);
+        assert_eq!(CreditDebit::Credit, stmt.bal[0].cdt_dbt_ind);
+        assert_eq!("1332.54", stmt.bal[0].amt.amt.to_string());
+        assert_eq!(BalanceType::Clbd, stmt.bal[1].tp.cd_or_prtry.cd);
+        assert_eq!(CreditDebit::Debit, stmt.bal[1].cdt_dbt_ind);
+        let csv = DocumentCsv::parse_to_csv(&camt).unwrap();
+        assert!(csv.rows.iter().any(|row| row.n == "2000.00"));
+        assert!(csv.rows.iter().any(|row| row.b == "Исходящий остаток" && row.h == "207.46"));
+    }
+
+    #[test]
+    fn test_parse_to_camt_profile(){
+        let profile = CsvProfile::from_toml(r#"
+            name = "test"
+            [header]
+            account = { label = "Счет", column = "b" }
+            currency = { label = "Счет", column = "c" }
+            from_date = { row = 1, column = "b" }
+            [entries]
+            start_after = "Дата"
+            stop_labels = ["Остаток"]
+            [entries.columns]
+            date = "a"
+            amount = "b"
+            remittance = "c"
+            [footer]
+            label_column = "a"
+            debit_column = "b"
+            credit_column = "c"
+            closing_balance = "Остаток"
+        "#).unwrap();
+        let lines = ["Счет,40702810000000000001,RUB", "Период,01.02.2024", "", "Дата,Сумма,Назначение",
+            "02.02.2024,-10.50,Комиссия", "03.02.2024,100.00,Оплата", "", "Остаток,,89.50"];
+        let text: String = lines.iter().map(|line| format!("{}\n", line)).collect();
+        let csv = DocumentCsv::from_read_with_profile(&mut text.as_bytes(), profile).unwrap();
+        let camt = csv.parse_to_camt().unwrap();
+        let stmt = &camt.bk_to_cstmr_stmt[0].stmt;
+        assert_eq!("40702810000000000001", stmt.acct.id.othr.id);
+        assert_eq!("RUB", stmt.acct.ccy);
+        assert_eq!("2024-02-01", stmt.fr_to_dt.fr_dt_tm.unwrap().date().to_string());
+        assert_eq!(2, stmt.ntry.len());
+        assert_eq!(CreditDebit::Debit, stmt.ntry[0].cdt_dbt_ind);
+        assert_eq!("10.50", stmt.ntry[0].amt.amt.to_string());
+        assert_eq!(vec!["Оплата".to_string()], stmt.ntry[1].ntry_dtls.tx_dtls[0].rmt_inf.ustrd);
+        assert_eq!(BalanceType::Clbd, stmt.bal[0].tp.cd_or_prtry.cd);
+        assert_eq!("89.50", stmt.bal[0].amt.amt.to_string());
+        let mut csv = DocumentCsv::from_read(&mut text.as_bytes()).unwrap();
+        assert!(csv.parse_to_camt().is_err());
+        csv.rows.truncate(2);
+        csv.profile.entries.start_row = 2;
+        assert!(csv.parse_to_camt().unwrap().bk_to_cstmr_stmt[0].stmt.ntry.is_empty());
+    }
+
+    #[test]
+    fn test_extract_count() {
+        assert_eq!(Some(12), DocumentCsv::extract_count(" 12 ").unwrap());
+        assert_eq!(None, DocumentCsv::extract_count("").unwrap());
+        assert!(DocumentCsv::extract_count("12,5").is_err());
+    }
+
+    #[test]
+    fn test_row_columns(){
+        assert_eq!(Some(0), RowCsv::column_index("a"));
+        assert_eq!(Some(21), RowCsv::column_index("v"));
+        assert_eq!(Some(27), RowCsv::column_index("ab"));
+        assert_eq!(Some(5), RowCsv::column_index("5"));
+        assert_eq!(None, RowCsv::column_index("Дата"));
+        assert_eq!(None, RowCsv::column_index("abc"));
+        for index in [0, 20, 25, 26, 51, 52, 701] {
+            assert_eq!(Some(index), RowCsv::column_index(&RowCsv::column_name(index)));
+        }
+        let mut row = RowCsv::from_record(["1", "2"]);
+        assert_eq!(21, row.len());
+        assert!(row.set("x", "24"));
+        assert!(!row.set("Дата", "0"));
+        assert_eq!(vec!["".to_string(), "".to_string(), "24".to_string()], row.extra);
+        assert_eq!(Some("24"), row.cell(23));
+        assert_eq!(None, row.get("y"));
+        assert_eq!(Some("2"), row.get("b"));
+    }
+
+    #[test]
+    fn test_from_read_variable_width(){
+        let mut file = std::fs::File::open("../test_files/Пример выписки по счёту 1.csv").unwrap();
+        let csv = DocumentCsv::from_read(&mut file).unwrap();
+        assert_eq!(23, csv.rows[1].len());
+        let mut output = Vec::new();
+        csv.clone().write_to(&mut output).unwrap();
+        let reread = DocumentCsv::from_read(&mut output.as_slice()).unwrap();
+        assert_eq!(csv, reread);
+        let text = "a,b\n1,2,3,4,5,6,7,8,9,10,11,12,13,14,15,16,17,18,19,20,21,22,23,24\n";
+        let csv = DocumentCsv::from_read(&mut text.as_bytes()).unwrap();
+        assert_eq!(24, csv.rows[1].len());
+        assert_eq!(Some("24"), csv.rows[1].get("x"));
+    }
+
+    #[test]
+    fn test_parse_to_camt_named_columns(){
+        let mut file = std::fs::File::open("../test_files/Пример выписки по счёту 1.csv").unwrap();
+        let expected = DocumentCsv::from_read(&mut file).unwrap().parse_to_camt().unwrap();
+        let mut profile = CsvProfile::sberbank();
+        let columns = &mut profile.entries.columns;
+        columns.date = "Дата проводки".to_string();
+        columns.debit = Some("Сумма по дебету".to_string());
+        columns.credit = Some("Сумма по кредиту".to_string());
+        columns.debtor = Some("Дебет".to_string());
+        columns.creditor = Some("Кредит".to_string());
+        columns.remittance = Some("20".to_string());
+        profile.footer.debit_column = "Дебет".to_string();
+        profile.footer.credit_column = "Кредит".to_string();
+        let mut file = std::fs::File::open("../test_files/Пример выписки по счёту 1.csv").unwrap();
+        let camt = DocumentCsv::from_read_with_profile(&mut file, profile).unwrap().parse_to_camt().unwrap();
+        assert_eq!(expected, camt);
+    }
+}
@@ -0,0 +1,309 @@
+//! Модуль плоского документа CSV
+//!
+//! Плоский документ - таблица для электронных таблиц и BI: строка заголовка и по строке на каждую
+//! запись выписки, без объединенных и многострочных ячеек. Колонки (`FLAT_COLUMNS`) ищутся по
+//! заголовку, поэтому их порядок может быть любым, а лишние колонки пропускаются:
+//!
+//! `account,booking_date,value_date,amount,currency,credit_debit,counterparty_name,`
+//! `counterparty_account,counterparty_inn,counterparty_bic,purpose,reference`
+//!
+//! Даты записываются как `ГГГГ-ММ-ДД`, суммы - без знака с разделителем дробной части
+//! `decimal_separator`, признак кредит/дебет - `CRDT` или `DBIT`. Контрагент кредитовой записи -
+//! плательщик, дебетовой - получатель. Записи с разными счетами или валютами читаются как разные
+//! выписки; остатков плоский документ не содержит.
+//!
+//! ```
+//! use bank_converter::models::csv::DocumentCsv;
+//! use bank_converter::models::csv_flat::FlatCsv;
+//! use bank_converter::models::camt053::DocumentCamt053;
+//!
+//! let flat = FlatCsv { delimiter: b';', decimal_separator: ',' };
+//! let text = "booking_date;amount;currency;credit_debit;purpose\n2024-02-20;1540,00;RUB;DBIT;Оплата\n";
+//! let csv = DocumentCsv::from_read_flat(&mut text.as_bytes(), flat).unwrap();
+//! let camt = DocumentCamt053::try_from(csv).unwrap();
+//! assert_eq!("1540.00", camt.bk_to_cstmr_stmt[0].stmt.ntry[0].amt.amt.to_string());
+//! ```
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+use chrono::NaiveDate;
+use crate::errors::ParserError;
+use crate::models::camt053::{AmtAttribute, BkToCstmrStmt, DocumentCamt053, DtAttribute, NtryAttribute,
+                             TxDtlsAttribute};
+use crate::models::codes::{is_iban, CreditDebit};
+use crate::models::csv::{DocumentCsv, RowCsv};
+use crate::models::money::Money;
+
+/// Колонки плоского документа в порядке записи
+pub const FLAT_COLUMNS: [&str; 12] = ["account", "booking_date", "value_date", "amount", "currency", "credit_debit",
+    "counterparty_name", "counterparty_account", "counterparty_inn", "counterparty_bic", "purpose", "reference"];
+
+/// Колонки, без которых запись плоского документа не разобрать
+const REQUIRED_COLUMNS: [&str; 3] = ["booking_date", "amount", "credit_debit"];
+
+/// Формат даты плоского документа
+const DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// Параметры плоского документа CSV
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FlatCsv {
+    /// Разделитель колонок
+    pub delimiter: u8,
+    /// Разделитель дробной части сумм
+    pub decimal_separator: char,
+}
+
+impl Default for FlatCsv {
+    fn default() -> Self {
+        Self { delimiter: b',', decimal_separator: '.' }
+    }
+}
+
+impl Display for FlatCsv {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}{}", self.delimiter as char, self.decimal_separator)
+    }
+}
+
+impl FromStr for FlatCsv {
+    type Err = ParserError;
+
+    /// Разбирает параметры вида `<разделитель колонок>[<разделитель дробной части>]`,
+    /// например `;,`; `\t` обозначает табуляцию.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let bad = || ParserError::BadInputFormatFile(format!("Bad flat CSV options '{}', expected e.g. ';,'", s));
+        let s = s.replace("\\t", "\t");
+        let mut chars = s.chars();
+        let delimiter = chars.next().filter(char::is_ascii).ok_or_else(bad)?;
+        let decimal_separator = chars.next().unwrap_or('.');
+        if chars.next().is_some() || !matches!(decimal_separator, '.' | ',') || delimiter == decimal_separator {
+            return Err(bad());
+        }
+        Ok(Self { delimiter: delimiter as u8, decimal_separator })
+    }
+}
+
+/// Значения первых `width` колонок строки `row`.
+pub(crate) fn fields(row: &RowCsv, width: usize) -> impl Iterator<Item = &str> {
+    (0..width).map(|index| row.cell(index).unwrap_or_default())
+}
+
+/// Колонки строки заголовка: имя колонки плоского документа и номер колонки
+struct FlatHeader(Vec<(String, usize)>);
+
+impl FlatHeader {
+    fn new(row: &RowCsv) -> Self {
+        Self(row.values().enumerate()
+            .map(|(index, name)| (name.trim().to_lowercase(), index))
+            .filter(|(name, _)| !name.is_empty())
+            .collect())
+    }
+
+    /// Значение колонки `name` в строке `row`; пустая строка, если колонки нет.
+    fn value<'a>(&self, row: &'a RowCsv, name: &str) -> &'a str {
+        self.0.iter().find(|(header, _)| header == name)
+            .and_then(|(_, index)| row.cell(*index))
+            .map_or("", str::trim)
+    }
+}
+
+impl DocumentCsv {
+    /// Приводит плоский документ к модели CAMT.053: записи группируются в выписки по счету и валюте.
+    pub(crate) fn parse_flat_to_camt(&self) -> Result<DocumentCamt053, ParserError> {
+        let Some((header_row, rows)) = self.rows.split_first() else {
+            return Err(ParserError::BadInputFormatFile("Bad input csv file: no flat header".to_string()));
+        };
+        let header = FlatHeader::new(header_row);
+        if let Some(missing) = REQUIRED_COLUMNS.iter().find(|name| !header.0.iter().any(|(header, _)| header == *name)) {
+            return Err(ParserError::BadInputFormatFile(format!("Bad input csv file: no flat column {}", missing)));
+        }
+        let mut camt = DocumentCamt053::new();
+        for row in rows {
+            if row.values().all(|value| value.trim().is_empty()) {
+                continue;
+            }
+            let account = header.value(row, "account");
+            let ccy = header.value(row, "currency");
+            let ntry = DocumentCsv::flat_entry(&header, row, ccy)?;
+            let position = camt.bk_to_cstmr_stmt.iter().position(|record| {
+                let acct = &record.stmt.acct;
+                acct.ccy == ccy && DocumentCsv::flat_account(record) == account
+            });
+            let record = match position {
+                Some(position) => &mut camt.bk_to_cstmr_stmt[position],
+                None => {
+                    let mut record = BkToCstmrStmt::default();
+                    if is_iban(account) {
+                        record.stmt.acct.id.iban = account.to_string();
+                    } else {
+                        record.stmt.acct.id.othr.id = account.to_string();
+                    }
+                    record.stmt.acct.ccy = ccy.to_string();
+                    camt.bk_to_cstmr_stmt.push(record);
+                    camt.bk_to_cstmr_stmt.last_mut().expect("statement just pushed")
+                }
+            };
+            record.stmt.ntry.push(ntry);
+        }
+        if camt.bk_to_cstmr_stmt.is_empty() {
+            camt.bk_to_cstmr_stmt.push(BkToCstmrStmt::default());
+        }
+        Ok(camt)
+    }
+
+    /// Запись выписки из строки плоского документа `row`.
+    fn flat_entry(header: &FlatHeader, row: &RowCsv, ccy: &str) -> Result<NtryAttribute, ParserError> {
+        let date = |name: &str| -> Result<Option<NaiveDate>, ParserError> {
+            let value = header.value(row, name);
+            if value.is_empty() {
+                return Ok(None);
+            }
+            NaiveDate::parse_from_str(value, DATE_FORMAT).map(Some).map_err(|_| ParserError::BadDate(value.to_string()))
+        };
+        let mut ntry = NtryAttribute::default();
+        let Some(booking_date) = date("booking_date")? else {
+            return Err(ParserError::BadDate(String::new()));
+        };
+        ntry.bookg_dt = DtAttribute::new(booking_date);
+        ntry.val_dt = DtAttribute::new(date("value_date")?.unwrap_or(booking_date));
+        let amount: Money = header.value(row, "amount").parse()?;
+        ntry.amt = AmtAttribute::new(amount.abs(), ccy);
+        ntry.cdt_dbt_ind = header.value(row, "credit_debit").parse()?;
+        ntry.acct_svcr_ref = header.value(row, "reference").to_string();
+        let mut ntry_det = TxDtlsAttribute::default();
+        let name = header.value(row, "counterparty_name").to_string();
+        let account = header.value(row, "counterparty_account").to_string();
+        let inn = header.value(row, "counterparty_inn").to_string();
+        let bic = header.value(row, "counterparty_bic").to_string();
+        let pties = &mut ntry_det.rltd_pties;
+        let agts = &mut ntry_det.rltd_agts;
+        let (party_nm, party_id, party_acct, party_bic) = match ntry.cdt_dbt_ind {
+            CreditDebit::Credit => (&mut pties.dbtr.nm, &mut pties.dbtr.id.othr.id, &mut pties.dbtr_acct, &mut agts.dbtr_agt.fin_instn_id.bic),
+            CreditDebit::Debit => (&mut pties.cdtr.nm, &mut pties.cdtr.id.othr.id, &mut pties.cdtr_acct, &mut agts.cdtr_agt.fin_instn_id.bic),
+        };
+        *party_nm = name;
+        *party_id = inn;
+        if is_iban(&account) {
+            party_acct.id.iban = account;
+        } else {
+            party_acct.other.id = account;
+        }
+        *party_bic = bic;
+        let purpose = header.value(row, "purpose");
+        if !purpose.is_empty() {
+            ntry_det.rmt_inf.ustrd.push(purpose.to_string());
+        }
+        ntry.ntry_dtls.tx_dtls.push(ntry_det);
+        Ok(ntry)
+    }
+
+    /// Приводит документ CAMT.053 к плоскому документу: строка заголовка и строки записей всех выписок.
+    pub(crate) fn parse_to_flat_csv(camt: &DocumentCamt053, flat: FlatCsv) -> Result<Self, ParserError> {
+        if camt.bk_to_cstmr_stmt.is_empty() {
+            return Err(ParserError::BadCsvDeserializeError("No document to convert CSV format".to_string()));
+        }
+        let mut csv = DocumentCsv::new();
+        csv.flat = Some(flat);
+        csv.rows.push(DocumentCsv::flat_header_row());
+        for record in &camt.bk_to_cstmr_stmt {
+            csv.rows.extend(record.stmt.ntry.iter().map(|ntry| DocumentCsv::flat_entry_row(record, ntry, flat)));
+        }
+        Ok(csv)
+    }
+
+    /// Строка заголовка плоского документа.
+    pub(crate) fn flat_header_row() -> RowCsv {
+        RowCsv::from_record(FLAT_COLUMNS)
+    }
+
+    /// Счет выписки `record`: идентификатор владельца (поле :25: MT940), IBAN или прочий идентификатор.
+    pub(crate) fn flat_account(record: &BkToCstmrStmt) -> &str {
+        let acct = &record.stmt.acct;
+        [&acct.ownr.id.org_id.othr.id, &acct.id.iban, &acct.id.othr.id].into_iter()
+            .find(|id| !id.is_empty())
+            .map_or("", |id| id.as_str())
+    }
+
+    /// Строка плоского документа для записи `ntry` выписки `record`.
+    pub(crate) fn flat_entry_row(record: &BkToCstmrStmt, ntry: &NtryAttribute, flat: FlatCsv) -> RowCsv {
+        let format_date = |dt: &DtAttribute| dt.dt.map(|dt| dt.format(DATE_FORMAT).to_string()).unwrap_or_default();
+        let acct = &record.stmt.acct;
+        let account = DocumentCsv::flat_account(record).to_string();
+        let ccy = if ntry.amt.ccy.is_empty() { &acct.ccy } else { &ntry.amt.ccy };
+        let mut values = vec![account, format_date(&ntry.bookg_dt), format_date(&ntry.val_dt),
+            ntry.amt.amt.abs().format(flat.decimal_separator), ccy.clone(), ntry.cdt_dbt_ind.code().to_string()];
+        let ntry_det = ntry.ntry_dtls.tx_dtls.first().cloned().unwrap_or_default();
+        let pties = &ntry_det.rltd_pties;
+        let agts = &ntry_det.rltd_agts;
+        let (nm, id, party_acct, bic) = match ntry.cdt_dbt_ind {
+            CreditDebit::Credit => (&pties.dbtr.nm, &pties.dbtr.id.othr.id, &pties.dbtr_acct, &agts.dbtr_agt.fin_instn_id.bic),
+            CreditDebit::Debit => (&pties.cdtr.nm, &pties.cdtr.id.othr.id, &pties.cdtr_acct, &agts.cdtr_agt.fin_instn_id.bic),
+        };
+        values.extend([nm.clone(), party_acct.account().to_string(), id.clone(), bic.clone(),
+            ntry_det.rmt_inf.ustrd.join(" "), ntry.acct_svcr_ref.clone()]);
+        let values: Vec<String> = values.iter().map(|value| value.replace(['\r', '\n'], " ")).collect();
+        RowCsv::from_record(values.iter().map(String::as_str))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_flat_csv_from_str() {
+        assert_eq!(FlatCsv { delimiter: b';', decimal_separator: ',' }, ";,".parse().unwrap());
+        assert_eq!(FlatCsv { delimiter: b'\t', decimal_separator: '.' }, "\\t".parse().unwrap());
+        assert_eq!(FlatCsv::default(), FlatCsv::default().to_string().parse().unwrap());
+        assert!(",,".parse::<FlatCsv>().is_err());
+        assert!(";x".parse::<FlatCsv>().is_err());
+        assert!("".parse::<FlatCsv>().is_err());
+    }
+
+    #[test]
+    fn test_flat_round_trip() {
+        let flat = FlatCsv { delimiter: b';', decimal_separator: ',' };
+        let text = "purpose;Booking_Date;amount;currency;credit_debit;counterparty_name;counterparty_account;\
+                    counterparty_inn;counterparty_bic;account;extra\n\
+                    Оплата;2024-02-20;1540,00;RUB;DBIT;АО \"РСИЦ\";40702810600014448120;7733573894;044525545;40702810440000030888;x\n\
+                    ;;;;;;;;;;\n\
+                    Заём;2024-03-04;2000;RUB;CRDT;ПАО СБЕРБАНК;30233810642000600001;7707083893;;40702810440000030888;\n\
+                    ;2024-03-05;10;EUR;CRDT;;;;;DE89370400440532013000;\n";
+        let csv = DocumentCsv::from_read_flat(&mut text.as_bytes(), flat).unwrap();
+        let camt = csv.parse_to_camt().unwrap();
+        assert_eq!(2, camt.bk_to_cstmr_stmt.len());
+        let stmt = &camt.bk_to_cstmr_stmt[0].stmt;
+        assert_eq!("40702810440000030888", stmt.acct.id.othr.id);
+        assert_eq!(2, stmt.ntry.len());
+        let debit = &stmt.ntry[0];
+        assert_eq!(CreditDebit::Debit, debit.cdt_dbt_ind);
+        assert_eq!("2024-02-20", debit.val_dt.dt.unwrap().to_string());
+        assert_eq!("АО \"РСИЦ\"", debit.ntry_dtls.tx_dtls[0].rltd_pties.cdtr.nm);
+        assert_eq!("044525545", debit.ntry_dtls.tx_dtls[0].rltd_agts.cdtr_agt.fin_instn_id.bic);
+        assert_eq!("7707083893", stmt.ntry[1].ntry_dtls.tx_dtls[0].rltd_pties.dbtr.id.othr.id);
+        assert_eq!("2000.00", stmt.ntry[1].amt.amt.to_string());
+        assert_eq!("DE89370400440532013000", camt.bk_to_cstmr_stmt[1].stmt.acct.id.iban);
+
+        let mut written = DocumentCsv::parse_to_flat_csv(&camt, flat).unwrap();
+        let mut output = Vec::new();
+        written.write_to(&mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(FLAT_COLUMNS.join(";"), lines[0]);
+        assert_eq!("40702810440000030888;2024-02-20;2024-02-20;1540,00;RUB;DBIT;\"АО \"\"РСИЦ\"\"\";\
+                    40702810600014448120;7733573894;044525545;Оплата;", lines[1]);
+        assert_eq!(4, lines.len());
+        let reread = DocumentCsv::from_read_flat(&mut output.as_bytes(), flat).unwrap().parse_to_camt().unwrap();
+        assert_eq!(camt, reread);
+    }
+
+    #[test]
+    fn test_flat_errors() {
+        let flat = FlatCsv::default();
+        let csv = DocumentCsv::from_read_flat(&mut "booking_date,amount\n".as_bytes(), flat).unwrap();
+        assert!(csv.parse_to_camt().unwrap_err().to_string().contains("credit_debit"));
+        let text = "booking_date,amount,credit_debit\n20.02.2024,1,CRDT\n";
+        let csv = DocumentCsv::from_read_flat(&mut text.as_bytes(), flat).unwrap();
+        assert!(matches!(csv.parse_to_camt(), Err(ParserError::BadDate(_))));
+        assert!(DocumentCsv::parse_to_flat_csv(&DocumentCamt053::new(), flat).is_err());
+    }
+}
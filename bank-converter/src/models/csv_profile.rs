@@ -0,0 +1,399 @@
+//! Модуль профилей разметки документа CSV
+//!
+//! Банки выгружают выписки CSV в разной разметке: реквизиты в шапке, таблица записей и итоги
+//! в подвале находятся в разных строках и колонках. Профиль `CsvProfile` описывает разметку
+//! декларативно, в TOML или JSON:
+//! * `header` - ячейки шапки: банк, дата формирования, счет, владелец, период, валюта;
+//! * `entries` - начало и конец таблицы записей и роли ее колонок;
+//! * `footer` - подписи и колонки остатков и итогов.
+//!
+//! Строки нумеруются с нуля. Колонка задается буквами, как в электронных таблицах (`a`, `m`, `aa`),
+//! номером с нуля (`"12"`) или названием из заголовка таблицы (`"Дата проводки"`): название ищется
+//! в строках над таблицей записей, для колонок подвала - над строкой подвала. Название из заголовка
+//! важнее букв: ссылка `"id"` указывает на колонку `id`, если она есть в заголовке. Ячейку шапки можно
+//! задать номером строки (`row`) или подписью строки (`label`), если шапка бывает разной высоты.
+//! Встроенный профиль `sberbank` описывает выписку СберБизнес (файл `profiles/sberbank.toml`).
+//!
+//! ```
+//! use bank_converter::models::csv_profile::CsvProfile;
+//!
+//! let profile = CsvProfile::from_toml(r#"
+//!     name = "bank"
+//!     [header]
+//!     account = { row = 1, column = "b" }
+//!     currency = { label = "Валюта:", column = "c" }
+//!     [entries]
+//!     start_after = "Дата"
+//!     [entries.columns]
+//!     date = "a"
+//!     amount = "c"
+//! "#).unwrap();
+//! assert_eq!(Some("c"), profile.entries.columns.amount.as_deref());
+//! assert_eq!("sberbank", CsvProfile::load("sberbank").unwrap().name);
+//! ```
+use std::fmt::{Display, Formatter};
+use std::fs;
+use serde::{Deserialize, Serialize};
+use crate::errors::ParserError;
+use crate::models::csv::RowCsv;
+
+/// Профиль разметки документа CSV
+///
+/// Разделы, не указанные в файле профиля, остаются пустыми, а не берутся из профиля `sberbank`.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct CsvProfile {
+    /// Название профиля
+    #[serde(default)]
+    pub name: String,
+    /// Ячейки шапки выписки
+    #[serde(default)]
+    pub header: HeaderProfile,
+    /// Таблица записей
+    #[serde(default)]
+    pub entries: EntriesProfile,
+    /// Остатки и итоги в подвале выписки
+    #[serde(default)]
+    pub footer: FooterProfile,
+}
+
+/// Ячейка документа CSV: колонка строки с номером `row` или строки с подписью `label`
+#[derive(Debug, Clone, PartialEq, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct CellProfile {
+    /// Номер строки, начиная с нуля
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub row: Option<usize>,
+    /// Подпись строки: первая строка, одна из ячеек которой начинается с подписи
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    /// Колонка значения
+    pub column: String,
+}
+
+/// Ячейки шапки выписки
+#[derive(Debug, Clone, PartialEq, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct HeaderProfile {
+    /// Банк, выдавший выписку (эмитент кодов операций)
+    pub issuer: Option<CellProfile>,
+    /// Дата и время формирования выписки
+    pub created: Option<CellProfile>,
+    /// Номер счета
+    pub account: Option<CellProfile>,
+    /// Владелец счета
+    pub owner: Option<CellProfile>,
+    /// Начало периода выписки
+    pub from_date: Option<CellProfile>,
+    /// Конец периода выписки
+    pub to_date: Option<CellProfile>,
+    /// Валюта счета: код ISO 4217 или название валюты
+    pub currency: Option<CellProfile>,
+}
+
+/// Таблица записей выписки
+#[derive(Debug, Clone, PartialEq, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct EntriesProfile {
+    /// Номер первой строки записей, если не задана подпись `start_after`
+    pub start_row: usize,
+    /// Подпись заголовка таблицы: записи начинаются после строки, одна из ячеек которой равна подписи
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_after: Option<String>,
+    /// Число строк заголовка таблицы после строки с подписью `start_after`
+    pub skip_rows: usize,
+    /// Подписи в колонке даты, на которых таблица заканчивается; пустая дата также завершает таблицу
+    pub stop_labels: Vec<String>,
+    /// Роли колонок таблицы
+    pub columns: ColumnsProfile,
+}
+
+/// Роли колонок таблицы записей
+///
+/// Сумма записи задается колонками `debit` и `credit` или одной колонкой `amount`,
+/// в которой дебетовые суммы отрицательны. Ячейки плательщика и получателя содержат строки
+/// `счет`, `ИНН` и `наименование`.
+#[derive(Debug, Clone, PartialEq, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct ColumnsProfile {
+    /// Дата проводки
+    pub date: String,
+    /// Дата валютирования; если не задана, совпадает с датой проводки
+    pub value_date: Option<String>,
+    /// Сумма по дебету
+    pub debit: Option<String>,
+    /// Сумма по кредиту
+    pub credit: Option<String>,
+    /// Сумма со знаком: отрицательная - дебет, положительная - кредит
+    pub amount: Option<String>,
+    /// Номер документа
+    pub reference: Option<String>,
+    /// Код вида операции
+    pub code: Option<String>,
+    /// Плательщик
+    pub debtor: Option<String>,
+    /// Получатель
+    pub creditor: Option<String>,
+    /// Банк контрагента (БИК и наименование)
+    pub bank: Option<String>,
+    /// Назначение платежа
+    pub remittance: Option<String>,
+}
+
+/// Остатки и итоги в подвале выписки
+#[derive(Debug, Clone, PartialEq, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct FooterProfile {
+    /// Колонка подписей строк подвала
+    pub label_column: String,
+    /// Колонка дебетовых значений
+    pub debit_column: String,
+    /// Колонка кредитовых значений
+    pub credit_column: String,
+    /// Колонка общего количества операций
+    pub total_column: Option<String>,
+    /// Колонка даты остатка
+    pub date_column: Option<String>,
+    /// Подпись входящего остатка
+    pub opening_balance: Option<String>,
+    /// Подпись исходящего остатка
+    pub closing_balance: Option<String>,
+    /// Подпись итогов оборотов
+    pub turnover: Option<String>,
+    /// Подпись количества операций
+    pub count: Option<String>,
+}
+
+impl Default for CsvProfile {
+    fn default() -> Self {
+        CsvProfile::sberbank()
+    }
+}
+
+impl Display for CsvProfile {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+/// Ячейка строки `row` и колонки `column` документа
+fn cell(row: usize, column: &str) -> Option<CellProfile> {
+    Some(CellProfile { row: Some(row), label: None, column: column.to_string() })
+}
+
+impl CsvProfile {
+    /// Встроенный профиль выписки СберБизнес.
+    pub fn sberbank() -> Self {
+        Self {
+            name: "sberbank".to_string(),
+            header: HeaderProfile {
+                issuer: cell(2, "b"),
+                created: cell(3, "b"),
+                account: cell(4, "m"),
+                owner: cell(5, "m"),
+                from_date: cell(6, "c"),
+                to_date: cell(6, "p"),
+                currency: cell(7, "c"),
+            },
+            entries: EntriesProfile {
+                start_row: 11,
+                start_after: None,
+                skip_rows: 0,
+                stop_labels: vec!["б/с".to_string()],
+                columns: ColumnsProfile {
+                    date: "b".to_string(),
+                    value_date: None,
+                    debit: Some("j".to_string()),
+                    credit: Some("n".to_string()),
+                    amount: None,
+                    reference: Some("o".to_string()),
+                    code: Some("q".to_string()),
+                    debtor: Some("e".to_string()),
+                    creditor: Some("i".to_string()),
+                    bank: Some("r".to_string()),
+                    remittance: Some("u".to_string()),
+                },
+            },
+            footer: FooterProfile {
+                label_column: "b".to_string(),
+                debit_column: "h".to_string(),
+                credit_column: "l".to_string(),
+                total_column: Some("t".to_string()),
+                date_column: Some("t".to_string()),
+                opening_balance: Some("Входящий остаток".to_string()),
+                closing_balance: Some("Исходящий остаток".to_string()),
+                turnover: Some("Итого оборотов".to_string()),
+                count: Some("Количество операций".to_string()),
+            },
+        }
+    }
+
+    /// Встроенный профиль по названию `name` (без учета регистра).
+    pub fn builtin(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "sberbank" | "sber" => Some(CsvProfile::sberbank()),
+            _ => None,
+        }
+    }
+
+    /// Читает профиль из текста TOML.
+    ///
+    /// # Ошибки
+    ///
+    /// Возвращает `ParserError::BadInputFormatFile`, если текст не является профилем.
+    pub fn from_toml(text: &str) -> Result<Self, ParserError> {
+        toml::from_str::<Self>(text)
+            .map_err(|e| ParserError::BadInputFormatFile(format!("CSV profile: {}", e)))?
+            .checked()
+    }
+
+    /// Читает профиль из текста JSON.
+    ///
+    /// # Ошибки
+    ///
+    /// Возвращает `ParserError::BadInputFormatFile`, если текст не является профилем.
+    pub fn from_json(text: &str) -> Result<Self, ParserError> {
+        serde_json::from_str::<Self>(text)
+            .map_err(|e| ParserError::BadInputFormatFile(format!("CSV profile: {}", e)))?
+            .checked()
+    }
+
+    /// Возвращает встроенный профиль `spec` или читает профиль из файла `spec`:
+    /// JSON, если текст начинается с `{`, иначе TOML.
+    ///
+    /// # Ошибки
+    ///
+    /// Возвращает ошибку чтения файла или разбора профиля.
+    pub fn load(spec: &str) -> Result<Self, ParserError> {
+        if let Some(profile) = CsvProfile::builtin(spec) {
+            return Ok(profile);
+        }
+        let text = fs::read_to_string(spec).map_err(|e| ParserError::FileReadError(format!("{}: {}", spec, e)))?;
+        if text.trim_start().starts_with('{') {
+            CsvProfile::from_json(&text)
+        } else {
+            CsvProfile::from_toml(&text)
+        }
+    }
+
+    /// Проверяет, что все колонки профиля существуют и сумма записи задана.
+    fn checked(self) -> Result<Self, ParserError> {
+        let header = &self.header;
+        let header_cells = [&header.issuer, &header.created, &header.account, &header.owner,
+            &header.from_date, &header.to_date, &header.currency];
+        if let Some(cell) = header_cells.iter().flat_map(|cell| cell.iter()).find(|cell| cell.column.trim().is_empty()) {
+            return Err(ParserError::BadInputFormatFile(
+                format!("CSV profile {}: header cell {:?} has no column", self.name, cell)));
+        }
+        let columns = &self.entries.columns;
+        if columns.date.trim().is_empty() {
+            return Err(ParserError::BadInputFormatFile(
+                format!("CSV profile {}: entry date column is not set", self.name)));
+        }
+        if columns.amount.is_none() && (columns.debit.is_none() || columns.credit.is_none()) {
+            return Err(ParserError::BadInputFormatFile(
+                format!("CSV profile {}: entry amount columns are not set", self.name)));
+        }
+        Ok(self)
+    }
+}
+
+impl CellProfile {
+    /// Значение ячейки в строках документа `rows` или `None`, если строки нет.
+    pub fn value<'a>(&self, rows: &'a [RowCsv]) -> Option<&'a str> {
+        let index = match (&self.row, &self.label) {
+            (Some(index), _) => *index,
+            (None, Some(label)) => rows.iter().position(|row| row.values().any(|cell| cell.trim().starts_with(label.as_str())))?,
+            (None, None) => return None,
+        };
+        rows.get(index)?.cell(resolve_column(rows, &self.column, index)?)
+    }
+}
+
+impl EntriesProfile {
+    /// Номер первой строки записей в строках документа `rows` или `None`, если заголовок
+    /// таблицы не найден.
+    pub fn start(&self, rows: &[RowCsv]) -> Option<usize> {
+        match &self.start_after {
+            Some(label) => rows.iter()
+                .position(|row| row.values().any(|cell| cell.trim() == label.as_str()))
+                .map(|index| index + 1 + self.skip_rows),
+            None => Some(self.start_row),
+        }
+    }
+}
+
+/// Номер колонки по ссылке `column`: номер с нуля, название колонки, которое ищется в заголовке таблицы -
+/// в ближайшей строке до строки `before`, содержащей ячейку с этим названием, - а если такой ячейки нет,
+/// буквы (`m`, `aa`). Колонка с названием `id` в заголовке не спутается с колонкой по буквам.
+pub fn resolve_column(rows: &[RowCsv], column: &str, before: usize) -> Option<usize> {
+    let column = column.trim();
+    if !column.is_empty() && column.bytes().all(|b| b.is_ascii_digit()) {
+        return RowCsv::column_index(column);
+    }
+    rows[..before.min(rows.len())].iter().rev()
+        .find_map(|row| row.values().position(|cell| cell.trim() == column))
+        .or_else(|| RowCsv::column_index(column))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_profile_file() {
+        let profile = CsvProfile::from_toml(include_str!("../../profiles/sberbank.toml")).unwrap();
+        assert_eq!(CsvProfile::sberbank(), profile);
+        let json = serde_json::to_string(&profile).unwrap();
+        assert_eq!(profile, CsvProfile::from_json(&json).unwrap());
+        assert_eq!(Some(profile), CsvProfile::builtin("SberBank"));
+    }
+
+    #[test]
+    fn test_checked() {
+        let error = CsvProfile::from_toml("[entries.columns]\ndate = \" \"\namount = \"c\"").unwrap_err();
+        assert!(error.to_string().contains("date column"));
+        let error = CsvProfile::from_toml("[header]\naccount = { row = 1 }\n[entries.columns]\ndate = \"a\"\namount = \"c\"").unwrap_err();
+        assert!(error.to_string().contains("no column"));
+        let error = CsvProfile::from_json(r#"{"entries": {"columns": {"date": "a", "debit": "b"}}}"#).unwrap_err();
+        assert!(error.to_string().contains("amount columns"));
+        assert!(CsvProfile::from_toml("name = 1").is_err());
+        assert!(CsvProfile::load("no such profile.toml").is_err());
+    }
+
+    #[test]
+    fn test_cell_value_and_start() {
+        let mut rows = vec![RowCsv::new(); 4];
+        rows[1].a = "Валюта: ".to_string();
+        rows[1].c = "RUB".to_string();
+        rows[2].b = "Дата".to_string();
+        let by_label = CellProfile { row: None, label: Some("Валюта:".to_string()), column: "c".to_string() };
+        assert_eq!(Some("RUB"), by_label.value(&rows));
+        let by_row = CellProfile { row: Some(9), label: None, column: "c".to_string() };
+        assert_eq!(None, by_row.value(&rows));
+        let mut entries = EntriesProfile { start_after: Some("Дата".to_string()), skip_rows: 1, ..Default::default() };
+        assert_eq!(Some(4), entries.start(&rows));
+        entries.start_after = Some("Нет".to_string());
+        assert_eq!(None, entries.start(&rows));
+    }
+
+    #[test]
+    fn test_resolve_column() {
+        let mut rows = vec![RowCsv::new(); 3];
+        rows[0].set("w", "Сумма");
+        rows[1].b = "Сумма".to_string();
+        assert_eq!(Some(12), resolve_column(&rows, "m", 0));
+        assert_eq!(Some(26), resolve_column(&rows, "aa", 0));
+        assert_eq!(Some(21), resolve_column(&rows, "21", 0));
+        assert_eq!(Some(1), resolve_column(&rows, " Сумма ", 3));
+        assert_eq!(Some(22), resolve_column(&rows, "Сумма", 1));
+        assert_eq!(None, resolve_column(&rows, "Сумма", 0));
+        assert_eq!(None, resolve_column(&rows, "Дата", 3));
+
+        rows[2].set("e", "m");
+        rows[2].set("f", "12");
+        assert_eq!(Some(4), resolve_column(&rows, "m", 3));
+        assert_eq!(Some(12), resolve_column(&rows, " 12 ", 3));
+        assert_eq!(Some(12), resolve_column(&rows, "m", 2));
+        assert_eq!(Some(0), resolve_column(&rows, "a", 3));
+    }
+}
@@ -0,0 +1,281 @@
+//! Модуль содержит типы даты и времени модели выписки
+//!
+//! Предоставляет функциональность по разбору дат ISO 8601 и SWIFT (YYMMDD, MMDD, YYMMDDHHMM±HHMM)
+//! и хранению даты и времени вместе со смещением часового пояса
+//!
+//! ```
+//! use bank_converter::models::date::{parse_yymmdd, IsoDateTime};
+//!
+//! let created: IsoDateTime = "2024-01-02T10:15:00+03:00".parse().unwrap();
+//! assert_eq!("2024-01-02T10:15:00+03:00", created.to_string());
+//! assert_eq!("1998-01-22", parse_yymmdd("980122").unwrap().to_string());
+//! ```
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+use chrono::{DateTime, Datelike, FixedOffset, NaiveDate, NaiveDateTime, NaiveTime};
+use serde::de::{Error, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use crate::errors::ParserError;
+
+/// Граница столетий для двузначного года SWIFT: годы `00..CENTURY_PIVOT` относятся к 20xx,
+/// годы `CENTURY_PIVOT..=99` к 19xx
+pub const CENTURY_PIVOT: i32 = 80;
+
+/// Дата и время ISO 8601 со смещением часового пояса
+///
+/// Смещение не задано (`None`), если оно отсутствовало в исходном документе.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct IsoDateTime {
+    /// Дата и время без часового пояса
+    pub datetime: NaiveDateTime,
+    /// Смещение часового пояса
+    pub offset: Option<FixedOffset>,
+}
+
+impl IsoDateTime {
+    /// Создает дату и время со смещением `offset`.
+    pub fn new(datetime: NaiveDateTime, offset: Option<FixedOffset>) -> Self {
+        Self { datetime, offset }
+    }
+
+    /// Создает дату и время начала дня `date`.
+    pub fn start_of_day(date: NaiveDate) -> Self {
+        Self::new(date.and_time(NaiveTime::MIN), None)
+    }
+
+    /// Создает дату и время конца дня `date` (23:59:59).
+    pub fn end_of_day(date: NaiveDate) -> Self {
+        Self::new(date.and_hms_opt(23, 59, 59).unwrap_or(date.and_time(NaiveTime::MIN)), None)
+    }
+
+    /// Дата без времени.
+    pub fn date(&self) -> NaiveDate {
+        self.datetime.date()
+    }
+
+    /// Дата и время с часовым поясом, если смещение задано.
+    pub fn to_fixed_offset(&self) -> Option<DateTime<FixedOffset>> {
+        self.offset.and_then(|offset| self.datetime.and_local_timezone(offset).single())
+    }
+}
+
+impl From<DateTime<FixedOffset>> for IsoDateTime {
+    fn from(value: DateTime<FixedOffset>) -> Self {
+        Self::new(value.naive_local(), Some(*value.offset()))
+    }
+}
+
+impl FromStr for IsoDateTime {
+    type Err = ParserError;
+
+    /// Разбирает дату и время вида `2024-01-02T10:15:00`, `2024-01-02T10:15:00.123Z`,
+    /// `2024-01-02T10:15:00+03:00` или дату `2024-01-02`.
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let value = value.trim();
+        if let Ok(datetime) = DateTime::parse_from_rfc3339(value) {
+            return Ok(datetime.into());
+        }
+        if let Ok(datetime) = NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S%.f") {
+            return Ok(Self::new(datetime, None));
+        }
+        parse_date(value).map(Self::start_of_day)
+    }
+}
+
+impl Display for IsoDateTime {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.datetime.format("%Y-%m-%dT%H:%M:%S%.f"))?;
+        if let Some(offset) = self.offset {
+            write!(f, "{}", offset)?;
+        }
+        Ok(())
+    }
+}
+
+impl Serialize for IsoDateTime {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+struct IsoDateTimeVisitor;
+
+impl Visitor<'_> for IsoDateTimeVisitor {
+    type Value = IsoDateTime;
+
+    fn expecting(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(f, "ISO 8601 date time")
+    }
+
+    fn visit_str<E: Error>(self, value: &str) -> Result<Self::Value, E> {
+        value.parse().map_err(E::custom)
+    }
+}
+
+impl<'de> Deserialize<'de> for IsoDateTime {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_str(IsoDateTimeVisitor)
+    }
+}
+
+/// Читает необязательную дату или дату и время; пустой элемент XML считается отсутствующим.
+pub(crate) fn deserialize_optional<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: FromStr,
+{
+    let value: Option<String> = Option::deserialize(deserializer)?;
+    match value.as_deref().map(str::trim) {
+        None | Some("") => Ok(None),
+        Some(value) => value.parse().map(Some)
+            .map_err(|_| D::Error::custom(ParserError::BadDate(value.to_string()))),
+    }
+}
+
+/// Разбирает дату ISO 8601 вида `YYYY-MM-DD`.
+///
+/// # Ошибки
+///
+/// Возвращает `ParserError::BadDate`, если строка не является датой.
+pub fn parse_date(value: &str) -> Result<NaiveDate, ParserError> {
+    NaiveDate::parse_from_str(value.trim(), "%Y-%m-%d")
+        .map_err(|_| ParserError::BadDate(value.to_string()))
+}
+
+/// Разбирает дату SWIFT вида `YYMMDD` с учетом границы столетий `CENTURY_PIVOT`.
+///
+/// # Ошибки
+///
+/// Возвращает `ParserError::BadDate`, если строка не является датой.
+pub fn parse_yymmdd(value: &str) -> Result<NaiveDate, ParserError> {
+    let bad_date = || ParserError::BadDate(value.to_string());
+    if value.len() != 6 || !value.chars().all(|c| c.is_ascii_digit()) {
+        return Err(bad_date());
+    }
+    let yy: i32 = value[0..2].parse().map_err(|_| bad_date())?;
+    let year = if yy < CENTURY_PIVOT { 2000 + yy } else { 1900 + yy };
+    NaiveDate::from_ymd_opt(year, value[2..4].parse().map_err(|_| bad_date())?,
+                            value[4..6].parse().map_err(|_| bad_date())?)
+        .ok_or_else(bad_date)
+}
+
+/// Разбирает дату SWIFT вида `MMDD`, выбирая год, ближайший к дате `reference`.
+///
+/// Используется для даты проводки поля :61:, год которой берется из даты валютирования,
+/// в том числе при переходе через границу года.
+///
+/// # Ошибки
+///
+/// Возвращает `ParserError::BadDate`, если строка не является датой.
+pub fn parse_mmdd(value: &str, reference: NaiveDate) -> Result<NaiveDate, ParserError> {
+    let bad_date = || ParserError::BadDate(value.to_string());
+    if value.len() != 4 || !value.chars().all(|c| c.is_ascii_digit()) {
+        return Err(bad_date());
+    }
+    let month: u32 = value[0..2].parse().map_err(|_| bad_date())?;
+    let day: u32 = value[2..4].parse().map_err(|_| bad_date())?;
+    [reference.year(), reference.year() - 1, reference.year() + 1].into_iter()
+        .filter_map(|year| NaiveDate::from_ymd_opt(year, month, day))
+        .min_by_key(|date| (*date - reference).num_days().abs())
+        .ok_or_else(bad_date)
+}
+
+/// Разбирает дату и время SWIFT вида `YYMMDDHHMM±HHMM` (поле :13D: сообщения MT942).
+///
+/// # Ошибки
+///
+/// Возвращает `ParserError::BadDate`, если строка не является датой и временем со смещением.
+pub fn parse_yymmddhhmm(value: &str) -> Result<IsoDateTime, ParserError> {
+    let bad_date = || ParserError::BadDate(value.to_string());
+    let value = value.trim();
+    let digits = |range: std::ops::Range<usize>| -> Result<u32, ParserError> {
+        let part = value.get(range).ok_or_else(bad_date)?;
+        if !part.chars().all(|c| c.is_ascii_digit()) {
+            return Err(bad_date());
+        }
+        part.parse().map_err(|_| bad_date())
+    };
+    if value.len() != 15 || !value.is_ascii() {
+        return Err(bad_date());
+    }
+    let date = parse_yymmdd(&value[0..6]).map_err(|_| bad_date())?;
+    let time = NaiveTime::from_hms_opt(digits(6..8)?, digits(8..10)?, 0).ok_or_else(bad_date)?;
+    let seconds = (digits(11..13)? * 3600 + digits(13..15)? * 60) as i32;
+    let offset = match &value[10..11] {
+        "+" => FixedOffset::east_opt(seconds),
+        "-" => FixedOffset::west_opt(seconds),
+        _ => None,
+    }.ok_or_else(bad_date)?;
+    Ok(IsoDateTime::new(date.and_time(time), Some(offset)))
+}
+
+/// Выводит дату и время в формате SWIFT `YYMMDDHHMM±HHMM`; без смещения записывается `+0000`.
+pub fn format_yymmddhhmm(value: IsoDateTime) -> String {
+    let seconds = value.offset.map_or(0, |offset| offset.local_minus_utc());
+    let sign = if seconds < 0 { '-' } else { '+' };
+    let minutes = seconds.abs() / 60;
+    format!("{}{}{:02}{:02}", value.datetime.format("%y%m%d%H%M"), sign, minutes / 60, minutes % 60)
+}
+
+/// Выводит дату в формате SWIFT `YYMMDD`.
+pub fn format_yymmdd(date: NaiveDate) -> String {
+    date.format("%y%m%d").to_string()
+}
+
+/// Выводит дату в формате SWIFT `MMDD`.
+pub fn format_mmdd(date: NaiveDate) -> String {
+    date.format("%m%d").to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_iso_date_time() {
+        let value: IsoDateTime = "2023-04-20T23:24:31".parse().unwrap();
+        assert_eq!(None, value.offset);
+        assert_eq!("2023-04-20T23:24:31", value.to_string());
+        let value: IsoDateTime = "2023-04-20T23:24:31.5Z".parse().unwrap();
+        assert_eq!(Some(FixedOffset::east_opt(0).unwrap()), value.offset);
+        assert_eq!("2023-04-20T23:24:31.500+00:00", value.to_string());
+        let value: IsoDateTime = "2023-04-20".parse().unwrap();
+        assert_eq!("2023-04-20T00:00:00", value.to_string());
+        assert!("20.04.2023".parse::<IsoDateTime>().is_err());
+    }
+
+    #[test]
+    fn test_parse_yymmdd() {
+        assert_eq!(NaiveDate::from_ymd_opt(2025, 2, 18), parse_yymmdd("250218").ok());
+        assert_eq!(NaiveDate::from_ymd_opt(1998, 1, 22), parse_yymmdd("980122").ok());
+        assert_eq!(NaiveDate::from_ymd_opt(2079, 12, 31), parse_yymmdd("791231").ok());
+        assert!(parse_yymmdd("250230").is_err());
+        assert!(parse_yymmdd("2502").is_err());
+        assert_eq!("250218", format_yymmdd(NaiveDate::from_ymd_opt(2025, 2, 18).unwrap()));
+    }
+
+    #[test]
+    fn test_parse_mmdd() {
+        let reference = NaiveDate::from_ymd_opt(2025, 1, 2).unwrap();
+        assert_eq!(NaiveDate::from_ymd_opt(2024, 12, 31), parse_mmdd("1231", reference).ok());
+        assert_eq!(NaiveDate::from_ymd_opt(2025, 1, 3), parse_mmdd("0103", reference).ok());
+        let reference = NaiveDate::from_ymd_opt(2024, 12, 31).unwrap();
+        assert_eq!(NaiveDate::from_ymd_opt(2025, 1, 2), parse_mmdd("0102", reference).ok());
+        assert!(parse_mmdd("1332", reference).is_err());
+    }
+
+    #[test]
+    fn test_parse_yymmddhhmm() {
+        let value = parse_yymmddhhmm("2402201530+0300").unwrap();
+        assert_eq!("2024-02-20T15:30:00+03:00", value.to_string());
+        assert_eq!("2402201530+0300", format_yymmddhhmm(value));
+        let value = parse_yymmddhhmm("2402200905-0130").unwrap();
+        assert_eq!("2402200905-0130", format_yymmddhhmm(value));
+        assert_eq!("2402200000+0000", format_yymmddhhmm(IsoDateTime::start_of_day(value.date())));
+        assert!(parse_yymmddhhmm("2402201530").is_err());
+        assert!(parse_yymmddhhmm("2402202530+0300").is_err());
+        assert!(parse_yymmddhhmm("2402201530*0300").is_err());
+        assert!(parse_yymmddhhmm("24022é530+0100").is_err());
+        assert!(parse_yymmddhhmm("240220153é+010").is_err());
+    }
+}
@@ -0,0 +1,397 @@
+//! Модуль содержит денежный тип с фиксированной точкой
+//!
+//! Предоставляет функциональность по разбору сумм из текстового вида всех форматов,
+//! арифметике без ошибок округления и выводу суммы для каждого формата
+//!
+//! ```
+//! use bank_converter::models::money::Money;
+//!
+//! let amount: Money = "1 540,5".parse().unwrap();
+//! assert_eq!("1540.50", amount.with_minor_units("RUB").to_string());
+//! assert_eq!("1540,5", amount.to_mt940());
+//! ```
+use std::cmp::Ordering;
+use std::fmt::{Display, Formatter};
+use std::hash::{Hash, Hasher};
+use std::iter::Sum;
+use std::ops::{Add, AddAssign, Neg, Sub, SubAssign};
+use std::str::FromStr;
+use serde::de::{Error, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use crate::errors::ParserError;
+
+/// Максимальное количество значащих цифр суммы
+const MAX_DIGITS: usize = 18;
+
+/// Денежная сумма с фиксированной точкой
+///
+/// Хранит значение в минимальных единицах (`minor`) и количество знаков после запятой (`scale`).
+/// Суммы с разным количеством знаков равны, если равны их значения: `1540` и `1540.00`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Money {
+    minor: i64,
+    scale: u32,
+}
+
+/// Возвращает количество знаков дробной части валюты по ISO 4217.
+///
+/// Для неизвестных валют возвращает 2.
+pub fn minor_units(ccy: &str) -> u32 {
+    match ccy {
+        "BIF" | "CLP" | "DJF" | "GNF" | "ISK" | "JPY" | "KMF" | "KRW" | "PYG" | "RWF" | "UGX"
+        | "UYI" | "VND" | "VUV" | "XAF" | "XOF" | "XPF" => 0,
+        "BHD" | "IQD" | "JOD" | "KWD" | "LYD" | "OMR" | "TND" => 3,
+        "CLF" | "UYW" => 4,
+        _ => 2,
+    }
+}
+
+impl Money {
+    /// Создает сумму из значения в минимальных единицах `minor` и количества знаков `scale`.
+    pub fn new(minor: i64, scale: u32) -> Self {
+        Self { minor, scale }
+    }
+
+    /// Создает сумму из значения в минимальных единицах валюты `ccy`.
+    pub fn from_minor(minor: i64, ccy: &str) -> Self {
+        Self::new(minor, minor_units(ccy))
+    }
+
+    /// Значение в минимальных единицах.
+    pub fn minor(&self) -> i64 {
+        self.minor
+    }
+
+    /// Количество знаков после запятой.
+    pub fn scale(&self) -> u32 {
+        self.scale
+    }
+
+    /// Возвращает `true`, если сумма равна нулю.
+    pub fn is_zero(&self) -> bool {
+        self.minor == 0
+    }
+
+    /// Возвращает `true`, если сумма отрицательная.
+    pub fn is_negative(&self) -> bool {
+        self.minor < 0
+    }
+
+    /// Возвращает абсолютное значение суммы.
+    pub fn abs(self) -> Self {
+        Self::new(self.minor.abs(), self.scale)
+    }
+
+    /// Приводит сумму к `scale` знакам после запятой с округлением половины от нуля.
+    ///
+    /// # Паника
+    ///
+    /// Паникует, если сумма с `scale` знаками не помещается в `i64` (см. [`Money::checked_rescale`]).
+    pub fn rescale(self, scale: u32) -> Self {
+        self.checked_rescale(scale).expect("money overflow")
+    }
+
+    /// Приводит сумму к `scale` знакам после запятой с округлением половины от нуля.
+    ///
+    /// # Ошибки
+    ///
+    /// Возвращает `ParserError::BadAmount`, если сумма с `scale` знаками не помещается в `i64`.
+    pub fn checked_rescale(self, scale: u32) -> Result<Self, ParserError> {
+        match scale.cmp(&self.scale) {
+            Ordering::Equal => Ok(self),
+            Ordering::Greater => 10i64.checked_pow(scale - self.scale)
+                .and_then(|factor| self.minor.checked_mul(factor))
+                .map(|minor| Self::new(minor, scale))
+                .ok_or_else(|| ParserError::BadAmount(format!("{} overflows with {} decimal places", self, scale))),
+            Ordering::Less => {
+                let divisor = 10i64.pow(self.scale - scale);
+                let rest = self.minor % divisor;
+                let mut minor = self.minor / divisor;
+                if rest.abs() * 2 >= divisor {
+                    minor += self.minor.signum();
+                }
+                Ok(Self::new(minor, scale))
+            }
+        }
+    }
+
+    /// Складывает суммы.
+    ///
+    /// # Ошибки
+    ///
+    /// Возвращает `ParserError::BadAmount`, если результат не помещается в `i64`.
+    pub fn checked_add(self, other: Self) -> Result<Self, ParserError> {
+        let (left, right, scale) = self.aligned(&other);
+        Self::from_aligned(left + right, scale)
+            .ok_or_else(|| ParserError::BadAmount(format!("{} + {} overflows", self, other)))
+    }
+
+    /// Вычитает сумму `other`.
+    ///
+    /// # Ошибки
+    ///
+    /// Возвращает `ParserError::BadAmount`, если результат не помещается в `i64`.
+    pub fn checked_sub(self, other: Self) -> Result<Self, ParserError> {
+        let (left, right, scale) = self.aligned(&other);
+        Self::from_aligned(left - right, scale)
+            .ok_or_else(|| ParserError::BadAmount(format!("{} - {} overflows", self, other)))
+    }
+
+    /// Дополняет дробную часть до количества знаков валюты `ccy` по ISO 4217.
+    ///
+    /// Лишние знаки дробной части не отбрасываются, чтобы не потерять точность суммы;
+    /// сумма, которая с дополненной дробной частью не помещается в `i64`, остается без изменений.
+    pub fn with_minor_units(self, ccy: &str) -> Self {
+        let scale = minor_units(ccy);
+        if scale > self.scale {
+            self.checked_rescale(scale).unwrap_or(self)
+        } else {
+            self
+        }
+    }
+
+    /// Выводит сумму с разделителем дробной части `separator`.
+    pub fn format(&self, separator: char) -> String {
+        let sign = if self.minor < 0 { "-" } else { "" };
+        let digits = format!("{:0width$}", self.minor.unsigned_abs(), width = self.scale as usize + 1);
+        let (int_part, frac_part) = digits.split_at(digits.len() - self.scale as usize);
+        if frac_part.is_empty() {
+            format!("{}{}", sign, int_part)
+        } else {
+            format!("{}{}{}{}", sign, int_part, separator, frac_part)
+        }
+    }
+
+    /// Значение суммы числом с плавающей точкой, например для числовых ячеек электронных таблиц.
+    pub fn to_f64(&self) -> f64 {
+        self.minor as f64 / 10f64.powi(self.scale as i32)
+    }
+
+    /// Выводит сумму в формате SWIFT MT940: десятичная запятая обязательна, например `1540,00` или `1540,`.
+    pub fn to_mt940(&self) -> String {
+        let value = self.abs().format(',');
+        if value.contains(',') { value } else { value + "," }
+    }
+
+    fn aligned(&self, other: &Self) -> (i128, i128, u32) {
+        let scale = self.scale.max(other.scale);
+        (self.minor as i128 * 10i128.pow(scale - self.scale),
+         other.minor as i128 * 10i128.pow(scale - other.scale),
+         scale)
+    }
+
+    fn from_aligned(minor: i128, scale: u32) -> Option<Self> {
+        i64::try_from(minor).ok().map(|minor| Self::new(minor, scale))
+    }
+}
+
+impl FromStr for Money {
+    type Err = ParserError;
+
+    /// Разбирает сумму вида `1540,00`, `1 540.00`, `1,540.00` или `-17.5`.
+    ///
+    /// Разделителем дробной части считается последняя точка или запятая,
+    /// остальные точки, запятые, пробелы и апострофы считаются разделителями разрядов.
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let bad_amount = || ParserError::BadAmount(value.to_string());
+        let trimmed = value.trim();
+        let (negative, unsigned) = match trimmed.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, trimmed.strip_prefix('+').unwrap_or(trimmed)),
+        };
+        let cleaned: String = unsigned.chars()
+            .filter(|c| !matches!(c, ' ' | '\u{a0}' | '\u{202f}' | '\''))
+            .collect();
+        let (int_part, frac_part) = match cleaned.rfind([',', '.']) {
+            Some(pos) => (&cleaned[..pos], &cleaned[pos + 1..]),
+            None => (cleaned.as_str(), ""),
+        };
+        let int_digits: String = int_part.chars().filter(|c| *c != ',' && *c != '.').collect();
+        if int_digits.is_empty() && frac_part.is_empty()
+            || !int_digits.chars().all(|c| c.is_ascii_digit())
+            || !frac_part.chars().all(|c| c.is_ascii_digit())
+            || int_digits.len() + frac_part.len() > MAX_DIGITS
+        {
+            return Err(bad_amount());
+        }
+        let digits = int_digits + frac_part;
+        let minor: i64 = if digits.is_empty() { 0 } else { digits.parse().map_err(|_| bad_amount())? };
+        Ok(Self::new(if negative { -minor } else { minor }, frac_part.len() as u32))
+    }
+}
+
+impl Display for Money {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.format('.'))
+    }
+}
+
+impl PartialEq for Money {
+    fn eq(&self, other: &Self) -> bool {
+        let (left, right, _) = self.aligned(other);
+        left == right
+    }
+}
+
+impl Eq for Money {}
+
+impl PartialOrd for Money {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Money {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let (left, right, _) = self.aligned(other);
+        left.cmp(&right)
+    }
+}
+
+impl Hash for Money {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        let mut minor = self.minor;
+        let mut scale = self.scale;
+        while scale > 0 && minor % 10 == 0 {
+            minor /= 10;
+            scale -= 1;
+        }
+        minor.hash(state);
+        scale.hash(state);
+    }
+}
+
+/// Сложение сумм; паникует при переполнении, для сумм из входных данных используется [`Money::checked_add`].
+impl Add for Money {
+    type Output = Money;
+    fn add(self, other: Self) -> Self::Output {
+        self.checked_add(other).expect("money overflow")
+    }
+}
+
+/// Вычитание сумм; паникует при переполнении, для сумм из входных данных используется [`Money::checked_sub`].
+impl Sub for Money {
+    type Output = Money;
+    fn sub(self, other: Self) -> Self::Output {
+        self.checked_sub(other).expect("money overflow")
+    }
+}
+
+impl Neg for Money {
+    type Output = Money;
+    fn neg(self) -> Self::Output {
+        Self::new(-self.minor, self.scale)
+    }
+}
+
+impl AddAssign for Money {
+    fn add_assign(&mut self, other: Self) {
+        *self = *self + other;
+    }
+}
+
+impl SubAssign for Money {
+    fn sub_assign(&mut self, other: Self) {
+        *self = *self - other;
+    }
+}
+
+impl Sum for Money {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Money::default(), |acc, value| acc + value)
+    }
+}
+
+impl<'a> Sum<&'a Money> for Money {
+    fn sum<I: Iterator<Item = &'a Money>>(iter: I) -> Self {
+        iter.fold(Money::default(), |acc, value| acc + *value)
+    }
+}
+
+impl Serialize for Money {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+struct MoneyVisitor;
+
+impl Visitor<'_> for MoneyVisitor {
+    type Value = Money;
+
+    fn expecting(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(f, "decimal amount")
+    }
+
+    fn visit_str<E: Error>(self, value: &str) -> Result<Self::Value, E> {
+        value.parse().map_err(E::custom)
+    }
+
+    fn visit_i64<E: Error>(self, value: i64) -> Result<Self::Value, E> {
+        Ok(Money::new(value, 0))
+    }
+
+    fn visit_u64<E: Error>(self, value: u64) -> Result<Self::Value, E> {
+        i64::try_from(value).map(|value| Money::new(value, 0)).map_err(E::custom)
+    }
+}
+
+impl<'de> Deserialize<'de> for Money {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_str(MoneyVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(Money::new(154000, 2), "1540,00".parse().unwrap());
+        assert_eq!(Money::new(154000, 2), "1 540.00".parse().unwrap());
+        assert_eq!(Money::new(154000, 2), "1,540.00".parse().unwrap());
+        assert_eq!(Money::new(154000, 2), "1\u{a0}540,00".parse().unwrap());
+        assert_eq!(Money::new(-175, 1), "-17.5".parse().unwrap());
+        assert_eq!(Money::new(1000, 0), "1000,".parse().unwrap());
+        for bad in ["", " ", "12a", "1.2.x", "--1", "1234567890123456789"] {
+            assert!(bad.parse::<Money>().is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn test_format() {
+        let amount = Money::new(5, 2);
+        assert_eq!("0.05", amount.to_string());
+        assert_eq!("0,05", amount.to_mt940());
+        assert_eq!("-0.05", (-amount).to_string());
+        assert_eq!("1000,", Money::new(1000, 0).to_mt940());
+        assert_eq!("1000.00", Money::new(1000, 0).with_minor_units("EUR").to_string());
+        assert_eq!("1000", Money::new(1000, 0).with_minor_units("JPY").to_string());
+        assert_eq!("1.005", Money::new(1005, 3).with_minor_units("EUR").to_string());
+        assert_eq!(-1540.5, Money::new(-154050, 2).to_f64());
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let amounts: Vec<Money> = ["0.1", "0.2", "1540"].iter().map(|v| v.parse().unwrap()).collect();
+        let total: Money = amounts.iter().sum();
+        assert_eq!("1540.3", total.to_string());
+        assert_eq!(Money::new(-15402, 1), amounts[0] - total);
+        assert_eq!(Money::new(13, 1), Money::new(125, 2).rescale(1));
+        assert_eq!(Money::new(-13, 1), Money::new(-125, 2).rescale(1));
+        assert!(Money::new(1, 2) < Money::new(1, 1));
+    }
+
+    #[test]
+    fn test_checked_arithmetic() {
+        let max: Money = "9999999999999999,99".parse().unwrap();
+        assert_eq!(Money::new(1999999999999999998, 2), max.checked_add(max).unwrap());
+        let total = (0..9).try_fold(max, |acc, _| acc.checked_add(max));
+        assert!(matches!(total, Err(ParserError::BadAmount(_))));
+        assert!(Money::new(i64::MIN, 0).checked_sub(Money::new(1, 0)).is_err());
+        assert!(Money::new(i64::MAX / 10, 0).checked_rescale(2).is_err());
+        assert_eq!(Money::new(100, 2), Money::new(1, 0).checked_rescale(2).unwrap());
+        assert_eq!(Money::new(i64::MAX / 10, 0), Money::new(i64::MAX / 10, 0).with_minor_units("EUR"));
+    }
+}
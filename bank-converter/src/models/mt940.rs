@@ -3,351 +3,506 @@
 //! Предоставляет функциональность по описании структуры и трансформаци данных между форматами
 //!
 
+use std::sync::LazyLock;
 use regex::Regex;
-use crate::models::camt053::{BalanceAttribute, BkToCstmrStmt, DtAttribute, NtryAttribute,
-                             NtryDtlsAttribute, TxDtlsAttribute};
+use crate::errors::ParserError;
+use crate::models::codes::{BalanceType, CreditDebit};
+use crate::models::date::{format_mmdd, format_yymmdd, parse_mmdd, parse_yymmdd, parse_yymmddhhmm};
+use crate::models::mt942::DocumentMt942;
+use crate::models::narrative::NarrativeDialect;
+use crate::models::swift;
+use crate::models::camt053::{AmtAttribute, BalanceAttribute, BkToCstmrStmt, DtAttribute, NtryAttribute,
+                             TxDtlsAttribute};
+
+/// Конец строки сообщения SWIFT
+const EOL: &str = "\r\n";
+
+/// Строка начала поля блока 4: `:тег:значение`
+static FIELD_TAG: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^:(\d{2}[A-Z]?):(.*)$").unwrap());
+
+/// Поле :61: по грамматике SWIFT (см. `DocumentMt940::parse_field_61`)
+static FIELD_61: LazyLock<Regex> = LazyLock::new(|| Regex::new(
+    r"^(\d{6})(\d{4})?(R?[CD])([A-Z])?(\d+,\d*)([NSF][A-Z0-9]{3})([^\n]*?)(?://([^\n]*?))?[ \t\r]*(?:\n((?s:.*)))?$").unwrap());
 
 /// Структура для документа MT940
 /// Содержит методы для чтения, транофрмации и записи данных MT940
-/// ```no_run
+/// ```ignore
 ///
 /// pub fn from_read<R: Read>(r: &mut R) -> Result<Self, ParserError>
 /// pub fn write_to<W: Write>(&mut self, writer: &mut W) -> Result<(), ConvertError>
-///
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
 pub struct DocumentMt940 {
-    pub(crate) document: Vec<BkToCstmrStmt>
+    /// Выписки документа, по одной на каждое сообщение MT940
+    pub document: Vec<BkToCstmrStmt>
 }
 
 impl DocumentMt940 {
+    /// Создает пустой документ MT940.
+    pub fn new() -> Self {
+        DocumentMt940::default()
+    }
 
+    /// Находит сообщения документа и возвращает их границы (начало, конец).
+    ///
+    /// Сообщение с заголовками SWIFT начинается блоком `{1:` и заканчивается перед трейлером `{5:`
+    /// или перед следующим сообщением, если трейлера нет. В файле без заголовков (выгрузки банков
+    /// вида `:20:` ... `-`) сообщение начинается строкой поля :20:.
     pub(crate) fn find_record(document: &str) -> Option<Vec<(usize, usize)>> {
-        let mut vec_start_pattern: Vec<usize> = Vec::new();
-        let mut vec_end_pattern: Vec<usize> = Vec::new();
-        let mut records: Vec<(usize, usize)> = Vec::new();
-        for (index, _found_pattern) in document.match_indices("{1"){
-            vec_start_pattern.push(index);
-        }
-        for (index, _found_pattern) in document.match_indices("{5:"){
-            vec_end_pattern.push(index);
+        let mut starts: Vec<usize> = document.match_indices("{1:").map(|(index, _)| index).collect();
+        if starts.is_empty() {
+            starts = DocumentMt940::bare_record_starts(document);
         }
-        for i in vec_start_pattern.iter().enumerate(){
-            records.push((vec_start_pattern[i.0], vec_end_pattern[i.0]));
+        if starts.is_empty() {
+            return None;
         }
+        let records = starts.iter().enumerate().map(|(i, &start)| {
+            let next = starts.get(i + 1).copied().unwrap_or(document.len());
+            let end = document[start..next].find("{5:").map_or(next, |end| start + end);
+            (start, end)
+        }).collect();
         Some(records)
     }
 
+    /// Начала сообщений без заголовков SWIFT: строки поля :20:.
+    fn bare_record_starts(document: &str) -> Vec<usize> {
+        let mut starts = Vec::new();
+        let mut offset = 0;
+        for line in document.split_inclusive('\n') {
+            let trimmed = line.trim_start();
+            if trimmed.starts_with(":20:") {
+                starts.push(offset + line.len() - trimmed.len());
+            }
+            offset += line.len();
+        }
+        starts
+    }
+
     fn parse_field_one(header: &str) -> String{
         let regex = Regex::new(r"F\d{2}([A-Z]*\d*[A-Z]*)\d");
-        if let Ok(regex) = regex {
-            if let Some(capture) = regex.captures(header) {
-             return capture[1].to_string();
-            }
+        if let Ok(regex) = regex
+            && let Some(capture) = regex.captures(header) {
+            return capture[1].to_string();
         }
         "UNKNOW_BIC".to_string()
     }
 
     fn parse_field_two(header: &str, document: &mut BkToCstmrStmt) {
         let regex = Regex::new(r"([IO])(\d{3})([\w\d]+)");
-        if let Ok(regex) = regex {
-            if let Some(capture) = regex.captures(header) {
-                document.grp_hdr.msg_id = capture[3].to_string();
-                document.stmt.id = capture[3].to_string() + "-940";
-            }
+        if let Ok(regex) = regex
+            && let Some(capture) = regex.captures(header) {
+            document.grp_hdr.msg_id = capture[3].to_string();
+            document.stmt.id = format!("{}-{}", &capture[3], &capture[2]);
         }
     }
 
-    fn parse_field_balance(header: &str) -> Option<BalanceAttribute>{
-        let regex = Regex::new(r"([CD])(\d{6})([A-Z]+)(\d+,\d+)");
-        if let Ok(regex) = regex {
-            if let Some(capture) = regex.captures(header) {
-                let mut balance = BalanceAttribute::default();
-                balance.dt= DtAttribute::format_dt(&capture[2]);
-                balance.amt.ccy = capture[3].to_string();
-                balance.amt.amt = capture[4].replace(",", ".").to_string();
-                balance.cd = capture[1].to_string();
-                return Some(balance);
-            }
-        }
-        None
-    }
-    fn parse_field_61(field: (&str, &str), vault: &str, ntry: &mut NtryAttribute){
-        let regex = Regex::new(r"(\d{6})(\d{4})([CD])(\d+,\d+)([A-Z]{4})(\w+)");
-        if let Ok(regex) = regex {
-            if let Some(capture) = regex.captures(field.0) {
-                ntry.val_dt = DtAttribute::format_dt(&capture[1]);
-                let dt =  capture[1][0 .. 2].to_string() + &capture[2][0..4].to_string();
-                ntry.bookg_dt = DtAttribute::format_dt(&dt);
-                ntry.bk_tx_cd.prtry.cd = capture[5].to_string();
-                ntry.amt.amt = capture[4].replace(",", ".").to_string();
-                ntry.amt.ccy = vault.to_string();
-                ntry.cdt_dbt_ind  = if capture[3].to_string() == "C".to_string(){
-                    "CRDT".to_string()
-                } else { "DBIT".to_string()};
-                let mut nxdet: NtryDtlsAttribute = NtryDtlsAttribute::default();
-                DocumentMt940::parse_field_86(field.1, capture[6].to_string(), &mut nxdet);
-                ntry.ntry_dtls = nxdet;
-            }
+    fn parse_field_balance(header: &str) -> Result<Option<BalanceAttribute>, ParserError>{
+        let regex = Regex::new(r"([CD])(\d{6})([A-Z]+)(\d+,\d*)");
+        if let Ok(regex) = regex
+            && let Some(capture) = regex.captures(header) {
+            let balance = BalanceAttribute {
+                dt: DtAttribute::new(parse_yymmdd(&capture[2])?),
+                cdt_dbt_ind: CreditDebit::from_mt940(&capture[1])?,
+                amt: AmtAttribute::new(capture[4].parse()?, &capture[3]),
+                ..Default::default()
+            };
+            return Ok(Some(balance));
         }
+        Ok(None)
+    }
+    /// Разбирает поле :61: по грамматике SWIFT: дата валютирования 6!n, дата проводки [4!n],
+    /// признак [R]C/[R]D, код средств [1!a], сумма 15d, тип операции 1!a3!c, ссылка клиента 16x,
+    /// ссылка банка [//16x] и дополнительные сведения [34x] на следующей строке.
+    fn parse_field_61(field: &str, vault: &str, ntry: &mut NtryAttribute) -> Result<(), ParserError>{
+        let field = field.trim();
+        let Some(capture) = FIELD_61.captures(field) else {
+            return Err(ParserError::BadInputFormatFile(
+                format!("Unparsable field :61:{}", field.lines().next().unwrap_or_default())));
+        };
+        let val_dt = parse_yymmdd(&capture[1])?;
+        ntry.val_dt = DtAttribute::new(val_dt);
+        // без даты проводки запись проведена в дату валютирования
+        ntry.bookg_dt = DtAttribute::new(match capture.get(2) {
+            Some(entry_dt) => parse_mmdd(entry_dt.as_str(), val_dt)?,
+            None => val_dt,
+        });
+        let mark = &capture[3];
+        let cdt_dbt_ind = CreditDebit::from_mt940(&mark[mark.len() - 1..])?;
+        // RC - сторно кредита, т.е. дебетовая запись; RD - сторно дебета, т.е. кредитовая
+        ntry.rvsl_ind = mark.starts_with('R');
+        ntry.cdt_dbt_ind = if ntry.rvsl_ind { cdt_dbt_ind.reverse() } else { cdt_dbt_ind };
+        ntry.funds_code = capture.get(4).map_or("", |m| m.as_str()).to_string();
+        ntry.amt = AmtAttribute::new(capture[5].parse()?, vault);
+        ntry.bk_tx_cd.prtry.cd = capture[6].to_string();
+        ntry.acct_svcr_ref = capture.get(8).map_or("", |m| m.as_str()).trim().to_string();
+        ntry.addtl_ntry_inf = capture.get(9).map_or("", |m| m.as_str()).trim().to_string();
+        let mut tlds = TxDtlsAttribute::default();
+        tlds.refs.end_to_end_id = capture[7].trim().to_string();
+        ntry.ntry_dtls.tx_dtls.push(tlds);
+        Ok(())
     }
 
-    fn parse_field_86(field: &str, refs: String, ntrydet: &mut NtryDtlsAttribute) {
-        let reg_pattern = Regex::new(r"/([A-Z]{4})/([\w]*)");
-        if let Ok(regexp) = reg_pattern {
-            let mut tlds: TxDtlsAttribute = TxDtlsAttribute::default();
-            for capture in regexp.captures_iter(field){
-                match &capture[1] {
-                    "EREF" =>{
-                        tlds.refs.end_to_end_id = capture[2].to_string();
-                    },
-                    "CRNM" =>{
-                        tlds.rltd_pties.cdtr.nm = capture[2].to_string();
-                    },
-                    "CACT" => {
-                        tlds.rltd_pties.cdtr_acct.other.id = capture[2].to_string();
-                    },
-                    "CBIC" => {
-                        tlds.rltd_agts.cdtr_agt.fin_instn_id.bic = capture[2].to_string();
-                    },
-                    "REMI" =>{
-                        tlds.rmt_inf.ustrd.push(capture[2].to_string());
-                    },
-                    "OPRP" =>{
-                        tlds.addtl_tx_inf = capture[2].to_string();
-                    },
-                    "DACT" =>{
-                        tlds.rltd_pties.dbtr_acct.other.id = capture[2].to_string();
-                    },
-                    "DBIC" =>{
-                        tlds.rltd_pties.dbtr_acct.other.id = capture[2].to_string();
-                    },
-                    "OAMT" =>{
-                        tlds.amt_dtls.amt = capture[2].to_string();
-                    },
-                    "DCID" =>{
-                        tlds.rltd_pties.dbtr.id.othr.id = capture[2].to_string();
-                    },
-                    "NREF" => {
-                        tlds.refs.prtry.refdt = capture[2].to_string();
-                        tlds.refs.prtry.tp = "NREF".to_string();
-                    },
-                    _ => {}
-                }
+    /// Разбивает блок 4 на поля: тег и значение вместе со строками продолжения.
+    fn split_fields(block: &str) -> Vec<(&str, String)> {
+        let mut fields: Vec<(&str, String)> = Vec::new();
+        let mut in_field = false;
+        for line in block.lines() {
+            let line = line.trim();
+            if let Some(capture) = FIELD_TAG.captures(line) {
+                let (Some(tag), Some(value)) = (capture.get(1), capture.get(2)) else {
+                    continue;
+                };
+                fields.push((tag.as_str(), value.as_str().to_string()));
+                in_field = true;
+            } else if line == "-" || line.starts_with("-}") {
+                // конец сообщения: строки до следующего поля не относятся к последнему полю
+                in_field = false;
+            } else if in_field
+                && let Some((_, value)) = fields.last_mut() {
+                value.push('\n');
+                value.push_str(line);
             }
-            tlds.refs.end_to_end_id = refs;
-            ntrydet.tx_dtls.push(tlds);
         }
+        fields
     }
 
-    fn parse_field_ntry(header: &str, vault: &str) -> Option<Vec<NtryAttribute>>{
-        let mut reg_pattern = Regex::new(r":61:([\n\w\d ,/-]+):");
-        let mut field_61: Vec<String> = Vec::new();
-        let mut field_86: Vec<String> = Vec::new();
+    /// Определяет диалект полей :86: документа `document` (см. [`NarrativeDialect::detect_document`]).
+    pub(crate) fn detect_dialect(document: &str) -> NarrativeDialect {
+        let fields = DocumentMt940::split_fields(document);
+        NarrativeDialect::detect_document(fields.iter().filter(|(tag, _)| *tag == "86").map(|(_, value)| value.as_str()))
+    }
+
+    fn parse_field_ntry(header: &str, vault: &str, dialect: NarrativeDialect) -> Result<Vec<NtryAttribute>, ParserError>{
         let mut nxtry : Vec<NtryAttribute> = Vec::new();
-        if let Ok(regexp) = reg_pattern {
-            for capture in regexp.captures_iter(header){
-                field_61.push(capture[1].to_string());
-            }
-        }
-        reg_pattern = Regex::new(r":86:([\n\w\d ,/-]+)");
-        if let Ok(regexp) = reg_pattern {
-            for capture in regexp.captures_iter(header){
-                field_86.push(capture[1].to_string());
+        let mut last_tag = "";
+        for (tag, value) in DocumentMt940::split_fields(header) {
+            match tag {
+                "61" => {
+                    let mut ntry = NtryAttribute::default();
+                    DocumentMt940::parse_field_61(&value, vault, &mut ntry)?;
+                    nxtry.push(ntry);
+                }
+                // :86: относится к записи, только если следует сразу за :61:
+                "86" if last_tag == "61" => {
+                    if let Some(ntry) = nxtry.last_mut()
+                        && let Some(tlds) = ntry.ntry_dtls.tx_dtls.last_mut() {
+                        dialect.parse(&value, ntry.cdt_dbt_ind, tlds);
+                    }
+                }
+                _ => {}
             }
+            last_tag = tag;
         }
-        let unions: Vec<(String, String)> = field_61.into_iter().zip(field_86.into_iter()).collect();
-        for union in unions.iter(){
-            let mut ntry = NtryAttribute::default();
-            DocumentMt940::parse_field_61((&union.0, &union.1), vault, &mut ntry);
-            nxtry.push(ntry);
-        }
-        Some(nxtry)
+        Ok(nxtry)
     }
 
-    fn parse_field_foo(header: &str, document: &mut BkToCstmrStmt) {
+    fn parse_field_foo(header: &str, document: &mut BkToCstmrStmt, dialect: NarrativeDialect) -> Result<(), ParserError> {
         let reg_codes = ["26", "25", "28C", "60F", "60M", "62F", "62M", "64", "65"];
         for reg_code in reg_codes.iter() {
             let reg_pattern = Regex::new(&format!(r":{}:([\n\w\d ,/-]+)",
                                                   reg_code));
-            if let Ok(regexp) = reg_pattern{
-                if let Some(capture) = regexp.captures(header){
-                    let capture = capture[1].replace("\n", "")
-                                                  .replace(" ", "");
-                    match reg_code.as_ref() {
-                        "26" => {
-                            document.grp_hdr.msg_id = capture;
-                            document.stmt.id = document.grp_hdr.msg_id.clone();
-                        },
-                        "25" => {
-                            document.stmt.acct.ownr.id.org_id.othr.id = capture;
-                        },
-                        "28C" => {
-                            let fields: Vec<&str> = capture.split('/').collect();
-                            if fields.len() > 1{
-                                document.stmt.elctrnc_seq_nb = fields[0].to_string();
-                                document.stmt.lgl_seq_nb = fields[1].to_string();
-                            }
-                        },
-                        "60F" | "60M" | "62F" | "62M" | "64"  | "65"=> {
-                            if  let Some(mut balance) = DocumentMt940::parse_field_balance(&capture){
-                                if *reg_code == "60F" {
-                                    balance.tp.cd_or_prtry.cd = "OPBD".to_string();
-                                }
-                                if *reg_code == "60M" {
-                                    balance.tp.cd_or_prtry.cd = "OPAV".to_string();
-                                }
-                                if *reg_code == "62F" {
-                                    balance.tp.cd_or_prtry.cd = "CLBD".to_string();
-                                }
-                                if *reg_code == "62M" {
-                                    balance.tp.cd_or_prtry.cd = "CLAV".to_string();
-                                }
-                                if *reg_code == "64" {
-                                    balance.tp.cd_or_prtry.cd = "ITAV".to_string();
-                                }
-                                if *reg_code == "65" {
-                                    balance.tp.cd_or_prtry.cd = "FPAV".to_string();
-                                }
-                                document.stmt.bal.push(balance);
-                            }
-                        },
-                        _=>{}
-                    }
+            if let Ok(regexp) = reg_pattern
+                && let Some(capture) = regexp.captures(header) {
+                let capture = capture[1].replace("\n", "")
+                                              .replace(" ", "");
+                match *reg_code {
+                    "26" => {
+                        document.grp_hdr.msg_id = capture;
+                        document.stmt.id = document.grp_hdr.msg_id.clone();
+                    },
+                    "25" => {
+                        document.stmt.acct.ownr.id.org_id.othr.id = capture;
+                    },
+                    "28C" => {
+                        let fields: Vec<&str> = capture.split('/').collect();
+                        if fields.len() > 1{
+                            document.stmt.elctrnc_seq_nb = fields[0].to_string();
+                            document.stmt.lgl_seq_nb = fields[1].to_string();
+                        }
+                    },
+                    "60F" | "60M" | "62F" | "62M" | "64"  | "65"=> {
+                        if  let Some(mut balance) = DocumentMt940::parse_field_balance(&capture)?
+                            && let Some(balance_type) = BalanceType::from_mt940_tag(reg_code) {
+                            balance.tp.cd_or_prtry.cd = balance_type;
+                            document.stmt.bal.push(balance);
+                        }
+                    },
+                    _=>{}
                 }
             }
         }
-        let mut acc = "";
-        if let Some(bal) = &document.stmt.bal.get(0){
-            acc = &bal.amt.ccy;
+        // поля промежуточного отчета MT942
+        for (tag, value) in DocumentMt940::split_fields(header) {
+            match tag {
+                "13D" => document.stmt.cre_dt_tm = Some(parse_yymmddhhmm(&value)?),
+                "34F" => document.stmt.flr_lmt.push(DocumentMt942::parse_field_34f(&value)?),
+                "90D" => document.stmt.txs_summry.ttl_dbt_ntries = Some(DocumentMt942::parse_field_90(&value)?),
+                "90C" => document.stmt.txs_summry.ttl_cdt_ntries = Some(DocumentMt942::parse_field_90(&value)?),
+                _ => {}
+            }
         }
-        if let Some(ntry) = DocumentMt940::parse_field_ntry(&header, acc){
-            document.stmt.ntry = ntry;
+        // в отчете MT942 нет остатков: валюта счета и записей - валюта минимальной суммы :34F:
+        if let Some(limit) = document.stmt.flr_lmt.first()
+            && document.stmt.acct.ccy.is_empty() {
+            document.stmt.acct.ccy = limit.amt.ccy.clone();
         }
+        let acc = document.stmt.bal.first().map_or(document.stmt.acct.ccy.as_str(), |bal| bal.amt.ccy.as_str());
+        document.stmt.ntry = DocumentMt940::parse_field_ntry(header, acc, dialect)?;
+        Ok(())
     }
-    pub(crate) fn parse_one_record(document: &str) -> Option<BkToCstmrStmt> {
+    pub(crate) fn parse_one_record(document: &str, dialect: NarrativeDialect) -> Result<BkToCstmrStmt, ParserError> {
+        DocumentMt940::parse_record(document, "940", dialect)
+    }
+    /// Разбирает сообщение типа `message_type` (940, 942). Тип из блока 2 заголовков SWIFT
+    /// имеет приоритет; он же записывается в идентификатор выписки `<ссылка>-<тип>`.
+    pub(crate) fn parse_record(document: &str, message_type: &str, dialect: NarrativeDialect) -> Result<BkToCstmrStmt, ParserError> {
         let mut record: BkToCstmrStmt = BkToCstmrStmt::default();
+        let mut text_block = None;
         for field in 1..6 {
-            let reg_pattern = Regex::new(&format!(r"\{{{}:([\n\w\d ,/:-]*)\}}",
+            let reg_pattern = Regex::new(&format!(r"\{{{}:([^}}]*)\}}",
                                                   field));
-            if let Ok(regexp) = reg_pattern {
-                match regexp.captures(document) {
-                    Some(capture) => {
-                        match field {
-                            1 => { record.stmt.acct.svcr.fin_instn_id.bic =
-                                DocumentMt940::parse_field_one(&capture[1].to_string());},
-                            2 => {
-                                DocumentMt940::parse_field_two(&capture[1].to_string(),
-                                                               &mut record);
-                            },
-                            4 => { DocumentMt940::parse_field_foo(&capture[1].to_string(),
-                                                                  &mut record); },
-                            _ => {}
-                        }
+            if let Ok(regexp) = reg_pattern
+                && let Some(capture) = regexp.captures(document) {
+                match field {
+                    1 => { record.stmt.acct.svcr.fin_instn_id.bic =
+                        DocumentMt940::parse_field_one(&capture[1]);},
+                    2 => {
+                        DocumentMt940::parse_field_two(&capture[1], &mut record);
+                    },
+                    4 => {
+                        DocumentMt940::parse_field_foo(&capture[1], &mut record, dialect)?;
+                        text_block = capture.get(1).map(|text| text.as_str());
+                    },
+                    _ => {}
+                }
+            }
+        }
+        if text_block.is_none() && !document.contains("{4:") && document.trim_start().starts_with(":20:") {
+            // сообщение без заголовков SWIFT: весь текст - блок 4
+            DocumentMt940::parse_field_foo(document, &mut record, dialect)?;
+            text_block = Some(document);
+        }
+        let Some(text_block) = text_block else {
+            return Err(ParserError::BadInputFormatFile("Missing block 4".to_string()));
+        };
+        // ссылка выписки - поле :20:, с заголовками SWIFT и без них
+        if let Some((_, reference)) = DocumentMt940::split_fields(text_block).into_iter().find(|(tag, _)| *tag == "20") {
+            record.grp_hdr.msg_id = reference.trim().to_string();
+            record.stmt.id = format!("{}-{}", record.grp_hdr.msg_id, message_type);
+        }
+        Ok(record)
+    }
+    /// Объединяет страницы выписок, разбитых на несколько сообщений.
+    ///
+    /// Сообщение продолжает предыдущее, если у них совпадают ссылка :20:, счет :25: и номер выписки :28C:,
+    /// а предыдущая страница закрыта промежуточным остатком 62M, а не итоговым 62F. В объединенной
+    /// выписке остаются входящий остаток первой страницы, исходящие остатки последней и записи всех страниц.
+    ///
+    /// # Ошибки
+    ///
+    /// Возвращает ошибку, если номер страницы продолжения не следует за номером предыдущей страницы.
+    pub(crate) fn stitch_pages(records: Vec<BkToCstmrStmt>) -> Result<Vec<BkToCstmrStmt>, ParserError> {
+        let mut statements: Vec<BkToCstmrStmt> = Vec::new();
+        // страница последней выписки, если выписка продолжается в следующем сообщении
+        let mut open_page: Option<u32> = None;
+        for record in records {
+            let page = record.stmt.lgl_seq_nb.trim().parse::<u32>().ok();
+            let closed = record.stmt.bal.iter().any(|bal| bal.tp.cd_or_prtry.cd == BalanceType::Clbd);
+            match (statements.last_mut(), open_page, page) {
+                (Some(statement), Some(last_page), Some(page)) if DocumentMt940::same_statement(statement, &record) => {
+                    if page != last_page + 1 {
+                        return Err(ParserError::BadInputFormatFile(format!(
+                            "MT940 statement {} page {} follows page {}", record.stmt.elctrnc_seq_nb, page, last_page)));
                     }
-                    None => {}
+                    DocumentMt940::merge_page(statement, record);
                 }
+                _ => statements.push(record),
             }
+            open_page = if closed { None } else { page };
+        }
+        Ok(statements)
+    }
+
+    /// Сообщения относятся к одной выписке: совпадают ссылка :20:, счет и номер выписки :28C:.
+    fn same_statement(statement: &BkToCstmrStmt, page: &BkToCstmrStmt) -> bool {
+        !page.stmt.elctrnc_seq_nb.is_empty()
+            && statement.grp_hdr.msg_id == page.grp_hdr.msg_id
+            && statement.stmt.elctrnc_seq_nb == page.stmt.elctrnc_seq_nb
+            && statement.stmt.acct.ownr.id.org_id.othr.id == page.stmt.acct.ownr.id.org_id.othr.id
+    }
+
+    /// Добавляет к выписке страницу продолжения: промежуточные остатки 62M/60M между страницами отбрасываются.
+    fn merge_page(statement: &mut BkToCstmrStmt, page: BkToCstmrStmt) {
+        let opening = |bal: &BalanceAttribute| matches!(bal.tp.cd_or_prtry.cd, BalanceType::Opbd | BalanceType::Opav);
+        statement.stmt.bal.retain(opening);
+        statement.stmt.bal.extend(page.stmt.bal.into_iter().filter(|bal| !opening(bal)));
+        statement.stmt.ntry.extend(page.stmt.ntry);
+    }
+
+    /// Логический терминал SWIFT (12 символов) по BIC банка: BIC8, код терминала `A` и филиал.
+    fn logical_terminal(bic: &str) -> String {
+        let bic = bic.trim();
+        let valid = bic.chars().all(|c| c.is_ascii_uppercase() || c.is_ascii_digit());
+        match bic.len() {
+            12 if valid => bic.to_string(),
+            11 if valid => format!("{}A{}", &bic[..8], &bic[8..]),
+            8 if valid => format!("{}AXXX", bic),
+            _ => "XXXXXXXXAXXX".to_string(),
         }
-        Some(record)
     }
-    pub(crate) fn extract_field_6x_mt940(record_camt: &BkToCstmrStmt, record_write: &mut String) {
+
+    /// Записывает поле `:tag:value` блока 4.
+    pub(crate) fn push_field(record_write: &mut String, tag: &str, value: &str) {
+        record_write.push(':');
+        record_write.push_str(tag);
+        record_write.push(':');
+        record_write.push_str(value);
+        record_write.push_str(EOL);
+    }
+
+    /// Номер выписки :28C: (5n[/5n]) из номера выписки и номера страницы.
+    fn statement_number(record: &BkToCstmrStmt) -> String {
+        // не более 5 младших цифр
+        let digits = |value: &str| -> String {
+            let digits: String = value.chars().filter(char::is_ascii_digit).collect();
+            digits[digits.len().saturating_sub(5)..].to_string()
+        };
+        let number = digits(&record.stmt.elctrnc_seq_nb);
+        let page = digits(&record.stmt.lgl_seq_nb);
+        match (number.is_empty(), page.is_empty()) {
+            (true, _) => "1".to_string(),
+            (false, true) => number,
+            (false, false) => format!("{}/{}", number, page),
+        }
+    }
+
+    /// Записывает заголовки сообщения MT940 (блоки 1, 2 и начало блока 4) и поля :20:, :25:, :28C:.
+    pub(crate) fn extract_header_mt940(record: &BkToCstmrStmt, record_write: &mut String) {
+        DocumentMt940::extract_header(record, "940", record_write);
+    }
+    /// Записывает заголовки сообщения типа `message_type` и поля :20:, :25:, :28C:.
+    ///
+    /// Блок 1 - `F01` с логическим терминалом банка выписки, блок 2 - входящее сообщение
+    /// для того же терминала с обычным приоритетом.
+    pub(crate) fn extract_header(record: &BkToCstmrStmt, message_type: &str, record_write: &mut String) {
+        let terminal = DocumentMt940::logical_terminal(&record.stmt.acct.svcr.fin_instn_id.bic);
+        record_write.push_str(&format!("{{1:F01{}0000000000}}{{2:I{}{}N}}{{4:{}", terminal, message_type, terminal, EOL));
+        let reference = swift::fit(&record.grp_hdr.msg_id, 16);
+        DocumentMt940::push_field(record_write, "20", if reference.is_empty() { "NONREF" } else { &reference });
+        let acct = &record.stmt.acct;
+        let account = [&acct.ownr.id.org_id.othr.id, &acct.id.iban, &acct.id.othr.id].into_iter()
+            .find(|id| !id.is_empty())
+            .map_or("", |id| id.as_str());
+        DocumentMt940::push_field(record_write, "25", &swift::fit(account, 35));
+        DocumentMt940::push_field(record_write, "28C", &DocumentMt940::statement_number(record));
+    }
+    /// Записывает входящие остатки :60F:/:60M: или, если `closing`, исходящие и прочие остатки
+    /// :62F:/:62M:, :64:, :65:, которые следуют за записями :61:.
+    pub(crate) fn extract_field_6x_mt940(record_camt: &BkToCstmrStmt, record_write: &mut String, closing: bool) {
         for balance in &record_camt.stmt.bal {
-            match balance.tp.cd_or_prtry.cd.as_ref() {
-                "OPBD" => { record_write.push_str(":60F:") },
-                "OPAV" => { record_write.push_str(":60M:") },
-                "CLBD" => { record_write.push_str(":62F:") },
-                "CLAV" => { record_write.push_str(":62M:") },
-                "ITAV" => { record_write.push_str(":64:") },
-                "FPAV" => { record_write.push_str(":65:") },
-                _ => { continue}
+            let Some(tag) = balance.tp.cd_or_prtry.cd.mt940_tag() else {
+                continue;
+            };
+            if tag.starts_with("60") == closing {
+                continue;
             }
-            record_write.push_str(balance.cd.as_ref());
-            let dt = balance.dt.dt.replace("-", "");
-            if dt.len() >= 8 {
-                record_write.push_str(&dt[2..8]);
+            let mut value = String::from(balance.cdt_dbt_ind.mt940());
+            if let Some(dt) = balance.dt.dt {
+                value.push_str(&format_yymmdd(dt));
             }
-            record_write.push_str(balance.amt.ccy.as_ref());
-            record_write.push_str(balance.amt.amt.replace(".", ",").as_ref());
-            record_write.push_str("\n");
+            value.push_str(balance.amt.ccy.as_ref());
+            value.push_str(balance.amt.amt.to_mt940().as_ref());
+            DocumentMt940::push_field(record_write, tag, &value);
         }
     }
-    pub(crate) fn extract_field_61_86_mt940(record_camt: &Vec<NtryAttribute>, record_write: &mut String) {
+    /// Тип операции поля :61: (1!a3!c); коды других форматов (например, вид операции CSV)
+    /// записываются как NMSC - прочие операции.
+    fn transaction_type(code: &str) -> &str {
+        let valid = code.len() == 4
+            && code.starts_with(['N', 'S', 'F'])
+            && code[1..].chars().all(|c| c.is_ascii_uppercase() || c.is_ascii_digit());
+        if valid { code } else { "NMSC" }
+    }
+    /// Описание операции для поля :86: кодами `/CODE/значение`.
+    fn narrative(tx_dtls: &TxDtlsAttribute) -> Vec<String> {
+        let remi = tx_dtls.rmt_inf.ustrd.join(" ");
+        let oamt = tx_dtls.amt_dtls.amt.map(|amt| amt.format(',')).unwrap_or_default();
+        [("NREF", tx_dtls.refs.end_to_end_id.as_str()),
+            ("CRNM", &tx_dtls.rltd_pties.cdtr.nm),
+            ("CACT", tx_dtls.rltd_pties.cdtr_acct.account()),
+            ("CBIC", &tx_dtls.rltd_agts.cdtr_agt.fin_instn_id.bic),
+            ("REMI", &remi),
+            ("OPRP", &tx_dtls.addtl_tx_inf),
+            ("DACT", tx_dtls.rltd_pties.dbtr_acct.account()),
+            ("OAMT", &oamt),
+            ("DCID", &tx_dtls.rltd_pties.dbtr.id.othr.id)]
+            .into_iter()
+            .filter(|(_, value)| !value.is_empty())
+            .map(|(code, value)| format!("/{}/{}", code, value))
+            .collect()
+    }
+    /// Записывает поля :61: и :86: записей.
+    ///
+    /// Ссылки :61: ограничены 16 символами, дополнительные сведения - 34. Поле :86: одно на запись:
+    /// описание первой операции TxDtls переносится по 65 символов, не более 6 строк. Текст приводится
+    /// к набору символов SWIFT X (см. [`crate::models::swift`]).
+    pub(crate) fn extract_field_61_86_mt940(record_camt: &[NtryAttribute], record_write: &mut String) {
         for ntry in record_camt {
-            record_write.push_str(":61:");
-            let mut dt = ntry.val_dt.dt.replace("-", "");
-            if dt.len() >= 8 {
-                record_write.push_str(&dt[2..8]);
+            let mut value = String::new();
+            if let Some(dt) = ntry.val_dt.dt.or(ntry.bookg_dt.dt) {
+                value.push_str(&format_yymmdd(dt));
             }
-            dt = ntry.bookg_dt.dt.replace("-", "");
-            if dt.len() >= 8 {
-                record_write.push_str(&dt[4..8]);
+            if let Some(dt) = ntry.bookg_dt.dt {
+                value.push_str(&format_mmdd(dt));
             }
-            if ntry.cdt_dbt_ind == "CRDT" {
-                record_write.push_str("C")
-            } else { record_write.push_str("D") };
-            record_write.push_str(ntry.amt.amt.replace(".", ",").as_ref());
-            record_write.push_str(ntry.bk_tx_cd.prtry.cd.as_ref());
-            if !ntry.ntry_dtls.tx_dtls.is_empty() {
-                record_write.push_str(ntry.ntry_dtls.tx_dtls[0].refs.end_to_end_id.as_ref());
-                record_write.push_str(" ");
+            if ntry.rvsl_ind {
+                value.push('R');
+                value.push_str(ntry.cdt_dbt_ind.reverse().mt940());
+            } else {
+                value.push_str(ntry.cdt_dbt_ind.mt940());
             }
-            record_write.push_str("\n");
-            for tx_dtls in &ntry.ntry_dtls.tx_dtls {
-                record_write.push_str(":86:/NREF/");
-                record_write.push_str(tx_dtls.refs.end_to_end_id.as_ref());
-                record_write.push_str("\n");
-                if !tx_dtls.rltd_pties.cdtr.nm.is_empty() {
-                    record_write.push_str("/CRNM/");
-                    record_write.push_str(tx_dtls.rltd_pties.cdtr.nm.as_ref());
-                    record_write.push_str("\n");
-                }
-                if !tx_dtls.rltd_pties.cdtr_acct.other.id.is_empty() {
-                    record_write.push_str("/CACT/");
-                    record_write.push_str(tx_dtls.rltd_pties.cdtr_acct.other.id.as_ref());
-                    record_write.push_str("\n");
-                }
-                if !tx_dtls.rltd_agts.cdtr_agt.fin_instn_id.bic.is_empty() {
-                    record_write.push_str("/CBIC/");
-                    record_write.push_str(tx_dtls.rltd_agts.cdtr_agt.fin_instn_id.bic.as_ref());
-                    record_write.push_str("\n");
-                }
-                if !tx_dtls.rmt_inf.ustrd.is_empty() {
-                    record_write.push_str("/REMI/");
-                    let mut ustrd_all = String::new();
-                    for ustrd in tx_dtls.rmt_inf.ustrd.clone(){
-                        ustrd_all.push_str(&ustrd);
-                        ustrd_all.push_str("/");
-                    }
-                    record_write.push_str(ustrd_all.as_ref());
-                    record_write.push_str("\n");
-                }
-                if !tx_dtls.addtl_tx_inf.is_empty() {
-                    record_write.push_str("/OPRP/");
-                    record_write.push_str(tx_dtls.addtl_tx_inf.as_ref());
-                    record_write.push_str("\n");
-                }
-                if !tx_dtls.rltd_pties.dbtr_acct.other.id.is_empty() {
-                    record_write.push_str("/DACT/");
-                    record_write.push_str(tx_dtls.rltd_pties.dbtr_acct.other.id.as_ref());
-                    record_write.push_str("\n");
-                }
-                if !tx_dtls.amt_dtls.amt.is_empty() {
-                    record_write.push_str("/OAMT/");
-                    record_write.push_str(tx_dtls.amt_dtls.amt.as_ref());
-                    record_write.push_str("\n");
-                }
-                if !tx_dtls.rltd_pties.dbtr.id.othr.id.is_empty() {
-                    record_write.push_str("/DCID/");
-                    record_write.push_str(tx_dtls.rltd_pties.dbtr.id.othr.id.as_ref());
-                    record_write.push_str("\n");
+            value.push_str(&swift::fit(&ntry.funds_code, 1));
+            value.push_str(ntry.amt.amt.to_mt940().as_ref());
+            value.push_str(DocumentMt940::transaction_type(&ntry.bk_tx_cd.prtry.cd));
+            let reference = ntry.ntry_dtls.tx_dtls.first()
+                .map(|tx_dtls| swift::fit(&tx_dtls.refs.end_to_end_id, 16))
+                .unwrap_or_default();
+            value.push_str(if reference.is_empty() { "NONREF" } else { &reference });
+            let bank_reference = swift::fit(&ntry.acct_svcr_ref, 16);
+            if !bank_reference.is_empty() {
+                value.push_str("//");
+                value.push_str(&bank_reference);
+            }
+            let supplementary = swift::fit(&ntry.addtl_ntry_inf, 34);
+            if !supplementary.is_empty() {
+                value.push_str(EOL);
+                value.push_str(&supplementary);
+            }
+            DocumentMt940::push_field(record_write, "61", &value);
+            if let Some(tx_dtls) = ntry.ntry_dtls.tx_dtls.first() {
+                let narrative = DocumentMt940::narrative(tx_dtls);
+                let segments: Vec<&str> = narrative.iter().map(String::as_str).collect();
+                let lines = swift::wrap(&segments, 65, 6);
+                if !lines.is_empty() {
+                    DocumentMt940::push_field(record_write, "86", &lines.join(EOL));
                 }
             }
         }
     }
+    /// Записывает конец блока 4 сообщения.
+    pub(crate) fn extract_trailer_mt940(record_write: &mut String) {
+        record_write.push('-');
+        record_write.push('}');
+        record_write.push_str(EOL);
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::models::camt053::*;
+    use crate::models::money::Money;
+    use chrono::NaiveDate;
+    use crate::models::codes::{BalanceType, CreditDebit};
     use super::*;
 
     #[test]
@@ -355,6 +510,12 @@ mod tests {
         let doc ="{1:}{5:-}{1:   }{2:}{3:}{4:}{5:-}".to_string();
         let result: Vec<(usize, usize)> = vec![(0, 4), (9, 28)];
         assert_eq!(DocumentMt940::find_record(&doc).unwrap(), result);
+        // сообщения без трейлера {5:
+        let doc = "{1:A}{4:\n-}{1:B}{4:\n-}{5:}";
+        assert_eq!(vec![(0, 11), (11, 22)], DocumentMt940::find_record(doc).unwrap());
+        let doc = ":20:A\n:25:1\n-\n  :20:B\n-\n";
+        assert_eq!(vec![(0, 16), (16, 24)], DocumentMt940::find_record(doc).unwrap());
+        assert_eq!(None, DocumentMt940::find_record("text"));
     }
     #[test]
     fn test_parse_field_one() {
@@ -369,52 +530,76 @@ mod tests {
         assert_eq!(BkToCstmrStmt {
             grp_hdr: HeaderAttribute {
                 msg_id: "ASNBNL21XXXXN".to_string(),
-                cre_dt_tm: "".to_string(),
+                cre_dt_tm: None,
             },
             stmt: StatementAttribute {
                 id: "ASNBNL21XXXXN-940".to_string(),
                 elctrnc_seq_nb: "".to_string(),
                 lgl_seq_nb: "".to_string(),
-                cre_dt_tm: "".to_string(),
+                cre_dt_tm: None,
                 fr_to_dt: Default::default(),
                 acct: Default::default(),
                 bal: vec![],
+                flr_lmt: vec![],
                 txs_summry: Default::default(),
                 ntry: vec![],
             },
         }, result);
     }
     #[test]
-    fn test_parse_field_86() {
-        let doc = ":86:/NREF/NIOBNL56ASNB9999999999\n".to_string();
-        let mut ntry_det_result: NtryDtlsAttribute = NtryDtlsAttribute::default();
-        let mut ntry_det_test: NtryDtlsAttribute = NtryDtlsAttribute::default();
-        let mut ntry_det_tlds: TxDtlsAttribute = TxDtlsAttribute::default();
-        ntry_det_tlds.refs.prtry.tp = "NREF".to_string();
-        ntry_det_tlds.refs.prtry.refdt = "NIOBNL56ASNB9999999999".to_string();
-        ntry_det_test.tx_dtls.push(ntry_det_tlds);
-        DocumentMt940::parse_field_86(&doc, "".to_string(), &mut ntry_det_result);
-        assert_eq!(ntry_det_test, ntry_det_result);
-    }
-    #[test]
     fn test_parse_field_61() {
-        let field_61 = ":61:2001050105C1000,00NIOBNL56ASNB9999999999\n".to_string();
+        let field_61 = "2001050105C1000,00NIOBNL56ASNB9999999999\n".to_string();
         let mut ntry_result = NtryAttribute::default();
         let mut ntry_test = NtryAttribute::default();
         ntry_test.amt.ccy = "EUR".to_string();
-        ntry_test.val_dt.dt = "2020-01-05".to_string();
-        ntry_test.bookg_dt.dt = "2020-01-05".to_string();
+        ntry_test.val_dt.dt = NaiveDate::from_ymd_opt(2020, 1, 5);
+        ntry_test.bookg_dt.dt = NaiveDate::from_ymd_opt(2020, 1, 5);
         ntry_test.bk_tx_cd.prtry.cd = "NIOB".to_string();
-        ntry_test.amt.amt = "1000.00".to_string();
-        ntry_test.cdt_dbt_ind = "CRDT".to_string();
-        let mut nxdet: NtryDtlsAttribute = NtryDtlsAttribute::default();
-        let field_86 = ":86:/NREF/NIOBNL56ASNB9999999999\n";
-        DocumentMt940::parse_field_86(field_86, "NL56ASNB9999999999".to_string(), &mut nxdet);
-        ntry_test.ntry_dtls = nxdet;
-        DocumentMt940::parse_field_61((&field_61, &field_86), "EUR", &mut ntry_result);
+        ntry_test.amt.amt = "1000.00".parse().unwrap();
+        ntry_test.cdt_dbt_ind = CreditDebit::Credit;
+        let mut tlds = TxDtlsAttribute::default();
+        tlds.refs.end_to_end_id = "NL56ASNB9999999999".to_string();
+        ntry_test.ntry_dtls.tx_dtls.push(tlds);
+        DocumentMt940::parse_field_61(&field_61, "EUR", &mut ntry_result).unwrap();
         assert_eq!(ntry_test, ntry_result);
     }
     #[test]
+    fn test_parse_field_61_subfields() {
+        let mut ntry = NtryAttribute::default();
+        DocumentMt940::parse_field_61("110708C688,35NTRFNONREF//7093070822670002", "EUR", &mut ntry).unwrap();
+        assert_eq!(NaiveDate::from_ymd_opt(2011, 7, 8), ntry.bookg_dt.dt);
+        assert_eq!("7093070822670002", ntry.acct_svcr_ref);
+        assert_eq!("NONREF", ntry.ntry_dtls.tx_dtls[0].refs.end_to_end_id);
+
+        let mut ntry = NtryAttribute::default();
+        DocumentMt940::parse_field_61("2401020103RDR5,50S103REF 1//BANK/REF\nSUPPLEMENTARY", "EUR", &mut ntry).unwrap();
+        assert!(ntry.rvsl_ind);
+        assert_eq!(CreditDebit::Credit, ntry.cdt_dbt_ind);
+        assert_eq!("R", ntry.funds_code);
+        assert_eq!("5.50".parse::<Money>().unwrap(), ntry.amt.amt);
+        assert_eq!(NaiveDate::from_ymd_opt(2024, 1, 3), ntry.bookg_dt.dt);
+        assert_eq!("S103", ntry.bk_tx_cd.prtry.cd);
+        assert_eq!("REF 1", ntry.ntry_dtls.tx_dtls[0].refs.end_to_end_id);
+        assert_eq!("BANK/REF", ntry.acct_svcr_ref);
+        assert_eq!("SUPPLEMENTARY", ntry.addtl_ntry_inf);
+
+        let mut ntry = NtryAttribute::default();
+        DocumentMt940::parse_field_61("2001250125D1,65NDIV", "EUR", &mut ntry).unwrap();
+        assert_eq!(CreditDebit::Debit, ntry.cdt_dbt_ind);
+        assert_eq!("", ntry.ntry_dtls.tx_dtls[0].refs.end_to_end_id);
+
+        let error = DocumentMt940::parse_field_61("20010X0125D1,65NDIV", "EUR", &mut ntry).unwrap_err();
+        assert_eq!("Bad input format file: Unparsable field :61:20010X0125D1,65NDIV", error.to_string());
+    }
+    #[test]
+    fn test_parse_field_ntry_without_86(){
+        let doc = ":61:2001250125D1,65NDIV\n:61:2001050105C1000,00NIOBREF\n:86:/REMI/text\n:62F:C200131EUR0,00\n";
+        let result = DocumentMt940::parse_field_ntry(doc, "EUR", NarrativeDialect::Swift).unwrap();
+        assert_eq!(2, result.len());
+        assert!(result[0].ntry_dtls.tx_dtls[0].rmt_inf.ustrd.is_empty());
+        assert_eq!(vec!["text".to_string()], result[1].ntry_dtls.tx_dtls[0].rmt_inf.ustrd);
+    }
+    #[test]
     fn test_parse_field_ntry(){
         let doc = "{1:F01GSCRUS30XXXX3614000002}{2:I940GSCRUS30XXXXN}{4:
                            :20:15486025400
@@ -432,17 +617,19 @@ mod tests {
                                 /CRNM/GOLDMAN SACHS BANK USA
                                 /CACT/107045863/CBIC/GSCRUS30XXX
                                 /REMI/The maximum length of the block is 65 characters
-                                /OPRP/Tag Payment}{5:-}".to_string();
-        let result = DocumentMt940::parse_field_ntry(&doc, "USD").unwrap();
+                                /OPRP/Tag Payment
+                           -}{5:-}".to_string();
+        let result = DocumentMt940::parse_field_ntry(&doc, "USD", NarrativeDialect::Swift).unwrap();
         let ntry_test: Vec<NtryAttribute> = vec![NtryAttribute { ntry_ref: 0,
-            amt: AmtAttribute { ccy: "USD".to_string(), amt: "12.01".to_string() },
-            cdt_dbt_ind: "DBIT".to_string(),
-            sts: "".to_string(), bookg_dt: DtAttribute { dt: "2025-02-18".to_string() },
-            val_dt: DtAttribute { dt: "2025-02-18".to_string() }, acct_svcr_ref: "".to_string(),
+            amt: AmtAttribute { ccy: "USD".to_string(), amt: "12.01".parse().unwrap() },
+            cdt_dbt_ind: CreditDebit::Debit,
+            sts: "".to_string(), bookg_dt: DtAttribute { dt: NaiveDate::from_ymd_opt(2025, 2, 18) },
+            val_dt: DtAttribute { dt: NaiveDate::from_ymd_opt(2025, 2, 18) }, acct_svcr_ref: "GI2504900007841".to_string(),
             bk_tx_cd: BxTxCdAttribute { domn: DomnAttribute { cd: "".to_string(),
                 fmly: FmlyAttribute { cd: "".to_string(), sub_fmly_cd: "".to_string() } },
                 prtry: PrtryAttribute { cd: "NTRF".to_string(), issr: "".to_string() } },
             addtl_inf_ind: AddtlTxInfAtttribute { msg_nm_id: "".to_string() },
+            rvsl_ind: false, funds_code: "".to_string(), addtl_ntry_inf: "".to_string(),
             ntry_dtls: NtryDtlsAttribute { btch: BtchAttribute {
                 nb_of_txs: 0 }, tx_dtls: vec![TxDtlsAttribute {
                 refs: EndToEndIdAttribute { pmt_inf_id: "".to_string(),
@@ -450,16 +637,16 @@ mod tests {
                     tx_id: "".to_string(), prtry: PrtryDetAttribute {
                         tp: "".to_string(), refdt: "".to_string() } }, amt_dtls: TxAmtAttribute {
                     end_to_end_id: "".to_string(), instd_amt: PrtryAmtAttribute {
-                        tp: "".to_string(), amt: AmtAttribute { ccy: "".to_string(), amt: "".to_string() },
+                        tp: "".to_string(), amt: AmtAttribute { ccy: "".to_string(), amt: Money::default() },
                         ccy_xchg: CcyXchgAttribute { src_ccy: "".to_string(),
                             trgt_ccy: "".to_string(), unit_ccy: "".to_string(), xchg_rate: "".to_string() } },
                     tx_amt: PrtryAmtAttribute { tp: "".to_string(), amt: AmtAttribute {
-                        ccy: "".to_string(), amt: "".to_string() }, ccy_xchg: CcyXchgAttribute {
+                        ccy: "".to_string(), amt: Money::default() }, ccy_xchg: CcyXchgAttribute {
                         src_ccy: "".to_string(), trgt_ccy: "".to_string(), unit_ccy: "".to_string(),
                         xchg_rate: "".to_string() } }, prtry_amt: PrtryAmtAttribute {
-                        tp: "".to_string(), amt: AmtAttribute { ccy: "".to_string(), amt: "".to_string() },
+                        tp: "".to_string(), amt: AmtAttribute { ccy: "".to_string(), amt: Money::default() },
                         ccy_xchg: CcyXchgAttribute { src_ccy: "".to_string(), trgt_ccy: "".to_string(),
-                            unit_ccy: "".to_string(), xchg_rate: "".to_string() } }, amt: "".to_string() },
+                            unit_ccy: "".to_string(), xchg_rate: "".to_string() } }, amt: None },
                 bk_tx_cd: BxTxCdAttribute { domn: DomnAttribute { cd: "".to_string(),
                     fmly: FmlyAttribute { cd: "".to_string(), sub_fmly_cd: "".to_string() } },
                     prtry: PrtryAttribute { cd: "".to_string(), issr: "".to_string() } },
@@ -472,7 +659,7 @@ mod tests {
                         id: "".to_string(), schme_nm: ShemeNumberAttribute { cd: "".to_string() } } },
                     other: IdDtldAttribute { id: "".to_string() } }, cdtr: CdtrAttribue {
                     id: PrvtIdAttribute { othr: IdDtldAttribute { id: "".to_string() } },
-                    nm: "GOLDMAN".to_string(), pstl_adr: PostalAddressAttribute {
+                    nm: "GOLDMAN SACHS BANK USA".to_string(), pstl_adr: PostalAddressAttribute {
                         strt_nm: "".to_string(), bldg_nb: "".to_string(), pst_cd: "".to_string(), twn_nm: "".to_string(),
                         ctry: "".to_string(), adr_line: Vec::new() } }, cdtr_acct: IdTxDtlsAttribute {
                     id: IdIbanAttribute { iban: "".to_string(), othr: OtherAttribute {
@@ -487,32 +674,33 @@ mod tests {
                         pstl_adr: PostalAddressAttribute { strt_nm: "".to_string(),
                             bldg_nb: "".to_string(), pst_cd: "".to_string(), twn_nm: "".to_string(), ctry: "".to_string(),
                             adr_line: Vec::new() } } } }, rmt_inf: RmtInfAttribute {
-                    ustrd: vec!["USD".to_string()], strd: StrdAttribute { cdtr_ref_inf: CdtrRefInfAttribute {
+                    ustrd: vec!["USD Payment to Vendor".to_string()], strd: StrdAttribute { cdtr_ref_inf: CdtrRefInfAttribute {
                         tp: CdOrPrtryAttribute { cd_or_prtry: CdAttribute { cd: "".to_string() } },
-                        ref_cdtr: "".to_string() } } }, rltd_dts: RltdDtsAttribute { accptnc_dt_tm: "".to_string() },
-                addtl_tx_inf: "Tag".to_string() }] } }, NtryAttribute { ntry_ref: 0,
-            amt: AmtAttribute { ccy: "USD".to_string(), amt: "12.01".to_string() }, cdt_dbt_ind: "DBIT".to_string(),
-            sts: "".to_string(), bookg_dt: DtAttribute { dt: "2025-02-18".to_string() },
-            val_dt: DtAttribute { dt: "2025-02-18".to_string() }, acct_svcr_ref: "".to_string(),
+                        ref_cdtr: "".to_string() } } }, rltd_dts: RltdDtsAttribute { accptnc_dt_tm: None }, tax: TaxAttribute::default(),
+                addtl_tx_inf: "Tag Payment".to_string() }] } }, NtryAttribute { ntry_ref: 0,
+            amt: AmtAttribute { ccy: "USD".to_string(), amt: "12.01".parse().unwrap() }, cdt_dbt_ind: CreditDebit::Debit,
+            sts: "".to_string(), bookg_dt: DtAttribute { dt: NaiveDate::from_ymd_opt(2025, 2, 18) },
+            val_dt: DtAttribute { dt: NaiveDate::from_ymd_opt(2025, 2, 18) }, acct_svcr_ref: "GI2504900005623".to_string(),
             bk_tx_cd: BxTxCdAttribute { domn: DomnAttribute { cd: "".to_string(),
                 fmly: FmlyAttribute { cd: "".to_string(), sub_fmly_cd: "".to_string() } },
                 prtry: PrtryAttribute { cd: "NTRF".to_string(), issr: "".to_string() } },
             addtl_inf_ind: AddtlTxInfAtttribute { msg_nm_id: "".to_string() },
+            rvsl_ind: false, funds_code: "".to_string(), addtl_ntry_inf: "".to_string(),
             ntry_dtls: NtryDtlsAttribute { btch: BtchAttribute { nb_of_txs: 0 },
                 tx_dtls: vec![TxDtlsAttribute { refs: EndToEndIdAttribute {
                     pmt_inf_id: "".to_string(), instr_id: "".to_string(), end_to_end_id: "GSOXWBAQYTF4VH".to_string(),
                     tx_id: "".to_string(), prtry: PrtryDetAttribute { tp: "".to_string(), refdt: "".to_string() } },
                     amt_dtls: TxAmtAttribute { end_to_end_id: "".to_string(),
                         instd_amt: PrtryAmtAttribute {
-                        tp: "".to_string(), amt: AmtAttribute { ccy: "".to_string(), amt: "".to_string() },
+                        tp: "".to_string(), amt: AmtAttribute { ccy: "".to_string(), amt: Money::default() },
                         ccy_xchg: CcyXchgAttribute { src_ccy: "".to_string(), trgt_ccy: "".to_string(),
                             unit_ccy: "".to_string(), xchg_rate: "".to_string() } }, tx_amt: PrtryAmtAttribute {
-                        tp: "".to_string(), amt: AmtAttribute { ccy: "".to_string(), amt: "".to_string() },
+                        tp: "".to_string(), amt: AmtAttribute { ccy: "".to_string(), amt: Money::default() },
                         ccy_xchg: CcyXchgAttribute { src_ccy: "".to_string(), trgt_ccy: "".to_string(),
                             unit_ccy: "".to_string(), xchg_rate: "".to_string() } }, prtry_amt: PrtryAmtAttribute {
-                        tp: "".to_string(), amt: AmtAttribute { ccy: "".to_string(), amt: "".to_string() },
+                        tp: "".to_string(), amt: AmtAttribute { ccy: "".to_string(), amt: Money::default() },
                         ccy_xchg: CcyXchgAttribute { src_ccy: "".to_string(), trgt_ccy: "".to_string(),
-                            unit_ccy: "".to_string(), xchg_rate: "".to_string() } }, amt: "".to_string() },
+                            unit_ccy: "".to_string(), xchg_rate: "".to_string() } }, amt: None },
                     bk_tx_cd: BxTxCdAttribute { domn: DomnAttribute { cd: "".to_string(),
                         fmly: FmlyAttribute { cd: "".to_string(), sub_fmly_cd: "".to_string() } }, prtry:
                     PrtryAttribute { cd: "".to_string(), issr: "".to_string() } }, rltd_pties: RltdPtiesAttribute {
@@ -524,7 +712,7 @@ mod tests {
                                 id: "".to_string(), schme_nm: ShemeNumberAttribute { cd: "".to_string() } } },
                             other: IdDtldAttribute { id: "".to_string() } }, cdtr: CdtrAttribue {
                             id: PrvtIdAttribute { othr: IdDtldAttribute { id: "".to_string() } },
-                            nm: "GOLDMAN".to_string(), pstl_adr: PostalAddressAttribute { strt_nm: "".to_string(),
+                            nm: "GOLDMAN SACHS BANK USA".to_string(), pstl_adr: PostalAddressAttribute { strt_nm: "".to_string(),
                                 bldg_nb: "".to_string(), pst_cd: "".to_string(),
                                 twn_nm: "".to_string(), ctry: "".to_string(), adr_line: Vec::new() } },
                         cdtr_acct: IdTxDtlsAttribute { id: IdIbanAttribute { iban: "".to_string(),
@@ -540,10 +728,10 @@ mod tests {
                             pstl_adr: PostalAddressAttribute { strt_nm: "".to_string(),
                                 bldg_nb: "".to_string(), pst_cd: "".to_string(), twn_nm: "".to_string(), ctry: "".to_string(),
                                 adr_line: Vec::new() } } } }, rmt_inf: RmtInfAttribute {
-                        ustrd: vec!["The".to_string()], strd: StrdAttribute { cdtr_ref_inf: CdtrRefInfAttribute {
+                        ustrd: vec!["The maximum length of the block is 65 characters".to_string()], strd: StrdAttribute { cdtr_ref_inf: CdtrRefInfAttribute {
                             tp: CdOrPrtryAttribute { cd_or_prtry: CdAttribute { cd: "".to_string() } },
-                            ref_cdtr: "".to_string() } } }, rltd_dts: RltdDtsAttribute { accptnc_dt_tm: "".to_string() },
-                    addtl_tx_inf: "Tag".to_string() }] } }];
+                            ref_cdtr: "".to_string() } } }, rltd_dts: RltdDtsAttribute { accptnc_dt_tm: None }, tax: TaxAttribute::default(),
+                    addtl_tx_inf: "Tag Payment".to_string() }] } }];
         assert_eq!(ntry_test, result);
     }
     #[test]
@@ -553,12 +741,12 @@ mod tests {
                                :25:107048825
                                :28C:49/2
                                :60M:C250218USD2732398848,02".to_string();
-        DocumentMt940::parse_field_foo(&document, &mut result);
+        DocumentMt940::parse_field_foo(&document, &mut result, NarrativeDialect::Swift).unwrap();
         let test = BkToCstmrStmt { grp_hdr: HeaderAttribute {
-            msg_id: "".to_string(), cre_dt_tm: "".to_string() }, stmt: StatementAttribute {
+            msg_id: "".to_string(), cre_dt_tm: None }, stmt: StatementAttribute {
             id: "".to_string(), elctrnc_seq_nb: "49".to_string(), lgl_seq_nb: "2".to_string(),
-            cre_dt_tm: "".to_string(), fr_to_dt: FromToDtAttribute {
-                fr_dt_tm: "".to_string(), to_dt_tm: "".to_string() },
+            cre_dt_tm: None, fr_to_dt: FromToDtAttribute {
+                fr_dt_tm: None, to_dt_tm: None },
             acct: AcctAttribute { id: IdIbanAttribute {
                 iban: "".to_string(), othr: OtherAttribute {
                     id: "".to_string(), schme_nm: ShemeNumberAttribute {
@@ -580,16 +768,15 @@ mod tests {
                         strt_nm: "".to_string(), bldg_nb: "".to_string(), pst_cd: "".to_string(),
                         twn_nm: "".to_string(), ctry: "".to_string(), adr_line: Vec::new() },  } } },
             bal: vec![BalanceAttribute { tp: TpBalanceAttribute {
-                cd_or_prtry: CdAttribute { cd: "OPAV".to_string() } },
-                amt: AmtAttribute { ccy: "USD".to_string(), amt: "2732398848.02".to_string() },
-                cdt_dbt_ind: "".to_string(), dt: DtAttribute { dt: "2025-02-18".to_string() }, cd: "C".to_string() }],
+                cd_or_prtry: BalanceCdAttribute { cd: BalanceType::Opav } },
+                amt: AmtAttribute { ccy: "USD".to_string(), amt: "2732398848.02".parse().unwrap() },
+                cdt_dbt_ind: CreditDebit::Credit, dt: DtAttribute { dt: NaiveDate::from_ymd_opt(2025, 2, 18) } }],
+            flr_lmt: vec![],
             txs_summry: TxsSummryAttribute {
                 ttl_ntries: TtlNtriesAttribute {
-                    nb_of_ntries: "".to_string(), ttl_net_ntry_amt: 0.0,
-                    cdt_dbt_ind: "" .to_string()}, ttl_cdt_ntries: TtlCdtDbtNtriesAttribute {
-                    nb_of_ntries: 0, sum: "".to_string() },
-                ttl_dbt_ntries: TtlCdtDbtNtriesAttribute {
-                    nb_of_ntries: 0, sum: "".to_string() } }, ntry: Vec::new() } };
+                    nb_of_ntries: None, ttl_net_ntry_amt: Money::default(),
+                    cdt_dbt_ind: None }, ttl_cdt_ntries: None,
+                ttl_dbt_ntries: None, ccy: String::new() }, ntry: Vec::new() } };
 
            assert_eq!(test, result);
     }
@@ -612,12 +799,12 @@ mod tests {
                                 /CACT/107045863/CBIC/GSCRUS30XXX
                                 /REMI/The maximum length of the block is 65 characters
                                 /OPRP/Tag Payment}{5:-}".to_string();
-        let result = DocumentMt940::parse_one_record(&doc).unwrap();
+        let result = DocumentMt940::parse_one_record(&doc, NarrativeDialect::Swift).unwrap();
         let test = BkToCstmrStmt { grp_hdr: HeaderAttribute {
-            msg_id: "GSCRUS30XXXXN".to_string(), cre_dt_tm: "".to_string() },
-            stmt: StatementAttribute { id: "GSCRUS30XXXXN-940".to_string(),
-                elctrnc_seq_nb: "49".to_string(), lgl_seq_nb: "2".to_string(), cre_dt_tm: "".to_string(),
-                fr_to_dt: FromToDtAttribute { fr_dt_tm: "".to_string(), to_dt_tm: "".to_string() },
+            msg_id: "15486025400".to_string(), cre_dt_tm: None },
+            stmt: StatementAttribute { id: "15486025400-940".to_string(),
+                elctrnc_seq_nb: "49".to_string(), lgl_seq_nb: "2".to_string(), cre_dt_tm: None,
+                fr_to_dt: FromToDtAttribute { fr_dt_tm: None, to_dt_tm: None },
                 acct: AcctAttribute { id: IdIbanAttribute { iban: "".to_string(),
                     othr: OtherAttribute { id: "".to_string(), schme_nm: ShemeNumberAttribute {
                         cd: "".to_string() } } }, ccy: "".to_string(), nm: "".to_string(),
@@ -638,25 +825,23 @@ mod tests {
                             pst_cd: "".to_string(), twn_nm: "".to_string(),
                             ctry: "".to_string(), adr_line: Vec::new() } } } },
                 bal: vec![BalanceAttribute { tp: TpBalanceAttribute {
-                    cd_or_prtry: CdAttribute { cd: "OPAV".to_string() } },
-                    amt: AmtAttribute { ccy: "USD".to_string(), amt: "2732398848.02".to_string() },
-                    cdt_dbt_ind: "".to_string(), dt: DtAttribute { dt: "2025-02-18".to_string() },
-                    cd: "C".to_string() }], txs_summry: TxsSummryAttribute {
-                    ttl_ntries: TtlNtriesAttribute { nb_of_ntries: "".to_string(),
-                        ttl_net_ntry_amt: 0.0, cdt_dbt_ind: "".to_string() },
-                    ttl_cdt_ntries: TtlCdtDbtNtriesAttribute {
-                        nb_of_ntries: 0, sum: "".to_string() },
-                    ttl_dbt_ntries: TtlCdtDbtNtriesAttribute {
-                        nb_of_ntries: 0, sum: "".to_string() } },
+                    cd_or_prtry: BalanceCdAttribute { cd: BalanceType::Opav } },
+                    amt: AmtAttribute { ccy: "USD".to_string(), amt: "2732398848.02".parse().unwrap() },
+                    cdt_dbt_ind: CreditDebit::Credit, dt: DtAttribute { dt: NaiveDate::from_ymd_opt(2025, 2, 18) }, }], flr_lmt: vec![], txs_summry: TxsSummryAttribute {
+                    ttl_ntries: TtlNtriesAttribute { nb_of_ntries: None,
+                        ttl_net_ntry_amt: Money::default(), cdt_dbt_ind: None },
+                    ttl_cdt_ntries: None,
+                    ttl_dbt_ntries: None, ccy: String::new() },
                 ntry: vec![NtryAttribute { ntry_ref: 0,
-                    amt: AmtAttribute { ccy: "USD".to_string(), amt: "12.01".to_string() },
-                    cdt_dbt_ind: "DBIT".to_string(), sts: "".to_string(), bookg_dt: DtAttribute {
-                        dt: "2025-02-18".to_string() }, val_dt: DtAttribute { dt: "2025-02-18".to_string() },
-                    acct_svcr_ref: "".to_string(), bk_tx_cd: BxTxCdAttribute {
+                    amt: AmtAttribute { ccy: "USD".to_string(), amt: "12.01".parse().unwrap() },
+                    cdt_dbt_ind: CreditDebit::Debit, sts: "".to_string(), bookg_dt: DtAttribute {
+                        dt: NaiveDate::from_ymd_opt(2025, 2, 18) }, val_dt: DtAttribute { dt: NaiveDate::from_ymd_opt(2025, 2, 18) },
+                    acct_svcr_ref: "GI2504900007841".to_string(), bk_tx_cd: BxTxCdAttribute {
                         domn: DomnAttribute { cd: "".to_string(), fmly: FmlyAttribute {
                             cd: "".to_string(), sub_fmly_cd: "".to_string() } },
                         prtry: PrtryAttribute { cd: "NTRF".to_string(), issr: "".to_string() } },
                     addtl_inf_ind: AddtlTxInfAtttribute { msg_nm_id: "".to_string() },
+            rvsl_ind: false, funds_code: "".to_string(), addtl_ntry_inf: "".to_string(),
                     ntry_dtls: NtryDtlsAttribute { btch: BtchAttribute {
                         nb_of_txs: 0 }, tx_dtls: vec![TxDtlsAttribute {
                         refs: EndToEndIdAttribute { pmt_inf_id: "".to_string(),
@@ -664,17 +849,17 @@ mod tests {
                             tx_id: "".to_string(), prtry: PrtryDetAttribute { tp: "".to_string(),
                                 refdt: "".to_string() } }, amt_dtls: TxAmtAttribute {
                             end_to_end_id: "".to_string(), instd_amt: PrtryAmtAttribute {
-                                tp: "".to_string(), amt: AmtAttribute { ccy: "".to_string(), amt: "".to_string() },
+                                tp: "".to_string(), amt: AmtAttribute { ccy: "".to_string(), amt: Money::default() },
                                 ccy_xchg: CcyXchgAttribute { src_ccy: "".to_string(),
                                     trgt_ccy: "".to_string(), unit_ccy: "".to_string(), xchg_rate: "".to_string() } },
                             tx_amt: PrtryAmtAttribute { tp: "".to_string(), amt: AmtAttribute {
-                                ccy: "".to_string(), amt: "".to_string() }, ccy_xchg: CcyXchgAttribute {
+                                ccy: "".to_string(), amt: Money::default() }, ccy_xchg: CcyXchgAttribute {
                                 src_ccy: "".to_string(), trgt_ccy: "".to_string(), unit_ccy: "".to_string(),
                                 xchg_rate: "".to_string() } }, prtry_amt: PrtryAmtAttribute {
-                                tp: "".to_string(), amt: AmtAttribute { ccy: "".to_string(), amt: "".to_string() },
+                                tp: "".to_string(), amt: AmtAttribute { ccy: "".to_string(), amt: Money::default() },
                                 ccy_xchg: CcyXchgAttribute { src_ccy: "".to_string(),
                                     trgt_ccy: "".to_string(), unit_ccy: "".to_string(), xchg_rate: "".to_string() } },
-                            amt: "".to_string() }, bk_tx_cd: BxTxCdAttribute { domn: DomnAttribute {
+                            amt: None }, bk_tx_cd: BxTxCdAttribute { domn: DomnAttribute {
                             cd: "".to_string(), fmly: FmlyAttribute { cd: "".to_string(), sub_fmly_cd: "".to_string() } },
                             prtry: PrtryAttribute { cd: "".to_string(), issr: "".to_string() } },
                         rltd_pties: RltdPtiesAttribute { dbtr: DbtrAttribute {
@@ -686,7 +871,7 @@ mod tests {
                                 schme_nm: ShemeNumberAttribute { cd: "".to_string() } } },
                             other: IdDtldAttribute { id: "".to_string() } }, cdtr: CdtrAttribue {
                             id: PrvtIdAttribute { othr: IdDtldAttribute { id: "".to_string() } },
-                            nm: "GOLDMAN".to_string(), pstl_adr: PostalAddressAttribute { strt_nm: "".to_string(),
+                            nm: "GOLDMAN SACHS BANK USA".to_string(), pstl_adr: PostalAddressAttribute { strt_nm: "".to_string(),
                                 bldg_nb: "".to_string(), pst_cd: "".to_string(), twn_nm: "".to_string(),
                                 ctry: "".to_string(), adr_line: Vec::new()} },
                             cdtr_acct: IdTxDtlsAttribute { id: IdIbanAttribute { iban: "".to_string(),
@@ -702,18 +887,19 @@ mod tests {
                                     bldg_nb: "".to_string(), pst_cd: "".to_string(), twn_nm: "".to_string(),
                                     ctry: "".to_string(),
                                     adr_line: Vec::new() } } } }, rmt_inf: RmtInfAttribute {
-                            ustrd: vec!["USD".to_string()], strd: StrdAttribute { cdtr_ref_inf:
+                            ustrd: vec!["USD Payment to Vendor".to_string()], strd: StrdAttribute { cdtr_ref_inf:
                             CdtrRefInfAttribute { tp: CdOrPrtryAttribute { cd_or_prtry:
                             CdAttribute { cd: "".to_string() } }, ref_cdtr: "".to_string() } } },
-                        rltd_dts: RltdDtsAttribute { accptnc_dt_tm: "".to_string() },
-                        addtl_tx_inf: "Tag".to_string() }] } }, NtryAttribute { ntry_ref: 0,
-                    amt: AmtAttribute { ccy: "USD".to_string(), amt: "12.01".to_string() }, cdt_dbt_ind:
-                    "DBIT".to_string(), sts: "".to_string(), bookg_dt: DtAttribute { dt: "2025-02-18".to_string() },
-                    val_dt: DtAttribute { dt: "2025-02-18".to_string() }, acct_svcr_ref: "".to_string(),
+                        rltd_dts: RltdDtsAttribute { accptnc_dt_tm: None }, tax: TaxAttribute::default(),
+                        addtl_tx_inf: "Tag Payment".to_string() }] } }, NtryAttribute { ntry_ref: 0,
+                    amt: AmtAttribute { ccy: "USD".to_string(), amt: "12.01".parse().unwrap() }, cdt_dbt_ind:
+                    CreditDebit::Debit, sts: "".to_string(), bookg_dt: DtAttribute { dt: NaiveDate::from_ymd_opt(2025, 2, 18) },
+                    val_dt: DtAttribute { dt: NaiveDate::from_ymd_opt(2025, 2, 18) }, acct_svcr_ref: "GI2504900005623".to_string(),
                     bk_tx_cd: BxTxCdAttribute { domn: DomnAttribute { cd: "".to_string(),
                         fmly: FmlyAttribute { cd: "".to_string(), sub_fmly_cd: "".to_string() } },
                         prtry: PrtryAttribute { cd: "NTRF".to_string(), issr: "".to_string() } },
                     addtl_inf_ind: AddtlTxInfAtttribute { msg_nm_id: "".to_string() },
+            rvsl_ind: false, funds_code: "".to_string(), addtl_ntry_inf: "".to_string(),
                     ntry_dtls: NtryDtlsAttribute { btch: BtchAttribute {
                         nb_of_txs: 0 }, tx_dtls: vec![TxDtlsAttribute {
                         refs: EndToEndIdAttribute { pmt_inf_id: "".to_string(),
@@ -721,19 +907,19 @@ mod tests {
                             tx_id: "".to_string(), prtry: PrtryDetAttribute { tp: "".to_string(),
                                 refdt: "".to_string() } }, amt_dtls: TxAmtAttribute {
                             end_to_end_id: "".to_string(), instd_amt: PrtryAmtAttribute {
-                                tp: "".to_string(), amt: AmtAttribute { ccy: "".to_string(), amt: "".to_string() },
+                                tp: "".to_string(), amt: AmtAttribute { ccy: "".to_string(), amt: Money::default() },
                                 ccy_xchg: CcyXchgAttribute { src_ccy: "".to_string(),
                                     trgt_ccy: "".to_string(), unit_ccy: "".to_string(),
                                     xchg_rate: "".to_string() } },
                             tx_amt: PrtryAmtAttribute { tp: "".to_string(), amt: AmtAttribute {
-                                ccy: "".to_string(), amt: "".to_string() }, ccy_xchg: CcyXchgAttribute {
+                                ccy: "".to_string(), amt: Money::default() }, ccy_xchg: CcyXchgAttribute {
                                 src_ccy: "".to_string(), trgt_ccy: "".to_string(),
                                 unit_ccy: "".to_string(), xchg_rate: "".to_string() } },
                             prtry_amt: PrtryAmtAttribute { tp: "".to_string(), amt: AmtAttribute {
-                                ccy: "".to_string(), amt: "".to_string() }, ccy_xchg: CcyXchgAttribute {
+                                ccy: "".to_string(), amt: Money::default() }, ccy_xchg: CcyXchgAttribute {
                                 src_ccy: "".to_string(), trgt_ccy: "".to_string(),
                                 unit_ccy: "".to_string(), xchg_rate: "".to_string() } },
-                            amt: "".to_string() }, bk_tx_cd: BxTxCdAttribute { domn: DomnAttribute {
+                            amt: None }, bk_tx_cd: BxTxCdAttribute { domn: DomnAttribute {
                             cd: "".to_string(), fmly: FmlyAttribute { cd: "".to_string(), sub_fmly_cd: "".to_string() } },
                             prtry: PrtryAttribute { cd: "".to_string(), issr: "".to_string() } },
                         rltd_pties: RltdPtiesAttribute { dbtr: DbtrAttribute {
@@ -745,7 +931,7 @@ mod tests {
                                 id: "".to_string(), schme_nm: ShemeNumberAttribute { cd: "".to_string() } } },
                             other: IdDtldAttribute { id: "".to_string() } }, cdtr: CdtrAttribue {
                             id: PrvtIdAttribute { othr: IdDtldAttribute { id: "".to_string() } },
-                            nm: "GOLDMAN".to_string(), pstl_adr: PostalAddressAttribute {
+                            nm: "GOLDMAN SACHS BANK USA".to_string(), pstl_adr: PostalAddressAttribute {
                                 strt_nm: "".to_string(), bldg_nb: "".to_string(),
                                 pst_cd: "".to_string(), twn_nm: "".to_string(),
                                 ctry: "".to_string(), adr_line: Vec::new() } }, cdtr_acct: IdTxDtlsAttribute {
@@ -764,21 +950,159 @@ mod tests {
                                     bldg_nb: "".to_string(), pst_cd: "".to_string(),
                                     twn_nm: "".to_string(), ctry: "".to_string(),
                                     adr_line: Vec::new() } } } }, rmt_inf: RmtInfAttribute {
-                            ustrd: vec!["The".to_string()], strd: StrdAttribute { cdtr_ref_inf:
+                            ustrd: vec!["The maximum length of the block is 65 characters".to_string()], strd: StrdAttribute { cdtr_ref_inf:
                             CdtrRefInfAttribute { tp: CdOrPrtryAttribute {
                                 cd_or_prtry: CdAttribute { cd: "".to_string() } },
                                 ref_cdtr: "".to_string() } } },
-                        rltd_dts: RltdDtsAttribute { accptnc_dt_tm: "".to_string() },
-                        addtl_tx_inf: "Tag".to_string() }] } }] } };
+                        rltd_dts: RltdDtsAttribute { accptnc_dt_tm: None }, tax: TaxAttribute::default(),
+                        addtl_tx_inf: "Tag Payment".to_string() }] } }] } };
         assert_eq!(test, result);
     }
     #[test]
+    fn test_from_read(){
+        let doc = "{1:F01ASNBNL21XXXX0000000000}{2:O940ASNBNL21XXXXN}{3:}{4:
+:20:0000000000
+:25:NL81ASNB9999999999
+:28C:1/1
+:60F:C200101EUR444,29
+:62F:C200101EUR379,29
+-}{5:}
+{1:F01ASNBNL21XXXX0000000000}{2:O940ASNBNL21XXXXN}{3:}{4:
+:20:0000000000
+:25:NL81ASNB9999999999
+:28C:2/1
+:60F:C200102EUR379,29
+:62F:C200102EUR379,29
+-}{5:}";
+        let result = DocumentMt940::from_read(&mut doc.as_bytes()).unwrap();
+        assert_eq!(2, result.document.len());
+        assert_eq!("1", result.document[0].stmt.elctrnc_seq_nb);
+        assert_eq!("2", result.document[1].stmt.elctrnc_seq_nb);
+        assert_eq!("379.29", result.document[1].stmt.bal[1].amt.amt.to_string());
+    }
+    #[test]
+    fn test_from_read_dialect(){
+        let doc = "{1:F01COBADEFFXXXX0000000000}{2:O940COBADEFFXXXXN}{3:}{4:
+:20:0000000000
+:25:DE89370400440532013000
+:28C:1/1
+:60F:C240101EUR0,00
+:61:2401020102C10,00NTRFNONREF
+:86:166?00SEPA-GUTSCHRIFT?20SVWZ+Rechnung 1?32Max Mustermann
+:61:2401020102C5,00NTRFNONREF
+:86:Bareinzahlung
+:62F:C240102EUR15,00
+-}{5:}";
+        let result = DocumentMt940::from_read(&mut doc.as_bytes()).unwrap();
+        let ntry = &result.document[0].stmt.ntry;
+        assert_eq!("Max Mustermann", ntry[0].ntry_dtls.tx_dtls[0].rltd_pties.dbtr.nm);
+        assert_eq!(vec!["Rechnung 1".to_string()], ntry[0].ntry_dtls.tx_dtls[0].rmt_inf.ustrd);
+        assert_eq!(vec!["Bareinzahlung".to_string()], ntry[1].ntry_dtls.tx_dtls[0].rmt_inf.ustrd);
+        let result = DocumentMt940::from_read_with_dialect(&mut doc.as_bytes(), Some(NarrativeDialect::FreeText)).unwrap();
+        let tlds = &result.document[0].stmt.ntry[0].ntry_dtls.tx_dtls[0];
+        assert!(tlds.rltd_pties.dbtr.nm.is_empty());
+        assert_eq!(1, tlds.rmt_inf.ustrd.len());
+    }
+    #[test]
+    fn test_from_read_pages(){
+        let doc = "{1:F01COBADEFFXXXX0000000000}{2:O940COBADEFFXXXXN}{4:
+:20:REF
+:25:DE89370400440532013000
+:28C:7/1
+:60F:C240101EUR100,00
+:61:2401020102C10,00NTRFNONREF
+:62M:C240102EUR110,00
+-}
+{1:F01COBADEFFXXXX0000000000}{2:O940COBADEFFXXXXN}{4:
+:20:REF
+:25:DE89370400440532013000
+:28C:7/2
+:60M:C240102EUR110,00
+:61:2401020102D5,00NTRFNONREF
+:62F:C240102EUR105,00
+:64:C240102EUR105,00
+-}
+{1:F01COBADEFFXXXX0000000000}{2:O940COBADEFFXXXXN}{4:
+:20:REF
+:25:DE89370400440532013000
+:28C:8/1
+:60F:C240103EUR105,00
+:62F:C240103EUR105,00
+-}";
+        let result = DocumentMt940::from_read(&mut doc.as_bytes()).unwrap();
+        assert_eq!(2, result.document.len());
+        let stmt = &result.document[0].stmt;
+        assert_eq!(2, stmt.ntry.len());
+        let balances: Vec<BalanceType> = stmt.bal.iter().map(|bal| bal.tp.cd_or_prtry.cd).collect();
+        assert_eq!(vec![BalanceType::Opbd, BalanceType::Clbd, BalanceType::Itav], balances);
+        assert_eq!("105.00", stmt.bal[1].amt.amt.to_string());
+        assert_eq!("8", result.document[1].stmt.elctrnc_seq_nb);
+        assert_eq!("REF", result.document[0].grp_hdr.msg_id);
+        assert_eq!("REF-940", stmt.id);
+
+        let other = doc.replacen(":20:REF", ":20:OTHER", 1);
+        let result = DocumentMt940::from_read(&mut other.as_bytes()).unwrap();
+        assert_eq!(3, result.document.len());
+        assert_eq!("OTHER", result.document[0].grp_hdr.msg_id);
+
+        let doc = doc.replace(":28C:7/2", ":28C:7/3");
+        let error = DocumentMt940::from_read(&mut doc.as_bytes()).unwrap_err();
+        assert_eq!("Bad input format file: MT940 statement 7 page 3 follows page 1", error.to_string());
+    }
+    #[test]
+    fn test_from_read_bare(){
+        let doc = ":20:STARTUMS
+:25:10020030/1234567
+:28C:0
+:60F:C240101EUR100,00
+:61:2401020102D10,00NMSCNONREF
+:86:005?00LASTSCHRIFT?20Miete
+:62F:C240102EUR90,00
+-
+:20:STARTUMS
+:25:10020030/1234567
+:28C:0
+:60F:C240102EUR90,00
+:62F:C240103EUR90,00
+-
+";
+        let result = DocumentMt940::from_read(&mut doc.as_bytes()).unwrap();
+        assert_eq!(2, result.document.len());
+        assert_eq!("STARTUMS", result.document[0].grp_hdr.msg_id);
+        assert_eq!("10020030/1234567", result.document[0].stmt.acct.ownr.id.org_id.othr.id);
+        assert_eq!(1, result.document[0].stmt.ntry.len());
+        assert_eq!("LASTSCHRIFT", result.document[0].stmt.ntry[0].ntry_dtls.tx_dtls[0].addtl_tx_inf);
+    }
+    #[test]
+    fn test_from_read_bad_record(){
+        let doc = "{1:F01ASNBNL21XXXX0000000000}{2:O940ASNBNL21XXXXN}{3:}{4:
+:20:0000000000
+:28C:1/1
+-}{5:}
+{1:F01ASNBNL21XXXX0000000000}{2:O940ASNBNL21XXXXN}{3:}{5:}";
+        let result = DocumentMt940::from_read(&mut doc.as_bytes());
+        assert_eq!("Bad input format file: Error parse MT940 record 2 at offset 89: \
+                    Bad input format file: Missing block 4",
+                   result.err().unwrap().to_string());
+    }
+    #[test]
+    fn test_from_read_bad_date(){
+        let doc = "{1:F01ASNBNL21XXXX0000000000}{2:O940ASNBNL21XXXXN}{3:}{4:
+:20:0000000000
+:28C:1/1
+:60F:C250230EUR1,00
+-}{5:}";
+        let result = DocumentMt940::from_read(&mut doc.as_bytes());
+        assert_eq!("Bad input format file: Error parse MT940 record 1 at offset 0: Bad date: 250230",
+                   result.err().unwrap().to_string());
+    }
+    #[test]
     fn test_extract_field_6x_mt940(){
         let document = BkToCstmrStmt { grp_hdr: HeaderAttribute {
-            msg_id: "GSCRUS30XXXXN".to_string(), cre_dt_tm: "".to_string() },
+            msg_id: "GSCRUS30XXXXN".to_string(), cre_dt_tm: None },
             stmt: StatementAttribute { id: "GSCRUS30XXXXN-940".to_string(),
-                elctrnc_seq_nb: "49".to_string(), lgl_seq_nb: "2".to_string(), cre_dt_tm: "".to_string(),
-                fr_to_dt: FromToDtAttribute { fr_dt_tm: "".to_string(), to_dt_tm: "".to_string() },
+                elctrnc_seq_nb: "49".to_string(), lgl_seq_nb: "2".to_string(), cre_dt_tm: None,
+                fr_to_dt: FromToDtAttribute { fr_dt_tm: None, to_dt_tm: None },
                 acct: AcctAttribute { id: IdIbanAttribute { iban: "".to_string(),
                     othr: OtherAttribute { id: "".to_string(),
                         schme_nm: ShemeNumberAttribute { cd: "".to_string() } } },
@@ -801,31 +1125,31 @@ mod tests {
                         pstl_adr: Default::default(),
                     } } },
                 bal: vec![BalanceAttribute { tp: TpBalanceAttribute {
-                    cd_or_prtry: CdAttribute { cd: "OPAV".to_string() } },
-                    amt: AmtAttribute { ccy: "USD".to_string(), amt: "2732398848.02".to_string() },
-                    cdt_dbt_ind: "".to_string(),
-                    dt: DtAttribute { dt: "2025-02-18".to_string() }, cd: "C".to_string() }],
+                    cd_or_prtry: BalanceCdAttribute { cd: BalanceType::Opav } },
+                    amt: AmtAttribute { ccy: "USD".to_string(), amt: "2732398848.02".parse().unwrap() },
+                    cdt_dbt_ind: CreditDebit::Credit,
+                    dt: DtAttribute { dt: NaiveDate::from_ymd_opt(2025, 2, 18) } }],
+                flr_lmt: vec![],
                 txs_summry: TxsSummryAttribute { ttl_ntries: TtlNtriesAttribute {
-                    nb_of_ntries: "".to_string(), ttl_net_ntry_amt: 0.0,
-                    cdt_dbt_ind: "".to_string() }, ttl_cdt_ntries: TtlCdtDbtNtriesAttribute {
-                    nb_of_ntries: 0, sum: "".to_string() },
-                    ttl_dbt_ntries: TtlCdtDbtNtriesAttribute { nb_of_ntries: 0,
-                        sum: "".to_string() } }, ntry: Vec::new()} };
+                    nb_of_ntries: None, ttl_net_ntry_amt: Money::default(),
+                    cdt_dbt_ind: None }, ttl_cdt_ntries: None,
+                    ttl_dbt_ntries: None, ccy: String::new() }, ntry: Vec::new()} };
         let mut result = String::new();
-        DocumentMt940::extract_field_6x_mt940(&document, &mut result);
-        assert_eq!(":60M:C250218USD2732398848,02\n".to_string(), result);
+        DocumentMt940::extract_field_6x_mt940(&document, &mut result, false);
+        assert_eq!(":60M:C250218USD2732398848,02\r\n".to_string(), result);
     }
     #[test]
     fn test_extract_field_61_86_mt940(){
         let vec_camt = vec![NtryAttribute { ntry_ref: 0,
-                    amt: AmtAttribute { ccy: "USD".to_string(), amt: "12.01".to_string() },
-                    cdt_dbt_ind: "DBIT".to_string(), sts: "".to_string(), bookg_dt: DtAttribute {
-                        dt: "2025-02-18".to_string() }, val_dt: DtAttribute { dt: "2025-02-18".to_string() },
+                    amt: AmtAttribute { ccy: "USD".to_string(), amt: "12.01".parse().unwrap() },
+                    cdt_dbt_ind: CreditDebit::Debit, sts: "".to_string(), bookg_dt: DtAttribute {
+                        dt: NaiveDate::from_ymd_opt(2025, 2, 18) }, val_dt: DtAttribute { dt: NaiveDate::from_ymd_opt(2025, 2, 18) },
                     acct_svcr_ref: "".to_string(), bk_tx_cd: BxTxCdAttribute {
                         domn: DomnAttribute { cd: "".to_string(), fmly: FmlyAttribute {
                             cd: "".to_string(), sub_fmly_cd: "".to_string() } },
                         prtry: PrtryAttribute { cd: "NTRF".to_string(), issr: "".to_string() } },
                     addtl_inf_ind: AddtlTxInfAtttribute { msg_nm_id: "".to_string() },
+            rvsl_ind: false, funds_code: "".to_string(), addtl_ntry_inf: "".to_string(),
                     ntry_dtls: NtryDtlsAttribute { btch: BtchAttribute {
                         nb_of_txs: 0 }, tx_dtls: vec![TxDtlsAttribute {
                         refs: EndToEndIdAttribute { pmt_inf_id: "".to_string(),
@@ -833,17 +1157,17 @@ mod tests {
                             tx_id: "".to_string(), prtry: PrtryDetAttribute { tp: "".to_string(),
                                 refdt: "".to_string() } }, amt_dtls: TxAmtAttribute {
                             end_to_end_id: "".to_string(), instd_amt: PrtryAmtAttribute {
-                                tp: "".to_string(), amt: AmtAttribute { ccy: "".to_string(), amt: "".to_string() },
+                                tp: "".to_string(), amt: AmtAttribute { ccy: "".to_string(), amt: Money::default() },
                                 ccy_xchg: CcyXchgAttribute { src_ccy: "".to_string(),
                                     trgt_ccy: "".to_string(), unit_ccy: "".to_string(), xchg_rate: "".to_string() } },
                             tx_amt: PrtryAmtAttribute { tp: "".to_string(), amt: AmtAttribute {
-                                ccy: "".to_string(), amt: "".to_string() }, ccy_xchg: CcyXchgAttribute {
+                                ccy: "".to_string(), amt: Money::default() }, ccy_xchg: CcyXchgAttribute {
                                 src_ccy: "".to_string(), trgt_ccy: "".to_string(), unit_ccy: "".to_string(),
                                 xchg_rate: "".to_string() } }, prtry_amt: PrtryAmtAttribute {
-                                tp: "".to_string(), amt: AmtAttribute { ccy: "".to_string(), amt: "".to_string() },
+                                tp: "".to_string(), amt: AmtAttribute { ccy: "".to_string(), amt: Money::default() },
                                 ccy_xchg: CcyXchgAttribute { src_ccy: "".to_string(),
                                     trgt_ccy: "".to_string(), unit_ccy: "".to_string(), xchg_rate: "".to_string() } },
-                            amt: "".to_string() }, bk_tx_cd: BxTxCdAttribute { domn: DomnAttribute {
+                            amt: None }, bk_tx_cd: BxTxCdAttribute { domn: DomnAttribute {
                             cd: "".to_string(), fmly: FmlyAttribute { cd: "".to_string(), sub_fmly_cd: "".to_string() } },
                             prtry: PrtryAttribute { cd: "".to_string(), issr: "".to_string() } },
                         rltd_pties: RltdPtiesAttribute { dbtr: DbtrAttribute {
@@ -855,7 +1179,7 @@ mod tests {
                                 schme_nm: ShemeNumberAttribute { cd: "".to_string() } } },
                             other: IdDtldAttribute { id: "".to_string() } }, cdtr: CdtrAttribue {
                             id: PrvtIdAttribute { othr: IdDtldAttribute { id: "".to_string() } },
-                            nm: "GOLDMAN".to_string(), pstl_adr: PostalAddressAttribute { strt_nm: "".to_string(),
+                            nm: "GOLDMAN SACHS BANK USA".to_string(), pstl_adr: PostalAddressAttribute { strt_nm: "".to_string(),
                                 bldg_nb: "".to_string(), pst_cd: "".to_string(), twn_nm: "".to_string(),
                                 ctry: "".to_string(), adr_line: Vec::new()} },
                             cdtr_acct: IdTxDtlsAttribute { id: IdIbanAttribute { iban: "".to_string(),
@@ -871,15 +1195,47 @@ mod tests {
                                     bldg_nb: "".to_string(), pst_cd: "".to_string(), twn_nm: "".to_string(),
                                     ctry: "".to_string(),
                                     adr_line: Vec::new() } } } }, rmt_inf: RmtInfAttribute {
-                            ustrd: vec!["USD".to_string()], strd: StrdAttribute { cdtr_ref_inf:
+                            ustrd: vec!["USD Payment to Vendor".to_string()], strd: StrdAttribute { cdtr_ref_inf:
                             CdtrRefInfAttribute { tp: CdOrPrtryAttribute { cd_or_prtry:
                             CdAttribute { cd: "".to_string() } }, ref_cdtr: "".to_string() } } },
-                        rltd_dts: RltdDtsAttribute { accptnc_dt_tm: "".to_string() },
-                        addtl_tx_inf: "Tag".to_string() }] } }];
+                        rltd_dts: RltdDtsAttribute { accptnc_dt_tm: None }, tax: TaxAttribute::default(),
+                        addtl_tx_inf: "Tag Payment".to_string() }] } }];
         let mut result = String::new();
-        let test = ":61:2502180218D12,01NTRFGSLNVSHSUTKWDR \n:86:/NREF/GSLNVSHSUTKWDR\n\
-        /CRNM/GOLDMAN\n/CACT/107045863\n/CBIC/GSCRUS30XXX\n/REMI/USD/\n/OPRP/Tag\n";
+        let test = ":61:2502180218D12,01NTRFGSLNVSHSUTKWDR\r\n\
+        :86:/NREF/GSLNVSHSUTKWDR/CRNM/GOLDMAN SACHS BANK USA/CACT/107045863\r\n\
+        /CBIC/GSCRUS30XXX/REMI/USD Payment to Vendor/OPRP/Tag Payment\r\n";
         DocumentMt940::extract_field_61_86_mt940(&vec_camt, &mut result);
         assert_eq!(test, result);
     }
+    #[test]
+    fn test_write_swift_rules(){
+        let mut statement = BkToCstmrStmt::default();
+        statement.grp_hdr.msg_id = "Выписка-2024-000000001".to_string();
+        statement.stmt.acct.id.othr.id = "40702810000000000001".to_string();
+        statement.stmt.acct.svcr.fin_instn_id.bic = "SABRRUMMXXX".to_string();
+        statement.stmt.elctrnc_seq_nb = "2024-17".to_string();
+        let mut ntry = NtryAttribute::default();
+        ntry.bookg_dt.dt = NaiveDate::from_ymd_opt(2024, 2, 20);
+        ntry.amt.amt = "1540.00".parse().unwrap();
+        ntry.cdt_dbt_ind = CreditDebit::Debit;
+        ntry.bk_tx_cd.prtry.cd = "01".to_string();
+        let mut tlds = TxDtlsAttribute::default();
+        tlds.refs.end_to_end_id = "ПП-1234567890123456".to_string();
+        tlds.rltd_pties.cdtr.nm = "ООО «Ромашка»".to_string();
+        tlds.rmt_inf.ustrd.push("Оплата по счету ".repeat(30));
+        ntry.ntry_dtls.tx_dtls.push(tlds);
+        statement.stmt.ntry.push(ntry);
+        let mut result = String::new();
+        DocumentMt940::extract_header_mt940(&statement, &mut result);
+        DocumentMt940::extract_field_61_86_mt940(&statement.stmt.ntry, &mut result);
+        DocumentMt940::extract_trailer_mt940(&mut result);
+        assert!(result.starts_with("{1:F01SABRRUMMAXXX0000000000}{2:I940SABRRUMMAXXXN}{4:\r\n\
+            :20:VYPISKA-2024-000\r\n:25:40702810000000000001\r\n:28C:02417\r\n\
+            :61:2402200220D1540,00NMSCPP-1234567890123\r\n:86:/NREF/PP-1234567890123456/CRNM/OOO 'ROMAQKA'\r\n/REMI/OPLATA"));
+        assert!(result.ends_with("\r\n-}\r\n"));
+        let lines: Vec<&str> = result.split("\r\n").collect();
+        assert!(lines.iter().all(|line| line.len() <= 69 && line.chars().all(|c| swift::is_swift_x(c) || "{}".contains(c))));
+        let field_86 = lines.iter().position(|line| line.starts_with(":86:")).unwrap();
+        assert_eq!("-}", lines[field_86 + 6]);
+    }
 }
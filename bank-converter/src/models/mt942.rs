@@ -0,0 +1,220 @@
+//! Модуль представляет модель для формата промежуточного отчета MT942
+//!
+//! Сообщение MT942 (Interim Transaction Report) содержит те же поля, что и MT940 (:20:, :25:, :28C:,
+//! :61:, :86:), но вместо остатков - минимальные суммы записей :34F:, дату и время отчета :13D:
+//! и итоги по дебету и кредиту :90D:/:90C:. Поля переносятся в модель по смыслу отчета camt.052:
+//! :13D: - дата создания отчета (CreDtTm), :90D:/:90C: - итоги TxsSummry (TtlDbtNtries, TtlCdtNtries).
+//!
+
+use std::sync::LazyLock;
+use regex::Regex;
+use crate::errors::{ConvertError, ParserError};
+use crate::models::camt053::{AmtAttribute, BkToCstmrStmt, FloorLimitAttribute, TtlCdtDbtNtriesAttribute};
+use crate::models::codes::CreditDebit;
+use crate::models::date::format_yymmddhhmm;
+use crate::models::mt940::DocumentMt940;
+
+/// Поле :34F: - валюта 3!a, признак [1!a] и сумма 15d
+static FIELD_34F: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^([A-Z]{3})([DC])?(\d+,\d*)$").unwrap());
+
+/// Поля :90D: и :90C: - количество записей 5n, валюта 3!a и сумма 15d
+static FIELD_90: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^(\d{1,5})([A-Z]{3})(\d+,\d*)$").unwrap());
+
+/// Структура для документа MT942
+/// Содержит методы для чтения, транофрмации и записи данных MT942
+/// ```ignore
+///
+/// pub fn from_read<R: Read>(r: &mut R) -> Result<Self, ParserError>
+/// pub fn write_to<W: Write>(&mut self, writer: &mut W) -> Result<(), ConvertError>
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DocumentMt942 {
+    /// Отчеты документа, по одному на каждое сообщение MT942
+    pub document: Vec<BkToCstmrStmt>
+}
+
+impl DocumentMt942 {
+    /// Создает пустой документ MT942.
+    pub fn new() -> Self {
+        DocumentMt942::default()
+    }
+
+    /// Разбирает поле :34F: - минимальную сумму записей. Единственное поле без признака относится
+    /// к дебетовым и кредитовым записям, пара полей - сначала к дебетовым (`D`), затем к кредитовым (`C`).
+    pub(crate) fn parse_field_34f(field: &str) -> Result<FloorLimitAttribute, ParserError> {
+        let field = field.trim();
+        let Some(capture) = FIELD_34F.captures(field) else {
+            return Err(ParserError::BadInputFormatFile(format!("Unparsable field :34F:{}", field)));
+        };
+        let cdt_dbt_ind = match capture.get(2) {
+            Some(mark) => Some(CreditDebit::from_mt940(mark.as_str())?),
+            None => None,
+        };
+        Ok(FloorLimitAttribute { amt: AmtAttribute::new(capture[3].parse()?, &capture[1]), cdt_dbt_ind })
+    }
+
+    /// Разбирает поле :90D: или :90C: - количество и сумму дебетовых или кредитовых записей отчета.
+    pub(crate) fn parse_field_90(field: &str) -> Result<TtlCdtDbtNtriesAttribute, ParserError> {
+        let field = field.trim();
+        let Some(capture) = FIELD_90.captures(field) else {
+            return Err(ParserError::BadInputFormatFile(format!("Unparsable field :90:{}", field)));
+        };
+        let nb_of_ntries = capture[1].parse()
+            .map_err(|_| ParserError::BadInputFormatFile(format!("Unparsable field :90:{}", field)))?;
+        Ok(TtlCdtDbtNtriesAttribute { nb_of_ntries, sum: capture[3].parse()? })
+    }
+
+    /// Валюта отчета: валюта минимальной суммы, счета или первого остатка.
+    pub(crate) fn currency(record: &BkToCstmrStmt) -> &str {
+        let stmt = &record.stmt;
+        stmt.flr_lmt.iter().map(|limit| &limit.amt.ccy)
+            .chain([&stmt.acct.ccy])
+            .chain(stmt.bal.iter().map(|bal| &bal.amt.ccy))
+            .find(|ccy| !ccy.is_empty())
+            .map_or("", |ccy| ccy.as_str())
+    }
+
+    /// Записывает заголовки сообщения MT942, поля :20:, :25:, :28C:, минимальные суммы :34F:
+    /// и дату и время отчета :13D:.
+    ///
+    /// Без минимальных сумм записывается нулевая сумма в валюте отчета - в отчет включены все записи.
+    /// Дата и время отчета - дата создания выписки, сообщения или конец периода выписки.
+    ///
+    /// Поля :34F: и :13D: обязательны: если у выписки нет ни валюты, ни даты создания или конца
+    /// периода, возвращается `ConvertError::WriteError`, и заголовок не записывается.
+    pub(crate) fn extract_header_mt942(record: &BkToCstmrStmt, record_write: &mut String) -> Result<(), ConvertError> {
+        let stmt = &record.stmt;
+        let currency = DocumentMt942::currency(record);
+        if currency.is_empty() {
+            return Err(ConvertError::WriteError(format!("No currency for field :34F: of statement {}", stmt.id)));
+        }
+        let Some(created) = stmt.cre_dt_tm.or(record.grp_hdr.cre_dt_tm).or(stmt.fr_to_dt.to_dt_tm) else {
+            return Err(ConvertError::WriteError(format!("No date for field :13D: of statement {}", stmt.id)));
+        };
+        DocumentMt940::extract_header(record, "942", record_write);
+        if stmt.flr_lmt.is_empty() {
+            DocumentMt940::push_field(record_write, "34F", &format!("{}0,", currency));
+        }
+        for limit in stmt.flr_lmt.iter().take(2) {
+            let mark = limit.cdt_dbt_ind.map_or("", |cdt_dbt_ind| cdt_dbt_ind.mt940());
+            DocumentMt940::push_field(record_write, "34F",
+                                      &format!("{}{}{}", limit.amt.ccy, mark, limit.amt.amt.to_mt940()));
+        }
+        DocumentMt940::push_field(record_write, "13D", &format_yymmddhhmm(created));
+        Ok(())
+    }
+
+    /// Записывает итоги отчета :90D: и :90C: в валюте `currency`.
+    pub(crate) fn extract_field_90_mt942(debit: &TtlCdtDbtNtriesAttribute, credit: &TtlCdtDbtNtriesAttribute,
+                                         currency: &str, record_write: &mut String) {
+        for (tag, total) in [("90D", debit), ("90C", credit)] {
+            DocumentMt940::push_field(record_write, tag,
+                                      &format!("{}{}{}", total.nb_of_ntries, currency, total.sum.to_mt940()));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+    use crate::models::camt053::BalanceAttribute;
+    use crate::models::date::IsoDateTime;
+    use crate::models::money::Money;
+    use crate::models::narrative::NarrativeDialect;
+
+    #[test]
+    fn test_parse_field_34f() {
+        let limit = DocumentMt942::parse_field_34f("EUR0,").unwrap();
+        assert_eq!(AmtAttribute::new(Money::new(0, 0), "EUR"), limit.amt);
+        assert_eq!(None, limit.cdt_dbt_ind);
+        let limit = DocumentMt942::parse_field_34f("EURD100,00").unwrap();
+        assert_eq!(Some(CreditDebit::Debit), limit.cdt_dbt_ind);
+        assert_eq!("100.00", limit.amt.amt.to_string());
+        assert!(DocumentMt942::parse_field_34f("EUR100").is_err());
+        assert!(DocumentMt942::parse_field_34f("EURX1,").is_err());
+    }
+
+    #[test]
+    fn test_parse_field_90() {
+        let total = DocumentMt942::parse_field_90("2EUR300,00").unwrap();
+        assert_eq!(2, total.nb_of_ntries);
+        assert_eq!(Money::new(30000, 2), total.sum);
+        assert!(DocumentMt942::parse_field_90("EUR300,00").is_err());
+        assert!(DocumentMt942::parse_field_90("123456EUR1,").is_err());
+    }
+
+    #[test]
+    fn test_parse_record() {
+        let doc = "{1:F01DEUTDEFFXXXX0000000000}{2:O942DEUTDEFFXXXXN}{3:}{4:
+                   :20:INTRADAY0001
+                   :25:DE89370400440532013000
+                   :28C:5/1
+                   :34F:EURD100,00
+                   :34F:EURC200,00
+                   :13D:2402201530+0100
+                   :61:2402200220C1500,00NTRFREF-0001//BANKREF0001
+                   :86:/EREF/REF-0001/CRNM/ACME GMBH
+                   :90D:0EUR0,
+                   :90C:1EUR1500,00
+                   -}{5:}";
+        let record = DocumentMt940::parse_record(doc, "942", NarrativeDialect::Swift).unwrap();
+        assert_eq!("INTRADAY0001-942", record.stmt.id);
+        assert_eq!("2024-02-20T15:30:00+01:00", record.stmt.cre_dt_tm.unwrap().to_string());
+        assert_eq!(2, record.stmt.flr_lmt.len());
+        assert_eq!(Some(CreditDebit::Credit), record.stmt.flr_lmt[1].cdt_dbt_ind);
+        assert_eq!(1, record.stmt.txs_summry.credit().nb_of_ntries);
+        assert_eq!(Money::new(150000, 2), record.stmt.txs_summry.credit().sum);
+        assert_eq!(Some(0), record.stmt.txs_summry.ttl_dbt_ntries.map(|total| total.nb_of_ntries));
+        assert_eq!("EUR", record.stmt.ntry[0].amt.ccy);
+        assert_eq!("BANKREF0001", record.stmt.ntry[0].acct_svcr_ref);
+
+        let bare = ":20:INTRADAY0001\n:25:DE89370400440532013000\n:28C:5/1\n:34F:EUR0,\n:13D:2402201530+0100\n-";
+        let record = DocumentMt940::parse_record(bare, "942", NarrativeDialect::Swift).unwrap();
+        assert_eq!("INTRADAY0001-942", record.stmt.id);
+        let bad = ":20:INTRADAY0001\n:25:DE89370400440532013000\n:13D:2402201530\n-";
+        assert!(DocumentMt940::parse_record(bad, "942", NarrativeDialect::Swift).is_err());
+        let bad = ":20:INTRADAY0001\n:25:DE89370400440532013000\n:13D:24022é530+0100\n-";
+        assert!(DocumentMt940::parse_record(bad, "942", NarrativeDialect::Swift).is_err());
+    }
+
+    #[test]
+    fn test_extract_header_mt942() {
+        let mut record = BkToCstmrStmt::default();
+        record.grp_hdr.msg_id = "INTRADAY0001".to_string();
+        record.stmt.acct.id.iban = "DE89370400440532013000".to_string();
+        record.stmt.acct.svcr.fin_instn_id.bic = "DEUTDEFF".to_string();
+        record.stmt.elctrnc_seq_nb = "5".to_string();
+        record.stmt.bal.push(BalanceAttribute { amt: AmtAttribute::new(Money::new(100, 2), "EUR"), ..Default::default() });
+        record.grp_hdr.cre_dt_tm = NaiveDate::from_ymd_opt(2024, 2, 20)
+            .map(|date| IsoDateTime::new(date.and_hms_opt(15, 30, 0).unwrap(), None));
+        let mut result = String::new();
+        DocumentMt942::extract_header_mt942(&record, &mut result).unwrap();
+        assert_eq!("{1:F01DEUTDEFFAXXX0000000000}{2:I942DEUTDEFFAXXXN}{4:\r\n:20:INTRADAY0001\r\n\
+                    :25:DE89370400440532013000\r\n:28C:5\r\n:34F:EUR0,\r\n:13D:2402201530+0000\r\n", result);
+
+        record.stmt.flr_lmt = vec![DocumentMt942::parse_field_34f("EURD100,00").unwrap(),
+                                   DocumentMt942::parse_field_34f("EURC200,00").unwrap()];
+        let mut result = String::new();
+        DocumentMt942::extract_header_mt942(&record, &mut result).unwrap();
+        assert!(result.contains(":34F:EURD100,00\r\n:34F:EURC200,00\r\n:13D:"), "{}", result);
+
+        let mut undated = record.clone();
+        undated.grp_hdr.cre_dt_tm = None;
+        let mut result = String::new();
+        let error = DocumentMt942::extract_header_mt942(&undated, &mut result).unwrap_err();
+        assert!(error.to_string().contains(":13D:"), "{}", error);
+        assert!(result.is_empty());
+        let mut no_currency = record.clone();
+        no_currency.stmt.flr_lmt.clear();
+        no_currency.stmt.bal.clear();
+        let error = DocumentMt942::extract_header_mt942(&no_currency, &mut result).unwrap_err();
+        assert!(error.to_string().contains(":34F:"), "{}", error);
+        assert!(result.is_empty());
+
+        let mut result = String::new();
+        let total = TtlCdtDbtNtriesAttribute { nb_of_ntries: 2, sum: Money::new(30000, 2) };
+        DocumentMt942::extract_field_90_mt942(&TtlCdtDbtNtriesAttribute::default(), &total, "EUR", &mut result);
+        assert_eq!(":90D:0EUR0,\r\n:90C:2EUR300,00\r\n", result);
+    }
+}
@@ -0,0 +1,445 @@
+//! Модуль разбора поля :86: MT940
+//!
+//! Банки заполняют описание операции в поле :86: по-разному. Поддерживаются диалекты:
+//! * `Swift` - коды `/EREF/`, `/CRNM/`, `/REMI/` и т.д. (так же записывает поле конвертер);
+//! * `Gvc` - немецкий формат с кодом операции GVC и подполями `?00` - `?63`
+//!   (Deutsche Bank, Commerzbank, Sparkassen);
+//! * `Dutch` - нидерландский формат `/TRTP/.../IBAN/.../NAME/...` (ING, Rabobank, ABN AMRO);
+//! * `FreeText` - произвольный текст, строки которого становятся назначением платежа.
+//!
+//! Диалект определяется по всем полям :86: файла (`NarrativeDialect::detect_document`)
+//! или задается явно.
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+use std::sync::LazyLock;
+use regex::Regex;
+use crate::errors::ParserError;
+use crate::models::camt053::{IdTxDtlsAttribute, PostalAddressAttribute, PrvtIdAttribute, TxDtlsAttribute};
+use crate::models::codes::{is_iban, CreditDebit};
+
+/// Коды диалекта SWIFT
+static SWIFT_CODES: LazyLock<Regex> = LazyLock::new(|| Regex::new(
+    r"/(EREF|NREF|CRNM|CACT|CBIC|DRNM|DACT|DBIC|REMI|OPRP|OAMT|DCID)/").unwrap());
+
+/// Коды нидерландского диалекта
+static DUTCH_CODES: LazyLock<Regex> = LazyLock::new(|| Regex::new(
+    r"/(TRTP|IBAN|BIC|NAME|ADDR|ID|CNTP|ORDP|BENM|REMI|EREF|MARF|CSID|PURP|RTRN|ULTC|ULTD)/").unwrap());
+
+/// Коды, которые встречаются только в нидерландском диалекте
+static DUTCH_ONLY: LazyLock<Regex> = LazyLock::new(|| Regex::new(
+    r"/(TRTP|IBAN|NAME|CNTP|ORDP|BENM|MARF|CSID)/").unwrap());
+
+/// Поле немецкого диалекта: трехзначный код GVC и подполя `?NN`
+static GVC_FIELD: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?s)^(\d{3})(.*?\?\d{2}.*)$").unwrap());
+
+/// Подполе немецкого диалекта
+static GVC_SUBFIELD: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\?(\d{2})").unwrap());
+
+/// Ключевые слова SEPA в назначении платежа немецкого диалекта
+static SEPA_KEYWORDS: LazyLock<Regex> = LazyLock::new(|| Regex::new(
+    r"(EREF|KREF|MREF|CRED|DEBT|SVWZ|ABWA|ABWE)\+").unwrap());
+
+/// Диалект поля :86: MT940
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NarrativeDialect {
+    /// Коды SWIFT `/CODE/значение`
+    #[default]
+    Swift,
+    /// Немецкий формат GVC с подполями `?NN`
+    Gvc,
+    /// Нидерландский формат `/TRTP/`
+    Dutch,
+    /// Произвольный текст
+    FreeText,
+}
+
+/// Участник операции в описании: плательщик или получатель
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Party {
+    Debtor,
+    Creditor,
+}
+
+impl Party {
+    /// Контрагент записи: для кредитовой записи - плательщик, для дебетовой - получатель.
+    fn counterparty(cdt_dbt_ind: CreditDebit) -> Self {
+        match cdt_dbt_ind {
+            CreditDebit::Credit => Party::Debtor,
+            CreditDebit::Debit => Party::Creditor,
+        }
+    }
+}
+
+/// Поля участника операции
+struct PartyFields<'a> {
+    nm: &'a mut String,
+    id: &'a mut PrvtIdAttribute,
+    pstl_adr: &'a mut PostalAddressAttribute,
+    acct: &'a mut IdTxDtlsAttribute,
+    bic: &'a mut String,
+}
+
+impl<'a> PartyFields<'a> {
+    fn of(tlds: &'a mut TxDtlsAttribute, party: Party) -> Self {
+        let pties = &mut tlds.rltd_pties;
+        let agts = &mut tlds.rltd_agts;
+        match party {
+            Party::Debtor => PartyFields { nm: &mut pties.dbtr.nm, id: &mut pties.dbtr.id,
+                pstl_adr: &mut pties.dbtr.pstl_adr, acct: &mut pties.dbtr_acct, bic: &mut agts.dbtr_agt.fin_instn_id.bic },
+            Party::Creditor => PartyFields { nm: &mut pties.cdtr.nm, id: &mut pties.cdtr.id,
+                pstl_adr: &mut pties.cdtr.pstl_adr, acct: &mut pties.cdtr_acct, bic: &mut agts.cdtr_agt.fin_instn_id.bic },
+        }
+    }
+
+    /// Записывает номер счета: IBAN в `Id/IBAN`, иной номер - в `Other/Id`.
+    fn set_account(&mut self, account: &str) {
+        if is_iban(account) {
+            self.acct.id.iban = account.to_string();
+        } else {
+            self.acct.other.id = account.to_string();
+        }
+    }
+}
+
+impl NarrativeDialect {
+    /// Определяет диалект одного поля :86:.
+    pub fn detect(field: &str) -> Self {
+        let field = join_lines(field);
+        if GVC_FIELD.is_match(&field) {
+            NarrativeDialect::Gvc
+        } else if DUTCH_ONLY.is_match(&field) {
+            NarrativeDialect::Dutch
+        } else if SWIFT_CODES.is_match(&field) {
+            NarrativeDialect::Swift
+        } else {
+            NarrativeDialect::FreeText
+        }
+    }
+
+    /// Определяет диалект файла по его полям :86:: выбирается структурированный диалект,
+    /// которому соответствует больше всего полей, а если таких нет - произвольный текст.
+    pub fn detect_document<'a, I: IntoIterator<Item = &'a str>>(fields: I) -> Self {
+        let detected: Vec<NarrativeDialect> = fields.into_iter().map(NarrativeDialect::detect).collect();
+        [NarrativeDialect::Swift, NarrativeDialect::Dutch, NarrativeDialect::Gvc].into_iter()
+            .map(|dialect| (detected.iter().filter(|found| **found == dialect).count(), dialect))
+            .filter(|(count, _)| *count > 0)
+            .max_by_key(|(count, _)| *count)
+            .map_or(NarrativeDialect::FreeText, |(_, dialect)| dialect)
+    }
+
+    /// Разбирает поле :86: записи с признаком `cdt_dbt_ind` в детали операции `tlds`.
+    ///
+    /// Поле, в котором нет подполей диалекта, разбирается как произвольный текст.
+    pub fn parse(self, field: &str, cdt_dbt_ind: CreditDebit, tlds: &mut TxDtlsAttribute) {
+        let parsed = match self {
+            NarrativeDialect::Swift => parse_swift(field, tlds),
+            NarrativeDialect::Gvc => parse_gvc(field, cdt_dbt_ind, tlds),
+            NarrativeDialect::Dutch => parse_dutch(field, cdt_dbt_ind, tlds),
+            NarrativeDialect::FreeText => false,
+        };
+        if !parsed {
+            parse_free_text(field, tlds);
+        }
+    }
+}
+
+impl FromStr for NarrativeDialect {
+    type Err = ParserError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_lowercase().as_str() {
+            "swift" => Ok(NarrativeDialect::Swift),
+            "gvc" | "german" => Ok(NarrativeDialect::Gvc),
+            "dutch" | "trtp" => Ok(NarrativeDialect::Dutch),
+            "text" | "free" => Ok(NarrativeDialect::FreeText),
+            _ => Err(ParserError::BadInputFormatFile(format!("Unsupported :86: dialect {}", value))),
+        }
+    }
+}
+
+impl Display for NarrativeDialect {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NarrativeDialect::Swift => write!(f, "SWIFT"),
+            NarrativeDialect::Gvc => write!(f, "GVC"),
+            NarrativeDialect::Dutch => write!(f, "TRTP"),
+            NarrativeDialect::FreeText => write!(f, "TEXT"),
+        }
+    }
+}
+
+/// Склеивает строки продолжения поля: перенос строки в MT940 не разделяет слова.
+fn join_lines(field: &str) -> String {
+    field.lines().map(str::trim).collect()
+}
+
+/// Разбивает текст на пары (код, значение) по кодам `codes`; завершающий `/` значения отбрасывается.
+fn subfields<'a>(text: &'a str, codes: &Regex) -> Vec<(&'a str, &'a str)> {
+    let found: Vec<_> = codes.captures_iter(text).collect();
+    found.iter().enumerate().filter_map(|(index, capture)| {
+        let (whole, code) = (capture.get(0)?, capture.get(1)?);
+        let end = found.get(index + 1).and_then(|next| next.get(0)).map_or(text.len(), |next| next.start());
+        let value = text[whole.end()..end].trim();
+        Some((code.as_str(), value.strip_suffix('/').unwrap_or(value).trim()))
+    }).collect()
+}
+
+/// Разбирает коды SWIFT `/CODE/значение`. Возвращает `false`, если кодов нет.
+fn parse_swift(field: &str, tlds: &mut TxDtlsAttribute) -> bool {
+    let field = join_lines(field);
+    let subfields = subfields(&field, &SWIFT_CODES);
+    for (code, value) in &subfields {
+        let value = value.to_string();
+        match *code {
+            "EREF" => tlds.refs.end_to_end_id = value,
+            "NREF" => {
+                tlds.refs.prtry.refdt = value;
+                tlds.refs.prtry.tp = "NREF".to_string();
+            }
+            "CRNM" => tlds.rltd_pties.cdtr.nm = value,
+            "CACT" => tlds.rltd_pties.cdtr_acct.other.id = value,
+            "CBIC" => tlds.rltd_agts.cdtr_agt.fin_instn_id.bic = value,
+            "DRNM" => tlds.rltd_pties.dbtr.nm = value,
+            "DACT" => tlds.rltd_pties.dbtr_acct.other.id = value,
+            "DBIC" => tlds.rltd_agts.dbtr_agt.fin_instn_id.bic = value,
+            "REMI" => tlds.rmt_inf.ustrd.push(value),
+            "OPRP" => tlds.addtl_tx_inf = value,
+            // значение, не являющееся суммой (например, `11-25` в выписках GS), не переносится
+            "OAMT" => tlds.amt_dtls.amt = value.parse().ok(),
+            "DCID" => tlds.rltd_pties.dbtr.id.othr.id = value,
+            _ => {}
+        }
+    }
+    !subfields.is_empty()
+}
+
+/// Разбирает немецкий формат: код GVC, текст проводки `?00`, назначение `?20` - `?29` и
+/// `?60` - `?63`, банк `?30`, счет `?31` и наименование `?32` - `?33` контрагента.
+/// Возвращает `false`, если поле не в этом формате.
+fn parse_gvc(field: &str, cdt_dbt_ind: CreditDebit, tlds: &mut TxDtlsAttribute) -> bool {
+    let field = join_lines(field);
+    let Some(capture) = GVC_FIELD.captures(&field) else {
+        return false;
+    };
+    tlds.bk_tx_cd.prtry.cd = capture[1].to_string();
+    tlds.bk_tx_cd.prtry.issr = "DK".to_string();
+    let body = &capture[2];
+    let tags: Vec<_> = GVC_SUBFIELD.captures_iter(body).collect();
+    let mut purpose = String::new();
+    let mut name = String::new();
+    let counterparty = Party::counterparty(cdt_dbt_ind);
+    for (index, capture) in tags.iter().enumerate() {
+        let (Some(whole), Some(tag)) = (capture.get(0), capture.get(1)) else {
+            continue;
+        };
+        let end = tags.get(index + 1).and_then(|next| next.get(0)).map_or(body.len(), |next| next.start());
+        let value = &body[whole.end()..end];
+        match tag.as_str() {
+            "00" => tlds.addtl_tx_inf = value.trim().to_string(),
+            "20" | "21" | "22" | "23" | "24" | "25" | "26" | "27" | "28" | "29" | "60" | "61" | "62" | "63" =>
+                purpose.push_str(value),
+            "30" => *PartyFields::of(tlds, counterparty).bic = value.trim().to_string(),
+            "31" => PartyFields::of(tlds, counterparty).set_account(value.trim()),
+            "32" | "33" => name.push_str(value),
+            // ?10 - номер первичного документа (Primanota), ?34 - дополнение кода операции
+            _ => {}
+        }
+    }
+    if !name.trim().is_empty() {
+        *PartyFields::of(tlds, counterparty).nm = name.trim().to_string();
+    }
+    parse_sepa_purpose(&purpose, tlds);
+    true
+}
+
+/// Разбирает назначение платежа немецкого формата с ключевыми словами SEPA `EREF+`, `SVWZ+` и т.д.
+fn parse_sepa_purpose(purpose: &str, tlds: &mut TxDtlsAttribute) {
+    let keywords: Vec<_> = SEPA_KEYWORDS.captures_iter(purpose).collect();
+    let head = keywords.first().and_then(|first| first.get(0)).map_or(purpose, |first| &purpose[..first.start()]);
+    if !head.trim().is_empty() {
+        tlds.rmt_inf.ustrd.push(head.trim().to_string());
+    }
+    for (index, capture) in keywords.iter().enumerate() {
+        let (Some(whole), Some(keyword)) = (capture.get(0), capture.get(1)) else {
+            continue;
+        };
+        let end = keywords.get(index + 1).and_then(|next| next.get(0)).map_or(purpose.len(), |next| next.start());
+        let value = purpose[whole.end()..end].trim().to_string();
+        if value.is_empty() {
+            continue;
+        }
+        match keyword.as_str() {
+            "EREF" if value != "NOTPROVIDED" => tlds.refs.end_to_end_id = value,
+            "KREF" => tlds.refs.pmt_inf_id = value,
+            "CRED" => tlds.rltd_pties.cdtr.id.othr.id = value,
+            "DEBT" => tlds.rltd_pties.dbtr.id.othr.id = value,
+            "SVWZ" => tlds.rmt_inf.ustrd.push(value),
+            // MREF - мандат, ABWA/ABWE - конечные плательщик и получатель
+            _ => {}
+        }
+    }
+}
+
+/// Разбирает нидерландский формат `/TRTP/тип/IBAN/счет/BIC/банк/NAME/наименование/REMI/назначение`.
+/// Реквизиты после `/ORDP/` относятся к плательщику, после `/BENM/` - к получателю, иначе - к контрагенту.
+/// Возвращает `false`, если кодов нет.
+fn parse_dutch(field: &str, cdt_dbt_ind: CreditDebit, tlds: &mut TxDtlsAttribute) -> bool {
+    let field = join_lines(field);
+    let subfields = subfields(&field, &DUTCH_CODES);
+    let mut party = Party::counterparty(cdt_dbt_ind);
+    for (code, value) in &subfields {
+        match *code {
+            "ORDP" => party = Party::Debtor,
+            "BENM" => party = Party::Creditor,
+            _ if value.is_empty() => {}
+            "TRTP" => tlds.addtl_tx_inf = value.to_string(),
+            "IBAN" => PartyFields::of(tlds, party).set_account(value),
+            "BIC" => *PartyFields::of(tlds, party).bic = value.to_string(),
+            "NAME" => *PartyFields::of(tlds, party).nm = value.to_string(),
+            "ID" => PartyFields::of(tlds, party).id.othr.id = value.to_string(),
+            "ADDR" => PartyFields::of(tlds, party).pstl_adr.adr_line.push(value.to_string()),
+            "CNTP" => {
+                // счет/BIC/наименование/город контрагента (ING)
+                let mut fields = PartyFields::of(tlds, Party::counterparty(cdt_dbt_ind));
+                let parts: Vec<&str> = value.split('/').map(str::trim).collect();
+                if let Some(account) = parts.first().filter(|account| !account.is_empty()) {
+                    fields.set_account(account);
+                }
+                if let Some(bic) = parts.get(1).filter(|bic| !bic.is_empty()) {
+                    *fields.bic = bic.to_string();
+                }
+                if let Some(nm) = parts.get(2).filter(|nm| !nm.is_empty()) {
+                    *fields.nm = nm.to_string();
+                }
+                if let Some(twn_nm) = parts.get(3).filter(|twn_nm| !twn_nm.is_empty()) {
+                    fields.pstl_adr.twn_nm = twn_nm.to_string();
+                }
+            }
+            "REMI" => {
+                if let Some(strd) = value.strip_prefix("STRD/") {
+                    // структурированная ссылка: STRD/тип/ссылка
+                    let (tp, reference) = strd.split_once('/').unwrap_or(("", strd));
+                    let cdtr_ref_inf = &mut tlds.rmt_inf.strd.cdtr_ref_inf;
+                    cdtr_ref_inf.tp.cd_or_prtry.cd = tp.to_string();
+                    cdtr_ref_inf.ref_cdtr = reference.to_string();
+                } else {
+                    let ustrd = value.strip_prefix("USTD//").unwrap_or(value);
+                    tlds.rmt_inf.ustrd.push(ustrd.to_string());
+                }
+            }
+            "EREF" if *value != "NOTPROVIDED" => tlds.refs.end_to_end_id = value.to_string(),
+            "CSID" => tlds.rltd_pties.cdtr.id.othr.id = value.to_string(),
+            // MARF - мандат, PURP - код цели, RTRN - причина возврата, ULTC/ULTD - конечные участники
+            _ => {}
+        }
+    }
+    !subfields.is_empty()
+}
+
+/// Разбирает произвольный текст: каждая непустая строка становится строкой назначения платежа.
+fn parse_free_text(field: &str, tlds: &mut TxDtlsAttribute) {
+    tlds.rmt_inf.ustrd.extend(field.lines().map(str::trim).filter(|line| !line.is_empty()).map(str::to_string));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::money::Money;
+
+    fn parse(dialect: NarrativeDialect, field: &str, cdt_dbt_ind: CreditDebit) -> TxDtlsAttribute {
+        let mut tlds = TxDtlsAttribute::default();
+        dialect.parse(field, cdt_dbt_ind, &mut tlds);
+        tlds
+    }
+
+    #[test]
+    fn test_parse_swift() {
+        let tlds = parse(NarrativeDialect::Swift, "/NREF/NIOBNL56ASNB9999999999\n", CreditDebit::Credit);
+        assert_eq!("NREF", tlds.refs.prtry.tp);
+        assert_eq!("NIOBNL56ASNB9999999999", tlds.refs.prtry.refdt);
+
+        let field = "/EREF/GS0DUTB31IOUHRS\n/CRNM/GOLDMAN SACHS BANK USA\n/CACT/107045863/CBIC/GSCRUS30XXX\n\
+                     /DACT/8348577826/DBIC/CITIUS30XXX\n/REMI/USD Payment to Vendor\n/OAMT/11-25/";
+        let tlds = parse(NarrativeDialect::Swift, field, CreditDebit::Credit);
+        assert_eq!("GS0DUTB31IOUHRS", tlds.refs.end_to_end_id);
+        assert_eq!("GOLDMAN SACHS BANK USA", tlds.rltd_pties.cdtr.nm);
+        assert_eq!("107045863", tlds.rltd_pties.cdtr_acct.account());
+        assert_eq!("GSCRUS30XXX", tlds.rltd_agts.cdtr_agt.fin_instn_id.bic);
+        assert_eq!("8348577826", tlds.rltd_pties.dbtr_acct.account());
+        assert_eq!("CITIUS30XXX", tlds.rltd_agts.dbtr_agt.fin_instn_id.bic);
+        assert_eq!(vec!["USD Payment to Vendor".to_string()], tlds.rmt_inf.ustrd);
+        assert_eq!(None, tlds.amt_dtls.amt);
+        let tlds = parse(NarrativeDialect::Swift, "/EREF/E2E-1/OAMT/1540,50/", CreditDebit::Credit);
+        assert_eq!(Some(Money::new(154050, 2)), tlds.amt_dtls.amt);
+    }
+
+    #[test]
+    fn test_parse_gvc() {
+        let field = "166?00SEPA-GUTSCHRIFT?109310?20EREF+E2E-4711?21SVWZ+Rechnung 12\n3 vom 01.02.?22KREF+K1\
+                     ?30COBADEFFXXX?31DE89370400440532013000?32Max Muster?33mann?34997";
+        let tlds = parse(NarrativeDialect::Gvc, field, CreditDebit::Credit);
+        assert_eq!("166", tlds.bk_tx_cd.prtry.cd);
+        assert_eq!("DK", tlds.bk_tx_cd.prtry.issr);
+        assert_eq!("SEPA-GUTSCHRIFT", tlds.addtl_tx_inf);
+        assert_eq!("E2E-4711", tlds.refs.end_to_end_id);
+        assert_eq!("K1", tlds.refs.pmt_inf_id);
+        assert_eq!(vec!["Rechnung 123 vom 01.02.".to_string()], tlds.rmt_inf.ustrd);
+        assert_eq!("Max Mustermann", tlds.rltd_pties.dbtr.nm);
+        assert_eq!("DE89370400440532013000", tlds.rltd_pties.dbtr_acct.id.iban);
+        assert_eq!("COBADEFFXXX", tlds.rltd_agts.dbtr_agt.fin_instn_id.bic);
+
+        let tlds = parse(NarrativeDialect::Gvc, "005?00LASTSCHRIFT?20Miete Januar?3110020030?32Vermieter",
+                         CreditDebit::Debit);
+        assert_eq!(vec!["Miete Januar".to_string()], tlds.rmt_inf.ustrd);
+        assert_eq!("10020030", tlds.rltd_pties.cdtr_acct.other.id);
+        assert_eq!("Vermieter", tlds.rltd_pties.cdtr.nm);
+    }
+
+    #[test]
+    fn test_parse_dutch() {
+        let field = "/TRTP/SEPA OVERBOEKING/IBAN/NL28ABNA0528420254/BIC/ABNANL2A/NAME/J. DE VRIES/REMI/USTD//\n\
+                     Factuur 2024/01 betaald/EREF/NOTPROVIDED";
+        let tlds = parse(NarrativeDialect::Dutch, field, CreditDebit::Credit);
+        assert_eq!("SEPA OVERBOEKING", tlds.addtl_tx_inf);
+        assert_eq!("NL28ABNA0528420254", tlds.rltd_pties.dbtr_acct.id.iban);
+        assert_eq!("ABNANL2A", tlds.rltd_agts.dbtr_agt.fin_instn_id.bic);
+        assert_eq!("J. DE VRIES", tlds.rltd_pties.dbtr.nm);
+        assert_eq!(vec!["Factuur 2024/01 betaald".to_string()], tlds.rmt_inf.ustrd);
+        assert_eq!("", tlds.refs.end_to_end_id);
+
+        let field = "/EREF/REF-1//CNTP/NL56INGB0001234567/INGBNL2A/ACME BV/AMSTERDAM//REMI/STRD/CUR/1234567890123456/";
+        let tlds = parse(NarrativeDialect::Dutch, field, CreditDebit::Debit);
+        assert_eq!("REF-1", tlds.refs.end_to_end_id);
+        assert_eq!("NL56INGB0001234567", tlds.rltd_pties.cdtr_acct.id.iban);
+        assert_eq!("ACME BV", tlds.rltd_pties.cdtr.nm);
+        assert_eq!("AMSTERDAM", tlds.rltd_pties.cdtr.pstl_adr.twn_nm);
+        assert_eq!("CUR", tlds.rmt_inf.strd.cdtr_ref_inf.tp.cd_or_prtry.cd);
+        assert_eq!("1234567890123456", tlds.rmt_inf.strd.cdtr_ref_inf.ref_cdtr);
+
+        let tlds = parse(NarrativeDialect::Dutch, "/ORDP//NAME/PAYER/BENM//NAME/PAYEE", CreditDebit::Credit);
+        assert_eq!("PAYER", tlds.rltd_pties.dbtr.nm);
+        assert_eq!("PAYEE", tlds.rltd_pties.cdtr.nm);
+    }
+
+    #[test]
+    fn test_parse_free_text() {
+        let field = "NL47INGB9999999999 hr gjlm paulissen\n\nBetaling sieraden";
+        let tlds = parse(NarrativeDialect::Swift, field, CreditDebit::Credit);
+        assert_eq!(vec!["NL47INGB9999999999 hr gjlm paulissen".to_string(), "Betaling sieraden".to_string()],
+                   tlds.rmt_inf.ustrd);
+        assert_eq!(tlds, parse(NarrativeDialect::FreeText, field, CreditDebit::Credit));
+    }
+
+    #[test]
+    fn test_detect() {
+        assert_eq!(NarrativeDialect::Gvc, NarrativeDialect::detect("166?00SEPA-GUTSCHRIFT?20Text"));
+        assert_eq!(NarrativeDialect::Dutch, NarrativeDialect::detect("/TRTP/SEPA OVERBOEKING/REMI/x"));
+        assert_eq!(NarrativeDialect::Swift, NarrativeDialect::detect("/EREF/1\n/REMI/x"));
+        assert_eq!(NarrativeDialect::FreeText, NarrativeDialect::detect("?ZKB:2300 78278"));
+        let fields = ["free text", "/EREF/1", "/EREF/2", "/TRTP/x"];
+        assert_eq!(NarrativeDialect::Swift, NarrativeDialect::detect_document(fields));
+        assert_eq!(NarrativeDialect::FreeText, NarrativeDialect::detect_document(["a", "b"]));
+        assert_eq!(NarrativeDialect::Dutch, "dutch".parse().unwrap());
+        assert!("other".parse::<NarrativeDialect>().is_err());
+    }
+}
@@ -0,0 +1,132 @@
+//! Модуль набора символов SWIFT X
+//!
+//! Поля сообщений MT допускают только символы набора X: латинские буквы, цифры, пробел и
+//! `/ - ? : ( ) . , ' +`. Кириллица транслитерируется по таблице SWIFT для рублевых платежей
+//! (прописными буквами, Ч - `c`, Ш - `Q`, Щ - `q` и т.д.), прочие символы заменяются близкими
+//! символами набора или точкой.
+//!
+//! ```
+//! use bank_converter::models::swift::to_swift_x;
+//!
+//! assert_eq!("OOO ROMAQKA", to_swift_x("ООО Ромашка"));
+//! assert_eq!("Scet N 12 .5.", to_swift_x("Scet № 12 {5}"));
+//! ```
+
+/// Символ входит в набор SWIFT X.
+pub fn is_swift_x(c: char) -> bool {
+    c.is_ascii_alphanumeric() || " /-?:().,'+".contains(c)
+}
+
+/// Транслитерация буквы кириллицы по таблице SWIFT.
+fn cyrillic(c: char) -> Option<&'static str> {
+    let latin = match c.to_uppercase().next()? {
+        'А' => "A", 'Б' => "B", 'В' => "V", 'Г' => "G", 'Д' => "D", 'Е' => "E", 'Ё' => "o",
+        'Ж' => "J", 'З' => "Z", 'И' => "I", 'Й' => "i", 'К' => "K", 'Л' => "L", 'М' => "M",
+        'Н' => "N", 'О' => "O", 'П' => "P", 'Р' => "R", 'С' => "S", 'Т' => "T", 'У' => "U",
+        'Ф' => "F", 'Х' => "H", 'Ц' => "C", 'Ч' => "c", 'Ш' => "Q", 'Щ' => "q", 'Ъ' => "x",
+        'Ы' => "Y", 'Ь' => "X", 'Э' => "e", 'Ю' => "u", 'Я' => "a",
+        _ => return None,
+    };
+    Some(latin)
+}
+
+/// Замена символа вне набора X: кириллица, диакритика, кавычки, тире и т.д.
+fn replacement(c: char) -> &'static str {
+    if let Some(latin) = cyrillic(c) {
+        return latin;
+    }
+    match c {
+        'Ä' => "AE", 'Ö' => "OE", 'Ü' => "UE", 'ä' => "ae", 'ö' => "oe", 'ü' => "ue", 'ß' => "ss",
+        'À' | 'Á' | 'Â' | 'Ã' | 'Å' => "A", 'à' | 'á' | 'â' | 'ã' | 'å' => "a",
+        'È' | 'É' | 'Ê' | 'Ë' => "E", 'è' | 'é' | 'ê' | 'ë' => "e",
+        'Ì' | 'Í' | 'Î' | 'Ï' => "I", 'ì' | 'í' | 'î' | 'ï' => "i",
+        'Ò' | 'Ó' | 'Ô' | 'Õ' => "O", 'ò' | 'ó' | 'ô' | 'õ' => "o",
+        'Ù' | 'Ú' | 'Û' => "U", 'ù' | 'ú' | 'û' => "u", 'Ç' => "C", 'ç' => "c", 'Ñ' => "N", 'ñ' => "n",
+        '№' => "N",
+        '"' | '«' | '»' | '“' | '”' | '„' | '‘' | '’' | '`' => "'",
+        '–' | '—' | '_' => "-",
+        '[' | '<' => "(", ']' | '>' => ")",
+        '&' => "+",
+        '\t' | '\r' | '\n' | '\u{a0}' => " ",
+        _ => ".",
+    }
+}
+
+/// Приводит текст к набору SWIFT X.
+pub fn to_swift_x(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    for c in text.chars() {
+        if is_swift_x(c) {
+            result.push(c);
+        } else {
+            result.push_str(replacement(c));
+        }
+    }
+    result
+}
+
+/// Приводит текст к набору SWIFT X и обрезает до `max` символов (формат `<max>x`).
+pub(crate) fn fit(text: &str, max: usize) -> String {
+    to_swift_x(text.trim()).chars().take(max).collect()
+}
+
+/// Приводит части текста `segments` к набору SWIFT X и разбивает на строки по `width` символов,
+/// не более `max_lines` строк (формат `<max_lines>*<width>x`); остаток текста отбрасывается.
+///
+/// Часть, которая не помещается в текущую строку, начинается с новой строки, а длинная часть
+/// переносится посимвольно. Строка продолжения не может начинаться с `:` или `-`, поэтому такая
+/// строка начинается с пробела.
+pub(crate) fn wrap(segments: &[&str], width: usize, max_lines: usize) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for segment in segments {
+        let chars: Vec<char> = to_swift_x(segment.trim()).chars().collect();
+        if let Some(line) = lines.last_mut()
+            && line.len() + chars.len() <= width {
+            line.extend(&chars);
+            continue;
+        }
+        let mut start = 0;
+        while start < chars.len() {
+            if lines.len() == max_lines {
+                return lines;
+            }
+            let mut line = String::with_capacity(width);
+            if !lines.is_empty() && matches!(chars[start], ':' | '-') {
+                line.push(' ');
+            }
+            let end = (start + width - line.len()).min(chars.len());
+            line.extend(&chars[start..end]);
+            lines.push(line);
+            start = end;
+        }
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_swift_x() {
+        assert_eq!("ZAO 'SBERBANK-TEHNOLOGII' q.ec", to_swift_x("ЗАО «Сбербанк–технологии» Щ@Эч"));
+        assert_eq!("Muenchen Strasse cafe", to_swift_x("München Straße café"));
+        assert_eq!("Payment 1/2 (test).", to_swift_x("Payment 1/2 (test)."));
+        assert!(to_swift_x("Выписка № 5 от 01.01.2024").chars().all(is_swift_x));
+    }
+
+    #[test]
+    fn test_wrap() {
+        let text = "A".repeat(64) + "-B" + &"C".repeat(200);
+        let lines = wrap(&[&text], 65, 3);
+        assert_eq!(3, lines.len());
+        assert_eq!("A".repeat(64) + "-", lines[0]);
+        assert_eq!(format!("B{}", "C".repeat(64)), lines[1]);
+        assert!(lines.iter().all(|line| line.len() <= 65));
+        assert_eq!(vec!["XY".to_string(), " -".to_string(), "1".to_string()], wrap(&["XY-1"], 2, 3));
+        assert_eq!(vec!["/A/1/B/2", "/C/Ia"], wrap(&["/A/1", "/B/2", "/C/Иa"], 8, 2));
+        assert_eq!(vec!["/A/1/B/2"], wrap(&["/A/1", "/B/2", "/C/3"], 8, 1));
+        assert!(wrap(&[""], 65, 6).is_empty());
+        assert_eq!("ABC", fit("  ABCDEF ", 3));
+    }
+}
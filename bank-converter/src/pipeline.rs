@@ -0,0 +1,512 @@
+//! Модуль конвейера конвертации банковских выписок
+//!
+//! Предоставляет выбор формата и конвертацию документов между форматами через модель CAMT.053
+//!
+//! ```no_run
+//! use std::fs::File;
+//! use bank_converter::pipeline::{convert, Format};
+//!
+//! let mut reader = File::open("statement.mt940").unwrap();
+//! let mut writer = File::create("statement.xml").unwrap();
+//! convert(&mut reader, Format::Mt940, &mut writer, Format::Camt053).unwrap();
+//! ```
+use std::fmt::{Display, Formatter};
+use std::io::{BufReader, Cursor, Read, Write};
+use std::str::FromStr;
+use chrono::{Local, NaiveDateTime};
+use crate::encoding::{DecodeReader, EncodeWriter, TextEncoding};
+use crate::errors::ConvertError;
+use crate::models::camt053::{CamtVersion, DocumentCamt053};
+use crate::models::client_bank::DocumentClientBank;
+use crate::models::csv::DocumentCsv;
+use crate::models::csv_flat::FlatCsv;
+use crate::models::csv_profile::CsvProfile;
+use crate::models::mt940::DocumentMt940;
+use crate::models::mt942::DocumentMt942;
+use crate::models::narrative::NarrativeDialect;
+use crate::stream::{CamtStreamReader, CamtStreamWriter, ClientBankStreamWriter, CsvStreamWriter, Mt940StreamWriter,
+                    Mt942StreamWriter, StatementWriter, XlsxStreamWriter};
+use crate::validate::validate;
+
+/// Перечисление поддерживаемых форматов документов
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// Выписка CSV (СберБизнес)
+    Csv,
+    /// Выписка SWIFT MT940
+    Mt940,
+    /// Промежуточный отчет SWIFT MT942
+    Mt942,
+    /// Выписка ISO 20022 CAMT.053 (XML)
+    Camt053,
+    /// Книга Excel XLSX: входной лист разбирается по профилю разметки CSV, выходная книга
+    /// записывается в раскладке отчета CSV
+    Xlsx,
+    /// Файл обмена 1С с клиент-банком (1CClientBankExchange)
+    ClientBank,
+}
+
+/// Документ одного из поддерживаемых форматов
+#[derive(Debug)]
+pub enum Document {
+    /// Документ CAMT.053
+    DocumentCamt053(DocumentCamt053),
+    /// Документ MT940
+    DocumentMt940(DocumentMt940),
+    /// Документ MT942
+    DocumentMt942(DocumentMt942),
+    /// Документ CSV
+    DocumentCsv(DocumentCsv),
+    /// Файл обмена 1С с клиент-банком
+    DocumentClientBank(DocumentClientBank),
+}
+
+/// Конвейер конвертации: читает документ входного формата и записывает его в выходном
+#[derive(Debug, Clone, PartialEq)]
+pub struct PipelineConverter {
+    /// Формат входного документа
+    pub data_in: Format,
+    /// Формат выходного документа
+    pub data_out: Format,
+    /// Проверять согласованность выписок перед записью
+    pub validate: bool,
+    /// Версия схемы выходного документа CAMT.053; если не задана, используется версия
+    /// входного документа CAMT.053 или camt.053.001.02
+    pub camt_version: Option<CamtVersion>,
+    /// Диалект поля :86: входного документа MT940 или MT942; если не задан, определяется по файлу
+    pub narrative_dialect: Option<NarrativeDialect>,
+    /// Профиль разметки входного документа CSV; если не задан, используется профиль СберБизнес
+    pub csv_profile: Option<CsvProfile>,
+    /// Параметры плоского документа CSV; если заданы, входной или выходной документ CSV -
+    /// плоская таблица (см. [`crate::models::csv_flat`])
+    pub csv_flat: Option<FlatCsv>,
+    /// Параметры выходного плоского документа CSV; если не заданы, используются параметры `csv_flat`.
+    /// Позволяют конвертировать документ CSV в плоскую таблицу CSV (например, выписку СберБизнес)
+    pub out_csv_flat: Option<FlatCsv>,
+    /// Лист входной книги XLSX; если не задан, читается первый лист
+    pub xlsx_sheet: Option<String>,
+    /// Кодировка входного документа; если не задана, определяется по началу документа
+    /// (см. [`crate::encoding`])
+    pub encoding: Option<TextEncoding>,
+    /// Кодировка выходного документа; если не задана, документ записывается в UTF-8,
+    /// файл обмена 1С - в Windows-1251
+    pub out_encoding: Option<TextEncoding>,
+    /// Дата формирования выписки в заголовке выходного документа CSV или XLSX; если не задана,
+    /// используется текущее время
+    pub created_at: Option<NaiveDateTime>,
+}
+
+impl FromStr for Format {
+    type Err = ConvertError;
+    fn from_str(format_str: &str) -> Result<Self, Self::Err> {
+        match format_str.to_lowercase().as_str() {
+            "csv" => Ok(Format::Csv),
+            "mt940" => Ok(Format::Mt940),
+            "mt942" => Ok(Format::Mt942),
+            "camt053" | "xml" => Ok(Format::Camt053),
+            "xlsx" => Ok(Format::Xlsx),
+            "1c" | "clientbank" | "1cclientbankexchange" => Ok(Format::ClientBank),
+            _ => Err(ConvertError::BadArgument(format!("Not support format {}", format_str)))
+        }
+    }
+}
+
+impl Display for Format {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Format::Csv => write!(f, "CSV"),
+            Format::Mt940 => write!(f, "MT940"),
+            Format::Mt942 => write!(f, "MT942"),
+            Format::Camt053 => write!(f, "CAMT053"),
+            Format::Xlsx => write!(f, "XLSX"),
+            Format::ClientBank => write!(f, "1C"),
+        }
+    }
+}
+
+impl PipelineConverter {
+    /// Создает конвейер конвертации из формата `data_in` в формат `data_out`.
+    pub fn new(data_in: Format, data_out: Format) -> Self {
+        Self { data_in, data_out, validate: false, camt_version: None, narrative_dialect: None, csv_profile: None, csv_flat: None,
+               out_csv_flat: None, xlsx_sheet: None, encoding: None, out_encoding: None, created_at: None }
+    }
+
+    /// Задает версию схемы выходного документа CAMT.053.
+    pub fn with_camt_version(mut self, camt_version: CamtVersion) -> Self {
+        self.camt_version = Some(camt_version);
+        self
+    }
+
+    /// Задает диалект поля :86: входного документа MT940 или MT942 (см. [`crate::models::narrative`]).
+    pub fn with_narrative_dialect(mut self, dialect: NarrativeDialect) -> Self {
+        self.narrative_dialect = Some(dialect);
+        self
+    }
+
+    /// Задает профиль разметки входного документа CSV (см. [`crate::models::csv_profile`]).
+    pub fn with_csv_profile(mut self, profile: CsvProfile) -> Self {
+        self.csv_profile = Some(profile);
+        self
+    }
+
+    /// Задает параметры плоского документа CSV для входного или выходного документа CSV.
+    pub fn with_flat_csv(mut self, flat: FlatCsv) -> Self {
+        self.csv_flat = Some(flat);
+        self
+    }
+
+    /// Задает параметры выходного плоского документа CSV независимо от входного документа CSV.
+    pub fn with_out_flat_csv(mut self, flat: FlatCsv) -> Self {
+        self.out_csv_flat = Some(flat);
+        self
+    }
+
+    /// Параметры выходного плоского документа CSV или `None`, если выходной документ CSV - выписка.
+    fn out_flat_csv(&self) -> Option<FlatCsv> {
+        self.out_csv_flat.or(self.csv_flat)
+    }
+
+    /// Задает лист входной книги XLSX.
+    pub fn with_xlsx_sheet(mut self, sheet: &str) -> Self {
+        self.xlsx_sheet = Some(sheet.to_string());
+        self
+    }
+
+    /// Задает кодировку входного документа вместо определяемой автоматически.
+    pub fn with_encoding(mut self, encoding: TextEncoding) -> Self {
+        self.encoding = Some(encoding);
+        self
+    }
+
+    /// Задает кодировку выходного документа, например Windows-1251 для старых систем.
+    pub fn with_out_encoding(mut self, encoding: TextEncoding) -> Self {
+        self.out_encoding = Some(encoding);
+        self
+    }
+
+    /// Задает дату формирования выписки в заголовке выходного документа CSV или XLSX,
+    /// например для воспроизводимого результата.
+    pub fn with_created_at(mut self, created_at: NaiveDateTime) -> Self {
+        self.created_at = Some(created_at);
+        self
+    }
+
+    /// Дата формирования выписки в заголовке выходного документа CSV или XLSX: заданная или текущее время.
+    fn created_at(&self) -> NaiveDateTime {
+        self.created_at.unwrap_or_else(|| Local::now().naive_local())
+    }
+
+    /// Включает проверку согласованности выписок перед записью (см. [`crate::validate`]).
+    pub fn with_validation(mut self, validate: bool) -> Self {
+        self.validate = validate;
+        self
+    }
+
+    /// Читает документ входного формата.
+    ///
+    /// # Ошибки
+    ///
+    /// Возвращает ошибку, если документ не удалось прочитать или разобрать.
+    pub fn read_document<R: Read>(&self, r: &mut R) -> Result<Document, ConvertError> {
+        match self.data_in {
+            Format::Csv => {
+                let r = &mut DecodeReader::new(r, self.encoding)?;
+                if let Some(flat) = self.csv_flat {
+                    return Ok(Document::DocumentCsv(DocumentCsv::from_read_flat(r, flat)?));
+                }
+                let profile = self.csv_profile.clone().unwrap_or_default();
+                Ok(Document::DocumentCsv(DocumentCsv::from_read_with_profile(r, profile)?))
+            }
+            Format::Mt940 => {
+                let r = &mut DecodeReader::new(r, self.encoding)?;
+                Ok(Document::DocumentMt940(DocumentMt940::from_read_with_dialect(r, self.narrative_dialect)?))
+            }
+            Format::Mt942 => {
+                let r = &mut DecodeReader::new(r, self.encoding)?;
+                Ok(Document::DocumentMt942(DocumentMt942::from_read_with_dialect(r, self.narrative_dialect)?))
+            }
+            Format::Camt053 => {
+                Ok(Document::DocumentCamt053(DocumentCamt053::from_read(&mut DecodeReader::new(r, self.encoding)?)?))
+            }
+            Format::Xlsx => {
+                let mut bytes = Vec::new();
+                r.read_to_end(&mut bytes)?;
+                let profile = self.csv_profile.clone().unwrap_or_default();
+                let document = DocumentCsv::from_xlsx(Cursor::new(bytes), self.xlsx_sheet.as_deref(), profile)?;
+                Ok(Document::DocumentCsv(document))
+            }
+            Format::ClientBank => {
+                let r = &mut DecodeReader::new(r, self.encoding)?;
+                Ok(Document::DocumentClientBank(DocumentClientBank::from_read(r)?))
+            }
+        }
+    }
+
+    /// Читает документ входного формата и приводит его к модели CAMT.053.
+    ///
+    /// # Ошибки
+    ///
+    /// Возвращает ошибку чтения или конвертации документа.
+    pub fn read_camt<R: Read>(&self, r: &mut R) -> Result<DocumentCamt053, ConvertError> {
+        match self.read_document(r)? {
+            Document::DocumentCamt053(doc) => Ok(doc),
+            Document::DocumentMt940(doc) => Ok(DocumentCamt053::try_from(doc)?),
+            Document::DocumentMt942(doc) => Ok(DocumentCamt053::try_from(doc)?),
+            Document::DocumentCsv(doc) => Ok(DocumentCamt053::try_from(doc)?),
+            Document::DocumentClientBank(doc) => Ok(DocumentCamt053::try_from(doc)?),
+        }
+    }
+
+    /// Конвертирует документ из `r` и записывает результат в `w`.
+    ///
+    /// Документ CAMT.053 конвертируется потоково (см. [`crate::stream`]): выписки и записи
+    /// записываются по мере чтения. При включенной проверке и для других входных форматов
+    /// документ читается целиком, чтобы несогласованная выписка не была записана.
+    ///
+    /// # Ошибки
+    ///
+    /// Возвращает ошибку чтения, конвертации или записи документа, а при включенной
+    /// проверке - `ConvertError::ValidationError` со списком несоответствий.
+    pub fn convert<R: Read, W: Write>(&self, r: &mut R, w: &mut W) -> Result<(), ConvertError> {
+        let Some(encoding) = self.out_encoding.filter(|_| self.data_out != Format::ClientBank) else {
+            return self.convert_utf8(r, w);
+        };
+        if self.data_out == Format::Xlsx {
+            return Err(ConvertError::BadArgument("Output encoding is not applicable to XLSX".to_string()));
+        }
+        let mut w = EncodeWriter::new(w, encoding)?;
+        self.convert_utf8(r, &mut w)?;
+        Ok(w.flush()?)
+    }
+
+    /// Кодировка выходного файла обмена 1С: заданная или Windows-1251.
+    fn client_bank_encoding(&self) -> TextEncoding {
+        self.out_encoding.unwrap_or(TextEncoding::Windows1251)
+    }
+
+    /// Конвертирует документ из `r` и записывает результат в `w` в кодировке UTF-8; файл обмена 1С
+    /// записывается в своей кодировке (см. [`ClientBankStreamWriter`]).
+    fn convert_utf8<R: Read, W: Write>(&self, r: &mut R, w: &mut W) -> Result<(), ConvertError> {
+        if self.data_in == Format::Camt053 && !self.validate {
+            return self.convert_stream(r, w);
+        }
+        let mut camt = self.read_camt(r)?;
+        if self.validate {
+            let issues = validate(&camt);
+            if !issues.is_empty() {
+                let issues: Vec<String> = issues.iter().map(ToString::to_string).collect();
+                return Err(ConvertError::ValidationError(issues.join("; ")));
+            }
+        }
+        match self.data_out {
+            Format::Csv => {
+                let mut csv = match self.out_flat_csv() {
+                    Some(flat) => DocumentCsv::parse_to_flat_csv(&camt, flat)?,
+                    None => DocumentCsv::parse_to_csv_at(&camt, self.created_at())?,
+                };
+                csv.write_to(w)
+            }
+            Format::Mt940 => {
+                let mut mt940 = DocumentMt940::try_from(camt)?;
+                mt940.write_to(w)
+            }
+            Format::Mt942 => {
+                let mut mt942 = DocumentMt942::try_from(camt)?;
+                mt942.write_to(w)
+            }
+            Format::Camt053 => match self.camt_version {
+                Some(version) => camt.write_to_version(w, version),
+                None => camt.write_to(w),
+            }
+            Format::Xlsx => XlsxStreamWriter::new(w).with_created_at(self.created_at()).write_document(&camt),
+            Format::ClientBank => ClientBankStreamWriter::new(w, self.client_bank_encoding())?.write_document(&camt),
+        }
+    }
+
+    /// Потоково конвертирует документ CAMT.053 из `r` и записывает результат в `w`.
+    fn convert_stream<R: Read, W: Write>(&self, r: &mut R, w: &mut W) -> Result<(), ConvertError> {
+        let mut reader = CamtStreamReader::new(BufReader::new(DecodeReader::new(r, self.encoding)?));
+        let mut item = reader.next_item()?;
+        let mut writer: Box<dyn StatementWriter + '_> = match self.data_out {
+            Format::Csv => match self.out_flat_csv() {
+                Some(flat) => Box::new(CsvStreamWriter::flat(w, flat)),
+                None => Box::new(CsvStreamWriter::new(w).with_created_at(self.created_at())),
+            },
+            Format::Mt940 => Box::new(Mt940StreamWriter::new(w)),
+            Format::Mt942 => Box::new(Mt942StreamWriter::new(w)),
+            Format::Camt053 => {
+                let version = self.camt_version
+                    .or_else(|| reader.version().filter(CamtVersion::is_writable))
+                    .unwrap_or_default();
+                Box::new(CamtStreamWriter::new(w, version)?)
+            }
+            Format::Xlsx => Box::new(XlsxStreamWriter::new(w).with_created_at(self.created_at())),
+            Format::ClientBank => Box::new(ClientBankStreamWriter::new(w, self.client_bank_encoding())?),
+        };
+        while let Some(current) = item {
+            writer.write_item(&current)?;
+            item = reader.next_item()?;
+        }
+        writer.finish()
+    }
+}
+
+/// Конвертирует документ формата `in_format` из `reader` в формат `out_format` и записывает в `writer`.
+///
+/// # Ошибки
+///
+/// Возвращает ошибку чтения, конвертации или записи документа.
+pub fn convert<R: Read, W: Write>(reader: &mut R, in_format: Format,
+                                  writer: &mut W, out_format: Format) -> Result<(), ConvertError> {
+    PipelineConverter::new(in_format, out_format).convert(reader, writer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    #[test]
+    fn test_format_from_str() {
+        assert_eq!(Format::Csv, "csv".parse::<Format>().unwrap());
+        assert_eq!(Format::Mt940, "MT940".parse::<Format>().unwrap());
+        assert_eq!(Format::Camt053, "XML".parse::<Format>().unwrap());
+        assert!("pdf".parse::<Format>().is_err());
+    }
+
+    #[test]
+    fn test_format_display() {
+        for format in [Format::Csv, Format::Mt940, Format::Mt942, Format::Camt053, Format::Xlsx, Format::ClientBank] {
+            assert_eq!(format, format.to_string().parse::<Format>().unwrap());
+        }
+    }
+
+    #[test]
+    fn test_convert_encodings() {
+        let text = std::fs::read_to_string("../test_files/Пример выписки по счёту 1.csv").unwrap();
+        let cp1251 = encoding_rs::WINDOWS_1251.encode(text.trim_start_matches('\u{feff}')).0.into_owned();
+        let expected = PipelineConverter::new(Format::Csv, Format::Camt053).read_camt(&mut text.as_bytes()).unwrap();
+        let converter = PipelineConverter::new(Format::Csv, Format::Camt053).with_out_encoding(TextEncoding::Windows1251);
+        assert_eq!(expected, converter.read_camt(&mut cp1251.as_slice()).unwrap());
+        let mut camt = Vec::new();
+        converter.convert(&mut cp1251.as_slice(), &mut camt).unwrap();
+        assert!(camt.starts_with(br#"<?xml version="1.0" encoding="windows-1251"?>"#));
+        assert!(std::str::from_utf8(&camt).is_err());
+        let mut utf8 = Vec::new();
+        PipelineConverter::new(Format::Csv, Format::Camt053).convert(&mut text.as_bytes(), &mut utf8).unwrap();
+        // дата формирования в заголовке CSV фиксируется, иначе результаты двух конвертаций могут различаться
+        let created_at = NaiveDate::from_ymd_opt(2024, 2, 20).unwrap().and_hms_opt(15, 30, 0).unwrap();
+        let to_csv = PipelineConverter::new(Format::Camt053, Format::Csv).with_created_at(created_at);
+        let (mut csv, mut expected_csv) = (Vec::new(), Vec::new());
+        to_csv.convert(&mut camt.as_slice(), &mut csv).unwrap();
+        to_csv.convert(&mut utf8.as_slice(), &mut expected_csv).unwrap();
+        assert_eq!(expected_csv, csv);
+        assert!(String::from_utf8(csv).unwrap().contains("Дата формирования выписки: 20.02.2024 15:30:00"));
+        let reread = to_csv.read_camt(&mut camt.as_slice()).unwrap();
+        assert_eq!(expected.bk_to_cstmr_stmt[0].stmt.acct.ownr.nm, reread.bk_to_cstmr_stmt[0].stmt.acct.ownr.nm);
+        let utf16 = PipelineConverter::new(Format::Csv, Format::Camt053).with_out_encoding(TextEncoding::Utf16Le);
+        assert!(utf16.convert(&mut cp1251.as_slice(), &mut Vec::new()).is_err());
+    }
+
+    #[test]
+    fn test_convert_xlsx() {
+        let xlsx = std::fs::read("../test_files/Пример выписки по счёту 1.xlsx").unwrap();
+        let csv = std::fs::read("../test_files/Пример выписки по счёту 1.csv").unwrap();
+        let mut output = Vec::new();
+        PipelineConverter::new(Format::Xlsx, Format::Mt940).convert(&mut xlsx.as_slice(), &mut output).unwrap();
+        let mut expected = Vec::new();
+        PipelineConverter::new(Format::Csv, Format::Mt940).convert(&mut csv.as_slice(), &mut expected).unwrap();
+        assert_eq!(expected, output);
+        let missing = PipelineConverter::new(Format::Xlsx, Format::Mt940).with_xlsx_sheet("Лист2");
+        assert!(missing.convert(&mut xlsx.as_slice(), &mut Vec::new()).is_err());
+        let mut workbook = Vec::new();
+        PipelineConverter::new(Format::Csv, Format::Xlsx).convert(&mut csv.as_slice(), &mut workbook).unwrap();
+        let camt = std::fs::read("../test_files/CAMT053 corrected.xml").unwrap();
+        let mut streamed = Vec::new();
+        PipelineConverter::new(Format::Camt053, Format::Xlsx).convert(&mut camt.as_slice(), &mut streamed).unwrap();
+        for output in [&workbook, &streamed] {
+            assert_eq!(Some(Format::Xlsx), crate::detect::detect_format(output).map(|detection| detection.format));
+        }
+        let to_xlsx = PipelineConverter::new(Format::Csv, Format::Xlsx).with_out_encoding(TextEncoding::Windows1251);
+        assert!(to_xlsx.convert(&mut csv.as_slice(), &mut Vec::new()).is_err());
+    }
+
+    #[test]
+    fn test_convert_mt942() {
+        let report = std::fs::read("../test_files/MT942 intraday.mt942.txt").unwrap();
+        let from_mt942 = PipelineConverter::new(Format::Mt942, Format::Mt942);
+        let expected = from_mt942.read_camt(&mut report.as_slice()).unwrap();
+        let mut output = Vec::new();
+        from_mt942.convert(&mut report.as_slice(), &mut output).unwrap();
+        let text = String::from_utf8(output.clone()).unwrap();
+        assert!(text.contains("{2:I942") && text.contains(":28C:5/1\r\n:34F:EUR0,00\r\n"), "{}", text);
+        assert!(text.contains(":34F:EURD100,00\r\n:34F:EURC200,00\r\n:13D:2402201800+0100\r\n"), "{}", text);
+        assert!(text.contains(":90D:2EUR300,00\r\n:90C:1EUR1500,00\r\n-}"), "{}", text);
+        let reread = from_mt942.read_camt(&mut output.as_slice()).unwrap();
+        for (expected, reread) in expected.bk_to_cstmr_stmt.iter().zip(&reread.bk_to_cstmr_stmt) {
+            let amounts = |record: &crate::models::camt053::BkToCstmrStmt| -> Vec<_> {
+                record.stmt.ntry.iter().map(|ntry| (ntry.amt.clone(), ntry.cdt_dbt_ind)).collect()
+            };
+            assert_eq!(amounts(expected), amounts(reread));
+            assert_eq!(expected.stmt.flr_lmt, reread.stmt.flr_lmt);
+            assert_eq!(expected.stmt.txs_summry, reread.stmt.txs_summry);
+            assert_eq!(expected.stmt.cre_dt_tm, reread.stmt.cre_dt_tm);
+        }
+        let mut camt = Vec::new();
+        PipelineConverter::new(Format::Mt942, Format::Camt053).with_validation(true)
+            .convert(&mut report.as_slice(), &mut camt).unwrap();
+        let camt_text = String::from_utf8(camt.clone()).unwrap();
+        assert!(camt_text.contains("<CreDtTm>2024-02-20T15:30:00+01:00</CreDtTm>"), "{}", camt_text);
+        let mut streamed = Vec::new();
+        PipelineConverter::new(Format::Camt053, Format::Mt942).convert(&mut camt.as_slice(), &mut streamed).unwrap();
+        let streamed = String::from_utf8(streamed).unwrap();
+        assert!(streamed.contains(":28C:5/1\r\n:34F:EUR0,\r\n:13D:2402201530+0100\r\n"), "{}", streamed);
+        assert!(streamed.contains(":90D:0EUR0,\r\n:90C:1EUR980,00\r\n"), "{}", streamed);
+    }
+
+    #[test]
+    fn test_convert_amount_overflow() {
+        let mut mt940 = String::from(":20:OVERFLOW\n:25:DE89370400440532013000\n:28C:1/1\n:13D:2402201530+0100\n:60F:C240220EUR0,\n");
+        for _ in 0..10 {
+            mt940.push_str(":61:2402200220C9999999999999999,99NTRFNONREF\n");
+        }
+        mt940.push_str(":62F:C240220EUR0,\n-");
+        for out_format in [Format::Camt053, Format::Mt942, Format::ClientBank] {
+            let result = PipelineConverter::new(Format::Mt940, out_format)
+                .convert(&mut mt940.as_bytes(), &mut Vec::new());
+            assert!(matches!(result, Err(ConvertError::ParseError(_))), "{}: {:?}", out_format, result);
+        }
+        let result = PipelineConverter::new(Format::Mt940, Format::Mt940).with_validation(true)
+            .convert(&mut mt940.as_bytes(), &mut Vec::new());
+        assert!(matches!(&result, Err(ConvertError::ValidationError(issues)) if issues.contains("overflows")),
+                "{:?}", result);
+
+        let camt = std::fs::read_to_string("../test_files/camt 053 danske bank.txt").unwrap();
+        let empty = camt.replacen(r#"<Amt Ccy="DKK">12345.67</Amt>"#, r#"<Amt Ccy="DKK"></Amt>"#, 1);
+        let result = PipelineConverter::new(Format::Camt053, Format::Mt940).with_validation(true)
+            .convert(&mut empty.as_bytes(), &mut Vec::new());
+        assert!(matches!(&result, Err(ConvertError::ParseError(error)) if error.contains("amount")), "{:?}", result);
+    }
+
+    #[test]
+    fn test_convert_client_bank() {
+        let exchange = std::fs::read("../test_files/1CClientBankExchange.txt").unwrap();
+        let from_client_bank = PipelineConverter::new(Format::ClientBank, Format::Camt053);
+        let expected = from_client_bank.read_camt(&mut exchange.as_slice()).unwrap();
+        let mut camt = Vec::new();
+        from_client_bank.clone().with_validation(true).convert(&mut exchange.as_slice(), &mut camt).unwrap();
+        let mut output = Vec::new();
+        PipelineConverter::new(Format::Camt053, Format::ClientBank).convert(&mut camt.as_slice(), &mut output).unwrap();
+        let (text, _, malformed) = encoding_rs::WINDOWS_1251.decode(&output);
+        assert!(!malformed);
+        assert!(text.starts_with("1CClientBankExchange\r\nВерсияФормата=1.03\r\nКодировка=Windows\r\n"), "{}", text);
+        assert!(text.contains("ПолучательКПП=500101001\r\n"), "{}", text);
+        assert_eq!(expected, from_client_bank.read_camt(&mut output.as_slice()).unwrap());
+        let to_cp866 = PipelineConverter::new(Format::ClientBank, Format::ClientBank).with_out_encoding(TextEncoding::Cp866);
+        let mut cp866 = Vec::new();
+        to_cp866.convert(&mut exchange.as_slice(), &mut cp866).unwrap();
+        assert!(encoding_rs::IBM866.decode(&cp866).0.contains("Кодировка=DOS\r\n"));
+        let to_utf8 = PipelineConverter::new(Format::ClientBank, Format::ClientBank).with_out_encoding(TextEncoding::Utf8);
+        assert!(to_utf8.convert(&mut exchange.as_slice(), &mut Vec::new()).is_err());
+    }
+}
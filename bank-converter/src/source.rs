@@ -0,0 +1,195 @@
+//! Модуль единого доступа к выпискам документов всех форматов
+//!
+//! Трейт `StatementSource` реализуют `DocumentCamt053`, `DocumentMt940`, `DocumentMt942`, `DocumentCsv`,
+//! `DocumentClientBank` и `pipeline::Document`: отборы, отчеты и выгрузки, написанные для трейта,
+//! работают с любым входным форматом. Выписки, остатки и записи выдаются в модели CAMT.053.
+//!
+//! ```
+//! use bank_converter::models::codes::CreditDebit;
+//! use bank_converter::models::money::Money;
+//! use bank_converter::models::mt940::DocumentMt940;
+//! use bank_converter::source::StatementSource;
+//!
+//! fn debit_total<S: StatementSource>(source: &S) -> Money {
+//!     source.entries().unwrap()
+//!         .filter(|ntry| ntry.cdt_dbt_ind == CreditDebit::Debit)
+//!         .map(|ntry| ntry.amt.amt)
+//!         .sum()
+//! }
+//!
+//! let mt940 = "{1:F01BANKBEBBAXXX0000000000}{2:O940BANKBEBBXXXXN}{3:}{4:\n:20:REF\n:25:123\n:28C:1/1\n\
+//!              :60F:C240102EUR0,00\n:61:2401020102D5,50NTRFNONREF\n:86:/NREF/1\n:62F:D240102EUR5,50\n-}{5:}";
+//! let document = DocumentMt940::from_read(&mut mt940.as_bytes()).unwrap();
+//! assert_eq!("5.50".parse::<Money>().unwrap(), debit_total(&document));
+//! ```
+use std::borrow::Cow;
+use crate::errors::ParserError;
+use crate::models::camt053::{BalanceAttribute, BkToCstmrStmt, DocumentCamt053, NtryAttribute, StatementAttribute};
+use crate::models::client_bank::DocumentClientBank;
+use crate::models::csv::DocumentCsv;
+use crate::models::mt940::DocumentMt940;
+use crate::models::mt942::DocumentMt942;
+use crate::pipeline::Document;
+
+/// Итератор выписок источника
+pub type Statements<'a> = Box<dyn Iterator<Item = Cow<'a, BkToCstmrStmt>> + 'a>;
+
+/// Итератор элементов выписок источника: остатков или записей
+pub type Items<'a, T> = Box<dyn Iterator<Item = Cow<'a, T>> + 'a>;
+
+/// Источник выписок документа
+///
+/// Документы, хранящие выписки в модели CAMT.053, выдают их без копирования; документ CSV
+/// приводится к модели при вызове.
+pub trait StatementSource {
+    /// Выписки документа вместе с остатками и записями.
+    ///
+    /// # Ошибки
+    ///
+    /// Возвращает ошибку, если документ не удалось привести к модели CAMT.053.
+    fn statements(&self) -> Result<Statements<'_>, ParserError>;
+
+    /// Заголовки выписок: заголовок сообщения, реквизиты, остатки и итоги без записей Ntry.
+    ///
+    /// # Ошибки
+    ///
+    /// Возвращает ошибку, если документ не удалось привести к модели CAMT.053.
+    fn headers(&self) -> Result<Box<dyn Iterator<Item = BkToCstmrStmt> + '_>, ParserError> {
+        Ok(Box::new(self.statements()?.map(|record| header(&record))))
+    }
+
+    /// Остатки всех выписок документа.
+    ///
+    /// # Ошибки
+    ///
+    /// Возвращает ошибку, если документ не удалось привести к модели CAMT.053.
+    fn balances(&self) -> Result<Items<'_, BalanceAttribute>, ParserError> {
+        Ok(Box::new(self.statements()?.flat_map(|record| items(record, |r| &r.stmt.bal, |r| r.stmt.bal))))
+    }
+
+    /// Записи всех выписок документа.
+    ///
+    /// # Ошибки
+    ///
+    /// Возвращает ошибку, если документ не удалось привести к модели CAMT.053.
+    fn entries(&self) -> Result<Items<'_, NtryAttribute>, ParserError> {
+        Ok(Box::new(self.statements()?.flat_map(|record| items(record, |r| &r.stmt.ntry, |r| r.stmt.ntry))))
+    }
+}
+
+/// Заголовок выписки `record` без записей; записи не копируются.
+fn header(record: &BkToCstmrStmt) -> BkToCstmrStmt {
+    let stmt = &record.stmt;
+    BkToCstmrStmt {
+        grp_hdr: record.grp_hdr.clone(),
+        stmt: StatementAttribute {
+            id: stmt.id.clone(),
+            elctrnc_seq_nb: stmt.elctrnc_seq_nb.clone(),
+            lgl_seq_nb: stmt.lgl_seq_nb.clone(),
+            cre_dt_tm: stmt.cre_dt_tm,
+            fr_to_dt: stmt.fr_to_dt.clone(),
+            acct: stmt.acct.clone(),
+            bal: stmt.bal.clone(),
+            flr_lmt: stmt.flr_lmt.clone(),
+            txs_summry: stmt.txs_summry.clone(),
+            ntry: Vec::new(),
+        },
+    }
+}
+
+/// Элементы выписки `record`: заимствованные, если выписка заимствована, иначе собственные.
+fn items<'a, T: Clone + 'a>(record: Cow<'a, BkToCstmrStmt>, borrow: fn(&BkToCstmrStmt) -> &Vec<T>,
+                            take: fn(BkToCstmrStmt) -> Vec<T>) -> Items<'a, T> {
+    match record {
+        Cow::Borrowed(record) => Box::new(borrow(record).iter().map(Cow::Borrowed)),
+        Cow::Owned(record) => Box::new(take(record).into_iter().map(Cow::Owned)),
+    }
+}
+
+impl StatementSource for DocumentCamt053 {
+    fn statements(&self) -> Result<Statements<'_>, ParserError> {
+        Ok(Box::new(self.bk_to_cstmr_stmt.iter().map(Cow::Borrowed)))
+    }
+}
+
+impl StatementSource for DocumentMt940 {
+    fn statements(&self) -> Result<Statements<'_>, ParserError> {
+        Ok(Box::new(self.document.iter().map(Cow::Borrowed)))
+    }
+}
+
+impl StatementSource for DocumentMt942 {
+    fn statements(&self) -> Result<Statements<'_>, ParserError> {
+        Ok(Box::new(self.document.iter().map(Cow::Borrowed)))
+    }
+}
+
+impl StatementSource for DocumentClientBank {
+    fn statements(&self) -> Result<Statements<'_>, ParserError> {
+        Ok(Box::new(self.document.iter().map(Cow::Borrowed)))
+    }
+}
+
+impl StatementSource for DocumentCsv {
+    fn statements(&self) -> Result<Statements<'_>, ParserError> {
+        let camt = self.parse_to_camt()?;
+        Ok(Box::new(camt.bk_to_cstmr_stmt.into_iter().map(Cow::Owned)))
+    }
+}
+
+impl StatementSource for Document {
+    fn statements(&self) -> Result<Statements<'_>, ParserError> {
+        match self {
+            Document::DocumentCamt053(document) => document.statements(),
+            Document::DocumentMt940(document) => document.statements(),
+            Document::DocumentMt942(document) => document.statements(),
+            Document::DocumentCsv(document) => document.statements(),
+            Document::DocumentClientBank(document) => document.statements(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::File;
+    use crate::pipeline::{Format, PipelineConverter};
+
+    fn read(path: &str, format: Format) -> Document {
+        PipelineConverter::new(format, format).read_document(&mut File::open(path).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_sources_match_camt_model() {
+        let files = [("../test_files/CAMT053 corrected.xml", Format::Camt053),
+            ("../test_files/MT940 github 1.mt940.txt", Format::Mt940),
+            ("../test_files/MT942 intraday.mt942.txt", Format::Mt942),
+            ("../test_files/Пример выписки по счёту 1.csv", Format::Csv),
+            ("../test_files/1CClientBankExchange.txt", Format::ClientBank)];
+        for (path, format) in files {
+            let document = read(path, format);
+            let camt = PipelineConverter::new(format, format).read_camt(&mut File::open(path).unwrap()).unwrap();
+            let statements: Vec<BkToCstmrStmt> = document.statements().unwrap().map(Cow::into_owned).collect();
+            assert_eq!(camt.bk_to_cstmr_stmt, statements, "{}", path);
+            let entries: Vec<NtryAttribute> = document.entries().unwrap().map(Cow::into_owned).collect();
+            let expected: Vec<NtryAttribute> = camt.bk_to_cstmr_stmt.iter().flat_map(|r| r.stmt.ntry.clone()).collect();
+            assert_eq!(expected, entries, "{}", path);
+            assert!(!entries.is_empty(), "{}", path);
+            let balances = document.balances().unwrap().count();
+            assert_eq!(camt.bk_to_cstmr_stmt.iter().map(|r| r.stmt.bal.len()).sum::<usize>(), balances, "{}", path);
+            for header in document.headers().unwrap() {
+                assert!(header.stmt.ntry.is_empty());
+            }
+        }
+    }
+
+    #[test]
+    fn test_borrowed_statements() {
+        let Document::DocumentCamt053(document) = read("../test_files/CAMT053 corrected.xml", Format::Camt053) else {
+            panic!("CAMT.053 document expected")
+        };
+        assert!(document.statements().unwrap().all(|record| matches!(record, Cow::Borrowed(_))));
+        assert!(document.entries().unwrap().all(|ntry| matches!(ntry, Cow::Borrowed(_))));
+        assert!(DocumentCsv::default().statements().is_err());
+    }
+}
@@ -0,0 +1,1170 @@
+//! Модуль потоковой обработки выписок
+//!
+//! Предоставляет чтение документа CAMT.053 по одной выписке и записи (`CamtStreamReader`) и запись
+//! выписок в форматах CAMT.053, MT940, MT942 и CSV по мере чтения (`StatementWriter`). В памяти
+//! находится только заголовок текущей выписки и текущая запись, поэтому размер обрабатываемого
+//! документа не ограничен объемом памяти; запись CAMT.053 переносит записи выписки во временный
+//! файл, чтобы записать рассчитанные по ним итоги TxsSummry до записей.
+//!
+//! ```
+//! use bank_converter::stream::{CamtStreamReader, Mt940StreamWriter, StatementWriter};
+//!
+//! let xml = r#"<Document xmlns="urn:iso:std:iso:20022:tech:xsd:camt.053.001.02"><BkToCstmrStmt>
+//!     <GrpHdr><MsgId>MSG1</MsgId></GrpHdr>
+//!     <Stmt><Id>1</Id><Ntry><Amt Ccy="EUR">10.00</Amt><CdtDbtInd>CRDT</CdtDbtInd></Ntry></Stmt>
+//!     </BkToCstmrStmt></Document>"#;
+//! let mut output = Vec::new();
+//! let mut writer = Mt940StreamWriter::new(&mut output);
+//! for item in CamtStreamReader::new(xml.as_bytes()) {
+//!     writer.write_item(&item.unwrap()).unwrap();
+//! }
+//! writer.finish().unwrap();
+//! assert!(String::from_utf8(output).unwrap().contains(":20:MSG1"));
+//! ```
+use std::io::{BufRead, Write};
+use chrono::{Datelike, Local, NaiveDate, NaiveDateTime};
+use csv::WriterBuilder;
+use quick_xml::events::Event;
+use quick_xml::Writer;
+use rust_xlsxwriter::{ExcelDateTime, Format, Workbook, Worksheet};
+use serde::de::DeserializeOwned;
+use crate::converter::camt_reader::CamtEvents;
+use crate::converter::camt_writer::{CamtWriter, EntrySpool};
+use crate::encoding::{EncodeWriter, TextEncoding};
+use crate::errors::{ConvertError, ParserError};
+use crate::models::client_bank::DocumentClientBank;
+use crate::models::camt053::{BkToCstmrStmt, CamtVersion, DocumentCamt053, DtAttribute, HeaderAttribute, NtryAttribute,
+                             TtlCdtDbtNtriesAttribute, TxsSummryAttribute};
+use crate::models::csv::{DocumentCsv, RowCsv};
+use crate::models::csv_flat::{fields, FlatCsv, FLAT_COLUMNS};
+use crate::models::codes::CreditDebit;
+use crate::models::date::IsoDateTime;
+use crate::models::money::Money;
+use crate::models::mt940::DocumentMt940;
+use crate::models::mt942::DocumentMt942;
+use crate::validate::summary_totals_issues;
+
+/// Элемент потока выписок
+#[derive(Debug, Clone, PartialEq)]
+pub enum StreamItem {
+    /// Начало выписки: заголовок сообщения, реквизиты, остатки и итоги выписки без записей Ntry
+    Statement(Box<BkToCstmrStmt>),
+    /// Запись выписки (Ntry)
+    Entry(Box<NtryAttribute>),
+    /// Конец выписки
+    EndStatement,
+}
+
+/// Назначение захватываемого поддерева документа
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Target {
+    /// Заголовок сообщения GrpHdr
+    Header,
+    /// Элемент выписки до первой записи Ntry
+    StatementHead,
+    /// Запись Ntry
+    Entry,
+    /// Элемент, отсутствующий в модели
+    Skip,
+}
+
+/// Захватываемое поддерево документа
+struct Capture {
+    target: Target,
+    writer: Writer<Vec<u8>>,
+    depth: usize,
+}
+
+/// Потоковое чтение документа CAMT.053
+///
+/// Выдает для каждой выписки `StreamItem::Statement`, затем ее записи `StreamItem::Entry`
+/// и `StreamItem::EndStatement`. Поддерживаются те же версии схемы, что и в
+/// [`DocumentCamt053::from_read`].
+pub struct CamtStreamReader<R: BufRead> {
+    events: CamtEvents<R>,
+    path: Vec<String>,
+    grp_hdr: HeaderAttribute,
+    head: Option<Vec<u8>>,
+    capture: Option<Capture>,
+    pending: Option<StreamItem>,
+}
+
+impl<R: BufRead> CamtStreamReader<R> {
+    /// Создает потоковое чтение документа из `r`.
+    pub fn new(r: R) -> Self {
+        Self { events: CamtEvents::new(r), path: Vec::new(), grp_hdr: HeaderAttribute::default(),
+            head: None, capture: None, pending: None }
+    }
+
+    /// Версия схемы документа; известна после чтения первого элемента потока.
+    pub fn version(&self) -> Option<CamtVersion> {
+        self.events.version()
+    }
+
+    /// Читает следующий элемент потока или `None` в конце документа.
+    ///
+    /// # Ошибки
+    ///
+    /// Возвращает ошибку, если документ не является корректным XML или элемент выписки
+    /// не удалось разобрать.
+    pub fn next_item(&mut self) -> Result<Option<StreamItem>, ParserError> {
+        if let Some(item) = self.pending.take() {
+            return Ok(Some(item));
+        }
+        while let Some(event) = self.events.next_event()? {
+            if let Some(capture) = &mut self.capture {
+                match event {
+                    Event::Start(_) => capture.depth += 1,
+                    Event::End(_) => capture.depth -= 1,
+                    _ => {}
+                }
+                if capture.target != Target::Skip {
+                    capture.writer.write_event(event)?;
+                }
+                if capture.depth == 0
+                    && let Some(item) = self.complete_capture()?
+                {
+                    return Ok(Some(item));
+                }
+                continue;
+            }
+            match event {
+                Event::Start(start) => {
+                    let name = String::from_utf8_lossy(start.local_name().as_ref()).into_owned();
+                    match self.target(&name) {
+                        Some(target) => {
+                            let mut writer = Writer::new(Vec::new());
+                            if target != Target::Skip {
+                                writer.write_event(Event::Start(start))?;
+                            }
+                            self.capture = Some(Capture { target, writer, depth: 1 });
+                            if target == Target::Entry
+                                && let Some(statement) = self.take_statement()?
+                            {
+                                return Ok(Some(statement));
+                            }
+                        }
+                        None => {
+                            if name == "Stmt" {
+                                self.head = Some(b"<Stmt>".to_vec());
+                            }
+                            self.path.push(name);
+                        }
+                    }
+                }
+                Event::Empty(start) => {
+                    let name = String::from_utf8_lossy(start.local_name().as_ref()).into_owned();
+                    match self.target(&name) {
+                        Some(Target::Skip) => {}
+                        Some(target) => {
+                            let mut writer = Writer::new(Vec::new());
+                            writer.write_event(Event::Empty(start))?;
+                            self.capture = Some(Capture { target, writer, depth: 0 });
+                            let statement = if target == Target::Entry { self.take_statement()? } else { None };
+                            let item = self.complete_capture()?;
+                            if statement.is_some() {
+                                self.pending = item;
+                                return Ok(statement);
+                            }
+                            if item.is_some() {
+                                return Ok(item);
+                            }
+                        }
+                        None if name == "Stmt" => {
+                            self.head = Some(b"<Stmt>".to_vec());
+                            self.pending = Some(StreamItem::EndStatement);
+                            return self.take_statement();
+                        }
+                        None => {}
+                    }
+                }
+                Event::End(_) if self.path.pop().as_deref() == Some("Stmt") => {
+                    if self.head.is_some() {
+                        self.pending = Some(StreamItem::EndStatement);
+                        return self.take_statement();
+                    }
+                    return Ok(Some(StreamItem::EndStatement));
+                }
+                _ => {}
+            }
+        }
+        Ok(None)
+    }
+
+    /// Назначение дочернего элемента `name` текущего элемента или `None` для корневого элемента,
+    /// BkToCstmrStmt и Stmt, содержимое которых разбирается по частям.
+    fn target(&self, name: &str) -> Option<Target> {
+        let parent = self.path.last().map(String::as_str);
+        match (parent, name) {
+            (None, _) | (Some("BkToCstmrStmt"), "Stmt") => None,
+            (Some(_), "BkToCstmrStmt") if self.path.len() == 1 => None,
+            (Some("BkToCstmrStmt"), "GrpHdr") => Some(Target::Header),
+            (Some("Stmt"), "Ntry") => Some(Target::Entry),
+            (Some("Stmt"), _) if self.head.is_some() => Some(Target::StatementHead),
+            _ => Some(Target::Skip),
+        }
+    }
+
+    /// Завершает захват поддерева и возвращает запись, если захвачена запись Ntry.
+    fn complete_capture(&mut self) -> Result<Option<StreamItem>, ParserError> {
+        let Some(capture) = self.capture.take() else {
+            return Ok(None);
+        };
+        let xml = capture.writer.into_inner();
+        match capture.target {
+            Target::Header => self.grp_hdr = deserialize(&xml)?,
+            Target::StatementHead => {
+                if let Some(head) = &mut self.head {
+                    head.extend_from_slice(&xml);
+                }
+            }
+            Target::Entry => return Ok(Some(StreamItem::Entry(Box::new(deserialize(&xml)?)))),
+            Target::Skip => {}
+        }
+        Ok(None)
+    }
+
+    /// Разбирает накопленный заголовок выписки, если он еще не выдан.
+    fn take_statement(&mut self) -> Result<Option<StreamItem>, ParserError> {
+        let Some(mut head) = self.head.take() else {
+            return Ok(None);
+        };
+        head.extend_from_slice(b"</Stmt>");
+        let record = BkToCstmrStmt { grp_hdr: self.grp_hdr.clone(), stmt: deserialize(&head)? };
+        Ok(Some(StreamItem::Statement(Box::new(record))))
+    }
+}
+
+impl<R: BufRead> Iterator for CamtStreamReader<R> {
+    type Item = Result<StreamItem, ParserError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_item().transpose()
+    }
+}
+
+fn deserialize<T: DeserializeOwned>(xml: &[u8]) -> Result<T, ParserError> {
+    let xml = std::str::from_utf8(xml).map_err(|e| ParserError::BadInputFormatFile(e.to_string()))?;
+    Ok(serde_xml_rs::from_str(xml)?)
+}
+
+/// Потоковая запись выписок в выходном формате
+pub trait StatementWriter {
+    /// Записывает начало выписки `record`; записи `record.stmt.ntry` не записываются.
+    fn start_statement(&mut self, record: &BkToCstmrStmt) -> Result<(), ConvertError>;
+
+    /// Записывает запись текущей выписки.
+    fn write_entry(&mut self, ntry: &NtryAttribute) -> Result<(), ConvertError>;
+
+    /// Записывает конец текущей выписки.
+    fn end_statement(&mut self) -> Result<(), ConvertError>;
+
+    /// Записывает конец документа.
+    fn finish(&mut self) -> Result<(), ConvertError>;
+
+    /// Записывает элемент потока `item`.
+    fn write_item(&mut self, item: &StreamItem) -> Result<(), ConvertError> {
+        match item {
+            StreamItem::Statement(record) => self.start_statement(record),
+            StreamItem::Entry(ntry) => self.write_entry(ntry),
+            StreamItem::EndStatement => self.end_statement(),
+        }
+    }
+
+    /// Записывает все выписки документа `document` и завершает документ.
+    fn write_document(&mut self, document: &DocumentCamt053) -> Result<(), ConvertError> {
+        for record in &document.bk_to_cstmr_stmt {
+            self.start_statement(record)?;
+            for ntry in &record.stmt.ntry {
+                self.write_entry(ntry)?;
+            }
+            self.end_statement()?;
+        }
+        self.finish()
+    }
+}
+
+/// Потоковая запись документа CAMT.053
+///
+/// Реквизиты и остатки выписки записываются сразу, итоги по записям рассчитываются по мере записи.
+/// Итоги TxsSummry в схеме стоят до записей, поэтому записи Ntry выписки записываются во временный
+/// файл и переносятся в документ после итогов в конце выписки; в памяти находится только текущая
+/// запись. Итоги исходного документа сверяются с рассчитанными, записываются рассчитанные итоги.
+/// При расхождении возвращается `ConvertError::ValidationError`, и выписка не завершается.
+pub struct CamtStreamWriter<W: Write> {
+    writer: Option<CamtWriter<W>>,
+    version: CamtVersion,
+    started: bool,
+    statement: Option<StatementTotals>,
+    spool: Option<EntrySpool>,
+}
+
+/// Итоги текущей выписки: из исходного документа и рассчитанные по записанным записям
+struct StatementTotals {
+    id: String,
+    summary: TxsSummryAttribute,
+    computed: TxsSummryAttribute,
+    entries: usize,
+}
+
+impl<W: Write> CamtStreamWriter<W> {
+    /// Создает запись в `w` по схеме версии `version`.
+    ///
+    /// # Ошибки
+    ///
+    /// Возвращает `ConvertError::BadArgument`, если версия не поддерживается для записи.
+    pub fn new(w: W, version: CamtVersion) -> Result<Self, ConvertError> {
+        if !version.is_writable() {
+            return Err(ConvertError::BadArgument(
+                format!("{} is not supported for writing, use 02, 04 or 08", version)));
+        }
+        Ok(Self { writer: Some(CamtWriter::new(w, version)), version, started: false, statement: None, spool: None })
+    }
+
+    fn writer(&mut self) -> Result<&mut CamtWriter<W>, ConvertError> {
+        self.writer.as_mut().ok_or_else(|| ConvertError::WriteError("Document is already finished".to_string()))
+    }
+}
+
+impl<W: Write> StatementWriter for CamtStreamWriter<W> {
+    fn start_statement(&mut self, record: &BkToCstmrStmt) -> Result<(), ConvertError> {
+        let started = std::mem::replace(&mut self.started, true);
+        let writer = self.writer()?;
+        if !started {
+            writer.start(&record.grp_hdr, Some(&record.stmt))?;
+        }
+        writer.start_statement(&record.stmt, record.grp_hdr.cre_dt_tm)?;
+        self.statement = Some(StatementTotals {
+            id: record.stmt.id.clone(),
+            summary: record.stmt.txs_summry.clone(),
+            computed: TxsSummryAttribute::zero(),
+            entries: 0,
+        });
+        Ok(())
+    }
+
+    fn write_entry(&mut self, ntry: &NtryAttribute) -> Result<(), ConvertError> {
+        let Some(totals) = self.statement.as_mut() else {
+            return Err(ConvertError::WriteError("Entry outside of a statement".to_string()));
+        };
+        totals.computed.add_entry(ntry)?;
+        totals.entries += 1;
+        let spool = match self.spool.as_mut() {
+            Some(spool) => spool,
+            None => self.spool.insert(EntrySpool::new(self.version)?),
+        };
+        spool.write_entry(ntry)
+    }
+
+    fn end_statement(&mut self) -> Result<(), ConvertError> {
+        let Some(totals) = self.statement.take() else {
+            return Ok(());
+        };
+        let issues = summary_totals_issues(&totals.id, &totals.summary, &totals.computed);
+        if !issues.is_empty() {
+            let issues: Vec<String> = issues.iter().map(ToString::to_string).collect();
+            return Err(ConvertError::ValidationError(issues.join("; ")));
+        }
+        let writer = self.writer.as_mut()
+            .ok_or_else(|| ConvertError::WriteError("Document is already finished".to_string()))?;
+        if totals.entries > 0 {
+            writer.write_summary(&totals.computed)?;
+        }
+        if let Some(spool) = self.spool.as_mut() {
+            writer.write_spooled(spool)?;
+        }
+        writer.end_statement()
+    }
+
+    fn finish(&mut self) -> Result<(), ConvertError> {
+        if !std::mem::replace(&mut self.started, true) {
+            self.writer()?.start(&HeaderAttribute::default(), None)?;
+        }
+        if let Some(writer) = self.writer.take() {
+            writer.finish()?;
+        }
+        Ok(())
+    }
+}
+
+/// Потоковая запись документа MT940: каждая выписка записывается отдельным сообщением
+pub struct Mt940StreamWriter<W: Write> {
+    writer: W,
+    record_write: String,
+    closing: String,
+}
+
+impl<W: Write> Mt940StreamWriter<W> {
+    /// Создает запись в `w`.
+    pub fn new(w: W) -> Self {
+        Self { writer: w, record_write: String::new(), closing: String::new() }
+    }
+
+    fn flush_record(&mut self) -> Result<(), ConvertError> {
+        self.writer.write_all(self.record_write.as_bytes())?;
+        self.record_write.clear();
+        Ok(())
+    }
+}
+
+impl<W: Write> StatementWriter for Mt940StreamWriter<W> {
+    fn start_statement(&mut self, record: &BkToCstmrStmt) -> Result<(), ConvertError> {
+        DocumentMt940::extract_header_mt940(record, &mut self.record_write);
+        DocumentMt940::extract_field_6x_mt940(record, &mut self.record_write, false);
+        // исходящие остатки записываются после записей выписки
+        self.closing.clear();
+        DocumentMt940::extract_field_6x_mt940(record, &mut self.closing, true);
+        self.flush_record()
+    }
+
+    fn write_entry(&mut self, ntry: &NtryAttribute) -> Result<(), ConvertError> {
+        DocumentMt940::extract_field_61_86_mt940(std::slice::from_ref(ntry), &mut self.record_write);
+        self.flush_record()
+    }
+
+    fn end_statement(&mut self) -> Result<(), ConvertError> {
+        self.record_write.push_str(&self.closing);
+        DocumentMt940::extract_trailer_mt940(&mut self.record_write);
+        self.flush_record()?;
+        self.writer.flush()?;
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<(), ConvertError> {
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+/// Наибольшее количество записей одного признака в поле :90D:/:90C: (5n)
+const MAX_MT942_ENTRIES: u32 = 99999;
+
+/// Потоковая запись документа MT942: каждая выписка записывается отдельным промежуточным отчетом
+///
+/// Итоги :90D:/:90C: рассчитываются по записанным записям отчета. Итоги записываются в валюте отчета,
+/// поэтому запись в другой валюте и больше 99999 записей одного признака (предел поля 5n) -
+/// ошибки `ConvertError::WriteError`.
+pub struct Mt942StreamWriter<W: Write> {
+    writer: W,
+    record_write: String,
+    currency: String,
+    debit: TtlCdtDbtNtriesAttribute,
+    credit: TtlCdtDbtNtriesAttribute,
+}
+
+impl<W: Write> Mt942StreamWriter<W> {
+    /// Создает запись в `w`.
+    pub fn new(w: W) -> Self {
+        Self { writer: w, record_write: String::new(), currency: String::new(),
+               debit: TtlCdtDbtNtriesAttribute::default(), credit: TtlCdtDbtNtriesAttribute::default() }
+    }
+
+    fn flush_record(&mut self) -> Result<(), ConvertError> {
+        self.writer.write_all(self.record_write.as_bytes())?;
+        self.record_write.clear();
+        Ok(())
+    }
+}
+
+impl<W: Write> StatementWriter for Mt942StreamWriter<W> {
+    fn start_statement(&mut self, record: &BkToCstmrStmt) -> Result<(), ConvertError> {
+        DocumentMt942::extract_header_mt942(record, &mut self.record_write)?;
+        self.currency = DocumentMt942::currency(record).to_string();
+        self.debit = TtlCdtDbtNtriesAttribute::default();
+        self.credit = TtlCdtDbtNtriesAttribute::default();
+        self.flush_record()
+    }
+
+    fn write_entry(&mut self, ntry: &NtryAttribute) -> Result<(), ConvertError> {
+        if !ntry.amt.ccy.is_empty() && ntry.amt.ccy != self.currency {
+            return Err(ConvertError::WriteError(format!("Entry currency {} differs from MT942 report currency {}",
+                                                        ntry.amt.ccy, self.currency)));
+        }
+        let total = match ntry.cdt_dbt_ind {
+            CreditDebit::Debit => &mut self.debit,
+            CreditDebit::Credit => &mut self.credit,
+        };
+        if total.nb_of_ntries >= MAX_MT942_ENTRIES {
+            return Err(ConvertError::WriteError(format!("More than {} {} entries in MT942 report",
+                                                        MAX_MT942_ENTRIES, ntry.cdt_dbt_ind.code())));
+        }
+        let sum = total.sum.checked_add(ntry.amt.amt)?;
+        total.nb_of_ntries += 1;
+        total.sum = sum;
+        DocumentMt940::extract_field_61_86_mt940(std::slice::from_ref(ntry), &mut self.record_write);
+        self.flush_record()
+    }
+
+    fn end_statement(&mut self) -> Result<(), ConvertError> {
+        DocumentMt942::extract_field_90_mt942(&self.debit, &self.credit, &self.currency, &mut self.record_write);
+        DocumentMt940::extract_trailer_mt940(&mut self.record_write);
+        self.flush_record()?;
+        self.writer.flush()?;
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<(), ConvertError> {
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+/// Потоковая запись выписки CSV (СберБизнес)
+///
+/// Формат CSV содержит одну выписку: записывается первая выписка потока, остальные пропускаются.
+/// Итоги оборотов рассчитываются по записанным записям. Плоский документ CSV
+/// (см. [`crate::models::csv_flat`]) содержит записи всех выписок потока.
+pub struct CsvStreamWriter<W: Write> {
+    writer: csv::Writer<W>,
+    statements: usize,
+    head: Option<BkToCstmrStmt>,
+    totals: TxsSummryAttribute,
+    flat: Option<FlatCsv>,
+    created_at: NaiveDateTime,
+}
+
+impl<W: Write> CsvStreamWriter<W> {
+    /// Создает запись в `w`.
+    pub fn new(w: W) -> Self {
+        Self { writer: WriterBuilder::new().has_headers(false).flexible(true).from_writer(w), statements: 0, head: None,
+            totals: TxsSummryAttribute::zero(), flat: None, created_at: Local::now().naive_local() }
+    }
+
+    /// Создает запись плоского документа с параметрами `flat` в `w`.
+    pub fn flat(w: W, flat: FlatCsv) -> Self {
+        Self { writer: WriterBuilder::new().has_headers(false).delimiter(flat.delimiter).from_writer(w),
+            statements: 0, head: None, totals: TxsSummryAttribute::zero(), flat: Some(flat),
+            created_at: Local::now().naive_local() }
+    }
+
+    /// Задает дату формирования выписки в заголовке отчета; по умолчанию - время создания записи.
+    pub fn with_created_at(mut self, created_at: NaiveDateTime) -> Self {
+        self.created_at = created_at;
+        self
+    }
+
+    /// Записывает строку плоского документа.
+    fn write_flat(&mut self, row: &RowCsv) -> Result<(), ConvertError> {
+        self.writer.write_record(fields(row, FLAT_COLUMNS.len()))?;
+        Ok(())
+    }
+}
+
+impl<W: Write> StatementWriter for CsvStreamWriter<W> {
+    fn start_statement(&mut self, record: &BkToCstmrStmt) -> Result<(), ConvertError> {
+        self.statements += 1;
+        if self.flat.is_some() {
+            if self.statements == 1 {
+                self.write_flat(&DocumentCsv::flat_header_row())?;
+            }
+            self.head = Some(record.clone());
+            return Ok(());
+        }
+        if self.statements > 1 {
+            return Ok(());
+        }
+        for row in DocumentCsv::head_rows(record, self.created_at) {
+            self.writer.write_record(row.values())?;
+        }
+        self.head = Some(record.clone());
+        Ok(())
+    }
+
+    fn write_entry(&mut self, ntry: &NtryAttribute) -> Result<(), ConvertError> {
+        if let Some(flat) = self.flat {
+            if let Some(head) = &self.head {
+                let row = DocumentCsv::flat_entry_row(head, ntry, flat);
+                self.write_flat(&row)?;
+            }
+            return Ok(());
+        }
+        if self.head.is_some() {
+            self.totals.add_entry(ntry)?;
+            self.writer.write_record(DocumentCsv::entry_row(ntry).values())?;
+        }
+        Ok(())
+    }
+
+    fn end_statement(&mut self) -> Result<(), ConvertError> {
+        if self.flat.is_some() {
+            self.head = None;
+            self.writer.flush()?;
+            return Ok(());
+        }
+        if let Some(head) = self.head.take() {
+            for row in DocumentCsv::tail_rows(&head, &self.totals) {
+                self.writer.write_record(row.values())?;
+            }
+            self.writer.flush()?;
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<(), ConvertError> {
+        if self.statements == 0 {
+            return Err(ParserError::BadCsvDeserializeError("No document to convert CSV format".to_string()).into());
+        }
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+/// Потоковая запись файла обмена 1С с клиент-банком (1CClientBankExchange)
+///
+/// Заголовок файла перечисляет счета и общий период выписок, а секции остатков предшествуют
+/// секциям документов, поэтому секции собираются в памяти и записываются в `w` при завершении
+/// документа. Обороты секций остатков рассчитываются по записанным записям.
+pub struct ClientBankStreamWriter<W: Write> {
+    writer: EncodeWriter<W>,
+    encoding_label: &'static str,
+    created: Option<IsoDateTime>,
+    accounts: Vec<String>,
+    period: Option<(NaiveDate, NaiveDate)>,
+    sections: String,
+    documents: String,
+    head: Option<BkToCstmrStmt>,
+    booked: Option<(NaiveDate, NaiveDate)>,
+    totals: TxsSummryAttribute,
+    statements: usize,
+}
+
+/// Расширяет период `period` датами `from` - `to`.
+fn extend_period(period: &mut Option<(NaiveDate, NaiveDate)>, from: NaiveDate, to: NaiveDate) {
+    *period = Some(match *period {
+        Some((start, end)) => (start.min(from), end.max(to)),
+        None => (from, to),
+    });
+}
+
+impl<W: Write> ClientBankStreamWriter<W> {
+    /// Создает запись в `w` в кодировке `encoding`.
+    ///
+    /// # Ошибки
+    ///
+    /// Возвращает `ConvertError::BadArgument`, если формат обмена не допускает кодировку:
+    /// поддерживаются Windows-1251 и CP866.
+    pub fn new(w: W, encoding: TextEncoding) -> Result<Self, ConvertError> {
+        let Some(encoding_label) = DocumentClientBank::encoding_label(encoding) else {
+            return Err(ConvertError::BadArgument(
+                format!("{} is not supported by 1C exchange files, use CP1251 or CP866", encoding)));
+        };
+        Ok(Self { writer: EncodeWriter::new(w, encoding)?, encoding_label, created: None, accounts: Vec::new(),
+            period: None, sections: String::new(), documents: String::new(), head: None, booked: None,
+            totals: TxsSummryAttribute::zero(), statements: 0 })
+    }
+}
+
+impl<W: Write> StatementWriter for ClientBankStreamWriter<W> {
+    fn start_statement(&mut self, record: &BkToCstmrStmt) -> Result<(), ConvertError> {
+        self.statements += 1;
+        if self.created.is_none() {
+            self.created = record.grp_hdr.cre_dt_tm.or(record.stmt.cre_dt_tm);
+        }
+        let account = DocumentClientBank::account(record).to_string();
+        if !self.accounts.contains(&account) {
+            self.accounts.push(account);
+        }
+        self.head = Some(record.clone());
+        self.booked = None;
+        self.totals = TxsSummryAttribute::zero();
+        Ok(())
+    }
+
+    fn write_entry(&mut self, ntry: &NtryAttribute) -> Result<(), ConvertError> {
+        if let Some(head) = &self.head {
+            self.totals.add_entry(ntry)?;
+            if let Some(date) = ntry.bookg_dt.dt.or(ntry.val_dt.dt) {
+                extend_period(&mut self.booked, date, date);
+            }
+            DocumentClientBank::extract_document(head, ntry, &mut self.documents);
+        }
+        Ok(())
+    }
+
+    fn end_statement(&mut self) -> Result<(), ConvertError> {
+        if let Some(head) = self.head.take() {
+            let period = DocumentClientBank::period(&head, self.booked);
+            if let Some((from, to)) = period {
+                extend_period(&mut self.period, from, to);
+            }
+            DocumentClientBank::extract_account_section(&head, period, &self.totals, &mut self.sections);
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<(), ConvertError> {
+        if self.statements == 0 {
+            return Err(ParserError::BadInputFormatFile("No document to convert 1C format".to_string()).into());
+        }
+        let mut record_write = String::new();
+        DocumentClientBank::extract_header(&mut record_write, self.encoding_label, self.created, self.period, &self.accounts);
+        record_write.push_str(&std::mem::take(&mut self.sections));
+        record_write.push_str(&std::mem::take(&mut self.documents));
+        DocumentClientBank::extract_trailer(&mut record_write);
+        self.writer.write_all(record_write.as_bytes())?;
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+/// Ширина колонок листа XLSX в раскладке отчета CSV
+const XLSX_COLUMN_WIDTHS: [(&str, f64); 11] = [("b", 12.0), ("e", 40.0), ("h", 16.0), ("i", 40.0), ("j", 16.0),
+    ("l", 16.0), ("n", 16.0), ("o", 12.0), ("r", 30.0), ("t", 16.0), ("u", 60.0)];
+
+/// Роль строки отчета на листе XLSX
+#[derive(Clone, Copy)]
+enum XlsxRow<'a> {
+    /// Строка шапки выписки
+    Head,
+    /// Строка заголовка таблицы
+    Title,
+    /// Строка записи `ntry`
+    Entry(&'a NtryAttribute),
+    /// Строка остатков и итогов
+    Footer,
+}
+
+/// Форматы ячеек листа XLSX
+struct XlsxFormats {
+    bold: Format,
+    wrap: Format,
+    amount: Format,
+    date: Format,
+}
+
+impl XlsxFormats {
+    fn new() -> Self {
+        Self {
+            bold: Format::new().set_bold().set_text_wrap(),
+            wrap: Format::new().set_text_wrap(),
+            amount: Format::new().set_num_format("#,##0.00"),
+            date: Format::new().set_num_format("dd.mm.yyyy"),
+        }
+    }
+
+    /// Записывает строку отчета `row` в строку `index` листа `sheet`.
+    ///
+    /// Суммы записываются числами, дата проводки - датой, многострочные ячейки - с переносом.
+    fn write_row(&self, sheet: &mut Worksheet, index: u32, row: &RowCsv, role: XlsxRow) -> Result<(), ConvertError> {
+        let amounts = [RowCsv::column_index("j"), RowCsv::column_index("n")];
+        let date = RowCsv::column_index("b");
+        for (column, value) in row.values().enumerate().filter(|(_, value)| !value.is_empty()) {
+            let cell = (index, column as u16);
+            match (role, value.parse::<i64>(), value.parse::<Money>()) {
+                (XlsxRow::Entry(NtryAttribute { bookg_dt: DtAttribute { dt: Some(dt) }, .. }), _, _) if Some(column) == date => {
+                    let dt = ExcelDateTime::from_ymd(dt.year() as u16, dt.month() as u8, dt.day() as u8)?;
+                    sheet.write_datetime_with_format(cell.0, cell.1, &dt, &self.date)?;
+                }
+                (XlsxRow::Entry(ntry), _, _) if amounts.contains(&Some(column)) => {
+                    sheet.write_number_with_format(cell.0, cell.1, ntry.amt.amt.to_f64(), &self.amount)?;
+                }
+                (XlsxRow::Footer, Ok(count), _) if Some(column) != date => {
+                    sheet.write_number(cell.0, cell.1, count as f64)?;
+                }
+                (XlsxRow::Footer, _, Ok(amount)) if Some(column) != date => {
+                    sheet.write_number_with_format(cell.0, cell.1, amount.to_f64(), &self.amount)?;
+                }
+                (XlsxRow::Title, _, _) => {
+                    sheet.write_string_with_format(cell.0, cell.1, value, &self.bold)?;
+                }
+                _ if value.contains('\n') => {
+                    sheet.write_string_with_format(cell.0, cell.1, value, &self.wrap)?;
+                }
+                _ => {
+                    sheet.write_string(cell.0, cell.1, value)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Потоковая запись книги Excel XLSX
+///
+/// Каждая выписка записывается на отдельный лист, названный по номеру счета, в раскладке отчета
+/// CSV (см. [`CsvStreamWriter`]): суммы - числовые ячейки, даты проводки - ячейки-даты,
+/// заголовки таблицы - полужирные, реквизиты плательщика и получателя - многострочные ячейки.
+/// Книга собирается в памяти и записывается в `w` при завершении документа.
+pub struct XlsxStreamWriter<W: Write> {
+    writer: W,
+    workbook: Workbook,
+    formats: XlsxFormats,
+    sheet: Option<Worksheet>,
+    sheet_names: Vec<String>,
+    row: u32,
+    head: Option<BkToCstmrStmt>,
+    totals: TxsSummryAttribute,
+    created_at: NaiveDateTime,
+}
+
+impl<W: Write> XlsxStreamWriter<W> {
+    /// Создает запись в `w`.
+    pub fn new(w: W) -> Self {
+        Self { writer: w, workbook: Workbook::new(), formats: XlsxFormats::new(), sheet: None, sheet_names: Vec::new(),
+            row: 0, head: None, totals: TxsSummryAttribute::zero(), created_at: Local::now().naive_local() }
+    }
+
+    /// Задает дату формирования выписки в заголовке листа; по умолчанию - время создания записи.
+    pub fn with_created_at(mut self, created_at: NaiveDateTime) -> Self {
+        self.created_at = created_at;
+        self
+    }
+
+    /// Имя листа выписки `record`: номер счета без недопустимых символов, не длиннее 31 символа
+    /// и не совпадающее с именами других листов.
+    fn sheet_name(&mut self, record: &BkToCstmrStmt) -> String {
+        let account: String = DocumentCsv::flat_account(record).chars()
+            .filter(|c| !"[]:*?/\\".contains(*c))
+            .take(31)
+            .collect();
+        let base = if account.is_empty() { format!("Sheet{}", self.sheet_names.len() + 1) } else { account };
+        let mut name = base.clone();
+        let mut number = 1;
+        while self.sheet_names.iter().any(|other| other.to_lowercase() == name.to_lowercase()) {
+            number += 1;
+            let suffix = format!(" ({})", number);
+            name = format!("{}{}", base.chars().take(31 - suffix.chars().count()).collect::<String>(), suffix);
+        }
+        self.sheet_names.push(name.clone());
+        name
+    }
+
+    fn write_rows(&mut self, rows: &[RowCsv], role: impl Fn(usize) -> XlsxRow<'static>) -> Result<(), ConvertError> {
+        if let Some(sheet) = self.sheet.as_mut() {
+            for (index, row) in rows.iter().enumerate() {
+                self.formats.write_row(sheet, self.row, row, role(index))?;
+                self.row += 1;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<W: Write> StatementWriter for XlsxStreamWriter<W> {
+    fn start_statement(&mut self, record: &BkToCstmrStmt) -> Result<(), ConvertError> {
+        let mut sheet = Worksheet::new();
+        sheet.set_name(self.sheet_name(record))?;
+        for (column, width) in XLSX_COLUMN_WIDTHS {
+            if let Some(column) = RowCsv::column_index(column) {
+                sheet.set_column_width(column as u16, width)?;
+            }
+        }
+        self.sheet = Some(sheet);
+        self.row = 0;
+        let head = DocumentCsv::head_rows(record, self.created_at);
+        let title = head.len() - 2;
+        self.write_rows(&head, |index| if index >= title { XlsxRow::Title } else { XlsxRow::Head })?;
+        if let Some(sheet) = self.sheet.as_mut() {
+            sheet.set_freeze_panes(self.row, 0)?;
+        }
+        self.totals = TxsSummryAttribute::zero();
+        self.head = Some(record.clone());
+        Ok(())
+    }
+
+    fn write_entry(&mut self, ntry: &NtryAttribute) -> Result<(), ConvertError> {
+        if let Some(sheet) = self.sheet.as_mut() {
+            self.totals.add_entry(ntry)?;
+            self.formats.write_row(sheet, self.row, &DocumentCsv::entry_row(ntry), XlsxRow::Entry(ntry))?;
+            self.row += 1;
+        }
+        Ok(())
+    }
+
+    fn end_statement(&mut self) -> Result<(), ConvertError> {
+        if let Some(head) = self.head.take() {
+            let tail = DocumentCsv::tail_rows(&head, &self.totals);
+            self.write_rows(&tail, |index| if index == 0 { XlsxRow::Title } else { XlsxRow::Footer })?;
+        }
+        if let Some(sheet) = self.sheet.take() {
+            self.workbook.push_worksheet(sheet);
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<(), ConvertError> {
+        if self.sheet_names.is_empty() {
+            return Err(ParserError::BadCsvDeserializeError("No document to convert XLSX format".to_string()).into());
+        }
+        self.writer.write_all(&self.workbook.save_to_buffer()?)?;
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::File;
+    use std::io::BufReader;
+
+    fn read_stream(xml: &str) -> Vec<StreamItem> {
+        CamtStreamReader::new(xml.as_bytes()).collect::<Result<_, _>>().unwrap()
+    }
+
+    #[test]
+    fn test_stream_reader_items() {
+        let xml = r#"<Document xmlns="urn:iso:std:iso:20022:tech:xsd:camt.053.001.08"><BkToCstmrStmt>
+            <GrpHdr><MsgId>MSG1</MsgId></GrpHdr>
+            <Stmt><Id>S1</Id><Bal><Amt Ccy="EUR">1.00</Amt></Bal>
+              <Ntry><Amt Ccy="EUR">2.00</Amt><Sts><Cd>BOOK</Cd></Sts></Ntry><Ntry/>
+              <AddtlStmtInf>skipped</AddtlStmtInf></Stmt>
+            <Stmt><Id>S2</Id></Stmt><Stmt/>
+            </BkToCstmrStmt></Document>"#;
+        let mut reader = CamtStreamReader::new(xml.as_bytes());
+        let items: Vec<StreamItem> = reader.by_ref().collect::<Result<_, _>>().unwrap();
+        assert_eq!(Some(CamtVersion::V08), reader.version());
+        assert_eq!(8, items.len());
+        let StreamItem::Statement(record) = &items[0] else { panic!("statement expected") };
+        assert_eq!("MSG1", record.grp_hdr.msg_id);
+        assert_eq!("S1", record.stmt.id);
+        assert_eq!(1, record.stmt.bal.len());
+        let StreamItem::Entry(ntry) = &items[1] else { panic!("entry expected") };
+        assert_eq!("BOOK", ntry.sts);
+        assert_eq!(StreamItem::Entry(Box::default()), items[2]);
+        assert_eq!(StreamItem::EndStatement, items[3]);
+        let StreamItem::Statement(record) = &items[4] else { panic!("statement expected") };
+        assert_eq!("S2", record.stmt.id);
+        assert_eq!(StreamItem::EndStatement, items[5]);
+        assert!(matches!(&items[6], StreamItem::Statement(record) if record.stmt.id.is_empty()));
+        assert_eq!(StreamItem::EndStatement, items[7]);
+    }
+
+    #[test]
+    fn test_stream_reader_matches_document() {
+        for path in ["../test_files/CAMT053 corrected.xml", "../test_files/camt 053 danske bank.txt"] {
+            let document = DocumentCamt053::from_read(&mut File::open(path).unwrap()).unwrap();
+            let mut records = Vec::new();
+            for item in CamtStreamReader::new(BufReader::new(File::open(path).unwrap())) {
+                match item.unwrap() {
+                    StreamItem::Statement(record) => records.push(*record),
+                    StreamItem::Entry(ntry) => records.last_mut().unwrap().stmt.ntry.push(*ntry),
+                    StreamItem::EndStatement => {}
+                }
+            }
+            assert_eq!(document.bk_to_cstmr_stmt, records, "{}", path);
+        }
+        // пустые суммы Sum итогов - ошибка и при чтении документа, и при потоковом чтении
+        let path = "../test_files/camt053_str.xml";
+        assert!(DocumentCamt053::from_read(&mut File::open(path).unwrap()).is_err());
+        assert!(CamtStreamReader::new(BufReader::new(File::open(path).unwrap())).any(|item| item.is_err()));
+    }
+
+    #[test]
+    fn test_stream_writers_match_document_writers() {
+        let path = "../test_files/camt 053 danske bank.txt";
+        let mut document = DocumentCamt053::from_read(&mut File::open(path).unwrap()).unwrap();
+        document.normalize_summary().unwrap();
+        let mut expected = Vec::new();
+        document.write_to_version(&mut expected, CamtVersion::V08).unwrap();
+        let mut output = Vec::new();
+        let mut writer = CamtStreamWriter::new(&mut output, CamtVersion::V08).unwrap();
+        for item in read_stream(std::str::from_utf8(&expected).unwrap()) {
+            writer.write_item(&item).unwrap();
+        }
+        writer.finish().unwrap();
+        assert_eq!(String::from_utf8(expected).unwrap(), String::from_utf8(output).unwrap());
+
+        let mut expected = Vec::new();
+        DocumentMt940::try_from(document.clone()).unwrap().write_to(&mut expected).unwrap();
+        let mut output = Vec::new();
+        Mt940StreamWriter::new(&mut output).write_document(&document).unwrap();
+        assert_eq!(expected, output);
+    }
+
+    #[test]
+    fn test_camt_stream_writer_rejects_inconsistent_summary() {
+        let xml = r#"<Document><BkToCstmrStmt><Stmt><Id>S1</Id><Acct><Id><IBAN>DE89370400440532013000</IBAN></Id></Acct>
+            <TxsSummry><TtlCdtNtries><NbOfNtries>2</NbOfNtries><Sum>5.00</Sum></TtlCdtNtries></TxsSummry>
+            <Ntry><Amt Ccy="EUR">5.00</Amt><CdtDbtInd>CRDT</CdtDbtInd></Ntry></Stmt></BkToCstmrStmt></Document>"#;
+        let mut output = Vec::new();
+        let mut writer = CamtStreamWriter::new(&mut output, CamtVersion::V02).unwrap();
+        let result: Result<(), ConvertError> = read_stream(xml).iter().try_for_each(|item| writer.write_item(item));
+        let error = result.unwrap_err().to_string();
+        assert!(error.contains("TtlCdtNtries"), "{}", error);
+        drop(writer);
+        // реквизиты выписки уже записаны, но итоги и записи с противоречием не записываются
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains("<Id>S1</Id>"), "{}", output);
+        assert!(!output.contains("<Ntry>") && !output.contains("<TxsSummry>"), "{}", output);
+        assert!(CamtStreamWriter::new(Vec::new(), CamtVersion::V05).is_err());
+    }
+
+    /// Запись в общий буфер, который можно читать, пока запись выписки не завершена
+    #[derive(Clone, Default)]
+    struct SharedOutput(std::rc::Rc<std::cell::RefCell<Vec<u8>>>);
+
+    impl Write for SharedOutput {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.borrow_mut().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_camt_stream_writer_large_statement() {
+        const ENTRIES: usize = 100_000;
+        let xml = r#"<Document><BkToCstmrStmt><GrpHdr><MsgId>MSG1</MsgId></GrpHdr>
+            <Stmt><Id>S1</Id><Acct><Id><IBAN>DE89370400440532013000</IBAN></Id></Acct>
+              <Bal><Tp><CdOrPrtry><Cd>OPBD</Cd></CdOrPrtry></Tp><Amt Ccy="EUR">1.00</Amt><CdtDbtInd>CRDT</CdtDbtInd></Bal>
+              <Ntry><Amt Ccy="EUR">0.01</Amt><CdtDbtInd>CRDT</CdtDbtInd></Ntry></Stmt></BkToCstmrStmt></Document>"#;
+        let items = read_stream(xml);
+        let output = SharedOutput::default();
+        let mut writer = CamtStreamWriter::new(output.clone(), CamtVersion::V08).unwrap();
+        writer.write_item(&items[0]).unwrap();
+        // заголовок, реквизиты и остатки записываются сразу
+        let head = output.0.borrow().len();
+        assert!(String::from_utf8_lossy(&output.0.borrow()).contains("<Bal>"));
+        let mut spooled = 0;
+        for index in 0..ENTRIES {
+            writer.write_item(&items[1]).unwrap();
+            if index % 10_000 == 0 {
+                // записи уходят во временный файл, а не в память и не в документ до итогов
+                let len = writer.spool.as_mut().unwrap().len().unwrap();
+                assert!(len > spooled);
+                spooled = len;
+                assert_eq!(head, output.0.borrow().len());
+            }
+        }
+        let totals = writer.statement.as_ref().unwrap();
+        assert_eq!(ENTRIES, totals.entries);
+        assert_eq!(Money::new(ENTRIES as i64, 2), totals.computed.credit().sum);
+        writer.write_item(&items[2]).unwrap();
+        writer.finish().unwrap();
+        assert_eq!(0, writer.spool.as_mut().unwrap().len().unwrap());
+
+        let output = String::from_utf8(output.0.take()).unwrap();
+        assert_eq!(ENTRIES, output.matches("<Ntry>").count());
+        let summary = output.find("<TxsSummry>").unwrap();
+        assert!(output.find("</Bal>").unwrap() < summary && summary < output.find("<Ntry>").unwrap());
+        let document = DocumentCamt053::from_read(&mut output.as_bytes()).unwrap();
+        let stmt = &document.bk_to_cstmr_stmt[0].stmt;
+        assert_eq!(Some(ENTRIES as u32), stmt.txs_summry.ttl_ntries.nb_of_ntries);
+        assert_eq!(Some(TtlCdtDbtNtriesAttribute { nb_of_ntries: ENTRIES as u32, sum: Money::new(ENTRIES as i64, 2) }),
+                   stmt.txs_summry.ttl_cdt_ntries);
+    }
+
+    #[test]
+    fn test_camt_stream_writer_computes_summary() {
+        let xml = r#"<Document><BkToCstmrStmt><Stmt><Id>S1</Id><Acct><Id><IBAN>DE89370400440532013000</IBAN></Id></Acct>
+            <TxsSummry><TtlNtries><NbOfNtries>2</NbOfNtries></TtlNtries></TxsSummry>
+            <Ntry><Amt Ccy="EUR">5.00</Amt><CdtDbtInd>CRDT</CdtDbtInd></Ntry>
+            <Ntry><Amt Ccy="EUR">2.00</Amt><CdtDbtInd>DBIT</CdtDbtInd></Ntry></Stmt>
+            <Stmt><Id>S2</Id><Acct><Id><IBAN>DE89370400440532013000</IBAN></Id></Acct>
+              <Ntry><Amt Ccy="EUR">1.00</Amt><CdtDbtInd>DBIT</CdtDbtInd></Ntry></Stmt>
+            </BkToCstmrStmt></Document>"#;
+        let mut output = Vec::new();
+        let mut writer = CamtStreamWriter::new(&mut output, CamtVersion::V02).unwrap();
+        for item in read_stream(xml) {
+            writer.write_item(&item).unwrap();
+        }
+        writer.finish().unwrap();
+        let document = DocumentCamt053::from_read(&mut output.as_slice()).unwrap();
+        let summaries: Vec<&TxsSummryAttribute> = document.bk_to_cstmr_stmt.iter().map(|r| &r.stmt.txs_summry).collect();
+        assert_eq!(Some(TtlCdtDbtNtriesAttribute { nb_of_ntries: 1, sum: Money::new(500, 2) }), summaries[0].ttl_cdt_ntries);
+        assert_eq!("3.00", summaries[0].ttl_ntries.ttl_net_ntry_amt.to_string());
+        assert_eq!(Some(TtlCdtDbtNtriesAttribute { nb_of_ntries: 1, sum: Money::new(100, 2) }), summaries[1].ttl_dbt_ntries);
+        assert_eq!(Some(CreditDebit::Debit), summaries[1].ttl_ntries.cdt_dbt_ind);
+    }
+
+    #[test]
+    fn test_mt942_stream_writer_checks() {
+        let xml = r#"<Document><BkToCstmrStmt><Stmt><Id>S1</Id><CreDtTm>2024-02-20T15:30:00+01:00</CreDtTm>
+            <Acct><Ccy>EUR</Ccy></Acct>
+            <Ntry><Amt Ccy="EUR">5.00</Amt><CdtDbtInd>CRDT</CdtDbtInd></Ntry>
+            <Ntry><Amt Ccy="USD">2.00</Amt><CdtDbtInd>CRDT</CdtDbtInd></Ntry></Stmt></BkToCstmrStmt></Document>"#;
+        let items = read_stream(xml);
+        let mut output = Vec::new();
+        let mut writer = Mt942StreamWriter::new(&mut output);
+        writer.write_item(&items[0]).unwrap();
+        writer.write_item(&items[1]).unwrap();
+        let error = writer.write_item(&items[2]).unwrap_err().to_string();
+        assert!(error.contains("USD") && error.contains("EUR"), "{}", error);
+        assert_eq!(1, writer.credit.nb_of_ntries);
+
+        writer.credit.nb_of_ntries = MAX_MT942_ENTRIES;
+        let error = writer.write_item(&items[1]).unwrap_err().to_string();
+        assert!(error.contains("99999"), "{}", error);
+        assert_eq!(MAX_MT942_ENTRIES, writer.credit.nb_of_ntries);
+        assert_eq!(Money::new(500, 2), writer.credit.sum);
+        drop(writer);
+        assert_eq!(1, String::from_utf8(output).unwrap().matches(":61:").count());
+
+        let undated = xml.replace("<CreDtTm>2024-02-20T15:30:00+01:00</CreDtTm>", "");
+        let error = Mt942StreamWriter::new(Vec::new()).write_item(&read_stream(&undated)[0]).unwrap_err();
+        assert!(error.to_string().contains(":13D:"), "{}", error);
+    }
+
+    #[test]
+    fn test_csv_stream_writer_totals() {
+        let xml = r#"<Document><BkToCstmrStmt><Stmt><Id>S1</Id>
+            <Ntry><Amt Ccy="RUB">5.00</Amt><CdtDbtInd>CRDT</CdtDbtInd></Ntry>
+            <Ntry><Amt Ccy="RUB">2.00</Amt><CdtDbtInd>DBIT</CdtDbtInd></Ntry></Stmt>
+            <Stmt><Id>S2</Id><Ntry><Amt Ccy="RUB">9.00</Amt></Ntry></Stmt></BkToCstmrStmt></Document>"#;
+        let mut output = Vec::new();
+        let mut writer = CsvStreamWriter::new(&mut output);
+        for item in read_stream(xml) {
+            writer.write_item(&item).unwrap();
+        }
+        writer.finish().unwrap();
+        drop(writer);
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains(",Количество операций,,,,,,1,,,,1,,,,,,,,2,"), "{}", output);
+        assert!(!output.contains("9.00"));
+        assert!(CsvStreamWriter::new(Vec::new()).finish().is_err());
+    }
+
+    #[test]
+    fn test_csv_stream_writer_flat() {
+        let xml = r#"<Document><BkToCstmrStmt><Stmt><Id>S1</Id><Acct><Id><Othr><Id>407</Id></Othr></Id></Acct>
+            <Ntry><Amt Ccy="RUB">5.00</Amt><CdtDbtInd>CRDT</CdtDbtInd><BookgDt><Dt>2024-01-02</Dt></BookgDt></Ntry></Stmt>
+            <Stmt><Id>S2</Id><Acct><Id><IBAN>DE89370400440532013000</IBAN></Id></Acct>
+            <Ntry><Amt Ccy="EUR">9.50</Amt><CdtDbtInd>DBIT</CdtDbtInd></Ntry></Stmt></BkToCstmrStmt></Document>"#;
+        let mut output = Vec::new();
+        let mut writer = CsvStreamWriter::flat(&mut output, FlatCsv { delimiter: b';', decimal_separator: ',' });
+        for item in read_stream(xml) {
+            writer.write_item(&item).unwrap();
+        }
+        writer.finish().unwrap();
+        drop(writer);
+        let output = String::from_utf8(output).unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(vec![FLAT_COLUMNS.join(";").as_str(), "407;2024-01-02;;5,00;RUB;CRDT;;;;;;",
+                        "DE89370400440532013000;;;9,50;EUR;DBIT;;;;;;"], lines);
+    }
+
+    #[test]
+    fn test_xlsx_stream_writer() {
+        use calamine::{Data, Reader, Xlsx};
+        let xml = r#"<Document><BkToCstmrStmt><Stmt><Id>S1</Id><Acct><Id><Othr><Id>407</Id></Othr></Id></Acct>
+            <Bal><Tp><CdOrPrtry><Cd>OPBD</Cd></CdOrPrtry></Tp><Amt Ccy="RUB">10.00</Amt><CdtDbtInd>CRDT</CdtDbtInd></Bal>
+            <Ntry><Amt Ccy="RUB">1540.50</Amt><CdtDbtInd>DBIT</CdtDbtInd><BookgDt><Dt>2024-02-20</Dt></BookgDt>
+            <NtryDtls><TxDtls><RltdPties><Dbtr><Nm>ООО РОМАШКА</Nm></Dbtr></RltdPties></TxDtls></NtryDtls></Ntry></Stmt>
+            <Stmt><Id>S2</Id><Acct><Id><Othr><Id>407</Id></Othr></Id></Acct>
+            <Ntry><Amt Ccy="RUB">9.00</Amt><CdtDbtInd>CRDT</CdtDbtInd></Ntry></Stmt></BkToCstmrStmt></Document>"#;
+        let mut output = Vec::new();
+        let mut writer = XlsxStreamWriter::new(&mut output);
+        for item in read_stream(xml) {
+            writer.write_item(&item).unwrap();
+        }
+        writer.finish().unwrap();
+        drop(writer);
+        let mut workbook: Xlsx<_> = calamine::open_workbook_from_rs(std::io::Cursor::new(output)).unwrap();
+        assert_eq!(vec!["407".to_string(), "407 (2)".to_string()], workbook.sheet_names());
+        let range = workbook.worksheet_range("407").unwrap();
+        assert_eq!(Some(&Data::String("Сумма по дебету".to_string())), range.get_value((5, 9)));
+        let Some(Data::DateTime(date)) = range.get_value((7, 1)) else {
+            panic!("{:?}", range.get_value((7, 1)));
+        };
+        assert_eq!("2024-02-20", date.as_datetime().unwrap().date().to_string());
+        assert_eq!(Some(&Data::Float(1540.5)), range.get_value((7, 9)));
+        assert_eq!(Some(&Data::String("\n\nООО РОМАШКА".to_string())), range.get_value((7, 4)));
+        assert_eq!(Some(&Data::Float(1.0)), range.get_value((9, 7)));
+        assert_eq!(Some(&Data::String("Входящий остаток".to_string())), range.get_value((10, 1)));
+        assert_eq!(Some(&Data::Float(10.0)), range.get_value((10, 11)));
+        assert!(XlsxStreamWriter::new(Vec::new()).finish().is_err());
+    }
+}
@@ -0,0 +1,374 @@
+//! Модуль проверки внутренней согласованности выписок
+//!
+//! Предоставляет функциональность по сверке остатков, итогов по операциям, дат проводки
+//! и валют остатков выписки CAMT.053
+//!
+//! ```
+//! use bank_converter::models::builder::{BalanceBuilder, EntryBuilder, StatementBuilder};
+//! use bank_converter::models::codes::BalanceType;
+//! use bank_converter::models::money::Money;
+//! use bank_converter::validate::validate_statement;
+//!
+//! let statement = StatementBuilder::new("STMT-1")
+//!     .account("40702810440000030888", "RUB")
+//!     .balance(BalanceBuilder::new(BalanceType::Opbd).amount(Money::new(10000, 2), "RUB").credit().build())
+//!     .entry(EntryBuilder::new().amount(Money::new(1000, 2), "RUB").debit().build())
+//!     .balance(BalanceBuilder::new(BalanceType::Clbd).amount(Money::new(9000, 2), "RUB").credit().build())
+//!     .build();
+//! assert!(validate_statement(&statement.stmt).is_empty());
+//! ```
+use std::fmt::{Display, Formatter};
+use chrono::NaiveDate;
+use crate::models::camt053::{BalanceAttribute, DocumentCamt053, StatementAttribute, TxsSummryAttribute};
+use crate::models::codes::{BalanceType, CreditDebit};
+use crate::models::money::Money;
+
+/// Несоответствие, найденное при проверке выписки
+#[derive(Debug, Clone, PartialEq)]
+pub enum ValidationIssue {
+    /// Входящий остаток плюс кредит минус дебет не равен исходящему остатку
+    BalanceMismatch {
+        /// Идентификатор выписки
+        statement: String,
+        /// Исходящий остаток, рассчитанный по записям
+        expected: Money,
+        /// Исходящий остаток из выписки
+        actual: Money,
+    },
+    /// Количество записей в TxsSummry не совпадает с количеством записей Ntry
+    SummaryCountMismatch {
+        /// Идентификатор выписки
+        statement: String,
+        /// Элемент итогов (TtlNtries, TtlCdtNtries, TtlDbtNtries)
+        element: &'static str,
+        /// Количество по записям
+        expected: u32,
+        /// Количество из итогов
+        actual: u32,
+    },
+    /// Сумма в TxsSummry не совпадает с суммой записей Ntry
+    SummarySumMismatch {
+        /// Идентификатор выписки
+        statement: String,
+        /// Элемент итогов (TtlNtries, TtlCdtNtries, TtlDbtNtries)
+        element: &'static str,
+        /// Сумма по записям
+        expected: Money,
+        /// Сумма из итогов
+        actual: Money,
+    },
+    /// Дата проводки записи вне периода выписки FrToDt
+    BookingDateOutOfPeriod {
+        /// Идентификатор выписки
+        statement: String,
+        /// Номер записи, начиная с 1
+        entry: usize,
+        /// Дата проводки
+        date: NaiveDate,
+    },
+    /// Валюта остатка не совпадает с валютой счета
+    CurrencyMismatch {
+        /// Идентификатор выписки
+        statement: String,
+        /// Тип остатка
+        balance: BalanceType,
+        /// Валюта счета
+        expected: String,
+        /// Валюта остатка
+        actual: String,
+    },
+    /// Валюта записи не совпадает с валютой предыдущих записей выписки
+    EntryCurrencyMismatch {
+        /// Идентификатор выписки
+        statement: String,
+        /// Номер записи, начиная с 1
+        entry: usize,
+        /// Валюта предыдущих записей
+        expected: String,
+        /// Валюта записи
+        actual: String,
+    },
+    /// Сумма записей Ntry не помещается в денежный тип
+    AmountOverflow {
+        /// Идентификатор выписки
+        statement: String,
+    },
+}
+
+impl Display for ValidationIssue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ValidationIssue::BalanceMismatch { statement, expected, actual } =>
+                write!(f, "Statement {}: closing balance {} does not match calculated {}",
+                       statement, actual, expected),
+            ValidationIssue::SummaryCountMismatch { statement, element, expected, actual } =>
+                write!(f, "Statement {}: {}/NbOfNtries {} does not match {} entries",
+                       statement, element, actual, expected),
+            ValidationIssue::SummarySumMismatch { statement, element, expected, actual } =>
+                write!(f, "Statement {}: {} sum {} does not match entries sum {}",
+                       statement, element, actual, expected),
+            ValidationIssue::BookingDateOutOfPeriod { statement, entry, date } =>
+                write!(f, "Statement {}: entry {} booking date {} is outside the statement period",
+                       statement, entry, date),
+            ValidationIssue::CurrencyMismatch { statement, balance, expected, actual } =>
+                write!(f, "Statement {}: balance {} currency {} does not match account currency {}",
+                       statement, balance, actual, expected),
+            ValidationIssue::EntryCurrencyMismatch { statement, entry, expected, actual } =>
+                write!(f, "Statement {}: entry {} currency {} does not match entries currency {}",
+                       statement, entry, actual, expected),
+            ValidationIssue::AmountOverflow { statement } =>
+                write!(f, "Statement {}: entries sum overflows", statement),
+        }
+    }
+}
+
+fn signed(amount: Money, cdt_dbt_ind: CreditDebit) -> Money {
+    match cdt_dbt_ind {
+        CreditDebit::Credit => amount,
+        CreditDebit::Debit => -amount,
+    }
+}
+
+fn find_balance<'a>(stmt: &'a StatementAttribute, types: &[BalanceType]) -> Option<&'a BalanceAttribute> {
+    types.iter().find_map(|balance_type| stmt.bal.iter().find(|bal| bal.tp.cd_or_prtry.cd == *balance_type))
+}
+
+fn check_balances(stmt: &StatementAttribute, issues: &mut Vec<ValidationIssue>) {
+    let opening = find_balance(stmt, &[BalanceType::Opbd, BalanceType::Prcd]);
+    let closing = find_balance(stmt, &[BalanceType::Clbd]);
+    if let (Some(opening), Some(closing)) = (opening, closing) {
+        let expected = stmt.ntry.iter()
+            .try_fold(signed(opening.amt.amt, opening.cdt_dbt_ind),
+                      |balance, ntry| balance.checked_add(signed(ntry.amt.amt, ntry.cdt_dbt_ind)));
+        let Ok(expected) = expected else {
+            issues.push(ValidationIssue::AmountOverflow { statement: stmt.id.clone() });
+            return;
+        };
+        let actual = signed(closing.amt.amt, closing.cdt_dbt_ind);
+        if expected != actual {
+            issues.push(ValidationIssue::BalanceMismatch { statement: stmt.id.clone(), expected, actual });
+        }
+    }
+}
+
+fn check_summary(stmt: &StatementAttribute, issues: &mut Vec<ValidationIssue>) {
+    if stmt.txs_summry.is_empty() {
+        return;
+    }
+    // итоги в разных валютах не складываются
+    let mut currencies = stmt.ntry.iter().enumerate().filter(|(_, ntry)| !ntry.amt.ccy.is_empty());
+    if let Some((_, first)) = currencies.next()
+        && let Some((index, ntry)) = currencies.find(|(_, ntry)| ntry.amt.ccy != first.amt.ccy)
+    {
+        issues.push(ValidationIssue::EntryCurrencyMismatch {
+            statement: stmt.id.clone(), entry: index + 1, expected: first.amt.ccy.clone(), actual: ntry.amt.ccy.clone() });
+        return;
+    }
+    let Ok(computed) = TxsSummryAttribute::from_entries(&stmt.ntry) else {
+        issues.push(ValidationIssue::AmountOverflow { statement: stmt.id.clone() });
+        return;
+    };
+    check_summary_totals(&stmt.id, &stmt.txs_summry, &computed, issues);
+}
+
+fn check_summary_totals(statement: &str, summary: &TxsSummryAttribute, computed: &TxsSummryAttribute,
+                        issues: &mut Vec<ValidationIssue>) {
+    let totals = [
+        ("TtlCdtNtries", &summary.ttl_cdt_ntries, computed.credit()),
+        ("TtlDbtNtries", &summary.ttl_dbt_ntries, computed.debit()),
+    ];
+    // сверяются только элементы итогов, которые есть в выписке
+    for (element, total, expected) in totals {
+        let Some(total) = total else {
+            continue;
+        };
+        if total.nb_of_ntries != expected.nb_of_ntries {
+            issues.push(ValidationIssue::SummaryCountMismatch {
+                statement: statement.to_string(), element, expected: expected.nb_of_ntries,
+                actual: total.nb_of_ntries });
+        }
+        if total.sum != expected.sum {
+            issues.push(ValidationIssue::SummarySumMismatch {
+                statement: statement.to_string(), element, expected: expected.sum, actual: total.sum });
+        }
+    }
+    let count = computed.credit().nb_of_ntries + computed.debit().nb_of_ntries;
+    if let Some(nb_of_ntries) = summary.ttl_ntries.nb_of_ntries
+        && nb_of_ntries != count
+    {
+        issues.push(ValidationIssue::SummaryCountMismatch {
+            statement: statement.to_string(), element: "TtlNtries", expected: count, actual: nb_of_ntries });
+    }
+    if let Some(cdt_dbt_ind) = summary.ttl_ntries.cdt_dbt_ind {
+        let net = computed.ttl_ntries.ttl_net_ntry_amt;
+        let net = if computed.ttl_ntries.cdt_dbt_ind == Some(CreditDebit::Debit) { -net } else { net };
+        let actual = signed(summary.ttl_ntries.ttl_net_ntry_amt, cdt_dbt_ind);
+        if net != actual {
+            issues.push(ValidationIssue::SummarySumMismatch {
+                statement: statement.to_string(), element: "TtlNtries", expected: net, actual });
+        }
+    }
+}
+
+fn check_booking_dates(stmt: &StatementAttribute, issues: &mut Vec<ValidationIssue>) {
+    let from = stmt.fr_to_dt.fr_dt_tm.map(|dt| dt.date());
+    let to = stmt.fr_to_dt.to_dt_tm.map(|dt| dt.date());
+    for (index, ntry) in stmt.ntry.iter().enumerate() {
+        if let Some(date) = ntry.bookg_dt.dt
+            && (from.is_some_and(|from| date < from) || to.is_some_and(|to| date > to))
+        {
+            issues.push(ValidationIssue::BookingDateOutOfPeriod { statement: stmt.id.clone(), entry: index + 1, date });
+        }
+    }
+}
+
+fn check_currencies(stmt: &StatementAttribute, issues: &mut Vec<ValidationIssue>) {
+    if stmt.acct.ccy.is_empty() {
+        return;
+    }
+    for bal in &stmt.bal {
+        if bal.amt.ccy != stmt.acct.ccy {
+            issues.push(ValidationIssue::CurrencyMismatch {
+                statement: stmt.id.clone(),
+                balance: bal.tp.cd_or_prtry.cd,
+                expected: stmt.acct.ccy.clone(),
+                actual: bal.amt.ccy.clone(),
+            });
+        }
+    }
+}
+
+/// Проверяет только согласованность итогов TxsSummry с записями выписки.
+pub(crate) fn summary_issues(stmt: &StatementAttribute) -> Vec<ValidationIssue> {
+    let mut issues = Vec::new();
+    check_summary(stmt, &mut issues);
+    issues
+}
+
+/// Сверяет итоги TxsSummry выписки `statement` с итогами `computed`, рассчитанными по ее записям
+/// без хранения самих записей (потоковая запись).
+pub(crate) fn summary_totals_issues(statement: &str, summary: &TxsSummryAttribute, computed: &TxsSummryAttribute)
+                                    -> Vec<ValidationIssue> {
+    let mut issues = Vec::new();
+    if !summary.is_empty() {
+        check_summary_totals(statement, summary, computed, &mut issues);
+    }
+    issues
+}
+
+/// Проверяет согласованность одной выписки.
+///
+/// # Возвращает
+///
+/// Список найденных несоответствий; пустой список, если выписка согласована.
+pub fn validate_statement(stmt: &StatementAttribute) -> Vec<ValidationIssue> {
+    let mut issues = Vec::new();
+    check_balances(stmt, &mut issues);
+    check_summary(stmt, &mut issues);
+    check_booking_dates(stmt, &mut issues);
+    check_currencies(stmt, &mut issues);
+    issues
+}
+
+/// Проверяет согласованность всех выписок документа.
+///
+/// # Возвращает
+///
+/// Список найденных несоответствий; пустой список, если все выписки согласованы.
+pub fn validate(document: &DocumentCamt053) -> Vec<ValidationIssue> {
+    document.bk_to_cstmr_stmt.iter()
+        .flat_map(|statement| validate_statement(&statement.stmt))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::builder::{BalanceBuilder, EntryBuilder, StatementBuilder};
+    use crate::models::camt053::TtlCdtDbtNtriesAttribute;
+    use crate::models::date::IsoDateTime;
+
+    fn money(value: &str) -> Money {
+        value.parse().unwrap()
+    }
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 1, day).unwrap()
+    }
+
+    fn statement(closing: &str) -> StatementBuilder {
+        StatementBuilder::new("STMT-1")
+            .account("40702810440000030888", "RUB")
+            .period(IsoDateTime::start_of_day(date(1)), IsoDateTime::end_of_day(date(31)))
+            .balance(BalanceBuilder::new(BalanceType::Opbd).amount(money("100.00"), "RUB").credit().build())
+            .entry(EntryBuilder::new().amount(money("30.50"), "RUB").credit().booking_date(date(2)).build())
+            .entry(EntryBuilder::new().amount(money("200.00"), "RUB").debit().booking_date(date(3)).build())
+            .balance(BalanceBuilder::new(BalanceType::Clbd).amount(money(closing), "RUB").debit().build())
+    }
+
+    #[test]
+    fn test_validate_consistent() {
+        assert_eq!(Vec::<ValidationIssue>::new(), validate_statement(&statement("69.50").build().stmt));
+    }
+
+    #[test]
+    fn test_validate_balance_mismatch() {
+        let issues = validate_statement(&statement("70.00").build().stmt);
+        assert_eq!(vec![ValidationIssue::BalanceMismatch {
+            statement: "STMT-1".to_string(), expected: money("-69.50"), actual: money("-70.00") }], issues);
+    }
+
+    #[test]
+    fn test_validate_summary() {
+        let mut stmt = statement("69.50").build().stmt;
+        stmt.txs_summry.ttl_cdt_ntries = Some(TtlCdtDbtNtriesAttribute { nb_of_ntries: 1, sum: money("30.50") });
+        stmt.txs_summry.ttl_dbt_ntries = Some(TtlCdtDbtNtriesAttribute { nb_of_ntries: 2, sum: money("200") });
+        stmt.txs_summry.ttl_ntries.nb_of_ntries = Some(2);
+        stmt.txs_summry.ttl_ntries.ttl_net_ntry_amt = money("169.50");
+        stmt.txs_summry.ttl_ntries.cdt_dbt_ind = Some(CreditDebit::Credit);
+        let issues = validate_statement(&stmt);
+        assert_eq!(2, issues.len());
+        assert_eq!("Statement STMT-1: TtlDbtNtries/NbOfNtries 2 does not match 1 entries", issues[0].to_string());
+        assert_eq!("Statement STMT-1: TtlNtries sum 169.50 does not match entries sum -169.50", issues[1].to_string());
+    }
+
+    #[test]
+    fn test_validate_summary_ttl_ntries_only() {
+        let mut stmt = statement("69.50").build().stmt;
+        stmt.txs_summry.ttl_ntries.nb_of_ntries = Some(2);
+        stmt.txs_summry.ttl_ntries.ttl_net_ntry_amt = money("169.50");
+        stmt.txs_summry.ttl_ntries.cdt_dbt_ind = Some(CreditDebit::Debit);
+        assert_eq!(Vec::<ValidationIssue>::new(), validate_statement(&stmt));
+        stmt.txs_summry.ttl_ntries.nb_of_ntries = Some(3);
+        let issues = validate_statement(&stmt);
+        assert_eq!(vec!["Statement STMT-1: TtlNtries/NbOfNtries 3 does not match 2 entries".to_string()],
+                   issues.iter().map(ToString::to_string).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_validate_summary_entry_currencies() {
+        let mut stmt = statement("69.50").build().stmt;
+        stmt.txs_summry = TxsSummryAttribute::from_entries(&stmt.ntry).unwrap();
+        stmt.ntry[1].amt.ccy = "JPY".to_string();
+        let issues = validate_statement(&stmt);
+        assert!(issues.contains(&ValidationIssue::EntryCurrencyMismatch {
+            statement: "STMT-1".to_string(), entry: 2, expected: "RUB".to_string(), actual: "JPY".to_string() }),
+                "{:?}", issues);
+        assert_eq!(1, summary_issues(&stmt).len());
+    }
+
+    #[test]
+    fn test_validate_dates_and_currencies() {
+        let mut stmt = statement("69.50").build().stmt;
+        stmt.ntry[1].bookg_dt.dt = NaiveDate::from_ymd_opt(2024, 2, 1);
+        stmt.bal[1].amt.ccy = "USD".to_string();
+        let issues = validate_statement(&stmt);
+        assert_eq!(vec![
+            ValidationIssue::BookingDateOutOfPeriod {
+                statement: "STMT-1".to_string(), entry: 2, date: NaiveDate::from_ymd_opt(2024, 2, 1).unwrap() },
+            ValidationIssue::CurrencyMismatch {
+                statement: "STMT-1".to_string(), balance: BalanceType::Clbd,
+                expected: "RUB".to_string(), actual: "USD".to_string() },
+        ], issues);
+    }
+}
@@ -1,2 +0,0 @@
-
-pub(crate) mod pipline;
\ No newline at end of file